@@ -0,0 +1,625 @@
+// FILE: crates/dloom-cli/src/commands.rs
+
+//! The CLI's subcommands and the instructions each one builds.
+
+use crate::{inspect, rpc::Rpc};
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program},
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address_with_program_id,
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::StateWithExtensions,
+        state::{Account as TokenAccount, Mint},
+    },
+};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Subcommand, ValueEnum};
+use dloom_flow::{accounts as ix_accounts, state::DlmmParameter, ParameterAction, ParameterList};
+use dloom_flow_sdk::{
+    accounts, instructions,
+    pda::{self, DlmmPoolAddresses},
+};
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create the protocol config, with the signing authority as its master authority.
+    InitProtocol,
+    /// Create the DLMM whitelist of (bin step, fee rate) pairs pools may be created with.
+    InitDlmmParameters {
+        /// A pair official pools may use, as BIN_STEP:FEE_RATE. Repeat for each pair.
+        #[arg(long, value_parser = parse_dlmm_parameter)]
+        official: Vec<DlmmParameter>,
+        /// A pair community pools may use, as BIN_STEP:FEE_RATE. Repeat for each pair.
+        #[arg(long, value_parser = parse_dlmm_parameter)]
+        community: Vec<DlmmParameter>,
+    },
+    /// Add a (bin step, fee rate) pair to, or remove it from, the DLMM whitelist.
+    DlmmWhitelist {
+        action: Action,
+        #[arg(value_enum)]
+        list: List,
+        #[arg(long)]
+        bin_step: u16,
+        /// The fee rate, in basis points.
+        #[arg(long)]
+        fee_rate: u16,
+    },
+    /// Allow a Token-2022 mint with a restricted extension to be pooled, or revoke it.
+    MintAllowlist { action: Action, mint: Pubkey },
+    /// Create an official DLMM pool. The mints may be given in either order.
+    CreateDlmmPool {
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        #[arg(long)]
+        bin_step: u16,
+        /// The fee rate, in basis points. Must be whitelisted for official pools.
+        #[arg(long)]
+        fee_rate: u16,
+        /// The protocol's share of each fee, in basis points.
+        #[arg(long)]
+        protocol_fee_share: u16,
+        /// The referrer's share of each fee, in basis points.
+        #[arg(long, default_value_t = 0)]
+        referrer_fee_share: u16,
+        /// The bin the pool's price starts at.
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        initial_bin_id: i32,
+    },
+    /// Override an AMM or official DLMM pool's fee rate, or recompute it from volatility.
+    SetFee {
+        pool: Pubkey,
+        /// The new fee rate, in basis points.
+        #[arg(long, required_unless_present = "dynamic", conflicts_with = "dynamic")]
+        fee_rate: Option<u16>,
+        /// Let the program derive the fee rate from recent volatility.
+        #[arg(long)]
+        dynamic: bool,
+    },
+    /// Transfer everything in a pool's protocol fee vaults to the destination's
+    /// associated token accounts, creating them if needed.
+    CollectFees {
+        pool: Pubkey,
+        /// The owner of the receiving token accounts. Defaults to the authority.
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Print any account owned by the program, such as a pool or a position.
+    Inspect { address: Pubkey },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Action {
+    Add,
+    Remove,
+}
+
+impl From<Action> for ParameterAction {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Add => ParameterAction::Add,
+            Action::Remove => ParameterAction::Remove,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum List {
+    Official,
+    Community,
+}
+
+impl From<List> for ParameterList {
+    fn from(list: List) -> Self {
+        match list {
+            List::Official => ParameterList::Official,
+            List::Community => ParameterList::Community,
+        }
+    }
+}
+
+fn parse_dlmm_parameter(value: &str) -> Result<DlmmParameter> {
+    let (bin_step, fee_rate) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected BIN_STEP:FEE_RATE, got {value}"))?;
+    Ok(DlmmParameter {
+        bin_step: bin_step.parse()?,
+        fee_rate: fee_rate.parse()?,
+    })
+}
+
+/// Who pays for and who authorizes the transaction. Either may be unknown for commands
+/// that do not send one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Keys {
+    pub fee_payer: Option<Pubkey>,
+    pub authority: Option<Pubkey>,
+}
+
+impl Keys {
+    pub fn fee_payer(&self) -> Result<Pubkey> {
+        self.fee_payer
+            .ok_or_else(|| anyhow!("no fee payer: pass --keypair or --fee-payer"))
+    }
+
+    pub fn authority(&self) -> Result<Pubkey> {
+        self.authority
+            .ok_or_else(|| anyhow!("no authority: pass --keypair or --authority"))
+    }
+}
+
+pub enum Output {
+    /// Instructions to sign and send in one transaction.
+    Instructions(Vec<Instruction>),
+    Text(String),
+}
+
+pub fn run(command: &Command, keys: &Keys, rpc: &impl Rpc) -> Result<Output> {
+    if let Command::Inspect { address } = command {
+        return inspect::describe(address, &rpc.account(address)?).map(Output::Text);
+    }
+
+    let authority = keys.authority()?;
+    let instruction = match command {
+        Command::InitProtocol => {
+            instructions::initialize_protocol(ix_accounts::InitializeProtocol {
+                protocol_config: pda::protocol_config().0,
+                authority,
+                system_program: system_program::ID,
+            })
+        }
+        Command::InitDlmmParameters {
+            official,
+            community,
+        } => instructions::initialize_dlmm_parameters(
+            ix_accounts::InitializeDlmmParameters {
+                dlmm_parameters: pda::dlmm_parameters().0,
+                authority,
+                system_program: system_program::ID,
+            },
+            official.clone(),
+            community.clone(),
+        ),
+        Command::DlmmWhitelist {
+            action,
+            list,
+            bin_step,
+            fee_rate,
+        } => instructions::update_dlmm_parameters(
+            ix_accounts::UpdateDlmmParameters {
+                authority,
+                protocol_config: pda::protocol_config().0,
+                dlmm_parameters: pda::dlmm_parameters().0,
+            },
+            (*list).into(),
+            (*action).into(),
+            *bin_step,
+            *fee_rate,
+        ),
+        Command::MintAllowlist { action, mint } => instructions::update_mint_allowlist(
+            ix_accounts::UpdateMintAllowlist {
+                authority,
+                protocol_config: pda::protocol_config().0,
+            },
+            (*action).into(),
+            *mint,
+        ),
+        Command::CreateDlmmPool {
+            mint_x,
+            mint_y,
+            bin_step,
+            fee_rate,
+            protocol_fee_share,
+            referrer_fee_share,
+            initial_bin_id,
+        } => {
+            let parameters =
+                accounts::dlmm_parameters(&rpc.account(&pda::dlmm_parameters().0)?.data)?;
+            let whitelisted = parameters.official_parameters.iter().any(|parameter| {
+                parameter.bin_step == *bin_step && parameter.fee_rate == *fee_rate
+            });
+            if !whitelisted {
+                bail!("bin step {bin_step} with fee rate {fee_rate} is not whitelisted for official pools");
+            }
+
+            let addresses = DlmmPoolAddresses::new(*mint_x, *mint_y, *bin_step);
+            instructions::create_dlmm_pool(
+                ix_accounts::CreateDlmmPool {
+                    payer: keys.fee_payer()?,
+                    authority,
+                    protocol_config: pda::protocol_config().0,
+                    dlmm_parameters: pda::dlmm_parameters().0,
+                    token_a_mint: addresses.token_a_mint,
+                    token_b_mint: addresses.token_b_mint,
+                    dlmm_pool: addresses.pool,
+                    token_a_vault: addresses.token_a_vault,
+                    token_b_vault: addresses.token_b_vault,
+                    protocol_fee_vault_a: addresses.protocol_fee_vault_a,
+                    protocol_fee_vault_b: addresses.protocol_fee_vault_b,
+                    system_program: system_program::ID,
+                    token_a_program: rpc.account(&addresses.token_a_mint)?.owner,
+                    token_b_program: rpc.account(&addresses.token_b_mint)?.owner,
+                    rent: anchor_lang::solana_program::sysvar::rent::ID,
+                },
+                *bin_step,
+                *fee_rate,
+                *protocol_fee_share,
+                *referrer_fee_share,
+                *initial_bin_id,
+            )
+        }
+        Command::SetFee {
+            pool,
+            fee_rate,
+            dynamic: _,
+        } => {
+            let data = rpc.account(pool)?.data;
+            if let Ok(amm_pool) = accounts::amm_pool(&data) {
+                instructions::update_amm_fees(
+                    ix_accounts::UpdateAmmFees {
+                        authority,
+                        protocol_config: pda::protocol_config().0,
+                        amm_pool: *pool,
+                        token_a_mint: amm_pool.token_a_mint,
+                        token_b_mint: amm_pool.token_b_mint,
+                        amm_oracle: amm_pool.has_oracle.then(|| pda::amm_oracle(pool).0),
+                    },
+                    *fee_rate,
+                )
+            } else if accounts::dlmm_pool(&data).is_ok() {
+                instructions::update_dlmm_fees(
+                    ix_accounts::UpdateDlmmFees {
+                        authority,
+                        protocol_config: pda::protocol_config().0,
+                        dlmm_pool: *pool,
+                    },
+                    *fee_rate,
+                )
+            } else {
+                bail!("{pool} is not an AMM or DLMM pool");
+            }
+        }
+        Command::CollectFees { pool, destination } => {
+            return collect_fees(pool, destination.unwrap_or(authority), keys, rpc)
+                .map(Output::Instructions);
+        }
+        Command::Inspect { .. } => unreachable!("handled above"),
+    };
+    Ok(Output::Instructions(vec![instruction]))
+}
+
+/// Empties both protocol fee vaults of an AMM or DLMM pool into `destination`'s
+/// associated token accounts. Vaults of mints with a transfer hook are not supported.
+fn collect_fees(
+    pool: &Pubkey,
+    destination: Pubkey,
+    keys: &Keys,
+    rpc: &impl Rpc,
+) -> Result<Vec<Instruction>> {
+    let (fee_payer, authority) = (keys.fee_payer()?, keys.authority()?);
+    let data = rpc.account(pool)?.data;
+    let vaults = if let Ok(amm_pool) = accounts::amm_pool(&data) {
+        [
+            (amm_pool.token_a_mint, amm_pool.protocol_fee_vault_a),
+            (amm_pool.token_b_mint, amm_pool.protocol_fee_vault_b),
+        ]
+    } else if let Ok(dlmm_pool) = accounts::dlmm_pool(&data) {
+        [
+            (dlmm_pool.token_a_mint, dlmm_pool.protocol_fee_vault_a),
+            (dlmm_pool.token_b_mint, dlmm_pool.protocol_fee_vault_b),
+        ]
+    } else {
+        bail!("{pool} is not an AMM or DLMM pool");
+    };
+
+    let mut instructions = Vec::new();
+    for (mint, vault) in vaults {
+        let vault_account = StateWithExtensions::<TokenAccount>::unpack(&rpc.account(&vault)?.data)
+            .with_context(|| format!("{vault} is not a token account"))?
+            .base;
+        if vault_account.owner != authority {
+            bail!(
+                "protocol fee vault {vault} is owned by {}, not {authority}",
+                vault_account.owner
+            );
+        }
+        if vault_account.amount == 0 {
+            continue;
+        }
+
+        let mint_account = rpc.account(&mint)?;
+        let decimals = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+            .with_context(|| format!("{mint} is not a mint"))?
+            .base
+            .decimals;
+        let token_program = mint_account.owner;
+        instructions.push(create_associated_token_account_idempotent(
+            &fee_payer,
+            &destination,
+            &mint,
+            &token_program,
+        ));
+        instructions.push(spl_token_2022::instruction::transfer_checked(
+            &token_program,
+            &vault,
+            &mint,
+            &get_associated_token_address_with_program_id(&destination, &mint, &token_program),
+            &authority,
+            &[],
+            vault_account.amount,
+            decimals,
+        )?);
+    }
+    if instructions.is_empty() {
+        bail!("the protocol fee vaults of {pool} are empty");
+    }
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RecordedRpc;
+    use anchor_lang::{
+        solana_program::{program_option::COption, program_pack::Pack},
+        AccountSerialize, Discriminator,
+    };
+    use dloom_flow::{amm::state::AmmPool, dlmm::state::DlmmPool, state::DlmmParameters};
+    use spl_token_2022::state::AccountState;
+
+    const TOKEN: Pubkey = anchor_spl::token::ID;
+
+    fn keys() -> (Keys, Pubkey, Pubkey) {
+        let (fee_payer, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = Keys {
+            fee_payer: Some(fee_payer),
+            authority: Some(authority),
+        };
+        (keys, fee_payer, authority)
+    }
+
+    fn serialize<T: AccountSerialize>(state: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn mint(decimals: u8) -> Vec<u8> {
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    fn instructions(command: Command, keys: &Keys, rpc: &RecordedRpc) -> Result<Vec<Instruction>> {
+        match run(&command, keys, rpc)? {
+            Output::Instructions(instructions) => Ok(instructions),
+            Output::Text(text) => panic!("expected instructions, got {text}"),
+        }
+    }
+
+    #[test]
+    fn parses_dlmm_parameters() {
+        let parameter = parse_dlmm_parameter("25:30").unwrap();
+        assert_eq!((parameter.bin_step, parameter.fee_rate), (25, 30));
+        assert!(parse_dlmm_parameter("25").is_err());
+        assert!(parse_dlmm_parameter("25:x").is_err());
+    }
+
+    #[test]
+    fn whitelist_updates_are_signed_by_the_authority() {
+        let (keys, _, authority) = keys();
+        let command = Command::DlmmWhitelist {
+            action: Action::Remove,
+            list: List::Community,
+            bin_step: 25,
+            fee_rate: 30,
+        };
+        let [instruction] = &instructions(command, &keys, &RecordedRpc::default()).unwrap()[..]
+        else {
+            panic!("expected one instruction");
+        };
+        assert_eq!(instruction.program_id, dloom_flow::ID);
+        assert!(instruction
+            .data
+            .starts_with(dloom_flow::instruction::UpdateDlmmParameters::DISCRIMINATOR));
+        assert_eq!(instruction.accounts[0].pubkey, authority);
+        assert!(instruction.accounts[0].is_signer);
+        assert_eq!(instruction.accounts[2].pubkey, pda::dlmm_parameters().0);
+    }
+
+    #[test]
+    fn instructions_need_an_authority() {
+        let keys = Keys::default();
+        assert!(instructions(Command::InitProtocol, &keys, &RecordedRpc::default()).is_err());
+    }
+
+    fn create_pool(bin_step: u16, fee_rate: u16, mint_x: Pubkey, mint_y: Pubkey) -> Command {
+        Command::CreateDlmmPool {
+            mint_x,
+            mint_y,
+            bin_step,
+            fee_rate,
+            protocol_fee_share: 2_000,
+            referrer_fee_share: 0,
+            initial_bin_id: -100,
+        }
+    }
+
+    #[test]
+    fn create_dlmm_pool_uses_whitelisted_parameters_and_the_mints_programs() {
+        let (keys, fee_payer, authority) = keys();
+        let (mint_x, mint_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut rpc = RecordedRpc::default();
+        rpc.insert(
+            pda::dlmm_parameters().0,
+            dloom_flow::ID,
+            serialize(&DlmmParameters {
+                authority,
+                official_parameters: vec![DlmmParameter {
+                    bin_step: 25,
+                    fee_rate: 30,
+                }],
+                community_parameters: Vec::new(),
+            }),
+        );
+        rpc.insert(mint_x, TOKEN, mint(6));
+        rpc.insert(mint_y, spl_token_2022::ID, mint(9));
+
+        assert!(instructions(create_pool(25, 50, mint_x, mint_y), &keys, &rpc).is_err());
+
+        let [instruction] =
+            &instructions(create_pool(25, 30, mint_y, mint_x), &keys, &rpc).unwrap()[..]
+        else {
+            panic!("expected one instruction");
+        };
+        let addresses = DlmmPoolAddresses::new(mint_x, mint_y, 25);
+        let keys: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(&keys[..2], &[fee_payer, authority]);
+        assert_eq!(keys[6], addresses.pool);
+        let (token_a_program, token_b_program) = if addresses.token_a_mint == mint_x {
+            (TOKEN, spl_token_2022::ID)
+        } else {
+            (spl_token_2022::ID, TOKEN)
+        };
+        assert_eq!(&keys[12..14], &[token_a_program, token_b_program]);
+    }
+
+    #[test]
+    fn set_fee_picks_the_instruction_for_the_pool_type() {
+        let (keys, _, _) = keys();
+        let (amm_pool, dlmm_pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut rpc = RecordedRpc::default();
+        let token_a_mint = Pubkey::new_unique();
+        rpc.insert(
+            amm_pool,
+            dloom_flow::ID,
+            serialize(&AmmPool {
+                token_a_mint,
+                ..Default::default()
+            }),
+        );
+        rpc.insert(dlmm_pool, dloom_flow::ID, serialize(&DlmmPool::default()));
+
+        let set_fee = |pool, fee_rate| Command::SetFee {
+            pool,
+            fee_rate,
+            dynamic: fee_rate.is_none(),
+        };
+        let amm = instructions(set_fee(amm_pool, Some(40)), &keys, &rpc).unwrap();
+        assert!(amm[0]
+            .data
+            .starts_with(dloom_flow::instruction::UpdateAmmFees::DISCRIMINATOR));
+        assert_eq!(amm[0].accounts[3].pubkey, token_a_mint);
+
+        let dlmm = instructions(set_fee(dlmm_pool, None), &keys, &rpc).unwrap();
+        assert!(dlmm[0]
+            .data
+            .starts_with(dloom_flow::instruction::UpdateDlmmFees::DISCRIMINATOR));
+
+        assert!(instructions(set_fee(Pubkey::new_unique(), None), &keys, &rpc).is_err());
+    }
+
+    #[test]
+    fn collect_fees_empties_the_vaults_that_hold_fees() {
+        let (keys, _, authority) = keys();
+        let pool = Pubkey::new_unique();
+        let (token_a_mint, token_b_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (vault_a, vault_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut rpc = RecordedRpc::default();
+        rpc.insert(
+            pool,
+            dloom_flow::ID,
+            serialize(&DlmmPool {
+                token_a_mint,
+                token_b_mint,
+                protocol_fee_vault_a: vault_a,
+                protocol_fee_vault_b: vault_b,
+                ..Default::default()
+            }),
+        );
+        rpc.insert(token_a_mint, TOKEN, mint(6));
+        rpc.insert(token_b_mint, TOKEN, mint(9));
+        rpc.insert(vault_a, TOKEN, token_account(token_a_mint, authority, 0));
+        rpc.insert(
+            vault_b,
+            TOKEN,
+            token_account(token_b_mint, authority, 1_500),
+        );
+
+        let destination = Pubkey::new_unique();
+        let command = Command::CollectFees {
+            pool,
+            destination: Some(destination),
+        };
+        let [create_account, transfer] = &instructions(command, &keys, &rpc).unwrap()[..] else {
+            panic!("expected one vault to be emptied");
+        };
+        let destination_account =
+            get_associated_token_address_with_program_id(&destination, &token_b_mint, &TOKEN);
+        assert_eq!(create_account.accounts[1].pubkey, destination_account);
+        assert_eq!(transfer.program_id, TOKEN);
+        assert_eq!(transfer.accounts[0].pubkey, vault_b);
+        assert_eq!(transfer.accounts[2].pubkey, destination_account);
+        assert_eq!(transfer.accounts[3].pubkey, authority);
+        // `TransferChecked`, the amount, then the decimals.
+        assert_eq!(transfer.data[0], 12);
+        assert_eq!(transfer.data[1..9], 1_500u64.to_le_bytes());
+        assert_eq!(transfer.data[9], 9);
+    }
+
+    #[test]
+    fn collect_fees_rejects_vaults_of_another_authority() {
+        let (keys, _, _) = keys();
+        let pool = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let mut rpc = RecordedRpc::default();
+        rpc.insert(
+            pool,
+            dloom_flow::ID,
+            serialize(&AmmPool {
+                protocol_fee_vault_a: vault,
+                protocol_fee_vault_b: vault,
+                ..Default::default()
+            }),
+        );
+        rpc.insert(
+            vault,
+            TOKEN,
+            token_account(Pubkey::default(), Pubkey::new_unique(), 10),
+        );
+        let command = Command::CollectFees {
+            pool,
+            destination: None,
+        };
+        let error = instructions(command, &keys, &rpc).unwrap_err();
+        assert!(error.to_string().contains("is owned by"));
+    }
+}
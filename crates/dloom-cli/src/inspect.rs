@@ -0,0 +1,100 @@
+// FILE: crates/dloom-cli/src/inspect.rs
+
+//! Decoding any account owned by the program for `dloom-cli inspect`.
+
+use crate::rpc::Account;
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use anyhow::{bail, Result};
+use dloom_flow::{
+    amm::state::{AmmPool, AmmPosition},
+    bonding_curve::state::BondingCurve,
+    dlmm::state::{Bin, DlmmPool, Position},
+    state::{DlmmParameters, ProtocolConfig, TransactionBins},
+};
+use dloom_flow_sdk::accounts;
+
+/// Identifies the account's type from its discriminator and pretty-prints its fields.
+pub fn describe(address: &Pubkey, account: &Account) -> Result<String> {
+    if account.owner != dloom_flow::ID {
+        bail!(
+            "{address} is owned by {}, not the dloom-flow program",
+            account.owner
+        );
+    }
+
+    macro_rules! decode_as {
+        ($($ty:ident => $decode:path),* $(,)?) => {
+            $(
+                if account.data.starts_with($ty::DISCRIMINATOR) {
+                    let decoded = $decode(&account.data)?;
+                    return Ok(format!("{} {address}\n{decoded:#?}\n", stringify!($ty)));
+                }
+            )*
+        };
+    }
+    decode_as! {
+        AmmPool => accounts::amm_pool,
+        AmmPosition => accounts::amm_position,
+        DlmmPool => accounts::dlmm_pool,
+        Position => accounts::position,
+        Bin => accounts::bin,
+        BondingCurve => accounts::bonding_curve,
+        ProtocolConfig => accounts::protocol_config,
+        DlmmParameters => accounts::dlmm_parameters,
+        TransactionBins => accounts::transaction_bins,
+    }
+    bail!("{address} is not a known dloom-flow account")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    fn account<T: AccountSerialize>(state: &T) -> Account {
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        Account {
+            owner: dloom_flow::ID,
+            data,
+        }
+    }
+
+    #[test]
+    fn describes_a_pool_by_its_discriminator() {
+        let address = Pubkey::new_unique();
+        let pool = DlmmPool {
+            active_bin_id: -42,
+            bin_step: 25,
+            ..Default::default()
+        };
+        let description = describe(&address, &account(&pool)).unwrap();
+        assert!(description.starts_with(&format!("DlmmPool {address}\n")));
+        assert!(description.contains("active_bin_id: -42"));
+        assert!(description.contains("bin_step: 25"));
+    }
+
+    #[test]
+    fn describes_a_position() {
+        let position = AmmPosition {
+            lp_token_amount: 1_234,
+            ..Default::default()
+        };
+        let description = describe(&Pubkey::new_unique(), &account(&position)).unwrap();
+        assert!(description.starts_with("AmmPosition "));
+        assert!(description.contains("lp_token_amount: 1234"));
+    }
+
+    #[test]
+    fn rejects_accounts_of_other_programs() {
+        let mut foreign = account(&ProtocolConfig::default());
+        foreign.owner = Pubkey::new_unique();
+        assert!(describe(&Pubkey::new_unique(), &foreign).is_err());
+
+        let unknown = Account {
+            owner: dloom_flow::ID,
+            data: vec![0; 16],
+        };
+        assert!(describe(&Pubkey::new_unique(), &unknown).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+// FILE: crates/dloom-cli/src/main.rs
+
+//! `dloom-cli`: the protocol's routine administrative jobs.
+//!
+//! Transactions are signed with `--keypair` and sent to `--url`. When the authority is a
+//! multisig or an offline wallet, pass its address with `--authority` and add
+//! `--sign-only` (with `--blockhash` on an air-gapped machine): the transaction is
+//! printed base64-encoded, signed by whichever keys are at hand, for the remaining
+//! signers or the multisig to import.
+
+mod commands;
+mod inspect;
+mod rpc;
+mod transaction;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use commands::{Command, Keys, Output};
+use rpc::{HttpRpc, Rpc};
+use solana_hash::Hash;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use std::{io::Write, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dloom-cli",
+    version,
+    about = "Administrative operations for the dloom-flow program"
+)]
+struct Cli {
+    /// The cluster's JSON-RPC endpoint.
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "DLOOM_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+    /// The keypair file that signs as fee payer and authority. Defaults to the Solana CLI's
+    /// keypair, if there is one.
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+    /// The protocol authority, when it is not the keypair, such as a multisig vault.
+    #[arg(long, global = true)]
+    authority: Option<Pubkey>,
+    /// The fee payer, when it is not the keypair.
+    #[arg(long, global = true)]
+    fee_payer: Option<Pubkey>,
+    /// Sign with this blockhash instead of fetching the latest one.
+    #[arg(long, global = true)]
+    blockhash: Option<Hash>,
+    /// Print the transaction instead of sending it. Signatures of signers without a
+    /// keypair are left empty.
+    #[arg(long, global = true)]
+    sign_only: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = HttpRpc::new(&cli.url);
+    run(&cli, &rpc, &mut std::io::stdout())
+}
+
+fn run(cli: &Cli, rpc: &impl Rpc, out: &mut impl Write) -> Result<()> {
+    let keypair = load_keypair(cli.keypair.as_ref())?;
+    let signer = keypair.as_ref().map(Keypair::pubkey);
+    let keys = Keys {
+        fee_payer: cli.fee_payer.or(signer),
+        authority: cli.authority.or(signer),
+    };
+
+    let instructions = match commands::run(&cli.command, &keys, rpc)? {
+        Output::Text(text) => return Ok(out.write_all(text.as_bytes())?),
+        Output::Instructions(instructions) => instructions,
+    };
+    let blockhash = match cli.blockhash {
+        Some(blockhash) => blockhash,
+        None => rpc.get_latest_blockhash()?,
+    };
+    let keypairs: Vec<&Keypair> = keypair.iter().collect();
+    let transaction = transaction::build(&instructions, &keys.fee_payer()?, blockhash, &keypairs)?;
+
+    if cli.sign_only {
+        return Ok(write!(
+            out,
+            "{}",
+            transaction::sign_only_report(&transaction)?
+        )?);
+    }
+    let absent = transaction::absent_signers(&transaction);
+    if !absent.is_empty() {
+        let absent: Vec<_> = absent.iter().map(Pubkey::to_string).collect();
+        bail!(
+            "the transaction also needs signatures from {}; pass --sign-only to print it for them",
+            absent.join(", ")
+        );
+    }
+    writeln!(out, "Signature: {}", rpc.send_transaction(&transaction)?)?;
+    Ok(())
+}
+
+/// Reads `path`, or the Solana CLI's default keypair if no path is given and it exists.
+fn load_keypair(path: Option<&PathBuf>) -> Result<Option<Keypair>> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => {
+            let default = std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config/solana/id.json"));
+            match default {
+                Some(default) if default.exists() => default,
+                _ => return Ok(None),
+            }
+        }
+    };
+    read_keypair_file(&path)
+        .map(Some)
+        .map_err(|error| anyhow!("failed to read keypair {}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RecordedRpc;
+    use anchor_lang::{AccountSerialize, Discriminator};
+    use dloom_flow::state::ProtocolConfig;
+    use solana_keypair::write_keypair_file;
+
+    /// Writes a fresh keypair to a temporary file.
+    fn keypair_file() -> (Keypair, PathBuf) {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("dloom-cli-{}.json", keypair.pubkey()));
+        write_keypair_file(&keypair, &path).unwrap();
+        (keypair, path)
+    }
+
+    fn execute(args: &[&str], rpc: &RecordedRpc) -> Result<String> {
+        let cli = Cli::try_parse_from([&["dloom-cli"], args].concat())?;
+        let mut out = Vec::new();
+        run(&cli, rpc, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn sends_transactions_the_keypair_can_sign() {
+        let (keypair, path) = keypair_file();
+        let rpc = RecordedRpc::default();
+        let output = execute(
+            &[
+                "mint-allowlist",
+                "add",
+                &Pubkey::new_unique().to_string(),
+                "-k",
+                path.to_str().unwrap(),
+            ],
+            &rpc,
+        )
+        .unwrap();
+
+        let sent = rpc.sent.borrow();
+        assert_eq!(output, format!("Signature: {}\n", sent[0].signatures[0]));
+        assert_eq!(sent[0].message.account_keys[0], keypair.pubkey());
+        assert!(sent[0].is_signed());
+    }
+
+    #[test]
+    fn sign_only_leaves_the_multisig_signature_empty() {
+        let (keypair, path) = keypair_file();
+        let multisig = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let rpc = RecordedRpc::default();
+        let output = execute(
+            &[
+                "dlmm-whitelist",
+                "add",
+                "official",
+                "--bin-step",
+                "25",
+                "--fee-rate",
+                "30",
+                "--keypair",
+                path.to_str().unwrap(),
+                "--authority",
+                &multisig.to_string(),
+                "--blockhash",
+                &blockhash.to_string(),
+                "--sign-only",
+            ],
+            &rpc,
+        )
+        .unwrap();
+        assert!(rpc.sent.borrow().is_empty());
+
+        let encoded = output
+            .lines()
+            .find_map(|line| line.strip_prefix("Transaction: "))
+            .unwrap();
+        let transaction = transaction::decode(encoded).unwrap();
+        assert_eq!(transaction.message.recent_blockhash, blockhash);
+        assert_eq!(transaction.message.account_keys[0], keypair.pubkey());
+        assert_eq!(transaction::absent_signers(&transaction), vec![multisig]);
+        assert!(output.contains(&format!("Absent Signers (Pubkey):\n  {multisig}\n")));
+    }
+
+    #[test]
+    fn sign_only_works_without_any_keypair() {
+        let multisig = Pubkey::new_unique();
+        let output = execute(
+            &[
+                "set-fee",
+                &Pubkey::new_unique().to_string(),
+                "--fee-rate",
+                "30",
+                "--fee-payer",
+                &multisig.to_string(),
+                "--authority",
+                &multisig.to_string(),
+                "--sign-only",
+            ],
+            &RecordedRpc::default(),
+        );
+        // The pool does not exist, so the command fails before any keypair is needed.
+        assert!(output.unwrap_err().to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn refuses_to_send_without_every_signature() {
+        let (_, path) = keypair_file();
+        let rpc = RecordedRpc::default();
+        let error = execute(
+            &[
+                "init-protocol",
+                "-k",
+                path.to_str().unwrap(),
+                "--authority",
+                &Pubkey::new_unique().to_string(),
+            ],
+            &rpc,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--sign-only"));
+        assert!(rpc.sent.borrow().is_empty());
+    }
+
+    #[test]
+    fn inspects_without_a_keypair() {
+        let address = Pubkey::new_unique();
+        let mut data = Vec::new();
+        ProtocolConfig::default().try_serialize(&mut data).unwrap();
+        assert!(data.starts_with(ProtocolConfig::DISCRIMINATOR));
+        let mut rpc = RecordedRpc::default();
+        rpc.insert(address, dloom_flow::ID, data);
+
+        let output = execute(
+            &["inspect", &address.to_string(), "-k", "/nonexistent"],
+            &rpc,
+        );
+        assert!(output.is_err(), "an explicit keypair must exist");
+        let output = execute(
+            &[
+                "inspect",
+                &address.to_string(),
+                "--authority",
+                &address.to_string(),
+            ],
+            &rpc,
+        )
+        .unwrap();
+        assert!(output.starts_with(&format!("ProtocolConfig {address}\n")));
+    }
+}
@@ -0,0 +1,134 @@
+// FILE: crates/dloom-cli/src/rpc.rs
+
+//! The few JSON-RPC calls the CLI makes, behind a trait so commands can be run against
+//! recorded accounts.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_transaction::Transaction;
+use std::str::FromStr;
+
+/// An account as returned by `getAccountInfo`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+pub trait Rpc {
+    /// Returns the account at `address`, or `None` if it does not exist.
+    fn get_account(&self, address: &Pubkey) -> Result<Option<Account>>;
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+    /// Submits a signed transaction and returns its signature.
+    fn send_transaction(&self, transaction: &Transaction) -> Result<String>;
+
+    /// Returns the account at `address`, failing if it does not exist.
+    fn account(&self, address: &Pubkey) -> Result<Account> {
+        self.get_account(address)?
+            .ok_or_else(|| anyhow!("account {address} does not exist"))
+    }
+}
+
+/// A JSON-RPC client for a cluster endpoint.
+pub struct HttpRpc {
+    url: String,
+}
+
+impl HttpRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = ureq::post(&self.url)
+            .send_json(request)
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!(
+                "{method} failed: {}",
+                error["message"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(response["result"].take())
+    }
+}
+
+impl Rpc for HttpRpc {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": "confirmed" }]),
+        )?;
+        let value = &result["value"];
+        if value.is_null() {
+            return Ok(None);
+        }
+        let owner = value["owner"]
+            .as_str()
+            .ok_or_else(|| anyhow!("getAccountInfo returned no owner for {address}"))?;
+        let data = value["data"][0]
+            .as_str()
+            .ok_or_else(|| anyhow!("getAccountInfo returned no data for {address}"))?;
+        Ok(Some(Account {
+            owner: Pubkey::from_str(owner)?,
+            data: STANDARD.decode(data)?,
+        }))
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("getLatestBlockhash returned no blockhash"))?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<String> {
+        let encoded = STANDARD.encode(bincode::serialize(transaction)?);
+        let result = self.call(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]),
+        )?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("sendTransaction returned no signature"))
+    }
+}
+
+/// Serves recorded accounts instead of a cluster, and keeps what is sent to it.
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordedRpc {
+    pub accounts: std::collections::HashMap<Pubkey, Account>,
+    pub blockhash: Hash,
+    pub sent: std::cell::RefCell<Vec<Transaction>>,
+}
+
+#[cfg(test)]
+impl RecordedRpc {
+    pub fn insert(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        self.accounts.insert(address, Account { owner, data });
+    }
+}
+
+#[cfg(test)]
+impl Rpc for RecordedRpc {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.accounts.get(address).cloned())
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<String> {
+        self.sent.borrow_mut().push(transaction.clone());
+        Ok(transaction.signatures[0].to_string())
+    }
+}
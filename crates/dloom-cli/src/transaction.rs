@@ -0,0 +1,151 @@
+// FILE: crates/dloom-cli/src/transaction.rs
+
+//! Building, signing and encoding the transactions a command produces.
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction};
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_hash::Hash;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::fmt::Write;
+
+/// Builds a transaction paid for by `fee_payer` and signs it with every keypair that is
+/// one of its signers. Signatures of the remaining signers are left empty.
+pub fn build(
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    blockhash: Hash,
+    keypairs: &[&Keypair],
+) -> Result<Transaction> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(fee_payer));
+    let required = &transaction.message.account_keys
+        [..transaction.message.header.num_required_signatures as usize];
+    let signers: Vec<&Keypair> = keypairs
+        .iter()
+        .copied()
+        .filter(|keypair| required.contains(&keypair.pubkey()))
+        .collect();
+    transaction.try_partial_sign(&signers, blockhash)?;
+    Ok(transaction)
+}
+
+/// The signers whose signatures are still empty.
+pub fn absent_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    transaction
+        .signatures
+        .iter()
+        .zip(&transaction.message.account_keys)
+        .filter(|(signature, _)| **signature == Default::default())
+        .map(|(_, signer)| *signer)
+        .collect()
+}
+
+/// The transaction in the wire format, base64-encoded, as accepted by `sendTransaction`
+/// and by multisig tools that import a serialized transaction.
+pub fn encode(transaction: &Transaction) -> Result<String> {
+    Ok(STANDARD.encode(bincode::serialize(transaction)?))
+}
+
+#[cfg(test)]
+pub fn decode(encoded: &str) -> Result<Transaction> {
+    Ok(bincode::deserialize(&STANDARD.decode(encoded.trim())?)?)
+}
+
+/// Describes a transaction signed with `--sign-only`: the signatures made so far, the
+/// signers still missing, and the encoded transaction to pass on to them.
+pub fn sign_only_report(transaction: &Transaction) -> Result<String> {
+    let absent = absent_signers(transaction);
+    let mut report = String::new();
+    writeln!(
+        report,
+        "Blockhash: {}",
+        transaction.message.recent_blockhash
+    )?;
+    writeln!(report, "Signers (Pubkey=Signature):")?;
+    for (signature, signer) in transaction
+        .signatures
+        .iter()
+        .zip(&transaction.message.account_keys)
+        .filter(|(_, signer)| !absent.contains(signer))
+    {
+        writeln!(report, "  {signer}={signature}")?;
+    }
+    if !absent.is_empty() {
+        writeln!(report, "Absent Signers (Pubkey):")?;
+        for signer in &absent {
+            writeln!(report, "  {signer}")?;
+        }
+    }
+    writeln!(report, "Transaction: {}", encode(transaction)?)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::AccountMeta;
+
+    fn instruction(signers: &[Pubkey]) -> Instruction {
+        Instruction {
+            program_id: dloom_flow::ID,
+            accounts: signers
+                .iter()
+                .map(|signer| AccountMeta::new_readonly(*signer, true))
+                .collect(),
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn signs_only_with_the_keypairs_it_needs() {
+        let fee_payer = Keypair::new();
+        let authority = Pubkey::new_unique();
+        let bystander = Keypair::new();
+        let transaction = build(
+            &[instruction(&[authority])],
+            &fee_payer.pubkey(),
+            Hash::new_unique(),
+            &[&fee_payer, &bystander],
+        )
+        .unwrap();
+
+        assert_eq!(transaction.signatures.len(), 2);
+        assert_eq!(absent_signers(&transaction), vec![authority]);
+        assert!(!transaction.is_signed());
+    }
+
+    #[test]
+    fn encoded_transactions_round_trip() {
+        let fee_payer = Keypair::new();
+        let blockhash = Hash::new_unique();
+        let transaction = build(
+            &[instruction(&[fee_payer.pubkey()])],
+            &fee_payer.pubkey(),
+            blockhash,
+            &[&fee_payer],
+        )
+        .unwrap();
+        assert!(transaction.is_signed());
+
+        let decoded = decode(&encode(&transaction).unwrap()).unwrap();
+        assert_eq!(decoded, transaction);
+        assert_eq!(decoded.message.recent_blockhash, blockhash);
+    }
+
+    #[test]
+    fn sign_only_report_lists_the_absent_signers() {
+        let authority = Pubkey::new_unique();
+        let transaction = build(
+            &[instruction(&[authority])],
+            &authority,
+            Hash::new_unique(),
+            &[],
+        )
+        .unwrap();
+        let report = sign_only_report(&transaction).unwrap();
+        assert!(report.contains(&format!("Absent Signers (Pubkey):\n  {authority}\n")));
+        assert!(report.contains(&format!("Transaction: {}\n", encode(&transaction).unwrap())));
+    }
+}
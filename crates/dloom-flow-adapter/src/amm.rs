@@ -0,0 +1,146 @@
+// FILE: crates/dloom-flow-adapter/src/amm.rs
+
+//! The adapter for AMM pools, executed with `swap_on_amm`.
+
+use crate::interface::{
+    get_account, is_a_to_b, quote_with_mode, AccountMap, Amm, AmmContext, ClockRef, KeyedAccount,
+    Quote, QuoteParams, Swap, SwapAndAccountMetas, SwapParams,
+};
+use crate::mint_supply;
+use anchor_lang::{prelude::Pubkey, ToAccountMetas};
+use anyhow::Result;
+use dloom_flow_quote::{amm::AmmPoolSnapshot, mint::MintSnapshot};
+use dloom_flow_sdk::{accounts, pda};
+
+pub struct DloomAmm {
+    key: Pubkey,
+    snapshot: AmmPoolSnapshot,
+    token_a_program: Pubkey,
+    token_b_program: Pubkey,
+    clock_ref: ClockRef,
+}
+
+impl DloomAmm {
+    pub fn snapshot(&self) -> &AmmPoolSnapshot {
+        &self.snapshot
+    }
+}
+
+impl Amm for DloomAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self> {
+        Ok(Self {
+            key: keyed_account.key,
+            snapshot: AmmPoolSnapshot {
+                pool: accounts::amm_pool(&keyed_account.account.data)?,
+                ..Default::default()
+            },
+            token_a_program: Pubkey::default(),
+            token_b_program: Pubkey::default(),
+            clock_ref: amm_context.clock_ref.clone(),
+        })
+    }
+
+    fn label(&self) -> String {
+        "Dloom AMM".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        dloom_flow::ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![
+            self.snapshot.pool.token_a_mint,
+            self.snapshot.pool.token_b_mint,
+        ]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let pool = &self.snapshot.pool;
+        vec![self.key, pool.lp_mint, pool.token_a_mint, pool.token_b_mint]
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let pool = accounts::amm_pool(&get_account(account_map, &self.key)?.data)?;
+        let lp_mint = get_account(account_map, &pool.lp_mint)?;
+        let token_a_mint = get_account(account_map, &pool.token_a_mint)?;
+        let token_b_mint = get_account(account_map, &pool.token_b_mint)?;
+
+        self.snapshot = AmmPoolSnapshot {
+            pool,
+            lp_supply: mint_supply(&lp_mint.data)?,
+            token_a_mint: MintSnapshot::unpack(&token_a_mint.data)?,
+            token_b_mint: MintSnapshot::unpack(&token_b_mint.data)?,
+        };
+        self.token_a_program = token_a_mint.owner;
+        self.token_b_program = token_b_mint.owner;
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let pool = &self.snapshot.pool;
+        let is_a_to_b = is_a_to_b(
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &quote_params.input_mint,
+            &quote_params.output_mint,
+        )?;
+        let clock = self.clock_ref.get();
+
+        quote_with_mode(quote_params, |amount_in| {
+            let quote = self.snapshot.quote_swap(amount_in, is_a_to_b, &clock)?;
+            Ok(Quote {
+                in_amount: amount_in,
+                out_amount: quote.amount_out,
+                fee_amount: quote.protocol_fee + quote.lp_fee,
+                fee_mint: quote_params.input_mint,
+            })
+        })
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        let pool = &self.snapshot.pool;
+        is_a_to_b(
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &swap_params.source_mint,
+            &swap_params.destination_mint,
+        )?;
+
+        let account_metas = dloom_flow::accounts::SwapOnAmm {
+            trader: swap_params.token_transfer_authority,
+            amm_pool: self.key,
+            lp_mint: pool.lp_mint,
+            token_a_mint: pool.token_a_mint,
+            token_b_mint: pool.token_b_mint,
+            user_source_token_account: Some(swap_params.source_token_account),
+            user_destination_token_account: Some(swap_params.destination_token_account),
+            wsol_account: None,
+            token_a_vault: pool.token_a_vault,
+            token_b_vault: pool.token_b_vault,
+            protocol_fee_vault_a: pool.protocol_fee_vault_a,
+            protocol_fee_vault_b: pool.protocol_fee_vault_b,
+            authority: pool.authority,
+            referrer_fee_account: None,
+            callback_program: None,
+            token_a_program: self.token_a_program,
+            token_b_program: self.token_b_program,
+            system_program: None,
+            amm_oracle: pool.has_oracle.then(|| pda::amm_oracle(&self.key).0),
+        }
+        .to_account_metas(None);
+
+        Ok(SwapAndAccountMetas {
+            swap: Swap::DloomAmm {
+                amount_in: swap_params.in_amount,
+                min_amount_out: swap_params.out_amount,
+            },
+            setup_instructions: Vec::new(),
+            account_metas,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+// FILE: crates/dloom-flow-adapter/src/dlmm.rs
+
+//! The adapter for DLMM pools, executed with `setup_bins` followed by `dlmm_swap`.
+
+use crate::interface::{
+    get_account, is_a_to_b, quote_with_mode, AccountMap, Amm, AmmContext, ClockRef, KeyedAccount,
+    Quote, QuoteParams, Swap, SwapAndAccountMetas, SwapParams,
+};
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::AccountMeta, system_program, ToAccountMetas,
+};
+use anyhow::{bail, Result};
+use dloom_flow_quote::{dlmm::DlmmPoolSnapshot, mint::MintSnapshot};
+use dloom_flow_sdk::{accounts, instructions, pda};
+use std::collections::BTreeMap;
+
+/// How many bins on each side of the active bin are kept up to date.
+pub const BIN_WINDOW: i32 = 10;
+
+/// How many bins past those a quote crosses are listed for a swap, so that it still
+/// succeeds if the price moves slightly before it lands.
+pub const EXTRA_SWAP_BINS: usize = 2;
+
+pub struct DloomDlmm {
+    key: Pubkey,
+    snapshot: DlmmPoolSnapshot,
+    token_a_program: Pubkey,
+    token_b_program: Pubkey,
+    clock_ref: ClockRef,
+}
+
+impl DloomDlmm {
+    pub fn snapshot(&self) -> &DlmmPoolSnapshot {
+        &self.snapshot
+    }
+
+    /// The bin ids kept up to date around the active bin.
+    fn bin_window(&self) -> impl Iterator<Item = i32> {
+        let active_bin_id = self.snapshot.pool.active_bin_id;
+        active_bin_id.saturating_sub(BIN_WINDOW)..=active_bin_id.saturating_add(BIN_WINDOW)
+    }
+
+    fn bin_key(&self, bin_id: i32) -> Pubkey {
+        pda::bin(&self.key, bin_id).0
+    }
+
+    /// The bins a swap of `amount_in` crosses, plus [`EXTRA_SWAP_BINS`] beyond them, in
+    /// the order the swap visits them. Stops at the first bin that does not exist.
+    fn swap_bins(&self, amount_in: u64, is_a_to_b: bool) -> Result<Vec<Pubkey>> {
+        let quote = self
+            .snapshot
+            .quote_swap(amount_in, is_a_to_b, &self.clock_ref.get())?;
+        let step = if is_a_to_b { -1 } else { 1 };
+        let bins = (0..)
+            .map(|offset| self.snapshot.pool.active_bin_id + step * offset)
+            .take(quote.bins_crossed as usize + EXTRA_SWAP_BINS)
+            .take_while(|bin_id| self.snapshot.bins.contains_key(bin_id))
+            .map(|bin_id| self.bin_key(bin_id))
+            .collect();
+        Ok(bins)
+    }
+}
+
+impl Amm for DloomDlmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self> {
+        Ok(Self {
+            key: keyed_account.key,
+            snapshot: DlmmPoolSnapshot {
+                pool: accounts::dlmm_pool(&keyed_account.account.data)?,
+                ..Default::default()
+            },
+            token_a_program: Pubkey::default(),
+            token_b_program: Pubkey::default(),
+            clock_ref: amm_context.clock_ref.clone(),
+        })
+    }
+
+    fn label(&self) -> String {
+        "Dloom DLMM".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        dloom_flow::ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![
+            self.snapshot.pool.token_a_mint,
+            self.snapshot.pool.token_b_mint,
+        ]
+    }
+
+    /// The pool, its mints, and the bins within [`BIN_WINDOW`] of the active bin.
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let pool = &self.snapshot.pool;
+        let mut keys = vec![self.key, pool.token_a_mint, pool.token_b_mint];
+        keys.extend(self.bin_window().map(|bin_id| self.bin_key(bin_id)));
+        keys
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let pool = accounts::dlmm_pool(&get_account(account_map, &self.key)?.data)?;
+        let token_a_mint = get_account(account_map, &pool.token_a_mint)?;
+        let token_b_mint = get_account(account_map, &pool.token_b_mint)?;
+        self.snapshot.token_a_mint = MintSnapshot::unpack(&token_a_mint.data)?;
+        self.snapshot.token_b_mint = MintSnapshot::unpack(&token_b_mint.data)?;
+        self.token_a_program = token_a_mint.owner;
+        self.token_b_program = token_b_mint.owner;
+
+        // Bins missing from the map have never been created and hold no liquidity. The
+        // window is the one listed by `get_accounts_to_update`, around the previous active
+        // bin; the next update follows the pool to its new active bin.
+        let mut bins = BTreeMap::new();
+        for bin_id in self.bin_window() {
+            if let Some(bin) = account_map.get(&self.bin_key(bin_id)) {
+                bins.insert(bin_id, accounts::bin(&bin.data)?);
+            }
+        }
+        self.snapshot.bins = bins;
+        self.snapshot.pool = pool;
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let pool = &self.snapshot.pool;
+        let is_a_to_b = is_a_to_b(
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &quote_params.input_mint,
+            &quote_params.output_mint,
+        )?;
+        let clock = self.clock_ref.get();
+
+        quote_with_mode(quote_params, |amount_in| {
+            let quote = self.snapshot.quote_swap(amount_in, is_a_to_b, &clock)?;
+            Ok(Quote {
+                in_amount: amount_in,
+                out_amount: quote.amount_out,
+                // The bins only total the protocol's share; the LP share accrues to them.
+                fee_amount: quote.protocol_fee,
+                fee_mint: quote_params.input_mint,
+            })
+        })
+    }
+
+    /// Lists the bins the swap would cross in a `setup_bins` instruction and passes them
+    /// to `dlmm_swap` as remaining accounts. `dlmm_swap` closes the `TransactionBins`
+    /// account again, so the two must run in the same transaction.
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        let pool = &self.snapshot.pool;
+        let is_a_to_b = is_a_to_b(
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &swap_params.source_mint,
+            &swap_params.destination_mint,
+        )?;
+        let bins = self.swap_bins(swap_params.in_amount, is_a_to_b)?;
+        if bins.is_empty() {
+            bail!("the active bin of {} has not been fetched", self.key);
+        }
+
+        let owner = swap_params.token_transfer_authority;
+        let transaction_bins = pda::transaction_bins(&owner).0;
+        let setup_bins = instructions::setup_bins(
+            dloom_flow::accounts::SetupBins {
+                owner,
+                transaction_bins,
+                system_program: system_program::ID,
+            },
+            bins.clone(),
+        );
+
+        let mut account_metas = dloom_flow::accounts::DlmmSwap {
+            owner,
+            dlmm_pool: self.key,
+            transaction_bins,
+            token_a_mint: pool.token_a_mint,
+            token_b_mint: pool.token_b_mint,
+            user_source_token_account: Some(swap_params.source_token_account),
+            user_destination_token_account: Some(swap_params.destination_token_account),
+            wsol_account: None,
+            token_a_vault: pool.token_a_vault,
+            token_b_vault: pool.token_b_vault,
+            protocol_fee_vault_a: pool.protocol_fee_vault_a,
+            protocol_fee_vault_b: pool.protocol_fee_vault_b,
+            referrer_fee_account: None,
+            token_a_program: self.token_a_program,
+            token_b_program: self.token_b_program,
+            system_program: None,
+        }
+        .to_account_metas(None);
+        account_metas.extend(bins.into_iter().map(|bin| AccountMeta::new(bin, false)));
+
+        Ok(SwapAndAccountMetas {
+            swap: Swap::DloomDlmm {
+                amount_in: swap_params.in_amount,
+                min_amount_out: swap_params.out_amount,
+            },
+            setup_instructions: vec![setup_bins],
+            account_metas,
+        })
+    }
+}
@@ -0,0 +1,272 @@
+// FILE: crates/dloom-flow-adapter/src/interface.rs
+
+//! The quoting interface aggregators expect of a pool adapter: build it from the pool's
+//! account, keep it fresh from the accounts it lists, quote in either swap mode, and
+//! produce the swap instruction's account metas.
+
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    solana_program::instruction::{AccountMeta, Instruction},
+    InstructionData,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// An account as fetched from the cluster.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyedAccount {
+    pub key: Pubkey,
+    pub account: Account,
+}
+
+/// The accounts fetched for [`Amm::get_accounts_to_update`]. Accounts that do not exist
+/// are left out.
+pub type AccountMap = HashMap<Pubkey, Account>;
+
+/// The cluster clock, shared by every adapter and refreshed by the aggregator. Curves
+/// that move over time and Token-2022 transfer fees are priced at this clock.
+#[derive(Clone, Debug, Default)]
+pub struct ClockRef(Arc<RwLock<Clock>>);
+
+impl ClockRef {
+    pub fn get(&self) -> Clock {
+        self.0.read().map(|clock| clock.clone()).unwrap_or_default()
+    }
+
+    pub fn update(&self, clock: Clock) {
+        if let Ok(mut current) = self.0.write() {
+            *current = clock;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AmmContext {
+    pub clock_ref: ClockRef,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwapMode {
+    /// `amount` is the input; the quote finds the output.
+    #[default]
+    ExactIn,
+    /// `amount` is the output wanted; the quote finds the smallest input that pays it.
+    ExactOut,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteParams {
+    pub amount: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub swap_mode: SwapMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quote {
+    /// What the trader sends, before any Token-2022 transfer fee.
+    pub in_amount: u64,
+    /// What the trader receives, after any Token-2022 transfer fee.
+    pub out_amount: u64,
+    /// The pool's fee, charged in `fee_mint`.
+    pub fee_amount: u64,
+    pub fee_mint: Pubkey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapParams {
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    pub source_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    /// The trader, who signs the swap and owns both token accounts.
+    pub token_transfer_authority: Pubkey,
+    pub in_amount: u64,
+    /// The minimum output, after any Token-2022 transfer fee.
+    pub out_amount: u64,
+}
+
+/// The swap instruction an adapter's account metas belong to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Swap {
+    DloomAmm { amount_in: u64, min_amount_out: u64 },
+    DloomDlmm { amount_in: u64, min_amount_out: u64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapAndAccountMetas {
+    pub swap: Swap,
+    /// Instructions that must run before the swap in the same transaction, such as the
+    /// `setup_bins` call that lists the bins a DLMM swap may cross.
+    pub setup_instructions: Vec<Instruction>,
+    pub account_metas: Vec<AccountMeta>,
+}
+
+impl SwapAndAccountMetas {
+    /// Builds the swap instruction itself.
+    pub fn instruction(&self) -> Instruction {
+        let data = match self.swap {
+            Swap::DloomAmm {
+                amount_in,
+                min_amount_out,
+            } => dloom_flow::instruction::SwapOnAmm {
+                amount_in,
+                min_amount_out,
+            }
+            .data(),
+            Swap::DloomDlmm {
+                amount_in,
+                min_amount_out,
+            } => dloom_flow::instruction::DlmmSwap {
+                amount_in,
+                min_amount_out,
+            }
+            .data(),
+        };
+        Instruction {
+            program_id: dloom_flow::ID,
+            accounts: self.account_metas.clone(),
+            data,
+        }
+    }
+}
+
+/// A pool adapter.
+pub trait Amm {
+    /// Builds the adapter from the pool account. Call [`Amm::update`] before quoting.
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self>
+    where
+        Self: Sized;
+    fn label(&self) -> String;
+    fn program_id(&self) -> Pubkey;
+    /// The pool's address.
+    fn key(&self) -> Pubkey;
+    fn get_reserve_mints(&self) -> Vec<Pubkey>;
+    /// The accounts [`Amm::update`] reads.
+    fn get_accounts_to_update(&self) -> Vec<Pubkey>;
+    fn update(&mut self, account_map: &AccountMap) -> Result<()>;
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote>;
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas>;
+}
+
+/// Returns the account for `key`, or an error naming it.
+pub(crate) fn get_account<'a>(account_map: &'a AccountMap, key: &Pubkey) -> Result<&'a Account> {
+    account_map
+        .get(key)
+        .ok_or_else(|| anyhow!("account {key} was not provided"))
+}
+
+/// Resolves the direction of a swap between `token_a_mint` and `token_b_mint`.
+pub(crate) fn is_a_to_b(
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+) -> Result<bool> {
+    if input_mint == token_a_mint && output_mint == token_b_mint {
+        Ok(true)
+    } else if input_mint == token_b_mint && output_mint == token_a_mint {
+        Ok(false)
+    } else {
+        bail!("the pool does not trade {input_mint} for {output_mint}")
+    }
+}
+
+/// Quotes in `quote_params.swap_mode`, given a quote for an exact input.
+///
+/// The program only executes exact-input swaps, so an exact-output quote is the smallest
+/// input whose exact-input quote pays at least `quote_params.amount`, found by bisection.
+/// The swap is then executed as an exact-input swap of that amount.
+pub(crate) fn quote_with_mode(
+    quote_params: &QuoteParams,
+    quote_exact_in: impl Fn(u64) -> Result<Quote>,
+) -> Result<Quote> {
+    let amount_out = quote_params.amount;
+    if quote_params.swap_mode == SwapMode::ExactIn {
+        return quote_exact_in(amount_out);
+    }
+
+    // Inputs that fall short, inputs that pay, and inputs past the pool's depth come in
+    // that order, so bisect for the first input that does not fall short.
+    let falls_short = |amount_in: u64| matches!(quote_exact_in(amount_in), Ok(quote) if quote.out_amount < amount_out);
+    let mut low = 0;
+    let mut high = amount_out.max(1);
+    while falls_short(high) {
+        low = high;
+        high = high
+            .checked_mul(2)
+            .ok_or_else(|| anyhow!("the pool cannot pay out {amount_out}"))?;
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if falls_short(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    quote_exact_in(high).map_err(|_| anyhow!("the pool cannot pay out {amount_out}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote_params(amount: u64, swap_mode: SwapMode) -> QuoteParams {
+        QuoteParams {
+            amount,
+            input_mint: Pubkey::new_from_array([1; 32]),
+            output_mint: Pubkey::new_from_array([2; 32]),
+            swap_mode,
+        }
+    }
+
+    /// Pays out two-thirds of the input, rounded down, up to 1_000.
+    fn two_thirds(amount_in: u64) -> Result<Quote> {
+        if amount_in > 1_500 {
+            bail!("insufficient liquidity");
+        }
+        Ok(Quote {
+            in_amount: amount_in,
+            out_amount: amount_in * 2 / 3,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn exact_out_finds_the_smallest_input() {
+        let quote = quote_with_mode(&quote_params(100, SwapMode::ExactOut), two_thirds).unwrap();
+        assert_eq!((quote.in_amount, quote.out_amount), (150, 100));
+        let quote = quote_with_mode(&quote_params(101, SwapMode::ExactOut), two_thirds).unwrap();
+        assert_eq!((quote.in_amount, quote.out_amount), (152, 101));
+        // Doubling from 900 overshoots the pool's depth before it pays.
+        let quote = quote_with_mode(&quote_params(900, SwapMode::ExactOut), two_thirds).unwrap();
+        assert_eq!((quote.in_amount, quote.out_amount), (1_350, 900));
+    }
+
+    #[test]
+    fn exact_out_fails_past_the_pool_depth() {
+        assert!(quote_with_mode(&quote_params(1_001, SwapMode::ExactOut), two_thirds).is_err());
+    }
+
+    #[test]
+    fn direction_follows_the_mints() {
+        let (a, b, c) = (
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            Pubkey::new_from_array([3; 32]),
+        );
+        assert!(is_a_to_b(&a, &b, &a, &b).unwrap());
+        assert!(!is_a_to_b(&a, &b, &b, &a).unwrap());
+        assert!(is_a_to_b(&a, &b, &a, &c).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+// FILE: crates/dloom-flow-adapter/src/lib.rs
+
+//! Aggregator adapters for dloom-flow pools.
+//!
+//! [`DloomAmm`] and [`DloomDlmm`] implement the [`Amm`] interface aggregators use to
+//! discover, refresh, quote and route through a pool. Quotes come from
+//! `dloom-flow-quote`, so they match what the program would execute.
+//!
+//! Mints with a Token-2022 transfer hook are not supported: the swap account metas do
+//! not include the hook's extra accounts.
+
+pub mod amm;
+pub mod dlmm;
+pub mod interface;
+
+pub use amm::DloomAmm;
+pub use dlmm::DloomDlmm;
+pub use interface::*;
+
+use anchor_spl::token_2022::spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+/// Reads the supply of a legacy or Token-2022 mint.
+pub(crate) fn mint_supply(data: &[u8]) -> anyhow::Result<u64> {
+    Ok(StateWithExtensions::<Mint>::unpack(data)?.base.supply)
+}
@@ -0,0 +1,229 @@
+// FILE: crates/dloom-flow-adapter/tests/snapshots.rs
+
+//! Runs the adapters against recorded account snapshots, in the format written by
+//! `solana account --output json`, and checks them against `dloom-flow-quote`.
+
+use anchor_lang::{prelude::Clock, prelude::Pubkey, AnchorDeserialize};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dloom_flow_adapter::{
+    dlmm::EXTRA_SWAP_BINS, Account, AccountMap, Amm, AmmContext, DloomAmm, DloomDlmm, KeyedAccount,
+    QuoteParams, SwapMode, SwapParams,
+};
+use std::{fs, path::Path, str::FromStr};
+
+/// Loads every account recorded under `tests/fixtures/<pool>`, returning the pool's own
+/// account and the map of all of them.
+fn load(pool: &str) -> (KeyedAccount, AccountMap) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(pool);
+    let mut pool_account = None;
+    let mut account_map = AccountMap::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        let keyed_account = KeyedAccount {
+            key: Pubkey::from_str(json["pubkey"].as_str().unwrap()).unwrap(),
+            account: Account {
+                owner: Pubkey::from_str(json["account"]["owner"].as_str().unwrap()).unwrap(),
+                data: STANDARD
+                    .decode(json["account"]["data"][0].as_str().unwrap())
+                    .unwrap(),
+            },
+        };
+        if path.file_stem().unwrap() == "pool" {
+            pool_account = Some(keyed_account.clone());
+        }
+        account_map.insert(keyed_account.key, keyed_account.account);
+    }
+    (pool_account.unwrap(), account_map)
+}
+
+fn context() -> AmmContext {
+    let amm_context = AmmContext::default();
+    amm_context.clock_ref.update(Clock {
+        unix_timestamp: 1_760_000_600,
+        epoch: 800,
+        ..Default::default()
+    });
+    amm_context
+}
+
+/// Builds an adapter and refreshes it from the accounts it asks for.
+fn adapter<T: Amm>(pool: &str) -> (T, AccountMap) {
+    let (pool_account, account_map) = load(pool);
+    let mut amm = T::from_keyed_account(&pool_account, &context()).unwrap();
+    let requested: AccountMap = amm
+        .get_accounts_to_update()
+        .into_iter()
+        .filter_map(|key| Some((key, account_map.get(&key)?.clone())))
+        .collect();
+    amm.update(&requested).unwrap();
+    (amm, account_map)
+}
+
+fn quote_params(amm: &impl Amm, amount: u64, is_a_to_b: bool, swap_mode: SwapMode) -> QuoteParams {
+    let [token_a_mint, token_b_mint] = amm.get_reserve_mints()[..] else {
+        unreachable!()
+    };
+    let (input_mint, output_mint) = if is_a_to_b {
+        (token_a_mint, token_b_mint)
+    } else {
+        (token_b_mint, token_a_mint)
+    };
+    QuoteParams {
+        amount,
+        input_mint,
+        output_mint,
+        swap_mode,
+    }
+}
+
+fn swap_params(quote_params: &QuoteParams, in_amount: u64, out_amount: u64) -> SwapParams {
+    SwapParams {
+        source_mint: quote_params.input_mint,
+        destination_mint: quote_params.output_mint,
+        source_token_account: Pubkey::new_unique(),
+        destination_token_account: Pubkey::new_unique(),
+        token_transfer_authority: Pubkey::new_unique(),
+        in_amount,
+        out_amount,
+    }
+}
+
+#[test]
+fn amm_update_reads_only_the_accounts_it_lists() {
+    let (pool_account, account_map) = load("amm");
+    let amm = DloomAmm::from_keyed_account(&pool_account, &context()).unwrap();
+    let keys = amm.get_accounts_to_update();
+    assert_eq!(keys.len(), account_map.len());
+    assert!(keys.iter().all(|key| account_map.contains_key(key)));
+}
+
+#[test]
+fn amm_exact_in_quotes_match_the_quote_engine() {
+    let (amm, _) = adapter::<DloomAmm>("amm");
+    let clock = context().clock_ref.get();
+    for (amount, is_a_to_b) in [(1_000_000, true), (5_000_000_000_000, false)] {
+        let quote = amm
+            .quote(&quote_params(&amm, amount, is_a_to_b, SwapMode::ExactIn))
+            .unwrap();
+        let expected = amm
+            .snapshot()
+            .quote_swap(amount, is_a_to_b, &clock)
+            .unwrap();
+        assert_eq!(quote.in_amount, amount);
+        assert_eq!(quote.out_amount, expected.amount_out);
+        assert_eq!(quote.fee_amount, expected.protocol_fee + expected.lp_fee);
+    }
+}
+
+#[test]
+fn amm_exact_out_quotes_the_smallest_input() {
+    let (amm, _) = adapter::<DloomAmm>("amm");
+    let params = quote_params(&amm, 7_000_000_000, true, SwapMode::ExactOut);
+    let quote = amm.quote(&params).unwrap();
+    assert!(quote.out_amount >= params.amount);
+
+    let one_less = QuoteParams {
+        amount: quote.in_amount - 1,
+        swap_mode: SwapMode::ExactIn,
+        ..params
+    };
+    assert!(amm.quote(&one_less).unwrap().out_amount < params.amount);
+}
+
+#[test]
+fn amm_swap_accounts_point_at_the_pool() {
+    let (amm, account_map) = adapter::<DloomAmm>("amm");
+    let params = quote_params(&amm, 1_000_000, true, SwapMode::ExactIn);
+    let swap = amm
+        .get_swap_and_account_metas(&swap_params(&params, 1_000_000, 1))
+        .unwrap();
+    assert!(swap.setup_instructions.is_empty());
+
+    let pool = &amm.snapshot().pool;
+    let instruction = swap.instruction();
+    assert_eq!(instruction.program_id, dloom_flow::ID);
+    for key in [
+        amm.key(),
+        pool.token_a_vault,
+        pool.token_b_vault,
+        pool.lp_mint,
+    ] {
+        assert!(instruction.accounts.iter().any(|meta| meta.pubkey == key));
+    }
+    // The token programs come from the recorded mint owners.
+    let token_program = account_map[&pool.token_a_mint].owner;
+    assert!(instruction
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == token_program));
+}
+
+#[test]
+fn dlmm_update_skips_bins_that_were_never_created() {
+    let (dlmm, account_map) = adapter::<DloomDlmm>("dlmm");
+    let recorded_bins = account_map.len() - 3;
+    assert_eq!(dlmm.snapshot().bins.len(), recorded_bins);
+    assert!(dlmm.get_accounts_to_update().len() > account_map.len());
+}
+
+#[test]
+fn dlmm_quotes_match_the_quote_engine_across_transfer_fees() {
+    let (dlmm, _) = adapter::<DloomDlmm>("dlmm");
+    let snapshot = dlmm.snapshot();
+    // One side of the recorded pool is a Token-2022 mint with a transfer fee.
+    assert!(
+        snapshot.token_a_mint.transfer_fee_config.is_some()
+            || snapshot.token_b_mint.transfer_fee_config.is_some()
+    );
+
+    let clock = context().clock_ref.get();
+    for (amount, is_a_to_b) in [(10_000, true), (10_000, false), (1_000_000, true)] {
+        let quote = dlmm
+            .quote(&quote_params(&dlmm, amount, is_a_to_b, SwapMode::ExactIn))
+            .unwrap();
+        let expected = snapshot.quote_swap(amount, is_a_to_b, &clock).unwrap();
+        assert_eq!(quote.out_amount, expected.amount_out);
+        assert_eq!(quote.fee_amount, expected.protocol_fee);
+    }
+}
+
+#[test]
+fn dlmm_swap_lists_the_crossed_bins_in_setup_bins() {
+    let (dlmm, _) = adapter::<DloomDlmm>("dlmm");
+    let amount = 1_000_000;
+    let params = quote_params(&dlmm, amount, true, SwapMode::ExactIn);
+    let expected = dlmm
+        .snapshot()
+        .quote_swap(amount, true, &context().clock_ref.get())
+        .unwrap();
+    assert!(expected.bins_crossed > 0);
+
+    let swap = dlmm
+        .get_swap_and_account_metas(&swap_params(&params, amount, 1))
+        .unwrap();
+    let [setup_bins] = &swap.setup_instructions[..] else {
+        panic!("expected a single setup instruction");
+    };
+    assert_eq!(setup_bins.program_id, dloom_flow::ID);
+    let listed = dloom_flow::instruction::SetupBins::try_from_slice(&setup_bins.data[8..]).unwrap();
+
+    let active_bin_id = dlmm.snapshot().pool.active_bin_id;
+    let expected_bins: Vec<_> = (0..expected.bins_crossed as i32 + EXTRA_SWAP_BINS as i32)
+        .map(|offset| dloom_flow_sdk::pda::bin(&dlmm.key(), active_bin_id - offset).0)
+        .collect();
+    assert_eq!(listed.bin_pubkeys, expected_bins);
+
+    // The bins follow the swap's own accounts, writable, in the same order.
+    let instruction = swap.instruction();
+    let remaining = &instruction.accounts[instruction.accounts.len() - expected_bins.len()..];
+    assert!(remaining
+        .iter()
+        .all(|meta| meta.is_writable && !meta.is_signer));
+    assert_eq!(
+        remaining.iter().map(|meta| meta.pubkey).collect::<Vec<_>>(),
+        expected_bins
+    );
+}
@@ -0,0 +1,299 @@
+// FILE: crates/dloom-flow-quote/src/amm.rs
+
+//! Quotes against a snapshot of an AMM pool. Each quote mirrors the matching quote
+//! instruction step for step.
+
+use crate::{mint::MintSnapshot, AmmAddLiquidityQuote, AmmRemoveLiquidityQuote, AmmSwapQuote};
+use anchor_lang::{prelude::Clock, require, Result};
+use dloom_flow::{
+    amm::{
+        math,
+        state::{AmmPool, CurveType},
+        weighted_math,
+    },
+    constants::MINIMUM_LIQUIDITY,
+    errors::DloomError,
+};
+
+/// An AMM pool together with the accounts its quotes depend on.
+#[derive(Debug, Default)]
+pub struct AmmPoolSnapshot {
+    pub pool: AmmPool,
+    /// The supply of the pool's LP mint.
+    pub lp_supply: u64,
+    pub token_a_mint: MintSnapshot,
+    pub token_b_mint: MintSnapshot,
+}
+
+impl AmmPoolSnapshot {
+    /// Prices a swap exactly as `swap_on_amm` would at `clock`. Mirrors `quote_amm_swap`.
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        is_a_to_b: bool,
+        clock: &Clock,
+    ) -> Result<AmmSwapQuote> {
+        let pool = &self.pool;
+        if pool.curve_type == CurveType::LiquidityBootstrapping {
+            require!(pool.swaps_enabled, DloomError::SwapsDisabled);
+        }
+
+        let (source_mint, destination_mint) = if is_a_to_b {
+            (&self.token_a_mint, &self.token_b_mint)
+        } else {
+            (&self.token_b_mint, &self.token_a_mint)
+        };
+
+        let net_amount_in = source_mint.net_received(clock.epoch, amount_in)?;
+        require!(net_amount_in > 0, DloomError::ZeroAmount);
+        let (amount_out, protocol_fee, lp_fee) = math::calculate_swap_on_curve(
+            pool,
+            net_amount_in,
+            is_a_to_b,
+            source_mint.decimals,
+            destination_mint.decimals,
+            clock.unix_timestamp,
+        )?;
+
+        Ok(AmmSwapQuote {
+            net_amount_in,
+            amount_out: destination_mint.net_received(clock.epoch, amount_out)?,
+            protocol_fee,
+            lp_fee,
+        })
+    }
+
+    /// Sizes a deposit exactly as `add_amm_liquidity` would. Mirrors `quote_add_liquidity`.
+    pub fn quote_add_liquidity(
+        &self,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        clock: &Clock,
+    ) -> Result<AmmAddLiquidityQuote> {
+        require!(
+            amount_a_desired > 0 && amount_b_desired > 0,
+            DloomError::ZeroLiquidity
+        );
+
+        let net_amount_a_desired = self
+            .token_a_mint
+            .net_received(clock.epoch, amount_a_desired)?;
+        let net_amount_b_desired = self
+            .token_b_mint
+            .net_received(clock.epoch, amount_b_desired)?;
+
+        let (amount_a_deposited, amount_b_deposited, total_lp_tokens) =
+            if self.pool.curve_type.is_weighted() {
+                weighted_math::calculate_weighted_lp_tokens_to_mint(
+                    &self.pool,
+                    self.lp_supply,
+                    net_amount_a_desired,
+                    net_amount_b_desired,
+                )?
+            } else {
+                math::calculate_lp_tokens_to_mint(
+                    &self.pool,
+                    self.lp_supply,
+                    net_amount_a_desired,
+                    net_amount_b_desired,
+                )?
+            };
+
+        let lp_tokens_to_lock = if self.lp_supply == 0 {
+            MINIMUM_LIQUIDITY
+        } else {
+            0
+        };
+
+        Ok(AmmAddLiquidityQuote {
+            amount_a_to_send: self
+                .token_a_mint
+                .gross_to_send(clock.epoch, amount_a_deposited)?,
+            amount_b_to_send: self
+                .token_b_mint
+                .gross_to_send(clock.epoch, amount_b_deposited)?,
+            amount_a_deposited,
+            amount_b_deposited,
+            lp_tokens_to_mint: total_lp_tokens
+                .checked_sub(lp_tokens_to_lock)
+                .ok_or(DloomError::InsufficientInitialLiquidity)?,
+        })
+    }
+
+    /// Prices a withdrawal exactly as `remove_amm_liquidity` would. Mirrors
+    /// `quote_remove_liquidity`.
+    pub fn quote_remove_liquidity(
+        &self,
+        lp_tokens_to_burn: u64,
+        clock: &Clock,
+    ) -> Result<AmmRemoveLiquidityQuote> {
+        let pool = &self.pool;
+        if pool.curve_type == CurveType::LiquidityBootstrapping {
+            require!(
+                clock.unix_timestamp >= pool.weight_end_timestamp,
+                DloomError::LaunchNotEnded
+            );
+        }
+
+        let (amount_a_withdrawn, amount_b_withdrawn) = math::calculate_assets_to_withdraw(
+            pool.reserves_a,
+            pool.reserves_b,
+            self.lp_supply,
+            lp_tokens_to_burn,
+        )?;
+
+        Ok(AmmRemoveLiquidityQuote {
+            amount_a_withdrawn,
+            amount_b_withdrawn,
+            amount_a_received: self
+                .token_a_mint
+                .net_received(clock.epoch, amount_a_withdrawn)?,
+            amount_b_received: self
+                .token_b_mint
+                .net_received(clock.epoch, amount_b_withdrawn)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dloom_flow::constants::MAX_AMP;
+
+    fn constant_product_pool() -> AmmPoolSnapshot {
+        AmmPoolSnapshot {
+            pool: AmmPool {
+                fee_rate: 30,
+                protocol_fee_share: 2_000,
+                reserves_a: 1_000_000,
+                reserves_b: 2_000_000,
+                ..Default::default()
+            },
+            lp_supply: 1_414_213,
+            token_a_mint: MintSnapshot::new(6),
+            token_b_mint: MintSnapshot::new(6),
+        }
+    }
+
+    #[test]
+    fn swap_quote_matches_the_program_curve() {
+        let snapshot = constant_product_pool();
+        let quote = snapshot
+            .quote_swap(10_000, true, &Clock::default())
+            .unwrap();
+
+        let (amount_out, protocol_fee, lp_fee) =
+            math::calculate_swap_out_amount(&snapshot.pool, 10_000, 1_000_000, 2_000_000).unwrap();
+        assert_eq!(quote.net_amount_in, 10_000);
+        assert_eq!(quote.amount_out, amount_out);
+        assert_eq!((quote.protocol_fee, quote.lp_fee), (protocol_fee, lp_fee));
+        // 2_000_000 * 9_970 / 1_009_970, after the 0.3% fee.
+        assert_eq!(quote.amount_out, 19_743);
+    }
+
+    #[test]
+    fn swap_quote_applies_transfer_fees_on_both_sides() {
+        let mut snapshot = constant_product_pool();
+        snapshot.token_a_mint = MintSnapshot::with_transfer_fee(6, 100);
+        snapshot.token_b_mint = MintSnapshot::with_transfer_fee(6, 100);
+        let quote = snapshot
+            .quote_swap(10_000, true, &Clock::default())
+            .unwrap();
+
+        let (amount_out, _, _) =
+            math::calculate_swap_out_amount(&snapshot.pool, 9_900, 1_000_000, 2_000_000).unwrap();
+        assert_eq!(quote.net_amount_in, 9_900);
+        assert_eq!(
+            quote.amount_out,
+            snapshot.token_b_mint.net_received(0, amount_out).unwrap()
+        );
+    }
+
+    #[test]
+    fn stable_swap_quote_holds_at_the_top_of_the_amp_range() {
+        // Balances this large overflowed `u128` in the invariant at the maximum amp.
+        for reserves in [10u64.pow(16), u64::MAX / 2] {
+            let snapshot = AmmPoolSnapshot {
+                pool: AmmPool {
+                    curve_type: CurveType::StableSwap,
+                    amp_initial: MAX_AMP,
+                    amp_target: MAX_AMP,
+                    fee_rate: 4,
+                    reserves_a: reserves,
+                    reserves_b: reserves,
+                    ..Default::default()
+                },
+                token_a_mint: MintSnapshot::new(6),
+                token_b_mint: MintSnapshot::new(6),
+                ..Default::default()
+            };
+            let amount_in = 10u64.pow(12);
+            let quote = snapshot
+                .quote_swap(amount_in, true, &Clock::default())
+                .unwrap();
+
+            // A balanced pool this flat trades one for one, less the 0.04% fee.
+            let amount_in_after_fees = amount_in - amount_in * 4 / 10_000;
+            assert!(quote.amount_out < amount_in_after_fees);
+            assert!(quote.amount_out >= amount_in_after_fees - amount_in_after_fees / 100_000);
+        }
+    }
+
+    #[test]
+    fn swap_quote_rejects_a_paused_bootstrapping_pool() {
+        let mut snapshot = constant_product_pool();
+        snapshot.pool.curve_type = CurveType::LiquidityBootstrapping;
+        assert!(snapshot
+            .quote_swap(10_000, true, &Clock::default())
+            .is_err());
+    }
+
+    #[test]
+    fn first_deposit_quote_excludes_the_locked_liquidity() {
+        let mut snapshot = constant_product_pool();
+        snapshot.pool.reserves_a = 0;
+        snapshot.pool.reserves_b = 0;
+        snapshot.lp_supply = 0;
+        let quote = snapshot
+            .quote_add_liquidity(1_000_000, 4_000_000, &Clock::default())
+            .unwrap();
+
+        let (_, _, total_lp_tokens) =
+            math::calculate_lp_tokens_to_mint(&snapshot.pool, 0, 1_000_000, 4_000_000).unwrap();
+        assert_eq!(quote.lp_tokens_to_mint, total_lp_tokens - MINIMUM_LIQUIDITY);
+        assert_eq!(quote.amount_a_to_send, 1_000_000);
+    }
+
+    #[test]
+    fn withdrawal_quote_waits_for_a_bootstrapping_sale_to_end() {
+        let mut snapshot = constant_product_pool();
+        snapshot.pool.curve_type = CurveType::LiquidityBootstrapping;
+        snapshot.pool.weight_end_timestamp = 1_000;
+        let during_sale = Clock {
+            unix_timestamp: 999,
+            ..Clock::default()
+        };
+        let after_sale = Clock {
+            unix_timestamp: 1_000,
+            ..Clock::default()
+        };
+        assert!(snapshot
+            .quote_remove_liquidity(snapshot.lp_supply / 2, &during_sale)
+            .is_err());
+        assert!(snapshot
+            .quote_remove_liquidity(snapshot.lp_supply / 2, &after_sale)
+            .is_ok());
+    }
+
+    #[test]
+    fn withdrawal_quote_is_pro_rata() {
+        let snapshot = constant_product_pool();
+        let quote = snapshot
+            .quote_remove_liquidity(snapshot.lp_supply / 2, &Clock::default())
+            .unwrap();
+        assert_eq!(
+            (quote.amount_a_withdrawn, quote.amount_b_withdrawn),
+            math::calculate_assets_to_withdraw(1_000_000, 2_000_000, 1_414_213, 707_106).unwrap()
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// FILE: crates/dloom-flow-quote/src/dlmm.rs
+
+//! Quotes against a snapshot of a DLMM pool and its bins. The bins are walked by the
+//! program's own `simulate_swap`, reading from the snapshot instead of bin accounts.
+
+use crate::{mint::MintSnapshot, DlmmSwapQuote};
+use anchor_lang::{prelude::Clock, require, Result};
+use dloom_flow::{
+    dlmm::{
+        math::{self, BinSource},
+        state::{Bin, DlmmPool},
+    },
+    errors::DloomError,
+};
+use std::collections::BTreeMap;
+
+/// A DLMM pool together with the accounts its quotes depend on.
+#[derive(Debug, Default)]
+pub struct DlmmPoolSnapshot {
+    pub pool: DlmmPool,
+    /// The pool's bins, keyed by bin id. Only the bins a swap would cross are needed.
+    pub bins: BTreeMap<i32, Bin>,
+    pub token_a_mint: MintSnapshot,
+    pub token_b_mint: MintSnapshot,
+}
+
+impl DlmmPoolSnapshot {
+    /// Prices a swap exactly as `dlmm_swap` would at `clock`, given the same bins. Mirrors
+    /// `quote_dlmm_swap` called with every bin in the snapshot.
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        is_a_to_b: bool,
+        clock: &Clock,
+    ) -> Result<DlmmSwapQuote> {
+        let (source_mint, destination_mint) = if is_a_to_b {
+            (&self.token_a_mint, &self.token_b_mint)
+        } else {
+            (&self.token_b_mint, &self.token_a_mint)
+        };
+
+        let net_amount_in = source_mint.net_received(clock.epoch, amount_in)?;
+        require!(net_amount_in > 0, DloomError::ZeroAmount);
+        let (amount_out, protocol_fee, lp_fee, final_active_bin_id) = math::simulate_swap(
+            &self.pool,
+            net_amount_in,
+            is_a_to_b,
+            self.bins.len(),
+            &mut BinSnapshots(&self.bins),
+        )?;
+
+        Ok(DlmmSwapQuote {
+            net_amount_in,
+            amount_out: destination_mint.net_received(clock.epoch, amount_out)?,
+            protocol_fee,
+            lp_fee,
+            bins_crossed: final_active_bin_id.abs_diff(self.pool.active_bin_id),
+            final_active_bin_id,
+        })
+    }
+}
+
+/// Read-only bins for a quote. The walk visits each bin once, so the writes it makes
+/// never affect the result and are dropped.
+struct BinSnapshots<'a>(&'a BTreeMap<i32, Bin>);
+
+impl BinSource for BinSnapshots<'_> {
+    fn load(&mut self, bin_id: i32) -> Result<Bin> {
+        self.0
+            .get(&bin_id)
+            .copied()
+            .ok_or_else(|| DloomError::BinCacheMismatch.into())
+    }
+
+    fn store(&mut self, _bin_id: i32, _bin: &Bin) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{
+        prelude::{Account, AccountInfo, Pubkey},
+        AccountSerialize, Discriminator,
+    };
+    use dloom_flow::state::TransactionBins;
+    use dloom_flow_sdk::pda;
+
+    const POOL_KEY: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    fn snapshot() -> DlmmPoolSnapshot {
+        let bins = (-5..=5)
+            .map(|bin_id| {
+                let bin = Bin {
+                    liquidity: 1_000_000 + (bin_id + 5) as u128 * 10_000,
+                    fee_growth_per_unit_a: 0,
+                    fee_growth_per_unit_b: 0,
+                };
+                (bin_id, bin)
+            })
+            .collect();
+        DlmmPoolSnapshot {
+            pool: DlmmPool {
+                active_bin_id: 0,
+                bin_step: 10,
+                fee_rate: 30,
+                protocol_fee_share: 2_000,
+                ..Default::default()
+            },
+            bins,
+            token_a_mint: MintSnapshot::new(6),
+            token_b_mint: MintSnapshot::new(6),
+        }
+    }
+
+    /// Builds a program-owned account. Its data is leaked so the account can be handed to
+    /// functions that take `&'info [AccountInfo<'info>]`.
+    fn account(key: Pubkey, data: &[u8]) -> AccountInfo<'static> {
+        // Zero-copy accounts are read in place, so the body after the 8-byte discriminator
+        // must be 16-byte aligned: allocate `u128`s and start the data 8 bytes in.
+        let words = Box::leak(vec![0u128; data.len().div_ceil(16) + 1].into_boxed_slice());
+        let buffer = &mut bytemuck::cast_slice_mut::<u128, u8>(words)[8..8 + data.len()];
+        buffer.copy_from_slice(data);
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(1_000_000_000)),
+            buffer,
+            &dloom_flow::ID,
+            false,
+            0,
+        )
+    }
+
+    fn bin_accounts(snapshot: &DlmmPoolSnapshot) -> &'static [AccountInfo<'static>] {
+        let accounts: Vec<_> = snapshot
+            .bins
+            .iter()
+            .map(|(bin_id, bin)| {
+                let mut data = Bin::DISCRIMINATOR.to_vec();
+                data.extend_from_slice(bytemuck::bytes_of(bin));
+                account(pda::bin(&POOL_KEY, *bin_id).0, &data)
+            })
+            .collect();
+        Box::leak(accounts.into_boxed_slice())
+    }
+
+    /// Swaps inside one bin, across bins, and past the snapshot's last bin.
+    const SWAPS: [(u64, bool); 6] = [
+        (500, true),
+        (400_000, true),
+        (2_500_000, true),
+        (500, false),
+        (1_000_000, false),
+        (3_000_000, false),
+    ];
+
+    /// Compares an off-chain quote with the program's result, including its error.
+    fn assert_same(quote: Result<DlmmSwapQuote>, program: Result<(u64, u64, u64, i32)>) {
+        match (quote, program) {
+            (Ok(quote), Ok(program)) => assert_eq!(
+                (
+                    quote.amount_out,
+                    quote.protocol_fee,
+                    quote.lp_fee,
+                    quote.final_active_bin_id
+                ),
+                program
+            ),
+            (Err(quote), Err(program)) => assert_eq!(quote.to_string(), program.to_string()),
+            (quote, program) => panic!("quote {:?} but program {:?}", quote.is_ok(), program),
+        }
+    }
+
+    #[test]
+    fn swap_quotes_match_the_quote_instruction_walk() {
+        let snapshot = snapshot();
+        let accounts = bin_accounts(&snapshot);
+        for (amount_in, is_a_to_b) in SWAPS {
+            assert_same(
+                snapshot.quote_swap(amount_in, is_a_to_b, &Clock::default()),
+                math::quote_swap(
+                    &snapshot.pool,
+                    amount_in,
+                    is_a_to_b,
+                    accounts,
+                    &dloom_flow::ID,
+                    &POOL_KEY,
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn swap_quote_can_cross_into_the_next_bin() {
+        let quote = snapshot()
+            .quote_swap(1_000_000, false, &Clock::default())
+            .unwrap();
+        assert_eq!(quote.final_active_bin_id, 1);
+        assert_eq!(quote.bins_crossed, 1);
+    }
+
+    #[test]
+    fn swap_quotes_match_committed_swaps() {
+        let snapshot = snapshot();
+        for (amount_in, is_a_to_b) in SWAPS {
+            // Committed swaps write to the bins, so each one gets fresh accounts.
+            let accounts = bin_accounts(&snapshot);
+            let mut data = Vec::new();
+            TransactionBins {
+                owner: Pubkey::new_unique(),
+                bins: accounts.iter().map(|account| *account.key).collect(),
+            }
+            .try_serialize(&mut data)
+            .unwrap();
+            let transaction_bins_info = Box::leak(Box::new(account(Pubkey::new_unique(), &data)));
+            let transaction_bins =
+                Account::<TransactionBins>::try_from(&*transaction_bins_info).unwrap();
+
+            let swap = if is_a_to_b {
+                math::swap_a_to_b
+            } else {
+                math::swap_b_to_a
+            };
+            assert_same(
+                snapshot.quote_swap(amount_in, is_a_to_b, &Clock::default()),
+                swap(
+                    &snapshot.pool,
+                    amount_in,
+                    &transaction_bins,
+                    accounts,
+                    &dloom_flow::ID,
+                    &POOL_KEY,
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn swap_quote_fails_when_it_runs_out_of_bins() {
+        let mut snapshot = snapshot();
+        snapshot.bins.retain(|bin_id, _| *bin_id >= 0);
+        assert!(snapshot
+            .quote_swap(2_500_000, true, &Clock::default())
+            .is_err());
+    }
+}
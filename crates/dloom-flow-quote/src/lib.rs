@@ -0,0 +1,26 @@
+// FILE: crates/dloom-flow-quote/src/lib.rs
+
+//! Off-chain quotes for the dloom-flow program.
+//!
+//! Quotes are computed from snapshots of the program's accounts with the program's own
+//! math, so they match what `swap_on_amm`, `dlmm_swap` and the quote instructions would
+//! return for the same state, without an RPC round-trip.
+//!
+//! - [`mint`] describes a token mint's decimals and Token-2022 transfer fee.
+//! - [`amm`] quotes swaps, deposits and withdrawals against an AMM pool.
+//! - [`dlmm`] quotes swaps against a DLMM pool and its bins.
+//! - [`oracle`] reads TWAPs from an AMM pool's observation ring buffer.
+//!
+//! With the `wasm` feature the crate also exposes JavaScript bindings in `wasm`.
+
+pub mod amm;
+pub mod dlmm;
+pub mod mint;
+pub mod oracle;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use dloom_flow::amm::instructions::quote::{
+    AmmAddLiquidityQuote, AmmRemoveLiquidityQuote, AmmSwapQuote,
+};
+pub use dloom_flow::dlmm::instructions::quote::DlmmSwapQuote;
@@ -0,0 +1,114 @@
+// FILE: crates/dloom-flow-quote/src/mint.rs
+
+//! Mint snapshots and the Token-2022 transfer fees they charge.
+
+use anchor_lang::{error::ErrorCode, Result};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+use dloom_flow::errors::DloomError;
+
+/// The parts of a mint that affect a quote.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MintSnapshot {
+    pub decimals: u8,
+    /// The mint's `TransferFeeConfig`, if it is a Token-2022 mint with transfer fees.
+    pub transfer_fee_config: Option<TransferFeeConfig>,
+}
+
+impl MintSnapshot {
+    /// A mint that never charges a transfer fee.
+    pub fn new(decimals: u8) -> Self {
+        Self {
+            decimals,
+            transfer_fee_config: None,
+        }
+    }
+
+    /// Decodes a mint from its account data. Works for legacy and Token-2022 mints.
+    pub fn unpack(data: &[u8]) -> Result<Self> {
+        let mint = StateWithExtensions::<Mint>::unpack(data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        Ok(Self {
+            decimals: mint.base.decimals,
+            transfer_fee_config: mint.get_extension::<TransferFeeConfig>().ok().copied(),
+        })
+    }
+
+    /// Returns the fee withheld when `amount` is transferred during `epoch`. Mirrors
+    /// `token_extensions::calculate_transfer_fee`.
+    pub fn transfer_fee(&self, epoch: u64, amount: u64) -> Result<u64> {
+        match &self.transfer_fee_config {
+            Some(transfer_fee_config) => transfer_fee_config
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or_else(|| DloomError::MathOverflow.into()),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the amount the recipient is credited when `amount` is sent. Mirrors
+    /// `token_extensions::calculate_net_received`.
+    pub fn net_received(&self, epoch: u64, amount: u64) -> Result<u64> {
+        let fee = self.transfer_fee(epoch, amount)?;
+        amount
+            .checked_sub(fee)
+            .ok_or_else(|| DloomError::MathOverflow.into())
+    }
+
+    /// Returns the amount that must be sent so the recipient is credited `net_amount`.
+    /// Mirrors `token_extensions::calculate_gross_to_send`.
+    pub fn gross_to_send(&self, epoch: u64, net_amount: u64) -> Result<u64> {
+        let fee = match &self.transfer_fee_config {
+            Some(transfer_fee_config) if net_amount > 0 => transfer_fee_config
+                .calculate_inverse_epoch_fee(epoch, net_amount)
+                .ok_or(DloomError::MathOverflow)?,
+            _ => 0,
+        };
+        net_amount
+            .checked_add(fee)
+            .ok_or_else(|| DloomError::MathOverflow.into())
+    }
+}
+
+#[cfg(test)]
+impl MintSnapshot {
+    /// A Token-2022 mint charging `basis_points` on every transfer, with no maximum fee.
+    pub(crate) fn with_transfer_fee(decimals: u8, basis_points: u16) -> Self {
+        use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: u64::MAX.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        Self {
+            decimals,
+            transfer_fee_config: Some(TransferFeeConfig {
+                older_transfer_fee: fee,
+                newer_transfer_fee: fee,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_mint_without_fees_passes_amounts_through() {
+        let mint = MintSnapshot::new(6);
+        assert_eq!(mint.net_received(0, 1_000).unwrap(), 1_000);
+        assert_eq!(mint.gross_to_send(0, 1_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn gross_to_send_inverts_net_received() {
+        let mint = MintSnapshot::with_transfer_fee(6, 100);
+        assert_eq!(mint.net_received(0, 10_000).unwrap(), 9_900);
+        let gross = mint.gross_to_send(0, 9_900).unwrap();
+        assert_eq!(mint.net_received(0, gross).unwrap(), 9_900);
+    }
+}
@@ -0,0 +1,156 @@
+// FILE: crates/dloom-flow-quote/src/oracle.rs
+
+//! Reads an AMM pool's observation ring buffer, as `observe_amm_oracle` does on-chain.
+
+use anchor_lang::{prelude::Clock, require, Result};
+use dloom_flow::{
+    amm::{
+        instructions::observe_oracle::current_observation,
+        state::{AmmObservation, AmmOracle, AmmPool},
+    },
+    errors::DloomError,
+};
+
+/// Returns the pool's cumulative prices as they were `seconds_ago` seconds before
+/// `clock`. Mirrors `observe_amm_oracle`, without its limit on the number of queries.
+pub fn observe(
+    pool: &AmmPool,
+    oracle: &AmmOracle,
+    seconds_ago: &[u32],
+    clock: &Clock,
+) -> Result<Vec<AmmObservation>> {
+    let now = clock.unix_timestamp;
+    let current = current_observation(pool, now)?;
+    seconds_ago
+        .iter()
+        .map(|seconds_ago| oracle.observe(&current, now - *seconds_ago as i64))
+        .collect()
+}
+
+/// The time-weighted average `(price_a, price_b)` over the `window` seconds up to
+/// `clock`, scaled by 1e9.
+pub fn twap(
+    pool: &AmmPool,
+    oracle: &AmmOracle,
+    window: u32,
+    clock: &Clock,
+) -> Result<(u128, u128)> {
+    require!(window > 0, DloomError::InvalidOracleQuery);
+    let observations = observe(pool, oracle, &[window, 0], clock)?;
+    let (start, end) = (&observations[0], &observations[1]);
+    let average = |start: u128, end: u128| {
+        end.checked_sub(start)
+            .map(|accrued| accrued / window as u128)
+            .ok_or(DloomError::MathOverflow)
+    };
+    Ok((
+        average(start.price_a_cumulative, end.price_a_cumulative)?,
+        average(start.price_b_cumulative, end.price_b_cumulative)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRICE: u128 = 1_000_000_000;
+
+    /// An oracle with `cardinality_next` slots, initialized at t = 100.
+    fn oracle(cardinality_next: u16) -> AmmOracle {
+        let mut oracle = AmmOracle {
+            cardinality: 1,
+            cardinality_next,
+            observations: vec![AmmObservation::default(); cardinality_next as usize],
+            ..Default::default()
+        };
+        oracle.observations[0].timestamp = 100;
+        oracle
+    }
+
+    /// A pool priced at 2 whose oracle was last updated at `timestamp`.
+    fn pool(timestamp: i64, price_a_cumulative: u128) -> AmmPool {
+        AmmPool {
+            reserves_a: 1_000_000,
+            reserves_b: 2_000_000,
+            price_a_cumulative,
+            last_update_timestamp: timestamp,
+            has_oracle: true,
+            ..Default::default()
+        }
+    }
+
+    fn clock(unix_timestamp: i64) -> Clock {
+        Clock {
+            unix_timestamp,
+            ..Default::default()
+        }
+    }
+
+    /// Priced at 2 from t = 100 to 200, then at 4 until 300.
+    fn observed_oracle() -> AmmOracle {
+        let mut oracle = oracle(3);
+        oracle.write(200, 200 * PRICE, 0);
+        oracle.write(300, 600 * PRICE, 0);
+        oracle
+    }
+
+    fn price_a_cumulatives(observations: &[AmmObservation]) -> Vec<u128> {
+        observations
+            .iter()
+            .map(|observation| observation.price_a_cumulative)
+            .collect()
+    }
+
+    #[test]
+    fn interpolates_between_observations_and_extrapolates_from_the_pool() {
+        let (pool, oracle) = (pool(300, 600 * PRICE), observed_oracle());
+        let observations = observe(&pool, &oracle, &[0, 100, 150, 300], &clock(400)).unwrap();
+        assert_eq!(
+            price_a_cumulatives(&observations),
+            [800 * PRICE, 600 * PRICE, 400 * PRICE, 0]
+        );
+        assert_eq!(observations[2].timestamp, 250);
+
+        assert!(observe(&pool, &oracle, &[301], &clock(400)).is_err());
+    }
+
+    #[test]
+    fn averages_prices_over_a_window() {
+        let (pool, oracle) = (pool(300, 600 * PRICE), observed_oracle());
+        assert_eq!(twap(&pool, &oracle, 200, &clock(400)).unwrap().0, 3 * PRICE);
+        assert_eq!(twap(&pool, &oracle, 50, &clock(400)).unwrap().0, 2 * PRICE);
+        assert!(twap(&pool, &oracle, 0, &clock(400)).is_err());
+    }
+
+    #[test]
+    fn writes_at_most_once_per_second() {
+        let mut oracle = observed_oracle();
+        oracle.write(300, 700 * PRICE, 0);
+        assert_eq!((oracle.index, oracle.cardinality), (2, 3));
+        assert_eq!(oracle.observations[2].price_a_cumulative, 600 * PRICE);
+    }
+
+    #[test]
+    fn grows_into_new_slots_once_the_buffer_wraps() {
+        let mut oracle = oracle(2);
+        oracle.write(200, 200 * PRICE, 0);
+        assert_eq!((oracle.index, oracle.cardinality), (1, 2));
+
+        // As `grow_amm_oracle` does.
+        oracle.observations.resize(3, AmmObservation::default());
+        oracle.cardinality_next = 3;
+        oracle.write(300, 600 * PRICE, 0);
+        assert_eq!((oracle.index, oracle.cardinality), (2, 3));
+
+        // The oldest observation is overwritten once every slot is in use.
+        oracle.write(400, 800 * PRICE, 0);
+        assert_eq!(oracle.index, 0);
+        let pool = pool(400, 800 * PRICE);
+        let observations = observe(&pool, &oracle, &[200, 150, 0], &clock(400)).unwrap();
+        assert_eq!(
+            price_a_cumulatives(&observations),
+            [200 * PRICE, 400 * PRICE, 800 * PRICE]
+        );
+        assert!(observe(&pool, &oracle, &[201], &clock(400)).is_err());
+    }
+}
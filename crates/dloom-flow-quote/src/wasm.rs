@@ -0,0 +1,187 @@
+// FILE: crates/dloom-flow-quote/src/wasm.rs
+
+//! JavaScript bindings, built with `wasm-pack build --features wasm`.
+//!
+//! Pools are constructed from raw account data as returned by `getAccountInfo`, and
+//! quotes are returned as plain objects whose amounts are `bigint`s.
+
+use crate::{amm::AmmPoolSnapshot, dlmm::DlmmPoolSnapshot, mint::MintSnapshot};
+use anchor_lang::prelude::Clock;
+use dloom_flow_sdk::accounts;
+use wasm_bindgen::prelude::*;
+
+fn to_js_error(error: anchor_lang::error::Error) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn clock(unix_timestamp: i64, epoch: u64) -> Clock {
+    Clock {
+        unix_timestamp,
+        epoch,
+        ..Default::default()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct SwapQuote {
+    pub net_amount_in: u64,
+    pub amount_out: u64,
+    pub protocol_fee: u64,
+    /// Always zero for DLMM swaps, whose LP fees accrue to the bins.
+    pub lp_fee: u64,
+    /// Zero for AMM swaps.
+    pub bins_crossed: u32,
+    /// The pool's active bin after a DLMM swap. Zero for AMM swaps.
+    pub final_active_bin_id: i32,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct AddLiquidityQuote {
+    pub amount_a_to_send: u64,
+    pub amount_b_to_send: u64,
+    pub amount_a_deposited: u64,
+    pub amount_b_deposited: u64,
+    pub lp_tokens_to_mint: u64,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct RemoveLiquidityQuote {
+    pub amount_a_withdrawn: u64,
+    pub amount_b_withdrawn: u64,
+    pub amount_a_received: u64,
+    pub amount_b_received: u64,
+}
+
+/// An AMM pool snapshot for quoting.
+#[wasm_bindgen]
+pub struct AmmPool(AmmPoolSnapshot);
+
+#[wasm_bindgen]
+impl AmmPool {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        pool_data: &[u8],
+        lp_supply: u64,
+        token_a_mint_data: &[u8],
+        token_b_mint_data: &[u8],
+    ) -> Result<AmmPool, JsError> {
+        Ok(Self(AmmPoolSnapshot {
+            pool: accounts::amm_pool(pool_data).map_err(to_js_error)?,
+            lp_supply,
+            token_a_mint: MintSnapshot::unpack(token_a_mint_data).map_err(to_js_error)?,
+            token_b_mint: MintSnapshot::unpack(token_b_mint_data).map_err(to_js_error)?,
+        }))
+    }
+
+    #[wasm_bindgen(js_name = quoteSwap)]
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        is_a_to_b: bool,
+        unix_timestamp: i64,
+        epoch: u64,
+    ) -> Result<SwapQuote, JsError> {
+        let quote = self
+            .0
+            .quote_swap(amount_in, is_a_to_b, &clock(unix_timestamp, epoch))
+            .map_err(to_js_error)?;
+        Ok(SwapQuote {
+            net_amount_in: quote.net_amount_in,
+            amount_out: quote.amount_out,
+            protocol_fee: quote.protocol_fee,
+            lp_fee: quote.lp_fee,
+            bins_crossed: 0,
+            final_active_bin_id: 0,
+        })
+    }
+
+    #[wasm_bindgen(js_name = quoteAddLiquidity)]
+    pub fn quote_add_liquidity(
+        &self,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        epoch: u64,
+    ) -> Result<AddLiquidityQuote, JsError> {
+        let quote = self
+            .0
+            .quote_add_liquidity(amount_a_desired, amount_b_desired, &clock(0, epoch))
+            .map_err(to_js_error)?;
+        Ok(AddLiquidityQuote {
+            amount_a_to_send: quote.amount_a_to_send,
+            amount_b_to_send: quote.amount_b_to_send,
+            amount_a_deposited: quote.amount_a_deposited,
+            amount_b_deposited: quote.amount_b_deposited,
+            lp_tokens_to_mint: quote.lp_tokens_to_mint,
+        })
+    }
+
+    #[wasm_bindgen(js_name = quoteRemoveLiquidity)]
+    pub fn quote_remove_liquidity(
+        &self,
+        lp_tokens_to_burn: u64,
+        epoch: u64,
+    ) -> Result<RemoveLiquidityQuote, JsError> {
+        let quote = self
+            .0
+            .quote_remove_liquidity(lp_tokens_to_burn, &clock(0, epoch))
+            .map_err(to_js_error)?;
+        Ok(RemoveLiquidityQuote {
+            amount_a_withdrawn: quote.amount_a_withdrawn,
+            amount_b_withdrawn: quote.amount_b_withdrawn,
+            amount_a_received: quote.amount_a_received,
+            amount_b_received: quote.amount_b_received,
+        })
+    }
+}
+
+/// A DLMM pool snapshot for quoting. Add the bins a swap may cross with `addBin`.
+#[wasm_bindgen]
+pub struct DlmmPool(DlmmPoolSnapshot);
+
+#[wasm_bindgen]
+impl DlmmPool {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        pool_data: &[u8],
+        token_a_mint_data: &[u8],
+        token_b_mint_data: &[u8],
+    ) -> Result<DlmmPool, JsError> {
+        Ok(Self(DlmmPoolSnapshot {
+            pool: accounts::dlmm_pool(pool_data).map_err(to_js_error)?,
+            bins: Default::default(),
+            token_a_mint: MintSnapshot::unpack(token_a_mint_data).map_err(to_js_error)?,
+            token_b_mint: MintSnapshot::unpack(token_b_mint_data).map_err(to_js_error)?,
+        }))
+    }
+
+    #[wasm_bindgen(js_name = addBin)]
+    pub fn add_bin(&mut self, bin_id: i32, bin_data: &[u8]) -> Result<(), JsError> {
+        let bin = accounts::bin(bin_data).map_err(to_js_error)?;
+        self.0.bins.insert(bin_id, bin);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = quoteSwap)]
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        is_a_to_b: bool,
+        epoch: u64,
+    ) -> Result<SwapQuote, JsError> {
+        let quote = self
+            .0
+            .quote_swap(amount_in, is_a_to_b, &clock(0, epoch))
+            .map_err(to_js_error)?;
+        Ok(SwapQuote {
+            net_amount_in: quote.net_amount_in,
+            amount_out: quote.amount_out,
+            protocol_fee: quote.protocol_fee,
+            lp_fee: 0,
+            bins_crossed: quote.bins_crossed,
+            final_active_bin_id: quote.final_active_bin_id,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+// FILE: crates/dloom-flow-sdk/src/accounts.rs
+
+//! Decoders for every account type owned by the dloom-flow program.
+//!
+//! Each decoder takes the raw account data, including the 8-byte discriminator, and
+//! fails if the discriminator does not match the expected type.
+
+use anchor_lang::{error::ErrorCode, AccountDeserialize, Discriminator, Result};
+use dloom_flow::{
+    amm::state::{AmmOracle, AmmPool, AmmPosition},
+    bonding_curve::state::BondingCurve,
+    dlmm::state::{Bin, DlmmPool, Position},
+    state::{DlmmParameters, ProtocolConfig, TransactionBins},
+};
+
+fn decode<T: AccountDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut data)
+}
+
+pub fn protocol_config(data: &[u8]) -> Result<ProtocolConfig> {
+    decode(data)
+}
+
+pub fn dlmm_parameters(data: &[u8]) -> Result<DlmmParameters> {
+    decode(data)
+}
+
+pub fn transaction_bins(data: &[u8]) -> Result<TransactionBins> {
+    decode(data)
+}
+
+pub fn amm_pool(data: &[u8]) -> Result<AmmPool> {
+    decode(data)
+}
+
+pub fn amm_position(data: &[u8]) -> Result<AmmPosition> {
+    decode(data)
+}
+
+pub fn amm_oracle(data: &[u8]) -> Result<AmmOracle> {
+    decode(data)
+}
+
+pub fn dlmm_pool(data: &[u8]) -> Result<DlmmPool> {
+    decode(data)
+}
+
+pub fn position(data: &[u8]) -> Result<Position> {
+    decode(data)
+}
+
+pub fn bonding_curve(data: &[u8]) -> Result<BondingCurve> {
+    decode(data)
+}
+
+/// Decodes a bin. Bins are zero-copy accounts, so their data is read in place rather
+/// than Borsh-deserialized.
+pub fn bin(data: &[u8]) -> Result<Bin> {
+    let discriminator = Bin::DISCRIMINATOR;
+    if data.len() < discriminator.len() {
+        return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+    }
+    if &data[..discriminator.len()] != discriminator {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let body = &data[discriminator.len()..];
+    let size = std::mem::size_of::<Bin>();
+    if body.len() < size {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok(bytemuck::pod_read_unaligned(&body[..size]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{prelude::Pubkey, AccountSerialize};
+
+    #[test]
+    fn round_trips_a_borsh_account() {
+        let account = TransactionBins {
+            owner: Pubkey::new_from_array([1; 32]),
+            bins: vec![Pubkey::new_from_array([2; 32])],
+        };
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+
+        let decoded = transaction_bins(&data).unwrap();
+        assert_eq!(decoded.owner, account.owner);
+        assert_eq!(decoded.bins, account.bins);
+    }
+
+    #[test]
+    fn rejects_data_of_another_account_type() {
+        let mut data = Vec::new();
+        TransactionBins::default().try_serialize(&mut data).unwrap();
+        assert!(amm_pool(&data).is_err());
+        assert!(bin(&data).is_err());
+    }
+
+    #[test]
+    fn reads_a_zero_copy_bin() {
+        let mut data = Bin::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u128.to_le_bytes());
+        data.extend_from_slice(&8u128.to_le_bytes());
+        data.extend_from_slice(&9u128.to_le_bytes());
+
+        let decoded = bin(&data).unwrap();
+        assert_eq!(decoded.liquidity, 7);
+        assert_eq!(decoded.fee_growth_per_unit_a, 8);
+        assert_eq!(decoded.fee_growth_per_unit_b, 9);
+    }
+}
@@ -0,0 +1,271 @@
+// FILE: crates/dloom-flow-sdk/src/instructions.rs
+
+//! Builders for every instruction of the dloom-flow program.
+//!
+//! Each builder takes the instruction's accounts as the program's generated
+//! `dloom_flow::accounts` struct (optional accounts are `Option<Pubkey>`) followed by the
+//! instruction's arguments. Instructions that read remaining accounts, such as bins,
+//! transfer-hook accounts or route hops, take them by extending the returned
+//! instruction's `accounts`.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::{AccountMeta, Instruction},
+    InstructionData, ToAccountMetas,
+};
+use dloom_flow::{
+    amm::{instructions::LbpParams, state::FeePreference},
+    bonding_curve::state::GraduationFees,
+    instructions::{RouteHop, SplitLeg},
+    state::DlmmParameter,
+    ParameterAction, ParameterList,
+};
+
+macro_rules! instruction_builders {
+    ($($name:ident => $accounts:ident, $data:ident { $($arg:ident: $ty:ty),* $(,)? };)*) => {
+        $(
+            #[doc = concat!("Builds a `", stringify!($name), "` instruction.")]
+            pub fn $name(accounts: dloom_flow::accounts::$accounts, $($arg: $ty),*) -> Instruction {
+                Instruction {
+                    program_id: dloom_flow::ID,
+                    accounts: accounts.to_account_metas(None),
+                    data: dloom_flow::instruction::$data { $($arg),* }.data(),
+                }
+            }
+        )*
+    };
+}
+
+instruction_builders! {
+    // --- Protocol Admin ---
+    initialize_protocol => InitializeProtocol, InitializeProtocol {};
+    update_dlmm_fees => UpdateDlmmFees, UpdateDlmmFees { new_fee_rate: Option<u16> };
+    initialize_dlmm_parameters => InitializeDlmmParameters, InitializeDlmmParameters {
+        official_params: Vec<DlmmParameter>,
+        community_params: Vec<DlmmParameter>,
+    };
+    update_dlmm_parameters => UpdateDlmmParameters, UpdateDlmmParameters {
+        list: ParameterList,
+        action: ParameterAction,
+        bin_step: u16,
+        fee_rate: u16,
+    };
+    update_mint_allowlist => UpdateMintAllowlist, UpdateMintAllowlist {
+        action: ParameterAction,
+        mint: Pubkey,
+    };
+    update_fee_preference => UpdateFeePreference, UpdateFeePreference {
+        new_preference: FeePreference,
+    };
+    update_amm_fees => UpdateAmmFees, UpdateAmmFees { new_fee_rate: Option<u16> };
+
+    // --- AMM ---
+    create_amm_pool => CreateAmmPool, CreateAmmPool {
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+    };
+    create_stable_amm_pool => CreateStableAmmPool, CreateStableAmmPool {
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        amp: u64,
+    };
+    create_weighted_amm_pool => CreateWeightedAmmPool, CreateWeightedAmmPool {
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        weight_a: u16,
+    };
+    create_lbp_amm_pool => CreateLbpAmmPool, CreateLbpAmmPool {
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        params: LbpParams,
+    };
+    set_lbp_swaps_enabled => SetLbpSwapsEnabled, SetLbpSwapsEnabled { enabled: bool };
+    withdraw_lbp_proceeds => WithdrawLbpProceeds, WithdrawLbpProceeds {};
+    ramp_amm_amp => RampAmmAmp, RampAmmAmp { target_amp: u64, ramp_end_timestamp: i64 };
+    open_amm_position => OpenAmmPosition, OpenAmmPosition { fee_preference: FeePreference };
+    add_amm_liquidity => AddAmmLiquidity, AddAmmLiquidity {
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        min_lp_tokens_to_mint: u64,
+    };
+    swap_on_amm => SwapOnAmm, SwapOnAmm { amount_in: u64, min_amount_out: u64 };
+    flash_swap_on_amm => SwapOnAmm, FlashSwapOnAmm {
+        amount_in: u64,
+        min_amount_out: u64,
+        callback_data: Vec<u8>,
+    };
+    route_swap => RouteSwap, RouteSwap { amount_in: u64, min_amount_out: u64, hops: Vec<RouteHop> };
+    split_swap => SplitSwap, SplitSwap { min_amount_out: u64, legs: Vec<SplitLeg> };
+    quote_amm_swap => QuoteAmm, QuoteAmmSwap { amount_in: u64, is_a_to_b: bool };
+    quote_add_liquidity => QuoteAmm, QuoteAddLiquidity {
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+    };
+    quote_remove_liquidity => QuoteAmm, QuoteRemoveLiquidity { lp_tokens_to_burn: u64 };
+    remove_amm_liquidity => RemoveAmmLiquidity, RemoveAmmLiquidity {
+        lp_tokens_to_burn: u64,
+        min_amount_a_to_receive: u64,
+        min_amount_b_to_receive: u64,
+    };
+    claim_lp_fees => ClaimLpFees, ClaimLpFees {};
+    reinvest_lp_fees => ReinvestLpFees, ReinvestLpFees {};
+    sync_amm_reserves => SyncAmmReserves, SyncAmmReserves {};
+    skim_amm_reserves => SkimAmmReserves, SkimAmmReserves {};
+    amm_flash_loan => AmmFlashLoan, AmmFlashLoan {
+        amount_a: u64,
+        amount_b: u64,
+        callback_data: Vec<u8>,
+    };
+    initialize_amm_oracle => InitializeAmmOracle, InitializeAmmOracle { cardinality: u16 };
+    grow_amm_oracle => GrowAmmOracle, GrowAmmOracle { cardinality_next: u16 };
+    observe_amm_oracle => ObserveAmmOracle, ObserveAmmOracle { seconds_ago: Vec<u32> };
+
+    // --- DLMM ---
+    create_dlmm_pool => CreateDlmmPool, CreateDlmmPool {
+        bin_step: u16,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        initial_bin_id: i32,
+    };
+    create_dlmm_community_pool => CreateDlmmCommunityPool, CreateDlmmCommunityPool {
+        bin_step: u16,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        initial_bin_id: i32,
+    };
+    dlmm_open_position => DlmmOpenPosition, DlmmOpenPosition {
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+    };
+    dlmm_add_liquidity => DlmmAddLiquidity, DlmmAddLiquidity {
+        start_bin_id: i32,
+        liquidity_per_bin: u128,
+    };
+    setup_bins => SetupBins, SetupBins { bin_pubkeys: Vec<Pubkey> };
+    dlmm_swap => DlmmSwap, DlmmSwap { amount_in: u64, min_amount_out: u64 };
+    quote_dlmm_swap => QuoteDlmmSwap, QuoteDlmmSwap { amount_in: u64, is_a_to_b: bool };
+    dlmm_remove_liquidity => DlmmRemoveLiquidity, DlmmRemoveLiquidity {
+        liquidity_to_remove: u128,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    };
+    dlmm_modify_liquidity => DlmmModifyLiquidity, DlmmModifyLiquidity {
+        min_surplus_a_out: u64,
+        min_surplus_b_out: u64,
+    };
+    dlmm_burn_empty_position => DlmmBurnEmptyPosition, DlmmBurnEmptyPosition {};
+    dlmm_sync_reserves => DlmmSyncReserves, DlmmSyncReserves {};
+    dlmm_skim_reserves => DlmmSkimReserves, DlmmSkimReserves {};
+    dlmm_flash_loan => DlmmFlashLoan, DlmmFlashLoan {
+        amount_a: u64,
+        amount_b: u64,
+        callback_data: Vec<u8>,
+    };
+
+    // --- Bonding Curves ---
+    create_bonding_curve => CreateBondingCurve, CreateBondingCurve {
+        virtual_token_reserves: u64,
+        virtual_quote_reserves: u64,
+        token_amount: u64,
+        graduation_quote_threshold: u64,
+        fee_rate: u16,
+        graduation_fees: GraduationFees,
+    };
+    buy_on_bonding_curve => TradeOnBondingCurve, BuyOnBondingCurve {
+        quote_in: u64,
+        min_tokens_out: u64,
+    };
+    sell_on_bonding_curve => TradeOnBondingCurve, SellOnBondingCurve {
+        tokens_in: u64,
+        min_quote_out: u64,
+    };
+    graduate_bonding_curve_to_amm => GraduateBondingCurveToAmm, GraduateBondingCurveToAmm {};
+    graduate_bonding_curve_to_dlmm => GraduateBondingCurveToDlmm, GraduateBondingCurveToDlmm {
+        bin_step: u16,
+        initial_bin_id: i32,
+    };
+}
+
+/// Appends `remaining_accounts` to an instruction built by this module.
+pub fn with_remaining_accounts(
+    mut instruction: Instruction,
+    remaining_accounts: impl IntoIterator<Item = AccountMeta>,
+) -> Instruction {
+    instruction.accounts.extend(remaining_accounts);
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn swap_accounts(referrer_fee_account: Option<Pubkey>) -> dloom_flow::accounts::SwapOnAmm {
+        let key = |byte| Pubkey::new_from_array([byte; 32]);
+        dloom_flow::accounts::SwapOnAmm {
+            trader: key(1),
+            amm_pool: key(2),
+            lp_mint: key(3),
+            token_a_mint: key(4),
+            token_b_mint: key(5),
+            user_source_token_account: Some(key(6)),
+            user_destination_token_account: Some(key(7)),
+            wsol_account: None,
+            token_a_vault: key(8),
+            token_b_vault: key(9),
+            protocol_fee_vault_a: key(10),
+            protocol_fee_vault_b: key(11),
+            authority: key(12),
+            referrer_fee_account,
+            callback_program: None,
+            token_a_program: key(13),
+            token_b_program: key(14),
+            system_program: None,
+            amm_oracle: None,
+        }
+    }
+
+    #[test]
+    fn builds_the_instruction_data_the_program_expects() {
+        let instruction = swap_on_amm(swap_accounts(None), 1_000, 990);
+
+        assert_eq!(instruction.program_id, dloom_flow::ID);
+        assert_eq!(
+            &instruction.data[..8],
+            dloom_flow::instruction::SwapOnAmm::DISCRIMINATOR
+        );
+        assert_eq!(&instruction.data[8..16], &1_000u64.to_le_bytes());
+        assert_eq!(&instruction.data[16..24], &990u64.to_le_bytes());
+    }
+
+    #[test]
+    fn passes_omitted_optional_accounts_as_the_program_id() {
+        let instruction = swap_on_amm(swap_accounts(None), 1, 0);
+
+        assert_eq!(instruction.accounts.len(), 19);
+        assert!(instruction.accounts[0].is_signer);
+        assert_eq!(instruction.accounts[7].pubkey, dloom_flow::ID);
+        assert_eq!(instruction.accounts[13].pubkey, dloom_flow::ID);
+
+        let referrer = Pubkey::new_from_array([15; 32]);
+        let instruction = swap_on_amm(swap_accounts(Some(referrer)), 1, 0);
+        assert_eq!(instruction.accounts[13].pubkey, referrer);
+        assert!(instruction.accounts[13].is_writable);
+    }
+
+    #[test]
+    fn appends_remaining_accounts() {
+        let bin = Pubkey::new_from_array([16; 32]);
+        let instruction = with_remaining_accounts(
+            swap_on_amm(swap_accounts(None), 1, 0),
+            [AccountMeta::new(bin, false)],
+        );
+        assert_eq!(instruction.accounts.last().unwrap().pubkey, bin);
+    }
+}
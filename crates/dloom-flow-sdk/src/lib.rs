@@ -0,0 +1,13 @@
+// FILE: crates/dloom-flow-sdk/src/lib.rs
+
+//! Client SDK for the dloom-flow program.
+//!
+//! - [`pda`] derives every program address from its seeds.
+//! - [`instructions`] builds every instruction in the program.
+//! - [`accounts`] decodes every account type the program owns.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+pub use dloom_flow::ID as PROGRAM_ID;
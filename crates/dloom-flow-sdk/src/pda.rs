@@ -0,0 +1,260 @@
+// FILE: crates/dloom-flow-sdk/src/pda.rs
+
+//! Program-derived addresses used by the dloom-flow program.
+//!
+//! Every function returns the address together with its bump, exactly as
+//! `Pubkey::find_program_address` does for the program's own seeds.
+
+use anchor_lang::prelude::Pubkey;
+use dloom_flow::amm::state::CurveType;
+
+/// Orders two mints the way pools store them: `token_a_mint` is the smaller key.
+pub fn sort_mints(mint_x: Pubkey, mint_y: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_x <= mint_y {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    }
+}
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &dloom_flow::ID)
+}
+
+// --- Protocol ---
+
+pub fn protocol_config() -> (Pubkey, u8) {
+    find(&[b"protocol_config"])
+}
+
+pub fn dlmm_parameters() -> (Pubkey, u8) {
+    find(&[b"dlmm_parameters"])
+}
+
+/// The per-owner account listing the bins a DLMM instruction may touch.
+pub fn transaction_bins(owner: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"transaction_bins", owner.as_ref()])
+}
+
+/// The temporary wrapped SOL account used when a native SOL side is paid in lamports.
+pub fn wsol_account(owner: &Pubkey) -> (Pubkey, u8) {
+    find(&[dloom_flow::native_sol::WSOL_SEED, owner.as_ref()])
+}
+
+/// Signs the self-CPIs that carry events when the program is built with `event-cpi`.
+pub fn event_authority() -> (Pubkey, u8) {
+    find(&[b"__event_authority"])
+}
+
+// --- Vaults ---
+
+/// A token vault of an AMM pool, DLMM pool or bonding curve.
+pub fn vault(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"vault", pool.as_ref(), mint.as_ref()])
+}
+
+/// A protocol fee vault of an AMM pool, DLMM pool or bonding curve.
+pub fn protocol_fee_vault(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"protocol_fee_vault", pool.as_ref(), mint.as_ref()])
+}
+
+// --- AMM ---
+
+/// The AMM pool for a pair, fee tier and curve type. The mints must already be sorted;
+/// see [`sort_mints`].
+pub fn amm_pool(
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    fee_tier: u16,
+    curve_type: CurveType,
+) -> (Pubkey, u8) {
+    find(&[
+        b"amm_pool",
+        token_a_mint.as_ref(),
+        token_b_mint.as_ref(),
+        &fee_tier.to_le_bytes(),
+        &curve_type.seed(),
+    ])
+}
+
+pub fn lp_mint(amm_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"lp_mint", amm_pool.as_ref()])
+}
+
+/// The vault holding the minimum liquidity locked by an AMM pool's first deposit.
+pub fn lp_lock_vault(amm_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"lp_lock", amm_pool.as_ref()])
+}
+
+pub fn amm_position(owner: &Pubkey, amm_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"amm_position", owner.as_ref(), amm_pool.as_ref()])
+}
+
+/// The observation ring buffer of an AMM pool's oracle, once initialized.
+pub fn amm_oracle(amm_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"amm_oracle", amm_pool.as_ref()])
+}
+
+// --- DLMM ---
+
+/// The DLMM pool for a pair and bin step. The mints must already be sorted; see
+/// [`sort_mints`].
+pub fn dlmm_pool(token_a_mint: &Pubkey, token_b_mint: &Pubkey, bin_step: u16) -> (Pubkey, u8) {
+    find(&[
+        b"dlmm_pool",
+        token_a_mint.as_ref(),
+        token_b_mint.as_ref(),
+        &bin_step.to_le_bytes(),
+    ])
+}
+
+pub fn bin(dlmm_pool: &Pubkey, bin_id: i32) -> (Pubkey, u8) {
+    find(&[b"bin", dlmm_pool.as_ref(), &bin_id.to_le_bytes()])
+}
+
+/// The position account for a DLMM position NFT.
+pub fn position(position_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"position", position_mint.as_ref()])
+}
+
+// --- Bonding Curves ---
+
+pub fn bonding_curve(token_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"bonding_curve", token_mint.as_ref()])
+}
+
+/// Every address belonging to one AMM pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmmPoolAddresses {
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_lock_vault: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub protocol_fee_vault_a: Pubkey,
+    pub protocol_fee_vault_b: Pubkey,
+    pub oracle: Pubkey,
+}
+
+impl AmmPoolAddresses {
+    /// Derives the pool's addresses. The mints may be given in either order.
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey, fee_tier: u16, curve_type: CurveType) -> Self {
+        let (token_a_mint, token_b_mint) = sort_mints(mint_x, mint_y);
+        let pool = amm_pool(&token_a_mint, &token_b_mint, fee_tier, curve_type).0;
+        Self {
+            token_a_mint,
+            token_b_mint,
+            pool,
+            lp_mint: lp_mint(&pool).0,
+            lp_lock_vault: lp_lock_vault(&pool).0,
+            token_a_vault: vault(&pool, &token_a_mint).0,
+            token_b_vault: vault(&pool, &token_b_mint).0,
+            protocol_fee_vault_a: protocol_fee_vault(&pool, &token_a_mint).0,
+            protocol_fee_vault_b: protocol_fee_vault(&pool, &token_b_mint).0,
+            oracle: amm_oracle(&pool).0,
+        }
+    }
+}
+
+/// Every address belonging to one DLMM pool, apart from its bins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DlmmPoolAddresses {
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub pool: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub protocol_fee_vault_a: Pubkey,
+    pub protocol_fee_vault_b: Pubkey,
+}
+
+impl DlmmPoolAddresses {
+    /// Derives the pool's addresses. The mints may be given in either order.
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey, bin_step: u16) -> Self {
+        let (token_a_mint, token_b_mint) = sort_mints(mint_x, mint_y);
+        let pool = dlmm_pool(&token_a_mint, &token_b_mint, bin_step).0;
+        Self {
+            token_a_mint,
+            token_b_mint,
+            pool,
+            token_a_vault: vault(&pool, &token_a_mint).0,
+            token_b_vault: vault(&pool, &token_b_mint).0,
+            protocol_fee_vault_a: protocol_fee_vault(&pool, &token_a_mint).0,
+            protocol_fee_vault_b: protocol_fee_vault(&pool, &token_b_mint).0,
+        }
+    }
+
+    /// The addresses of the bins from `lower_bin_id` to `upper_bin_id`, inclusive.
+    pub fn bins(&self, lower_bin_id: i32, upper_bin_id: i32) -> Vec<Pubkey> {
+        (lower_bin_id..=upper_bin_id)
+            .map(|bin_id| bin(&self.pool, bin_id).0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_mints_puts_the_smaller_key_first() {
+        let low = Pubkey::new_from_array([1; 32]);
+        let high = Pubkey::new_from_array([2; 32]);
+        assert_eq!(sort_mints(high, low), (low, high));
+        assert_eq!(sort_mints(low, high), (low, high));
+    }
+
+    #[test]
+    fn amm_pool_addresses_match_the_program_seeds() {
+        let mint_a = Pubkey::new_from_array([1; 32]);
+        let mint_b = Pubkey::new_from_array([2; 32]);
+        let addresses = AmmPoolAddresses::new(mint_b, mint_a, 25, CurveType::StableSwap);
+
+        let (pool, _) = Pubkey::find_program_address(
+            &[
+                b"amm_pool",
+                mint_a.as_ref(),
+                mint_b.as_ref(),
+                &25u16.to_le_bytes(),
+                &[1],
+            ],
+            &dloom_flow::ID,
+        );
+        assert_eq!(addresses.pool, pool);
+        assert_eq!(addresses.token_a_mint, mint_a);
+        assert_eq!(
+            addresses.token_b_vault,
+            Pubkey::find_program_address(
+                &[b"vault", pool.as_ref(), mint_b.as_ref()],
+                &dloom_flow::ID
+            )
+            .0
+        );
+        assert_ne!(addresses.token_a_vault, addresses.protocol_fee_vault_a);
+        assert_eq!(
+            addresses.oracle,
+            Pubkey::find_program_address(&[b"amm_oracle", pool.as_ref()], &dloom_flow::ID).0
+        );
+    }
+
+    #[test]
+    fn bins_are_derived_from_little_endian_ids() {
+        let addresses = DlmmPoolAddresses::new(
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            10,
+        );
+        let bins = addresses.bins(-1, 1);
+        assert_eq!(bins.len(), 3);
+        assert_eq!(
+            bins[0],
+            Pubkey::find_program_address(
+                &[b"bin", addresses.pool.as_ref(), &(-1i32).to_le_bytes()],
+                &dloom_flow::ID
+            )
+            .0
+        );
+    }
+}
@@ -0,0 +1,205 @@
+// FILE: crates/dloom-indexer/src/analytics.rs
+
+//! Market data derived from the indexed swaps and liquidity of a pool.
+//!
+//! Windows are half-open ranges of unix timestamps, `from..to`, over the block time of
+//! each transaction; transactions without a block time are left out. Prices are the
+//! post-trade UI price of token A in token B that every swap logs.
+
+use crate::store::Store;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{ensure, Result};
+use serde::Serialize;
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candle {
+    /// The start of the candle's interval, as a unix timestamp.
+    pub start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Token A traded in either direction, in raw units.
+    pub volume_a: u128,
+    /// Token B traded in either direction, in raw units.
+    pub volume_b: u128,
+    pub trades: u64,
+}
+
+/// Totals over a window, in raw units. Fees are charged in the input token.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Volume {
+    pub trades: u64,
+    pub volume_a: u128,
+    pub volume_b: u128,
+    pub protocol_fees_a: u128,
+    pub protocol_fees_b: u128,
+    pub lp_fees_a: u128,
+    pub lp_fees_b: u128,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Decimals {
+    pub a: u8,
+    pub b: u8,
+}
+
+/// The annualized return LPs earned from fees over a window, in UI units of token B.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeeApr {
+    pub lp_fees: f64,
+    /// The net liquidity provided to the pool by the end of the window, valued at the
+    /// closing price. Only liquidity events that were indexed count.
+    pub liquidity: f64,
+    pub apr: f64,
+}
+
+struct Swap {
+    block_time: i64,
+    a_to_b: bool,
+    amount_in: u64,
+    amount_out: u64,
+    protocol_fee: u64,
+    lp_fee: u64,
+    price: f64,
+}
+
+impl Swap {
+    fn amounts(&self) -> (u64, u64) {
+        if self.a_to_b {
+            (self.amount_in, self.amount_out)
+        } else {
+            (self.amount_out, self.amount_in)
+        }
+    }
+}
+
+fn swaps(store: &Store, pool: &Pubkey, from: i64, to: i64) -> Result<Vec<Swap>> {
+    let mut statement = store.connection().prepare(
+        "SELECT block_time, a_to_b, amount_in, amount_out, protocol_fee, lp_fee, price
+         FROM swaps WHERE pool = ?1 AND block_time >= ?2 AND block_time < ?3
+         ORDER BY slot, rowid",
+    )?;
+    let swaps = statement
+        .query_map((pool.to_string(), from, to), |row| {
+            Ok(Swap {
+                block_time: row.get(0)?,
+                a_to_b: row.get(1)?,
+                amount_in: row.get(2)?,
+                amount_out: row.get(3)?,
+                protocol_fee: row.get(4)?,
+                lp_fee: row.get(5)?,
+                price: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(swaps)
+}
+
+/// OHLCV candles of `interval` seconds, aligned to multiples of the interval. Intervals
+/// without trades have no candle.
+pub fn candles(
+    store: &Store,
+    pool: &Pubkey,
+    interval: i64,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Candle>> {
+    ensure!(interval > 0, "the candle interval must be positive");
+    let mut candles: Vec<Candle> = Vec::new();
+    for swap in swaps(store, pool, from, to)? {
+        let start = swap.block_time - swap.block_time.rem_euclid(interval);
+        let (amount_a, amount_b) = swap.amounts();
+        match candles.last_mut() {
+            Some(candle) if candle.start == start => {
+                candle.high = candle.high.max(swap.price);
+                candle.low = candle.low.min(swap.price);
+                candle.close = swap.price;
+                candle.volume_a += amount_a as u128;
+                candle.volume_b += amount_b as u128;
+                candle.trades += 1;
+            }
+            _ => candles.push(Candle {
+                start,
+                open: swap.price,
+                high: swap.price,
+                low: swap.price,
+                close: swap.price,
+                volume_a: amount_a as u128,
+                volume_b: amount_b as u128,
+                trades: 1,
+            }),
+        }
+    }
+    Ok(candles)
+}
+
+pub fn volume(store: &Store, pool: &Pubkey, from: i64, to: i64) -> Result<Volume> {
+    let mut volume = Volume::default();
+    for swap in swaps(store, pool, from, to)? {
+        let (amount_a, amount_b) = swap.amounts();
+        volume.trades += 1;
+        volume.volume_a += amount_a as u128;
+        volume.volume_b += amount_b as u128;
+        let lp_fee = swap.lp_fee as u128;
+        if swap.a_to_b {
+            volume.protocol_fees_a += swap.protocol_fee as u128;
+            volume.lp_fees_a += lp_fee;
+        } else {
+            volume.protocol_fees_b += swap.protocol_fee as u128;
+            volume.lp_fees_b += lp_fee;
+        }
+    }
+    Ok(volume)
+}
+
+/// The LP fee APR over a window. `None` when there is no price to value token A at, or
+/// no liquidity was provided.
+pub fn fee_apr(
+    store: &Store,
+    pool: &Pubkey,
+    from: i64,
+    to: i64,
+    decimals: Decimals,
+) -> Result<Option<FeeApr>> {
+    ensure!(from < to, "the window must not be empty");
+    let Some(price) = swaps(store, pool, i64::MIN, to)?
+        .last()
+        .map(|swap| swap.price)
+    else {
+        return Ok(None);
+    };
+    // Values raw amounts of both tokens in UI units of token B.
+    let value = |amount_a: f64, amount_b: f64| {
+        amount_a / 10f64.powi(decimals.a.into()) * price + amount_b / 10f64.powi(decimals.b.into())
+    };
+
+    let volume = volume(store, pool, from, to)?;
+    let lp_fees = value(volume.lp_fees_a as f64, volume.lp_fees_b as f64);
+    let (liquidity_a, liquidity_b): (i64, i64) = store.connection().query_row(
+        "SELECT COALESCE(SUM(amount_a), 0), COALESCE(SUM(amount_b), 0)
+         FROM liquidity WHERE pool = ?1 AND block_time < ?2",
+        (pool.to_string(), to),
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let liquidity = value(liquidity_a as f64, liquidity_b as f64);
+    if liquidity <= 0.0 {
+        return Ok(None);
+    }
+    Ok(Some(FeeApr {
+        lp_fees,
+        liquidity,
+        apr: lp_fees / liquidity * SECONDS_PER_YEAR / (to - from) as f64,
+    }))
+}
+
+/// The block time of the pool's latest indexed swap.
+pub fn latest_swap_time(store: &Store, pool: &Pubkey) -> Result<Option<i64>> {
+    Ok(store.connection().query_row(
+        "SELECT MAX(block_time) FROM swaps WHERE pool = ?1",
+        [pool.to_string()],
+        |row| row.get(0),
+    )?)
+}
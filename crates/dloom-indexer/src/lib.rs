@@ -0,0 +1,18 @@
+// FILE: crates/dloom-indexer/src/lib.rs
+
+//! Indexes dloom-flow events into SQLite and derives market data from them.
+//!
+//! - [`logs`] decodes the events a transaction emitted, from its log messages or the
+//!   program's self-CPIs.
+//! - [`store`] writes them to a SQLite database, one table per kind of record.
+//! - [`analytics`] derives candles, volumes and fee APRs from the database.
+//!
+//! Transactions are read as JSON, as returned by `getTransaction`, so the indexer runs on
+//! recorded transactions without an RPC connection.
+
+pub mod analytics;
+pub mod logs;
+pub mod store;
+
+pub use logs::{Event, IndexedTransaction};
+pub use store::Store;
@@ -0,0 +1,427 @@
+// FILE: crates/dloom-indexer/src/logs.rs
+
+//! Decodes dloom-flow events from transactions.
+//!
+//! Anchor's `emit!` writes each event as a `Program data: <base64>` log line holding the
+//! event's 8-byte discriminator followed by its Borsh encoding. Only lines logged while
+//! dloom-flow itself is executing are decoded, so another program cannot forge events by
+//! logging the same bytes.
+//!
+//! A program built with the `event-cpi` feature instead emits each event as an instruction
+//! to itself, whose data is Anchor's event instruction tag followed by the logged bytes.
+//! The program only accepts these when signed by its event authority, so every such
+//! instruction in a successful transaction was emitted by the program. They are read from
+//! the transaction's inner instructions, which, unlike the logs, are never truncated.
+
+use anchor_lang::{event::EVENT_IX_TAG_LE, prelude::Pubkey, AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dloom_flow::events::*;
+use serde_json::Value;
+use std::io::Read;
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits.
+        pub enum Event {
+            $($name($name),)*
+        }
+
+        impl Event {
+            /// Decodes an event from its discriminator and Borsh encoding. Returns `None`
+            /// for data that does not start with a known event discriminator.
+            pub fn decode(data: &[u8]) -> Result<Option<Self>> {
+                $(
+                    if let Some(mut fields) = data.strip_prefix($name::DISCRIMINATOR) {
+                        let event = $name::deserialize(&mut fields)
+                            .with_context(|| format!("malformed {} event", stringify!($name)))?;
+                        return Ok(Some(Self::$name(event)));
+                    }
+                )*
+                Ok(None)
+            }
+
+            /// The event as it was logged: its discriminator, then its Borsh encoding.
+            pub fn data(&self) -> Vec<u8> {
+                match self {
+                    $(Self::$name(event) => anchor_lang::Event::data(event),)*
+                }
+            }
+
+            /// The event's type name, as declared in the program.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+events! {
+    ProtocolInitialized,
+    DlmmParametersInitialized,
+    AmmPoolCreated,
+    AmmFeesUpdated,
+    AmmAmpRampStarted,
+    AmmLbpSwapStatusUpdated,
+    AmmLbpProceedsWithdrawn,
+    AmmPositionOpened,
+    AmmFeePreferenceUpdated,
+    AmmLiquidityAdded,
+    AmmLiquidityRemoved,
+    AmmSwap,
+    AmmFeesClaimed,
+    AmmLpFeesReinvested,
+    AmmReservesSynced,
+    AmmSurplusSkimmed,
+    AmmFlashLoanRepaid,
+    AmmOracleInitialized,
+    AmmOracleGrown,
+    DlmmPoolCreated,
+    DlmmFeesUpdated,
+    DlmmPositionOpened,
+    DlmmLiquidityUpdate,
+    DlmmSwapResult,
+    DlmmPositionBurned,
+    DlmmParametersUpdated,
+    MintAllowlistUpdated,
+    DlmmLiquidityModified,
+    DlmmReservesSynced,
+    DlmmSurplusSkimmed,
+    DlmmFlashLoanRepaid,
+    RouteSwapCompleted,
+    SplitSwapCompleted,
+    BondingCurveCreated,
+    BondingCurveTrade,
+    BondingCurveCompleted,
+    BondingCurveGraduated,
+}
+
+impl Event {
+    /// The pool the event belongs to, when it names one.
+    pub fn pool(&self) -> Option<Pubkey> {
+        self.pool_sequence().map(|(pool, _)| pool)
+    }
+
+    /// The event's number in the sequence of events about its pool. Numbers start at 1
+    /// with the pool's creation and increase by one with every event, so a gap means
+    /// events were missed.
+    pub fn sequence(&self) -> Option<u64> {
+        self.pool_sequence().map(|(_, sequence)| sequence)
+    }
+
+    fn pool_sequence(&self) -> Option<(Pubkey, u64)> {
+        match self {
+            Self::AmmPoolCreated(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmFeesUpdated(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmAmpRampStarted(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmLbpSwapStatusUpdated(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmLbpProceedsWithdrawn(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmPositionOpened(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmFeePreferenceUpdated(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmLiquidityAdded(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmLiquidityRemoved(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmSwap(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmFeesClaimed(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmLpFeesReinvested(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmReservesSynced(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmSurplusSkimmed(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmFlashLoanRepaid(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmOracleInitialized(event) => Some((event.pool_address, event.sequence)),
+            Self::AmmOracleGrown(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmPoolCreated(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmFeesUpdated(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmPositionOpened(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmSwapResult(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmLiquidityUpdate(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmPositionBurned(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmLiquidityModified(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmReservesSynced(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmSurplusSkimmed(event) => Some((event.pool_address, event.sequence)),
+            Self::DlmmFlashLoanRepaid(event) => Some((event.pool_address, event.sequence)),
+            // The curve's own sequence is not tracked; the graduation also advances the pool's.
+            Self::BondingCurveGraduated(event) => Some((event.pool_address, event.pool_sequence)),
+            Self::ProtocolInitialized(_)
+            | Self::DlmmParametersInitialized(_)
+            | Self::DlmmParametersUpdated(_)
+            | Self::MintAllowlistUpdated(_)
+            | Self::RouteSwapCompleted(_)
+            | Self::SplitSwapCompleted(_)
+            | Self::BondingCurveCreated(_)
+            | Self::BondingCurveTrade(_)
+            | Self::BondingCurveCompleted(_) => None,
+        }
+    }
+}
+
+/// A successful transaction and the events it emitted, in the order they were emitted.
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub events: Vec<Event>,
+}
+
+/// Decodes the events logged by dloom-flow, in order.
+pub fn events_from_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<Event>> {
+    let program_id = dloom_flow::ID.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        let log = log.as_ref();
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if invocations.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            // An event is a single base64 field; anything else is not an Anchor event.
+            let Ok(data) = STANDARD.decode(data) else {
+                continue;
+            };
+            events.extend(Event::decode(&data)?);
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => invocations.push(program),
+                (Some(_), Some("success" | "failed:")) => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Decodes the events dloom-flow emitted through self-CPIs, in order. `transaction` is as
+/// returned by `getTransaction` with the `json` or `jsonParsed` encoding.
+pub fn events_from_inner_instructions(transaction: &Value) -> Result<Vec<Event>> {
+    let meta = &transaction["meta"];
+    // Inner instructions name their program by its index into the message's account keys,
+    // followed by any keys loaded from lookup tables.
+    let account_keys: Vec<&str> = transaction["transaction"]["message"]["accountKeys"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .chain(["writable", "readonly"].into_iter().flat_map(|kind| {
+            meta["loadedAddresses"][kind]
+                .as_array()
+                .into_iter()
+                .flatten()
+        }))
+        .map(|key| {
+            key.as_str()
+                .or_else(|| key["pubkey"].as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let program_id = dloom_flow::ID.to_string();
+    let mut events = Vec::new();
+    let groups = meta["innerInstructions"].as_array().into_iter().flatten();
+    for instruction in
+        groups.flat_map(|group| group["instructions"].as_array().into_iter().flatten())
+    {
+        let program = match instruction["programId"].as_str() {
+            Some(program) => Some(program),
+            None => instruction["programIdIndex"]
+                .as_u64()
+                .and_then(|index| account_keys.get(index as usize).copied()),
+        };
+        if program != Some(program_id.as_str()) {
+            continue;
+        }
+        let data = instruction["data"]
+            .as_str()
+            .ok_or_else(|| anyhow!("an inner instruction has no data"))?;
+        let data = bs58::decode(data)
+            .into_vec()
+            .context("an inner instruction's data is not base58")?;
+        if let Some(event) = data.strip_prefix(EVENT_IX_TAG_LE) {
+            events.extend(Event::decode(event)?);
+        }
+    }
+    Ok(events)
+}
+
+/// Reads a transaction as returned by `getTransaction` with the `json` encoding, either
+/// bare or wrapped in its JSON-RPC response. Failed transactions emitted nothing, so
+/// they come back as `None`.
+///
+/// Events are read from the logs and then from self-CPIs; a program emits through only
+/// one of the two. Truncated logs are rejected unless the program emits through self-CPIs.
+pub fn parse_transaction(value: &Value) -> Result<Option<IndexedTransaction>> {
+    let value = value.get("result").unwrap_or(value);
+    let meta = value
+        .get("meta")
+        .ok_or_else(|| anyhow!("transaction has no `meta`"))?;
+    if !meta["err"].is_null() {
+        return Ok(None);
+    }
+    let signature = value["transaction"]["signatures"][0]
+        .as_str()
+        .ok_or_else(|| anyhow!("transaction has no signature; fetch it with the json encoding"))?;
+    let slot = value["slot"]
+        .as_u64()
+        .ok_or_else(|| anyhow!("transaction {signature} has no slot"))?;
+    let logs: Vec<&str> = meta["logMessages"]
+        .as_array()
+        .ok_or_else(|| anyhow!("transaction {signature} has no log messages"))?
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    let mut events = events_from_logs(&logs)
+        .with_context(|| format!("decoding the events of transaction {signature}"))?;
+    let emitted_through_cpi = events_from_inner_instructions(value)
+        .with_context(|| format!("decoding the events of transaction {signature}"))?;
+    if emitted_through_cpi.is_empty() && logs.contains(&"Log truncated") {
+        bail!("the logs of transaction {signature} were truncated, so events may be missing");
+    }
+    events.extend(emitted_through_cpi);
+    Ok(Some(IndexedTransaction {
+        signature: signature.to_string(),
+        slot,
+        block_time: value["blockTime"].as_i64(),
+        events,
+    }))
+}
+
+/// Reads every transaction from a stream of JSON values: single transactions, arrays of
+/// them, or responses to a batch request, one after another.
+pub fn read_transactions(reader: impl Read) -> Result<Vec<IndexedTransaction>> {
+    let mut transactions = Vec::new();
+    for value in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+        match value? {
+            Value::Array(values) => {
+                for value in &values {
+                    transactions.extend(parse_transaction(value)?);
+                }
+            }
+            value => transactions.extend(parse_transaction(&value)?),
+        }
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+    use serde_json::json;
+
+    fn data_log(event: &impl anchor_lang::Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    fn fees_updated(new_fee_rate: u16) -> AmmFeesUpdated {
+        AmmFeesUpdated {
+            pool_address: Pubkey::new_unique(),
+            sequence: 1,
+            old_fee_rate: 0,
+            new_fee_rate,
+            is_automatic: false,
+        }
+    }
+
+    #[test]
+    fn decodes_only_what_the_program_logs() {
+        let program = dloom_flow::ID;
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            "Program log: Instruction: UpdateAmmFees".to_string(),
+            data_log(&fees_updated(1)),
+            format!("Program {other} invoke [2]"),
+            data_log(&fees_updated(2)),
+            format!("Program {other} success"),
+            data_log(&fees_updated(3)),
+            format!("Program {program} consumed 5000 of 200000 compute units"),
+            format!("Program {program} success"),
+            data_log(&fees_updated(4)),
+        ];
+        let fee_rates: Vec<_> = events_from_logs(&logs)
+            .unwrap()
+            .into_iter()
+            .map(|event| match event {
+                Event::AmmFeesUpdated(event) => event.new_fee_rate,
+                event => panic!("unexpected {}", event.name()),
+            })
+            .collect();
+        assert_eq!(fee_rates, [1, 3]);
+    }
+
+    #[test]
+    fn skips_unknown_data_and_rejects_malformed_events() {
+        assert!(Event::decode(&[0; 16]).unwrap().is_none());
+        let mut data = AmmFeesUpdated::DISCRIMINATOR.to_vec();
+        fees_updated(1).serialize(&mut data).unwrap();
+        assert!(Event::decode(&data).unwrap().is_some());
+        assert!(Event::decode(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn failed_transactions_have_no_events() {
+        let transaction = json!({
+            "slot": 1,
+            "blockTime": null,
+            "meta": { "err": { "InstructionError": [0, "Custom"] }, "logMessages": [] },
+            "transaction": { "signatures": ["sig"] },
+        });
+        assert!(parse_transaction(&transaction).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_logs() {
+        let transaction = json!({
+            "result": {
+                "slot": 1,
+                "meta": { "err": null, "logMessages": ["Log truncated"] },
+                "transaction": { "signatures": ["sig"] },
+            }
+        });
+        assert!(parse_transaction(&transaction).is_err());
+    }
+
+    #[test]
+    fn decodes_events_emitted_through_self_cpi() {
+        let cpi_data = |event: &AmmFeesUpdated| {
+            bs58::encode([EVENT_IX_TAG_LE, &anchor_lang::Event::data(event)[..]].concat())
+                .into_string()
+        };
+        let program = dloom_flow::ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let transaction = json!({
+            "slot": 1,
+            "meta": {
+                "err": null,
+                "logMessages": ["Log truncated"],
+                "loadedAddresses": { "writable": [], "readonly": [program] },
+                "innerInstructions": [
+                    { "index": 0, "instructions": [
+                        { "programIdIndex": 2, "accounts": [1], "data": cpi_data(&fees_updated(1)) },
+                        { "programIdIndex": 1, "accounts": [], "data": cpi_data(&fees_updated(2)) },
+                    ] },
+                    { "index": 1, "instructions": [
+                        { "programIdIndex": 2, "accounts": [1], "data": bs58::encode([3; 12]).into_string() },
+                        { "programIdIndex": 2, "accounts": [1], "data": cpi_data(&fees_updated(4)) },
+                    ] },
+                ],
+            },
+            "transaction": {
+                "signatures": ["sig"],
+                "message": { "accountKeys": [Pubkey::new_unique().to_string(), other] },
+            },
+        });
+        let fee_rates: Vec<_> = parse_transaction(&transaction)
+            .unwrap()
+            .unwrap()
+            .events
+            .into_iter()
+            .map(|event| match event {
+                Event::AmmFeesUpdated(event) => event.new_fee_rate,
+                event => panic!("unexpected {}", event.name()),
+            })
+            .collect();
+        // The program is only known from the lookup table; other instructions are skipped.
+        assert_eq!(fee_rates, [1, 4]);
+    }
+}
@@ -0,0 +1,235 @@
+// FILE: crates/dloom-indexer/src/main.rs
+
+//! `dloom-indexer`: indexes recorded dloom-flow transactions and queries the result.
+//!
+//! `ingest` reads transactions as JSON from files, or from stdin, for example the output
+//! of `getTransaction` requests saved by a script. Ingesting the same transaction twice
+//! has no effect. The query commands print JSON, one record per line.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use dloom_indexer::{
+    analytics::{self, Decimals},
+    logs, Store,
+};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::PathBuf,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dloom-indexer",
+    version,
+    about = "Indexes dloom-flow events into SQLite"
+)]
+struct Cli {
+    /// The SQLite database, created if it does not exist.
+    #[arg(long, global = true, default_value = "dloom-indexer.sqlite")]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Index transactions from JSON files, or from stdin if none are given.
+    Ingest { files: Vec<PathBuf> },
+    /// Print OHLCV candles for a pool.
+    Candles {
+        pool: Pubkey,
+        /// The candle interval, in seconds.
+        #[arg(long, default_value_t = 3600)]
+        interval: i64,
+        #[command(flatten)]
+        window: Window,
+    },
+    /// Print a pool's trading volume and fees.
+    Volume {
+        pool: Pubkey,
+        #[command(flatten)]
+        window: Window,
+    },
+    /// Print a pool's LP fee APR over the period ending at its latest swap, or at `--to`.
+    Apr {
+        pool: Pubkey,
+        #[arg(long)]
+        decimals_a: u8,
+        #[arg(long)]
+        decimals_b: u8,
+        /// The period the APR is measured over, in seconds.
+        #[arg(long, default_value_t = 86_400)]
+        period: i64,
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// Print the ranges of a pool's event sequence that were never indexed.
+    Gaps { pool: Pubkey },
+}
+
+/// A range of unix timestamps; unbounded by default.
+#[derive(Debug, clap::Args)]
+struct Window {
+    #[arg(long, default_value_t = i64::MIN, allow_negative_numbers = true)]
+    from: i64,
+    #[arg(long, default_value_t = i64::MAX)]
+    to: i64,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut store =
+        Store::open(&cli.db).with_context(|| format!("opening {}", cli.db.display()))?;
+    run(
+        &cli.command,
+        &mut store,
+        std::io::stdin(),
+        &mut std::io::stdout(),
+    )
+}
+
+fn run(command: &Command, store: &mut Store, stdin: impl Read, out: &mut impl Write) -> Result<()> {
+    match command {
+        Command::Ingest { files } => {
+            let transactions = if files.is_empty() {
+                logs::read_transactions(stdin)?
+            } else {
+                let mut transactions = Vec::new();
+                for file in files {
+                    let reader = BufReader::new(
+                        File::open(file).with_context(|| format!("opening {}", file.display()))?,
+                    );
+                    transactions.extend(
+                        logs::read_transactions(reader)
+                            .with_context(|| format!("reading {}", file.display()))?,
+                    );
+                }
+                transactions
+            };
+            let (mut indexed, mut events) = (0, 0);
+            for transaction in &transactions {
+                if store.ingest(transaction)? {
+                    indexed += 1;
+                    events += transaction.events.len();
+                }
+            }
+            writeln!(
+                out,
+                "Indexed {indexed} transactions with {events} events; {} were already indexed",
+                transactions.len() - indexed
+            )?;
+        }
+        Command::Candles {
+            pool,
+            interval,
+            window,
+        } => {
+            for candle in analytics::candles(store, pool, *interval, window.from, window.to)? {
+                print_json(out, &candle)?;
+            }
+        }
+        Command::Volume { pool, window } => {
+            print_json(
+                out,
+                &analytics::volume(store, pool, window.from, window.to)?,
+            )?;
+        }
+        Command::Apr {
+            pool,
+            decimals_a,
+            decimals_b,
+            period,
+            to,
+        } => {
+            // The window is half-open, so it ends just after the latest swap.
+            let to = match to {
+                Some(to) => *to,
+                None => {
+                    analytics::latest_swap_time(store, pool)?
+                        .with_context(|| format!("no swaps of {pool} are indexed"))?
+                        + 1
+                }
+            };
+            let decimals = Decimals {
+                a: *decimals_a,
+                b: *decimals_b,
+            };
+            let apr = analytics::fee_apr(store, pool, to - period, to, decimals)?
+                .with_context(|| format!("{pool} has no indexed price or liquidity"))?;
+            print_json(out, &apr)?;
+        }
+        Command::Gaps { pool } => {
+            for gap in store.sequence_gaps(pool)? {
+                print_json(out, &gap)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json(out: &mut impl Write, value: &impl Serialize) -> Result<()> {
+    serde_json::to_writer(&mut *out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn execute(store: &mut Store, args: &[&str], stdin: &[u8]) -> String {
+        let cli = Cli::try_parse_from([&["dloom-indexer"], args].concat()).unwrap();
+        let mut out = Vec::new();
+        run(&cli.command, store, stdin, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn ingests_stdin_and_prints_candles_as_json_lines() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dlmm.jsonl");
+        let mut store = Store::open_in_memory().unwrap();
+        let output = execute(&mut store, &["ingest"], &std::fs::read(&fixture).unwrap());
+        assert_eq!(
+            output,
+            "Indexed 6 transactions with 6 events; 0 were already indexed\n"
+        );
+        let output = execute(&mut store, &["ingest", fixture.to_str().unwrap()], &[]);
+        assert_eq!(
+            output,
+            "Indexed 0 transactions with 0 events; 6 were already indexed\n"
+        );
+
+        let dlmm_pool = Pubkey::new_from_array([0x3d; 32]).to_string();
+        let output = execute(
+            &mut store,
+            &["candles", &dlmm_pool, "--interval", "86400"],
+            &[],
+        );
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["close"], 5.02);
+        assert_eq!(lines[0]["volume_b"], 1_000_000_000);
+    }
+
+    #[test]
+    fn measures_apr_up_to_the_latest_swap() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dlmm.jsonl");
+        let mut store = Store::open_in_memory().unwrap();
+        execute(&mut store, &["ingest", fixture.to_str().unwrap()], &[]);
+
+        let dlmm_pool = Pubkey::new_from_array([0x3d; 32]).to_string();
+        let args = ["apr", &dlmm_pool, "--decimals-a", "6", "--decimals-b", "9"];
+        let apr: serde_json::Value =
+            serde_json::from_str(&execute(&mut store, &args, &[])).unwrap();
+        // 1.6e6 of token B in fees against 500 A at 5.02 plus 2500 B.
+        assert_eq!(apr["lp_fees"], 0.0016);
+        assert_eq!(apr["liquidity"], 500.0 * 5.02 + 2_500.0);
+    }
+}
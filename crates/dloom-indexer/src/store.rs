@@ -0,0 +1,429 @@
+// FILE: crates/dloom-indexer/src/store.rs
+
+//! The SQLite database the indexer writes to.
+//!
+//! Every decoded event is kept in `events`, as it was logged, with its pool and its number
+//! in the pool's event sequence. The events that analytics need are also written to tables
+//! keyed by pool: `pools`, `swaps` and `liquidity`, along with `positions` and `curves`,
+//! which resolve the owner of a position and the mints of a bonding curve. Amounts are raw
+//! token units; liquidity withdrawals are negative.
+
+use crate::logs::{Event, IndexedTransaction};
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use dloom_flow::constants::PRECISION;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    pool TEXT,
+    sequence INTEGER,
+    data BLOB NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_by_pool ON events (pool, name);
+CREATE INDEX IF NOT EXISTS events_by_sequence ON events (pool, sequence);
+CREATE TABLE IF NOT EXISTS pools (
+    address TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    token_a_mint TEXT NOT NULL,
+    token_b_mint TEXT NOT NULL,
+    fee_rate INTEGER NOT NULL,
+    bin_step INTEGER
+);
+CREATE TABLE IF NOT EXISTS positions (
+    address TEXT PRIMARY KEY,
+    pool TEXT NOT NULL,
+    owner TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS curves (
+    address TEXT PRIMARY KEY,
+    token_mint TEXT NOT NULL,
+    quote_mint TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS swaps (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    trader TEXT NOT NULL,
+    a_to_b INTEGER NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    protocol_fee INTEGER NOT NULL,
+    lp_fee INTEGER NOT NULL,
+    price REAL NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS swaps_by_pool ON swaps (pool, block_time);
+CREATE TABLE IF NOT EXISTS liquidity (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    owner TEXT,
+    amount_a INTEGER NOT NULL,
+    amount_b INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS liquidity_by_pool ON liquidity (pool, block_time);
+";
+
+/// `AmmSwap::ui_price_a` is scaled by 1e9; `DlmmSwapResult::ui_price_a` by `PRECISION`.
+const AMM_PRICE_SCALE: f64 = 1e9;
+
+/// Sequence numbers of a pool's events that were never indexed, from `first` to `last`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SequenceGap {
+    pub first: u64,
+    pub last: u64,
+}
+
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Writes a transaction's events. Returns `false`, writing nothing, if the
+    /// transaction was already indexed, so overlapping inputs can be ingested safely.
+    pub fn ingest(&mut self, transaction: &IndexedTransaction) -> Result<bool> {
+        let db = self.connection.transaction()?;
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![
+                transaction.signature,
+                transaction.slot,
+                transaction.block_time
+            ],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        for (index, event) in transaction.events.iter().enumerate() {
+            let row = Row {
+                db: &db,
+                transaction,
+                index,
+            };
+            row.insert(event)?;
+        }
+        db.commit()?;
+        Ok(true)
+    }
+
+    /// The gaps in a pool's indexed event sequence, including any before the first event
+    /// indexed. Events after the last one indexed cannot be detected as missing.
+    pub fn sequence_gaps(&self, pool: &Pubkey) -> Result<Vec<SequenceGap>> {
+        let mut statement = self.connection.prepare(
+            "SELECT DISTINCT sequence FROM events
+             WHERE pool = ?1 AND sequence IS NOT NULL ORDER BY sequence",
+        )?;
+        let mut gaps = Vec::new();
+        let mut next = 1;
+        for sequence in statement.query_map([pool.to_string()], |row| row.get::<_, u64>(0))? {
+            let sequence = sequence?;
+            if sequence > next {
+                gaps.push(SequenceGap {
+                    first: next,
+                    last: sequence - 1,
+                });
+            }
+            next = sequence + 1;
+        }
+        Ok(gaps)
+    }
+}
+
+/// Writes one event of a transaction.
+struct Row<'a> {
+    db: &'a Transaction<'a>,
+    transaction: &'a IndexedTransaction,
+    index: usize,
+}
+
+impl Row<'_> {
+    fn insert(&self, event: &Event) -> Result<()> {
+        self.db.execute(
+            "INSERT INTO events (signature, event_index, name, pool, sequence, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.transaction.signature,
+                self.index,
+                event.name(),
+                event.pool().map(|pool| pool.to_string()),
+                event.sequence(),
+                event.data(),
+            ],
+        )?;
+
+        match event {
+            Event::AmmPoolCreated(event) => self.pool(
+                "amm",
+                &event.pool_address,
+                &event.token_a_mint,
+                &event.token_b_mint,
+                event.fee_rate,
+                None,
+            ),
+            Event::DlmmPoolCreated(event) => self.pool(
+                "dlmm",
+                &event.pool_address,
+                &event.token_a_mint,
+                &event.token_b_mint,
+                event.fee_rate,
+                Some(event.bin_step),
+            ),
+            Event::AmmFeesUpdated(event) => self.fee_rate(&event.pool_address, event.new_fee_rate),
+            Event::DlmmFeesUpdated(event) => self.fee_rate(&event.pool_address, event.new_fee_rate),
+            Event::DlmmPositionOpened(event) => {
+                self.position(&event.position_address, &event.pool_address, &event.owner)
+            }
+            Event::AmmSwap(event) => self.swap(Swap {
+                pool: &event.pool_address,
+                trader: &event.trader,
+                a_to_b: event.input_mint < event.output_mint,
+                amount_in: event.amount_in,
+                amount_out: event.amount_out,
+                protocol_fee: event.protocol_fee,
+                lp_fee: event.lp_fee,
+                price: event.ui_price_a as f64 / AMM_PRICE_SCALE,
+            }),
+            Event::DlmmSwapResult(event) => self.swap(Swap {
+                pool: &event.pool_address,
+                trader: &event.trader,
+                a_to_b: event.input_mint < event.output_mint,
+                amount_in: event.amount_in,
+                amount_out: event.amount_out,
+                protocol_fee: event.protocol_fee,
+                lp_fee: event.lp_fee,
+                price: event.ui_price_a as f64 / PRECISION as f64,
+            }),
+            Event::AmmLiquidityAdded(event) => self.liquidity(
+                &event.pool_address.to_string(),
+                Some(&event.user),
+                event.amount_a_deposited,
+                event.amount_b_deposited,
+                false,
+            ),
+            Event::AmmLiquidityRemoved(event) => self.liquidity(
+                &event.pool_address.to_string(),
+                Some(&event.user),
+                event.amount_a_received,
+                event.amount_b_received,
+                true,
+            ),
+            Event::AmmLbpProceedsWithdrawn(event) => self.liquidity(
+                &event.pool_address.to_string(),
+                Some(&event.launch_owner),
+                event.amount_a_received,
+                event.amount_b_received,
+                true,
+            ),
+            Event::DlmmLiquidityUpdate(event) => {
+                // The owner of a position opened before indexing began is unknown.
+                let owner = self.position_owner(&event.position_address)?;
+                self.liquidity(
+                    &event.pool_address.to_string(),
+                    owner.as_ref(),
+                    event.amount_a,
+                    event.amount_b,
+                    event.liquidity_added < 0,
+                )
+            }
+            Event::DlmmLiquidityModified(event) => {
+                self.position(
+                    &event.new_position_address,
+                    &event.pool_address,
+                    &event.owner,
+                )?;
+                self.liquidity(
+                    &event.pool_address.to_string(),
+                    Some(&event.owner),
+                    event.surplus_a_out,
+                    event.surplus_b_out,
+                    true,
+                )
+            }
+            Event::BondingCurveCreated(event) => {
+                self.db.execute(
+                    "INSERT OR REPLACE INTO curves (address, token_mint, quote_mint) VALUES (?1, ?2, ?3)",
+                    params![
+                        event.curve_address.to_string(),
+                        event.token_mint.to_string(),
+                        event.quote_mint.to_string(),
+                    ],
+                )?;
+                Ok(())
+            }
+            Event::BondingCurveGraduated(event) => {
+                // The graduated reserves seed the pool; which side is token A follows from
+                // the mints' order.
+                let mints: Option<(String, String)> = self
+                    .db
+                    .query_row(
+                        "SELECT token_mint, quote_mint FROM curves WHERE address = ?1",
+                        [event.curve_address.to_string()],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?;
+                let Some((token_mint, quote_mint)) = mints else {
+                    return Ok(());
+                };
+                let (amount_a, amount_b) = if token_mint.parse::<Pubkey>()? < quote_mint.parse()? {
+                    (event.token_amount, event.quote_amount)
+                } else {
+                    (event.quote_amount, event.token_amount)
+                };
+                self.liquidity(
+                    &event.pool_address.to_string(),
+                    None,
+                    amount_a,
+                    amount_b,
+                    false,
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn pool(
+        &self,
+        kind: &str,
+        address: &Pubkey,
+        token_a_mint: &Pubkey,
+        token_b_mint: &Pubkey,
+        fee_rate: u16,
+        bin_step: Option<u16>,
+    ) -> Result<()> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO pools (address, kind, token_a_mint, token_b_mint, fee_rate, bin_step)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                address.to_string(),
+                kind,
+                token_a_mint.to_string(),
+                token_b_mint.to_string(),
+                fee_rate,
+                bin_step,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn fee_rate(&self, pool: &Pubkey, fee_rate: u16) -> Result<()> {
+        self.db.execute(
+            "UPDATE pools SET fee_rate = ?2 WHERE address = ?1",
+            params![pool.to_string(), fee_rate],
+        )?;
+        Ok(())
+    }
+
+    fn position(&self, address: &Pubkey, pool: &Pubkey, owner: &Pubkey) -> Result<()> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO positions (address, pool, owner) VALUES (?1, ?2, ?3)",
+            params![address.to_string(), pool.to_string(), owner.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn position_owner(&self, position: &Pubkey) -> Result<Option<Pubkey>> {
+        let owner: Option<String> = self
+            .db
+            .query_row(
+                "SELECT owner FROM positions WHERE address = ?1",
+                [position.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(owner.map(|owner| owner.parse()).transpose()?)
+    }
+
+    fn swap(&self, swap: Swap) -> Result<()> {
+        self.db.execute(
+            "INSERT INTO swaps (signature, event_index, pool, slot, block_time, trader, a_to_b,
+                                amount_in, amount_out, protocol_fee, lp_fee, price)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                self.transaction.signature,
+                self.index,
+                swap.pool.to_string(),
+                self.transaction.slot,
+                self.transaction.block_time,
+                swap.trader.to_string(),
+                swap.a_to_b,
+                swap.amount_in,
+                swap.amount_out,
+                swap.protocol_fee,
+                swap.lp_fee,
+                swap.price,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn liquidity(
+        &self,
+        pool: &str,
+        owner: Option<&Pubkey>,
+        amount_a: u64,
+        amount_b: u64,
+        is_withdrawal: bool,
+    ) -> Result<()> {
+        let sign = if is_withdrawal { -1 } else { 1 };
+        self.db.execute(
+            "INSERT INTO liquidity (signature, event_index, pool, slot, block_time, owner, amount_a, amount_b)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.transaction.signature,
+                self.index,
+                pool,
+                self.transaction.slot,
+                self.transaction.block_time,
+                owner.map(Pubkey::to_string),
+                sign * i64::try_from(amount_a)?,
+                sign * i64::try_from(amount_b)?,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+struct Swap<'a> {
+    pool: &'a Pubkey,
+    trader: &'a Pubkey,
+    a_to_b: bool,
+    amount_in: u64,
+    amount_out: u64,
+    protocol_fee: u64,
+    lp_fee: u64,
+    price: f64,
+}
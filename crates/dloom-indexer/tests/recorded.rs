@@ -0,0 +1,223 @@
+// FILE: crates/dloom-indexer/tests/recorded.rs
+
+//! Indexes recorded transactions, as returned by `getTransaction`, and checks the tables
+//! and analytics derived from them.
+//!
+//! `amm.json` is a batch of RPC responses for one AMM pool, including a failed swap and
+//! an event logged by another program. `dlmm.jsonl` has one transaction per line for a
+//! DLMM pool of the same mints, and repeats one of the AMM swaps.
+
+use anchor_lang::prelude::Pubkey;
+use dloom_indexer::{
+    analytics::{self, Candle, Decimals, Volume},
+    logs,
+    store::SequenceGap,
+    Event, Store,
+};
+use std::{fs::File, path::Path};
+
+const T0: i64 = 1_760_000_000;
+
+fn ingest(store: &mut Store, fixture: &str) -> (usize, usize) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(fixture);
+    let transactions = logs::read_transactions(File::open(path).unwrap()).unwrap();
+    let indexed = transactions
+        .iter()
+        .filter(|transaction| store.ingest(transaction).unwrap())
+        .count();
+    (transactions.len(), indexed)
+}
+
+fn indexed_store() -> Store {
+    let mut store = Store::open_in_memory().unwrap();
+    ingest(&mut store, "amm.json");
+    ingest(&mut store, "dlmm.jsonl");
+    store
+}
+
+fn pool(store: &Store, kind: &str) -> Pubkey {
+    let address: String = store
+        .connection()
+        .query_row("SELECT address FROM pools WHERE kind = ?1", [kind], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    address.parse().unwrap()
+}
+
+fn count(store: &Store, sql: &str) -> i64 {
+    store
+        .connection()
+        .query_row(sql, [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn ingests_each_successful_transaction_once() {
+    let mut store = Store::open_in_memory().unwrap();
+    // The failed swap is dropped when read.
+    assert_eq!(ingest(&mut store, "amm.json"), (5, 5));
+    assert_eq!(ingest(&mut store, "dlmm.jsonl"), (6, 5));
+    assert_eq!(ingest(&mut store, "amm.json"), (5, 0));
+
+    assert_eq!(count(&store, "SELECT COUNT(*) FROM transactions"), 10);
+    // The swap logged by another program is not an event.
+    assert_eq!(count(&store, "SELECT COUNT(*) FROM events"), 10);
+    assert_eq!(count(&store, "SELECT COUNT(*) FROM swaps"), 4);
+}
+
+#[test]
+fn detects_gaps_in_each_pool_event_sequence() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dlmm.jsonl");
+    let transactions = logs::read_transactions(File::open(path).unwrap()).unwrap();
+    let mut store = Store::open_in_memory().unwrap();
+    // Skip the pool's creation and its swap.
+    for transaction in &transactions {
+        let skipped = transaction
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::DlmmPoolCreated(_) | Event::DlmmSwapResult(_)));
+        if !skipped {
+            store.ingest(transaction).unwrap();
+        }
+    }
+    let dlmm_pool = Pubkey::new_from_array([0x3d; 32]);
+    assert_eq!(
+        store.sequence_gaps(&dlmm_pool).unwrap(),
+        [
+            SequenceGap { first: 1, last: 1 },
+            SequenceGap { first: 4, last: 4 }
+        ]
+    );
+
+    let store = indexed_store();
+    for kind in ["amm", "dlmm"] {
+        assert!(store.sequence_gaps(&pool(&store, kind)).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn resolves_liquidity_updates_to_the_position_pool() {
+    let store = indexed_store();
+    let dlmm_pool = pool(&store, "dlmm");
+    let mut statement = store
+        .connection()
+        .prepare(
+            "SELECT pool, owner, amount_a, amount_b FROM liquidity WHERE pool = ?1 ORDER BY slot",
+        )
+        .unwrap();
+    let rows: Vec<(String, String, i64, i64)> = statement
+        .query_map([dlmm_pool.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let owner = Pubkey::new_from_array([0x55; 32]).to_string();
+    assert_eq!(
+        rows,
+        [
+            (
+                dlmm_pool.to_string(),
+                owner.clone(),
+                500_000_000,
+                2_500_000_000_000
+            ),
+            (dlmm_pool.to_string(), owner, -100_000_000, -400_000_000_000),
+        ]
+    );
+    assert_eq!(
+        count(
+            &store,
+            "SELECT COUNT(*) FROM events WHERE name = 'DlmmLiquidityUpdate' AND pool IS NOT NULL"
+        ),
+        2
+    );
+}
+
+#[test]
+fn builds_hourly_candles() {
+    let store = indexed_store();
+    let candles =
+        analytics::candles(&store, &pool(&store, "amm"), 3600, i64::MIN, i64::MAX).unwrap();
+    assert_eq!(
+        candles,
+        [
+            Candle {
+                start: 1_759_996_800,
+                open: 4.9,
+                high: 4.95,
+                low: 4.9,
+                close: 4.95,
+                volume_a: 10_000_000 + 4_880_000,
+                volume_b: 49_350_000_000 + 24_000_000_000,
+                trades: 2,
+            },
+            Candle {
+                start: 1_760_000_400,
+                open: 4.94,
+                high: 4.94,
+                low: 4.94,
+                close: 4.94,
+                volume_a: 2_000_000,
+                volume_b: 9_900_000_000,
+                trades: 1,
+            },
+        ]
+    );
+    // Windows exclude their end.
+    let first_hour = analytics::candles(&store, &pool(&store, "amm"), 3600, T0, T0 + 120).unwrap();
+    assert_eq!(first_hour[0].trades, 1);
+}
+
+#[test]
+fn totals_volume_and_fees_by_side() {
+    let store = indexed_store();
+    assert_eq!(
+        analytics::volume(&store, &pool(&store, "amm"), i64::MIN, i64::MAX).unwrap(),
+        Volume {
+            trades: 3,
+            volume_a: 16_880_000,
+            volume_b: 83_250_000_000,
+            protocol_fees_a: 7_200,
+            protocol_fees_b: 14_400_000,
+            lp_fees_a: 28_800,
+            lp_fees_b: 57_600_000,
+        }
+    );
+    // The DLMM swap paid 1e9 of token B at a 20 bps fee rate, 400_000 of it to the protocol.
+    let dlmm = analytics::volume(&store, &pool(&store, "dlmm"), i64::MIN, i64::MAX).unwrap();
+    assert_eq!((dlmm.protocol_fees_b, dlmm.lp_fees_b), (400_000, 1_600_000));
+}
+
+#[test]
+fn annualizes_lp_fees_against_liquidity() {
+    let store = indexed_store();
+    let amm_pool = pool(&store, "amm");
+    let decimals = Decimals { a: 6, b: 9 };
+    let to = analytics::latest_swap_time(&store, &amm_pool)
+        .unwrap()
+        .unwrap()
+        + 1;
+    assert_eq!(to, T0 + 3_701);
+
+    let apr = analytics::fee_apr(&store, &amm_pool, T0, to, decimals)
+        .unwrap()
+        .unwrap();
+    // Valued at the closing price of 4.94.
+    let lp_fees = 0.028_8 * 4.94 + 0.057_6;
+    let liquidity = 1_000.0 * 4.94 + 5_000.0;
+    assert!((apr.lp_fees - lp_fees).abs() < 1e-9);
+    assert!((apr.liquidity - liquidity).abs() < 1e-6);
+    let expected = lp_fees / liquidity * 365.0 * 86_400.0 / 3_701.0;
+    assert!((apr.apr - expected).abs() < 1e-9);
+
+    // Before the first swap there is no price to value token A at.
+    assert!(
+        analytics::fee_apr(&store, &amm_pool, T0 - 10, T0 + 30, decimals)
+            .unwrap()
+            .is_none()
+    );
+}
@@ -3,11 +3,15 @@
 use crate::{
     amm::{
         instructions::swap::update_oracle, 
-        math,                              
-        state::{AmmPool, AmmPosition}, 
+        math,
+        state::{AmmOracle, AmmPool, AmmPosition, CurveType},
+        weighted_math,
     },
+    constants::MINIMUM_LIQUIDITY,
     errors::DloomError,
-    events::AmmLiquidityAdded,
+    events::{emit_event, AmmLiquidityAdded},
+    native_sol::{NativeSol, WSOL_SEED},
+    token_extensions,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -15,64 +19,132 @@ use anchor_spl::{
     token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
 };
 
-pub fn handle_add_amm_liquidity(
-    ctx: Context<AddAmmLiquidity>,
+pub fn handle_add_amm_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AddAmmLiquidity<'info>>,
     amount_a_desired: u64,
     amount_b_desired: u64,
     min_lp_tokens_to_mint: u64,
 ) -> Result<()> {
-    update_oracle(&mut ctx.accounts.amm_pool)?;
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
+    // Only the launch owner may seed a liquidity bootstrapping pool.
+    if ctx.accounts.amm_pool.curve_type == CurveType::LiquidityBootstrapping {
+        require_keys_eq!(
+            ctx.accounts.owner.key(),
+            ctx.accounts.amm_pool.launch_owner,
+            DloomError::Unauthorized
+        );
+    }
     require!(
         amount_a_desired > 0 && amount_b_desired > 0,
         DloomError::ZeroLiquidity
     );
 
-    let (amount_a_to_deposit, amount_b_to_deposit, lp_tokens_to_mint) =
-        math::calculate_lp_tokens_to_mint(
-            &ctx.accounts.amm_pool,
-            &ctx.accounts.lp_mint,
-            amount_a_desired,
-            amount_b_desired,
-        )?;
+    // Size the deposit in what the vaults will actually receive after any Token-2022
+    // transfer fees, then gross the transfers back up so the vaults are credited in full.
+    let net_amount_a_desired =
+        token_extensions::calculate_net_received(&ctx.accounts.token_a_mint, amount_a_desired)?;
+    let net_amount_b_desired =
+        token_extensions::calculate_net_received(&ctx.accounts.token_b_mint, amount_b_desired)?;
+
+    let is_first_deposit = ctx.accounts.lp_mint.supply == 0;
+    let (amount_a_to_deposit, amount_b_to_deposit, total_lp_tokens) =
+        if ctx.accounts.amm_pool.curve_type.is_weighted() {
+            weighted_math::calculate_weighted_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                net_amount_a_desired,
+                net_amount_b_desired,
+            )?
+        } else {
+            math::calculate_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                net_amount_a_desired,
+                net_amount_b_desired,
+            )?
+        };
+    let amount_a_to_send =
+        token_extensions::calculate_gross_to_send(&ctx.accounts.token_a_mint, amount_a_to_deposit)?;
+    let amount_b_to_send =
+        token_extensions::calculate_gross_to_send(&ctx.accounts.token_b_mint, amount_b_to_deposit)?;
+
+    // The first deposit permanently locks `MINIMUM_LIQUIDITY` of the new LP supply.
+    let lp_tokens_to_lock = if is_first_deposit { MINIMUM_LIQUIDITY } else { 0 };
+    let lp_tokens_to_mint = total_lp_tokens
+        .checked_sub(lp_tokens_to_lock)
+        .ok_or(DloomError::InsufficientInitialLiquidity)?;
 
     require!(
         lp_tokens_to_mint >= min_lp_tokens_to_mint,
         DloomError::SlippageExceeded
     );
 
-    token_interface::transfer_checked(
+    // Either side may be paid in native SOL, which is wrapped for the transfer below.
+    let native_sol = NativeSol::new(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        Some(ctx.accounts.system_program.to_account_info()),
+    );
+    let user_token_a_account = native_sol.resolve(
+        ctx.accounts.user_token_a_account.as_ref(),
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program.to_account_info(),
+        amount_a_to_send,
+    )?;
+    let user_token_b_account = native_sol.resolve(
+        ctx.accounts.user_token_b_account.as_ref(),
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program.to_account_info(),
+        amount_b_to_send,
+    )?;
+
+    token_extensions::transfer_checked(
         CpiContext::new(
             ctx.accounts.token_a_program.to_account_info(),
             TransferChecked {
-                from: ctx.accounts.user_token_a_account.to_account_info(),
+                from: user_token_a_account,
                 to: ctx.accounts.token_a_vault.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
                 mint: ctx.accounts.token_a_mint.to_account_info(),
             },
         ),
-        amount_a_to_deposit,
+        amount_a_to_send,
         ctx.accounts.token_a_mint.decimals,
+        ctx.remaining_accounts,
     )?;
 
-    token_interface::transfer_checked(
+    token_extensions::transfer_checked(
         CpiContext::new(
             ctx.accounts.token_b_program.to_account_info(),
             TransferChecked {
-                from: ctx.accounts.user_token_b_account.to_account_info(),
+                from: user_token_b_account,
                 to: ctx.accounts.token_b_vault.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
                 mint: ctx.accounts.token_b_mint.to_account_info(),
             },
         ),
-        amount_b_to_deposit,
+        amount_b_to_send,
         ctx.accounts.token_b_mint.decimals,
+        ctx.remaining_accounts,
     )?;
+    native_sol.close()?;
+
+    let fee_tier_bytes = &ctx.accounts.amm_pool.fee_tier.to_le_bytes()[..];
 
+    let curve_type_seed = &ctx.accounts.amm_pool.curve_type.seed()[..];
     let bump = &[ctx.accounts.amm_pool.bump][..];
     let signer_seeds = &[
         b"amm_pool",
         ctx.accounts.amm_pool.token_a_mint.as_ref(),
         ctx.accounts.amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
         bump,
     ][..];
 
@@ -89,6 +161,21 @@ pub fn handle_add_amm_liquidity(
         lp_tokens_to_mint,
     )?;
 
+    if lp_tokens_to_lock > 0 {
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.lp_lock_vault.to_account_info(),
+                    authority: ctx.accounts.amm_pool.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            lp_tokens_to_lock,
+        )?;
+    }
+
     // Update the AmmPosition state
     ctx.accounts.amm_position.lp_token_amount = ctx
         .accounts
@@ -107,8 +194,9 @@ pub fn handle_add_amm_liquidity(
         .checked_add(amount_b_to_deposit)
         .ok_or(DloomError::MathOverflow)?;
 
-    emit!(AmmLiquidityAdded {
+    emit_event!(ctx.accounts, ctx.bumps, AmmLiquidityAdded {
         pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
         user: ctx.accounts.owner.key(),
         lp_tokens_minted: lp_tokens_to_mint,
         amount_a_deposited: amount_a_to_deposit,
@@ -118,14 +206,22 @@ pub fn handle_add_amm_liquidity(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct AddAmmLiquidity<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()],
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
         bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
@@ -147,10 +243,16 @@ pub struct AddAmmLiquidity<'info> {
     pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, address = amm_pool.token_b_vault)]
     pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Omit to pay the native SOL side of the pool straight from `owner`'s lamports.
     #[account(mut, constraint = user_token_a_account.mint == amm_pool.token_a_mint, has_one = owner)]
-    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_a_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Omit to pay the native SOL side of the pool straight from `owner`'s lamports.
     #[account(mut, constraint = user_token_b_account.mint == amm_pool.token_b_mint, has_one = owner)]
-    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_b_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, owner.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
     #[account(
         init_if_needed,
         payer = owner,
@@ -158,9 +260,14 @@ pub struct AddAmmLiquidity<'info> {
         associated_token::authority = owner,
     )]
     pub user_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, seeds = [b"lp_lock", amm_pool.key().as_ref()], bump)]
+    pub lp_lock_vault: InterfaceAccount<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
 }
\ No newline at end of file
@@ -4,12 +4,15 @@ use crate::{
     constants::PRECISION,
     errors::DloomError,
     amm::{state::{AmmPool, AmmPosition}}, 
-    events::AmmFeesClaimed,
+    events::{emit_event, AmmFeesClaimed},
+    token_extensions,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 
-pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
+pub fn handle_claim_lp_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimLpFees<'info>>,
+) -> Result<()> {
     let amm_pool = &ctx.accounts.amm_pool;
     let position = &mut ctx.accounts.amm_position;
 
@@ -38,17 +41,21 @@ pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
     position.fee_growth_snapshot_b = amm_pool.fee_growth_per_lp_token_b;
 
     // Prepare signer seeds
+    let fee_tier_bytes = &ctx.accounts.amm_pool.fee_tier.to_le_bytes()[..];
+    let curve_type_seed = &ctx.accounts.amm_pool.curve_type.seed()[..];
     let bump = &[ctx.accounts.amm_pool.bump][..];
     let signer_seeds = &[
         b"amm_pool",
         ctx.accounts.amm_pool.token_a_mint.as_ref(),
         ctx.accounts.amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
         bump,
     ][..];
 
     // Transfer fees
     if fees_to_claim_a > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_a_program.to_account_info(),
                 TransferChecked {
@@ -61,11 +68,12 @@ pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
             ),
             fees_to_claim_a,
             ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
 
     if fees_to_claim_b > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_b_program.to_account_info(),
                 TransferChecked {
@@ -78,6 +86,7 @@ pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
             ),
             fees_to_claim_b,
             ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
     
@@ -89,8 +98,9 @@ pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
         amm_pool_mut.reserves_b = amm_pool_mut.reserves_b.checked_sub(fees_to_claim_b).ok_or(DloomError::MathOverflow)?;
     }
 
-    emit!(AmmFeesClaimed {
+    emit_event!(ctx.accounts, ctx.bumps, AmmFeesClaimed {
         pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
         user: ctx.accounts.owner.key(),
         fees_claimed_a: fees_to_claim_a,
         fees_claimed_b: fees_to_claim_b,
@@ -99,6 +109,7 @@ pub fn handle_claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct ClaimLpFees<'info> {
     #[account(mut)]
@@ -106,8 +117,15 @@ pub struct ClaimLpFees<'info> {
 
     #[account(
         mut, // The pool needs to be mutable to update reserves when fees are taken out
-        seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()],
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
         bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
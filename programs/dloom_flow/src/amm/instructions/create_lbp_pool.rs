@@ -0,0 +1,69 @@
+// FILE: programs/dloom_flow/src/amm/instructions/create_lbp_pool.rs
+
+use crate::{
+    amm::instructions::create_pool::CreateLbpAmmPool,
+    constants::{BASIS_POINT_MAX, MIN_WEIGHT},
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// The weight schedule of a liquidity bootstrapping pool.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LbpParams {
+    /// Token A's weight, in basis points, when the sale starts.
+    pub start_weight_a: u16,
+    /// Token A's weight, in basis points, when the sale ends.
+    pub end_weight_a: u16,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+/// The handler for the `create_lbp_amm_pool` instruction.
+///
+/// Token A's weight moves linearly from `params.start_weight_a` to `params.end_weight_a`
+/// across the sale window. The payer becomes the launch owner, and swaps stay disabled
+/// until the protocol authority enables them.
+pub fn handle_create_lbp_amm_pool(
+    ctx: Context<CreateLbpAmmPool>,
+    fee_rate: u16,
+    protocol_fee_share: u16,
+    referrer_fee_share: u16,
+    params: LbpParams,
+) -> Result<()> {
+    let LbpParams {
+        start_weight_a,
+        end_weight_a,
+        start_timestamp,
+        end_timestamp,
+    } = params;
+    let max_weight = (BASIS_POINT_MAX as u16) - MIN_WEIGHT;
+    require!(
+        (MIN_WEIGHT..=max_weight).contains(&start_weight_a)
+            && (MIN_WEIGHT..=max_weight).contains(&end_weight_a),
+        DloomError::InvalidWeights
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        start_timestamp >= now && end_timestamp > start_timestamp,
+        DloomError::InvalidWeightSchedule
+    );
+
+    ctx.accounts.initialize_pool(
+        &ctx.bumps,
+        fee_rate,
+        fee_rate,
+        protocol_fee_share,
+        referrer_fee_share,
+    )?;
+
+    let launch_owner = ctx.accounts.payer.key();
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.launch_owner = launch_owner;
+    amm_pool.start_weight_a = start_weight_a;
+    amm_pool.end_weight_a = end_weight_a;
+    amm_pool.weight_start_timestamp = start_timestamp;
+    amm_pool.weight_end_timestamp = end_timestamp;
+    amm_pool.swaps_enabled = false;
+
+    Ok(())
+}
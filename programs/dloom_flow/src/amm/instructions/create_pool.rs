@@ -1,6 +1,13 @@
 // FILE: programs/dloom_flow/src/instructions/amm_create_pool.rs
 
-use crate::{amm::state::AmmPool, constants::*, errors::DloomError, events::AmmPoolCreated};
+use crate::{
+    amm::state::{AmmPool, CurveType},
+    constants::*,
+    errors::DloomError,
+    events::{emit_event, AmmPoolCreated},
+    state::ProtocolConfig,
+    token_extensions,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
@@ -11,135 +18,239 @@ pub fn handle_create_amm_pool(
     protocol_fee_share: u16,
     referrer_fee_share: u16,
 ) -> Result<()> {
-    require!(
-        fee_rate as u128 <= BASIS_POINT_MAX,
-        DloomError::InvalidFeeRates
-    );
-    require!(
-        protocol_fee_share as u128 <= BASIS_POINT_MAX,
-        DloomError::InvalidFeeRates
-    );
-    require!(
-        referrer_fee_share as u128 <= BASIS_POINT_MAX,
-        DloomError::InvalidFeeRates
-    );
-
-    let total_fee_share = (protocol_fee_share as u128)
-        .checked_add(referrer_fee_share as u128)
-        .ok_or(DloomError::MathOverflow)?;
-    require!(
-        total_fee_share <= BASIS_POINT_MAX,
-        DloomError::FeeShareExceedsTotal
-    );
-
-    let amm_pool = &mut ctx.accounts.amm_pool;
-    amm_pool.bump = ctx.bumps.amm_pool;
-    amm_pool.authority = ctx.accounts.authority.key();
-    amm_pool.token_a_mint = ctx.accounts.token_a_mint.key();
-    amm_pool.token_b_mint = ctx.accounts.token_b_mint.key();
-    amm_pool.token_a_vault = ctx.accounts.token_a_vault.key();
-    amm_pool.token_b_vault = ctx.accounts.token_b_vault.key();
-    amm_pool.lp_mint = ctx.accounts.lp_mint.key();
-    amm_pool.fee_rate = fee_rate;
-    amm_pool.protocol_fee_share = protocol_fee_share;
-    amm_pool.referrer_fee_share = referrer_fee_share;
-    amm_pool.protocol_fee_vault_a = ctx.accounts.protocol_fee_vault_a.key();
-    amm_pool.protocol_fee_vault_b = ctx.accounts.protocol_fee_vault_b.key();
-    amm_pool.reserves_a = 0;
-    amm_pool.reserves_b = 0;
-    amm_pool.price_a_cumulative_last_fee_update = 0;
-
-    emit!(AmmPoolCreated {
-        pool_address: ctx.accounts.amm_pool.key(),
-        token_a_mint: ctx.accounts.token_a_mint.key(),
-        token_b_mint: ctx.accounts.token_b_mint.key(),
-        lp_mint: ctx.accounts.lp_mint.key(),
+    ctx.accounts.initialize_pool(
+        &ctx.bumps,
         fee_rate,
-    });
-
-    Ok(())
+        fee_rate,
+        protocol_fee_share,
+        referrer_fee_share,
+    )
 }
 
-#[derive(Accounts)]
-pub struct CreateAmmPool<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-
-    /// CHECK: The authority for the protocol, passed in to be stored.
-    pub authority: AccountInfo<'info>,
-
-    #[account(constraint = token_a_mint.key() < token_b_mint.key() @ DloomError::InvalidMintOrder)]
-    pub token_a_mint: InterfaceAccount<'info, Mint>,
-    pub token_b_mint: InterfaceAccount<'info, Mint>,
-
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 390,
-        seeds = [b"amm_pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
-        bump
-    )]
-    pub amm_pool: Box<Account<'info, AmmPool>>,
-
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"lp_mint", amm_pool.key().as_ref()],
-        bump,
-        mint::decimals = 6,
-        mint::authority = amm_pool,
-        mint::token_program = token_program
-    )]
-    pub lp_mint: InterfaceAccount<'info, Mint>,
-
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"vault", amm_pool.key().as_ref(), token_a_mint.key().as_ref()],
-        bump,
-        token::mint = token_a_mint,
-        token::authority = amm_pool,
-        token::token_program = token_a_program
-    )]
-    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
-
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"vault", amm_pool.key().as_ref(), token_b_mint.key().as_ref()],
-        bump,
-        token::mint = token_b_mint,
-        token::authority = amm_pool,
-        token::token_program = token_b_program
-    )]
-    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
-
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"protocol_fee_vault", amm_pool.key().as_ref(), token_a_mint.key().as_ref()],
-        bump,
-        token::mint = token_a_mint,
-        token::authority = authority,
-        token::token_program = token_a_program
-    )]
-    pub protocol_fee_vault_a: InterfaceAccount<'info, TokenAccount>,
-
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"protocol_fee_vault", amm_pool.key().as_ref(), token_b_mint.key().as_ref()],
-        bump,
-        token::mint = token_b_mint,
-        token::authority = authority,
-        token::token_program = token_b_program
-    )]
-    pub protocol_fee_vault_b: InterfaceAccount<'info, TokenAccount>,
-
-    pub system_program: Program<'info, System>,
-    pub token_a_program: Interface<'info, TokenInterface>,
-    pub token_b_program: Interface<'info, TokenInterface>,
-    pub token_program: Interface<'info, TokenInterface>,
-
-    pub rent: Sysvar<'info, Rent>,
+/// Declares the accounts for creating an AMM pool of one curve type, together with the
+/// `initialize_pool` helper every creator shares. The pool's PDA is seeded by the pair,
+/// the fee tier and the curve type, so each curve type gets its own accounts struct.
+///
+/// `$fee_tier` is evaluated in the caller's scope, so an instruction argument it reads
+/// must be declared by the caller's own `#[instruction]` attribute.
+macro_rules! create_amm_pool_accounts {
+    ($(#[$attr:meta])* $name:ident, $bumps:ident, $curve_type:expr, $fee_tier:expr) => {
+        #[cfg_attr(feature = "event-cpi", event_cpi)]
+        #[derive(Accounts)]
+        $(#[$attr])*
+        pub struct $name<'info> {
+            #[account(mut)]
+            pub payer: Signer<'info>,
+
+            /// CHECK: The authority for the protocol, passed in to be stored.
+            pub authority: AccountInfo<'info>,
+
+            #[account(seeds = [b"protocol_config"], bump)]
+            pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+            #[account(constraint = token_a_mint.key() < token_b_mint.key() @ DloomError::InvalidMintOrder)]
+            pub token_a_mint: InterfaceAccount<'info, Mint>,
+            pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+            #[account(
+                init,
+                payer = payer,
+                space = 8 + 512,
+                seeds = [
+                    b"amm_pool",
+                    token_a_mint.key().as_ref(),
+                    token_b_mint.key().as_ref(),
+                    &$fee_tier.to_le_bytes(),
+                    &$curve_type.seed()
+                ],
+                bump
+            )]
+            pub amm_pool: Box<Account<'info, AmmPool>>,
+
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"lp_mint", amm_pool.key().as_ref()],
+                bump,
+                mint::decimals = 6,
+                mint::authority = amm_pool,
+                mint::token_program = token_program
+            )]
+            pub lp_mint: InterfaceAccount<'info, Mint>,
+
+            /// Holds the `MINIMUM_LIQUIDITY` minted on the first deposit. It is owned by the pool
+            /// and no instruction moves tokens out of it, so its balance is locked forever.
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"lp_lock", amm_pool.key().as_ref()],
+                bump,
+                token::mint = lp_mint,
+                token::authority = amm_pool,
+                token::token_program = token_program
+            )]
+            pub lp_lock_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"vault", amm_pool.key().as_ref(), token_a_mint.key().as_ref()],
+                bump,
+                token::mint = token_a_mint,
+                token::authority = amm_pool,
+                token::token_program = token_a_program
+            )]
+            pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"vault", amm_pool.key().as_ref(), token_b_mint.key().as_ref()],
+                bump,
+                token::mint = token_b_mint,
+                token::authority = amm_pool,
+                token::token_program = token_b_program
+            )]
+            pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"protocol_fee_vault", amm_pool.key().as_ref(), token_a_mint.key().as_ref()],
+                bump,
+                token::mint = token_a_mint,
+                token::authority = authority,
+                token::token_program = token_a_program
+            )]
+            pub protocol_fee_vault_a: InterfaceAccount<'info, TokenAccount>,
+
+            #[account(
+                init,
+                payer = payer,
+                seeds = [b"protocol_fee_vault", amm_pool.key().as_ref(), token_b_mint.key().as_ref()],
+                bump,
+                token::mint = token_b_mint,
+                token::authority = authority,
+                token::token_program = token_b_program
+            )]
+            pub protocol_fee_vault_b: InterfaceAccount<'info, TokenAccount>,
+
+            pub system_program: Program<'info, System>,
+            pub token_a_program: Interface<'info, TokenInterface>,
+            pub token_b_program: Interface<'info, TokenInterface>,
+            pub token_program: Interface<'info, TokenInterface>,
+
+            pub rent: Sysvar<'info, Rent>,
+        }
+
+        impl<'info> $name<'info> {
+            /// Validates the fee parameters and writes the fields shared by every curve type.
+            /// Curve-specific parameters are set by the calling handler afterwards.
+            /// `fee_tier` must be the tier the pool's PDA was seeded with.
+            pub(crate) fn initialize_pool(
+                &mut self,
+                bumps: &$bumps,
+                fee_tier: u16,
+                fee_rate: u16,
+                protocol_fee_share: u16,
+                referrer_fee_share: u16,
+            ) -> Result<()> {
+                let curve_type = $curve_type;
+                token_extensions::validate_mint_extensions(&self.token_a_mint, &self.protocol_config)?;
+                token_extensions::validate_mint_extensions(&self.token_b_mint, &self.protocol_config)?;
+                require!(
+                    fee_rate as u128 <= BASIS_POINT_MAX,
+                    DloomError::InvalidFeeRates
+                );
+                require!(
+                    protocol_fee_share as u128 <= BASIS_POINT_MAX,
+                    DloomError::InvalidFeeRates
+                );
+                require!(
+                    referrer_fee_share as u128 <= BASIS_POINT_MAX,
+                    DloomError::InvalidFeeRates
+                );
+
+                let total_fee_share = (protocol_fee_share as u128)
+                    .checked_add(referrer_fee_share as u128)
+                    .ok_or(DloomError::MathOverflow)?;
+                require!(
+                    total_fee_share <= BASIS_POINT_MAX,
+                    DloomError::FeeShareExceedsTotal
+                );
+
+                let amm_pool = &mut self.amm_pool;
+                amm_pool.bump = bumps.amm_pool;
+                amm_pool.authority = self.authority.key();
+                amm_pool.token_a_mint = self.token_a_mint.key();
+                amm_pool.token_b_mint = self.token_b_mint.key();
+                amm_pool.token_a_vault = self.token_a_vault.key();
+                amm_pool.token_b_vault = self.token_b_vault.key();
+                amm_pool.lp_mint = self.lp_mint.key();
+                amm_pool.fee_tier = fee_tier;
+                amm_pool.fee_rate = fee_rate;
+                amm_pool.protocol_fee_share = protocol_fee_share;
+                amm_pool.referrer_fee_share = referrer_fee_share;
+                amm_pool.protocol_fee_vault_a = self.protocol_fee_vault_a.key();
+                amm_pool.protocol_fee_vault_b = self.protocol_fee_vault_b.key();
+                amm_pool.reserves_a = 0;
+                amm_pool.reserves_b = 0;
+                amm_pool.price_a_cumulative_last_fee_update = 0;
+                amm_pool.curve_type = curve_type;
+
+                emit_event!(self, bumps, AmmPoolCreated {
+                    pool_address: self.amm_pool.key(),
+                    sequence: self.amm_pool.next_event_sequence(),
+                    token_a_mint: self.token_a_mint.key(),
+                    token_b_mint: self.token_b_mint.key(),
+                    lp_mint: self.lp_mint.key(),
+                    fee_tier,
+                    fee_rate,
+                    curve_type,
+                });
+
+                Ok(())
+            }
+        }
+    };
 }
+
+create_amm_pool_accounts!(
+    /// The accounts for `create_amm_pool`.
+    #[instruction(fee_rate: u16)]
+    CreateAmmPool,
+    CreateAmmPoolBumps,
+    CurveType::ConstantProduct,
+    fee_rate
+);
+create_amm_pool_accounts!(
+    /// The accounts for `create_stable_amm_pool`.
+    #[instruction(fee_rate: u16)]
+    CreateStableAmmPool,
+    CreateStableAmmPoolBumps,
+    CurveType::StableSwap,
+    fee_rate
+);
+create_amm_pool_accounts!(
+    /// The accounts for `create_weighted_amm_pool`.
+    #[instruction(fee_rate: u16)]
+    CreateWeightedAmmPool,
+    CreateWeightedAmmPoolBumps,
+    CurveType::Weighted,
+    fee_rate
+);
+create_amm_pool_accounts!(
+    /// The accounts for `create_lbp_amm_pool`.
+    #[instruction(fee_rate: u16)]
+    CreateLbpAmmPool,
+    CreateLbpAmmPoolBumps,
+    CurveType::LiquidityBootstrapping,
+    fee_rate
+);
+create_amm_pool_accounts!(
+    /// The pool accounts for `graduate_bonding_curve_to_amm`.
+    CreateGraduatedAmmPool,
+    CreateGraduatedAmmPoolBumps,
+    CurveType::ConstantProduct,
+    GRADUATION_FEE_TIER
+);
@@ -0,0 +1,42 @@
+// FILE: programs/dloom_flow/src/amm/instructions/create_stable_pool.rs
+
+use crate::{
+    amm::instructions::create_pool::CreateStableAmmPool,
+    constants::{MAX_AMP, MIN_AMP},
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// The handler for the `create_stable_amm_pool` instruction.
+///
+/// The curve type is part of the pool's PDA seeds, so a stable pool can sit alongside a
+/// constant-product pool for the same pair and fee tier.
+pub fn handle_create_stable_amm_pool(
+    ctx: Context<CreateStableAmmPool>,
+    fee_rate: u16,
+    protocol_fee_share: u16,
+    referrer_fee_share: u16,
+    amp: u64,
+) -> Result<()> {
+    require!(
+        (MIN_AMP..=MAX_AMP).contains(&amp),
+        DloomError::InvalidAmplification
+    );
+
+    ctx.accounts.initialize_pool(
+        &ctx.bumps,
+        fee_rate,
+        fee_rate,
+        protocol_fee_share,
+        referrer_fee_share,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.amp_initial = amp;
+    amm_pool.amp_target = amp;
+    amm_pool.amp_ramp_start_timestamp = now;
+    amm_pool.amp_ramp_end_timestamp = now;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// FILE: programs/dloom_flow/src/amm/instructions/create_weighted_pool.rs
+
+use crate::{
+    amm::instructions::create_pool::CreateWeightedAmmPool,
+    constants::{BASIS_POINT_MAX, MIN_WEIGHT},
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// The handler for the `create_weighted_amm_pool` instruction.
+///
+/// `weight_a` is token A's share of the pool value in basis points; token B takes the
+/// remainder. Weights are fixed for the lifetime of the pool.
+pub fn handle_create_weighted_amm_pool(
+    ctx: Context<CreateWeightedAmmPool>,
+    fee_rate: u16,
+    protocol_fee_share: u16,
+    referrer_fee_share: u16,
+    weight_a: u16,
+) -> Result<()> {
+    require!(
+        weight_a >= MIN_WEIGHT && weight_a as u128 <= BASIS_POINT_MAX - MIN_WEIGHT as u128,
+        DloomError::InvalidWeights
+    );
+
+    ctx.accounts.initialize_pool(
+        &ctx.bumps,
+        fee_rate,
+        fee_rate,
+        protocol_fee_share,
+        referrer_fee_share,
+    )?;
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.weight_a = weight_a;
+    amm_pool.weight_b = (BASIS_POINT_MAX as u16) - weight_a;
+
+    Ok(())
+}
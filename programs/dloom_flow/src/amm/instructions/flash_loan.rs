@@ -0,0 +1,210 @@
+// FILE: programs/dloom_flow/src/amm/instructions/flash_loan.rs
+
+use crate::{
+    amm::{instructions::swap::update_oracle, state::{AmmOracle, AmmPool}},
+    constants::PRECISION,
+    errors::DloomError,
+    events::{emit_event, AmmFlashLoanRepaid},
+    flash_loan, token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the `amm_flash_loan` instruction.
+///
+/// Lends `amount_a` and `amount_b` out of the pool's vaults, invokes `borrower_program`
+/// with `callback_data`, and then requires each vault to hold at least its starting
+/// balance plus a fee at the pool's `fee_rate`. The fee is paid entirely to LPs. While
+/// the callback runs the pool is locked, so it cannot be traded against or drained.
+pub fn handle_amm_flash_loan<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AmmFlashLoan<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    require!(amount_a > 0 || amount_b > 0, DloomError::ZeroAmount);
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
+
+    let fee_a = flash_loan::calculate_flash_loan_fee(amount_a, ctx.accounts.amm_pool.fee_rate)?;
+    let fee_b = flash_loan::calculate_flash_loan_fee(amount_b, ctx.accounts.amm_pool.fee_rate)?;
+    let vault_a_before = ctx.accounts.token_a_vault.amount;
+    let vault_b_before = ctx.accounts.token_b_vault.amount;
+
+    // 1. Lend the tokens out.
+    let amm_pool = &ctx.accounts.amm_pool;
+    let fee_tier_bytes = &amm_pool.fee_tier.to_le_bytes()[..];
+    let curve_type_seed = &amm_pool.curve_type.seed()[..];
+    let bump = &[amm_pool.bump][..];
+    let signer_seeds = &[
+        b"amm_pool",
+        amm_pool.token_a_mint.as_ref(),
+        amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
+        bump,
+    ][..];
+
+    if amount_a > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_vault.to_account_info(),
+                    to: ctx.accounts.borrower_token_a_account.to_account_info(),
+                    authority: amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_a_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_a,
+            ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+    if amount_b > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_vault.to_account_info(),
+                    to: ctx.accounts.borrower_token_b_account.to_account_info(),
+                    authority: amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_b_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_b,
+            ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 2. Lock the pool and write the lock to the account before handing over control,
+    // since any instruction the callback invokes reads the account data, not our copy.
+    ctx.accounts.amm_pool.flash_loan_active = true;
+    ctx.accounts.amm_pool.exit(ctx.program_id)?;
+    flash_loan::invoke_callback(
+        &ctx.accounts.borrower_program,
+        &ctx.accounts.borrower.to_account_info(),
+        ctx.remaining_accounts,
+        callback_data,
+    )?;
+
+    // 3. Reload the pool, so the final updates start from the account rather than the
+    // copy taken before the callback, and check the repayment against the vaults' actual
+    // balances.
+    ctx.accounts.amm_pool.reload()?;
+    ctx.accounts.token_a_vault.reload()?;
+    ctx.accounts.token_b_vault.reload()?;
+    require!(
+        ctx.accounts.token_a_vault.amount
+            >= vault_a_before.checked_add(fee_a).ok_or(DloomError::MathOverflow)?,
+        DloomError::FlashLoanNotRepaid
+    );
+    require!(
+        ctx.accounts.token_b_vault.amount
+            >= vault_b_before.checked_add(fee_b).ok_or(DloomError::MathOverflow)?,
+        DloomError::FlashLoanNotRepaid
+    );
+
+    // 4. Unlock the pool and pay the fees to LPs, as swap fees are.
+    let lp_mint_supply = ctx.accounts.lp_mint.supply as u128;
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.flash_loan_active = false;
+    amm_pool.reserves_a = amm_pool
+        .reserves_a
+        .checked_add(fee_a)
+        .ok_or(DloomError::MathOverflow)?;
+    amm_pool.reserves_b = amm_pool
+        .reserves_b
+        .checked_add(fee_b)
+        .ok_or(DloomError::MathOverflow)?;
+    if lp_mint_supply > 0 {
+        amm_pool.fee_growth_per_lp_token_a = amm_pool
+            .fee_growth_per_lp_token_a
+            .checked_add(
+                (fee_a as u128)
+                    .checked_mul(PRECISION)
+                    .ok_or(DloomError::MathOverflow)?
+                    .checked_div(lp_mint_supply)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+        amm_pool.fee_growth_per_lp_token_b = amm_pool
+            .fee_growth_per_lp_token_b
+            .checked_add(
+                (fee_b as u128)
+                    .checked_mul(PRECISION)
+                    .ok_or(DloomError::MathOverflow)?
+                    .checked_div(lp_mint_supply)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+    }
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmFlashLoanRepaid {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        borrower: ctx.accounts.borrower.key(),
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct AmmFlashLoan<'info> {
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(address = amm_pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = amm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = amm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the borrowed token A. Any account of the right mint may be used.
+    #[account(mut, token::mint = token_a_mint)]
+    pub borrower_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    /// Receives the borrowed token B. Any account of the right mint may be used.
+    #[account(mut, token::mint = token_b_mint)]
+    pub borrower_token_b_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The program invoked with the borrowed funds. It receives `borrower` and the
+    /// instruction's remaining accounts, and must repay the vaults before returning.
+    #[account(executable)]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
+}
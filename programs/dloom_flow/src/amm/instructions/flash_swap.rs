@@ -0,0 +1,20 @@
+// FILE: programs/dloom_flow/src/amm/instructions/flash_swap.rs
+
+use crate::amm::instructions::swap::{execute_swap, SwapOnAmm};
+use anchor_lang::prelude::*;
+
+/// The handler for the `flash_swap_on_amm` instruction.
+///
+/// Takes the same accounts as `swap_on_amm`, but sends `amount_out` to the trader before
+/// any input is collected. `callback_program` is then invoked with `callback_data` while
+/// the pool is locked, and must pay `amount_in` into the source vault; the reserves and
+/// fee growth are only updated once that payment has been verified. The trader's source
+/// token account may be omitted, since the callback pays from wherever it chooses.
+pub fn handle_flash_swap_on_amm<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapOnAmm<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    execute_swap(ctx, amount_in, min_amount_out, Some(callback_data))
+}
@@ -0,0 +1,71 @@
+// FILE: programs/dloom_flow/src/amm/instructions/grow_oracle.rs
+
+use crate::{
+    amm::state::{AmmObservation, AmmOracle, AmmPool},
+    constants::MAX_ORACLE_CARDINALITY,
+    errors::DloomError,
+    events::{emit_event, AmmOracleGrown},
+};
+use anchor_lang::prelude::*;
+
+/// The handler for the permissionless `grow_amm_oracle` instruction.
+///
+/// Allocates slots for the oracle to hold `cardinality_next` observations, paid for by
+/// the caller. The ring buffer grows into them once it next wraps. The runtime limits how
+/// much an account may grow in one instruction, so large increases take several calls.
+pub fn handle_grow_amm_oracle(ctx: Context<GrowAmmOracle>, cardinality_next: u16) -> Result<()> {
+    let amm_oracle = &mut ctx.accounts.amm_oracle;
+    let old_cardinality_next = amm_oracle.cardinality_next;
+    require!(
+        cardinality_next > old_cardinality_next && cardinality_next <= MAX_ORACLE_CARDINALITY,
+        DloomError::InvalidOracleCardinality
+    );
+    amm_oracle
+        .observations
+        .resize(cardinality_next as usize, AmmObservation::default());
+    amm_oracle.cardinality_next = cardinality_next;
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    emit_event!(ctx.accounts, ctx.bumps, AmmOracleGrown {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        old_cardinality_next,
+        new_cardinality_next: cardinality_next,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(cardinality_next: u16)]
+pub struct GrowAmmOracle<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_oracle", amm_pool.key().as_ref()],
+        bump = amm_oracle.bump,
+        realloc = AmmOracle::space(cardinality_next),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub amm_oracle: Box<Account<'info, AmmOracle>>,
+
+    pub system_program: Program<'info, System>,
+}
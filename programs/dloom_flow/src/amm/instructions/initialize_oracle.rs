@@ -0,0 +1,98 @@
+// FILE: programs/dloom_flow/src/amm/instructions/initialize_oracle.rs
+
+use crate::{
+    amm::{
+        instructions::swap::update_oracle,
+        state::{AmmObservation, AmmOracle, AmmPool},
+    },
+    constants::MAX_INITIAL_ORACLE_CARDINALITY,
+    errors::DloomError,
+    events::{emit_event, AmmOracleInitialized},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// The handler for the permissionless `initialize_amm_oracle` instruction.
+///
+/// Creates the pool's observation ring buffer with `cardinality` slots and records the
+/// pool's current cumulative prices as its first observation. From then on, every
+/// instruction that updates the pool's oracle must pass the account.
+pub fn handle_initialize_amm_oracle(
+    ctx: Context<InitializeAmmOracle>,
+    cardinality: u16,
+) -> Result<()> {
+    require!(
+        cardinality > 0 && cardinality <= MAX_INITIAL_ORACLE_CARDINALITY,
+        DloomError::InvalidOracleCardinality
+    );
+
+    // Bring the cumulative prices up to date, so the first observation is taken now.
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        None,
+    )?;
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    let amm_oracle = &mut ctx.accounts.amm_oracle;
+    amm_oracle.pool = amm_pool.key();
+    amm_oracle.bump = ctx.bumps.amm_oracle;
+    amm_oracle.index = 0;
+    amm_oracle.cardinality = 1;
+    amm_oracle.cardinality_next = cardinality;
+    amm_oracle.observations = vec![AmmObservation::default(); cardinality as usize];
+    amm_oracle.observations[0] = AmmObservation {
+        timestamp: amm_pool.last_update_timestamp,
+        price_a_cumulative: amm_pool.price_a_cumulative,
+        price_b_cumulative: amm_pool.price_b_cumulative,
+    };
+    amm_pool.has_oracle = true;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmOracleInitialized {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        oracle_address: amm_oracle.key(),
+        cardinality,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(cardinality: u16)]
+pub struct InitializeAmmOracle<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = AmmOracle::space(cardinality),
+        seeds = [b"amm_oracle", amm_pool.key().as_ref()],
+        bump
+    )]
+    pub amm_oracle: Box<Account<'info, AmmOracle>>,
+
+    pub system_program: Program<'info, System>,
+}
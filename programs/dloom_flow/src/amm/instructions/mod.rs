@@ -1,16 +1,42 @@
 // FILE: programs/dloom_flow/src/amm/instructions/mod.rs
 pub mod add_liquidity;
 pub mod claim_lp_fees;
+pub mod create_lbp_pool;
 pub mod create_pool;
+pub mod create_stable_pool;
+pub mod create_weighted_pool;
+pub mod flash_loan;
+pub mod flash_swap;
+pub mod grow_oracle;
+pub mod initialize_oracle;
+pub mod observe_oracle;
 pub mod remove_liquidity;
 pub mod reinvest_lp_fees;
 pub mod open_position;
+pub mod quote;
+pub mod set_lbp_swaps_enabled;
+pub mod skim_reserves;
 pub mod swap;
+pub mod sync_reserves;
+pub mod withdraw_lbp_proceeds;
 
 pub use add_liquidity::*;
 pub use claim_lp_fees::*;
+pub use create_lbp_pool::*;
 pub use create_pool::*;
+pub use create_stable_pool::*;
+pub use create_weighted_pool::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
+pub use grow_oracle::*;
+pub use initialize_oracle::*;
+pub use observe_oracle::*;
 pub use remove_liquidity::*;
 pub use reinvest_lp_fees::*;
 pub use open_position::*;
-pub use swap::*;
\ No newline at end of file
+pub use quote::*;
+pub use set_lbp_swaps_enabled::*;
+pub use skim_reserves::*;
+pub use swap::*;
+pub use sync_reserves::*;
+pub use withdraw_lbp_proceeds::*;
\ No newline at end of file
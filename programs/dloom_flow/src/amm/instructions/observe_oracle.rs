@@ -0,0 +1,69 @@
+// FILE: programs/dloom_flow/src/amm/instructions/observe_oracle.rs
+
+use crate::{
+    amm::{
+        instructions::swap::calculate_spot_prices,
+        state::{AmmObservation, AmmOracle, AmmPool},
+    },
+    constants::MAX_ORACLE_QUERIES,
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// Returns the pool's cumulative prices at `now`, as `update_oracle` would leave them if
+/// it ran now.
+pub fn current_observation(pool: &AmmPool, now: i64) -> Result<AmmObservation> {
+    let mut observation = AmmObservation {
+        timestamp: now,
+        price_a_cumulative: pool.price_a_cumulative,
+        price_b_cumulative: pool.price_b_cumulative,
+    };
+    let time_elapsed = now
+        .checked_sub(pool.last_update_timestamp)
+        .ok_or(DloomError::MathOverflow)?;
+    if time_elapsed > 0 && pool.reserves_a > 0 && pool.reserves_b > 0 {
+        let (price_a, price_b) = calculate_spot_prices(pool, now)?;
+        let accumulate = |cumulative: u128, price: u128| {
+            price
+                .checked_mul(time_elapsed as u128)
+                .and_then(|accrued| cumulative.checked_add(accrued))
+                .ok_or(DloomError::MathOverflow)
+        };
+        observation.price_a_cumulative = accumulate(observation.price_a_cumulative, price_a)?;
+        observation.price_b_cumulative = accumulate(observation.price_b_cumulative, price_b)?;
+    }
+    Ok(observation)
+}
+
+/// The handler for the `observe_amm_oracle` instruction.
+///
+/// Returns the pool's cumulative prices as they were `seconds_ago` seconds before now,
+/// one observation per entry, as the instruction's return data. The TWAP between two of
+/// them is the difference in their cumulative prices divided by the time between them,
+/// scaled by 1e9 like `calculate_spot_prices`.
+pub fn handle_observe_amm_oracle(
+    ctx: Context<ObserveAmmOracle>,
+    seconds_ago: Vec<u32>,
+) -> Result<Vec<AmmObservation>> {
+    require!(
+        !seconds_ago.is_empty() && seconds_ago.len() <= MAX_ORACLE_QUERIES,
+        DloomError::InvalidOracleQuery
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let current = current_observation(&ctx.accounts.amm_pool, now)?;
+    seconds_ago
+        .iter()
+        .map(|seconds_ago| {
+            ctx.accounts
+                .amm_oracle
+                .observe(&current, now - *seconds_ago as i64)
+        })
+        .collect()
+}
+
+#[derive(Accounts)]
+pub struct ObserveAmmOracle<'info> {
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Box<Account<'info, AmmOracle>>,
+}
@@ -3,6 +3,8 @@ use crate::{
     amm::{
         state::{AmmPool, AmmPosition, FeePreference}, 
     },
+    errors::DloomError,
+    events::{emit_event, AmmPositionOpened},
 };
 use anchor_lang::prelude::*;
 
@@ -18,17 +20,33 @@ pub fn handle_open_amm_position(
     position.fee_growth_snapshot_b = 0;
     position.fee_preference = fee_preference;
 
+    emit_event!(ctx.accounts, ctx.bumps, AmmPositionOpened {
+        pool_address: position.pool,
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
+        owner: position.owner,
+        position_address: position.key(),
+        fee_preference,
+    });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct OpenAmmPosition<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()],
-        bump = amm_pool.bump
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
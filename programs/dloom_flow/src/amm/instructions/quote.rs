@@ -0,0 +1,196 @@
+// FILE: programs/dloom_flow/src/amm/instructions/quote.rs
+
+use crate::{
+    amm::{
+        math,
+        state::{AmmPool, CurveType},
+        weighted_math,
+    },
+    constants::MINIMUM_LIQUIDITY,
+    errors::DloomError,
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// The result of `quote_amm_swap`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AmmSwapQuote {
+    /// The input the swap is priced on, after any Token-2022 transfer fee.
+    pub net_amount_in: u64,
+    /// What the trader would receive, after any Token-2022 transfer fee.
+    pub amount_out: u64,
+    pub protocol_fee: u64,
+    pub lp_fee: u64,
+}
+
+/// The result of `quote_add_liquidity`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AmmAddLiquidityQuote {
+    /// What the depositor would send, grossed up for any Token-2022 transfer fee.
+    pub amount_a_to_send: u64,
+    pub amount_b_to_send: u64,
+    /// What the vaults would be credited with.
+    pub amount_a_deposited: u64,
+    pub amount_b_deposited: u64,
+    /// LP tokens minted to the depositor, excluding any locked minimum liquidity.
+    pub lp_tokens_to_mint: u64,
+}
+
+/// The result of `quote_remove_liquidity`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AmmRemoveLiquidityQuote {
+    /// What would leave the vaults.
+    pub amount_a_withdrawn: u64,
+    pub amount_b_withdrawn: u64,
+    /// What the owner would receive, after any Token-2022 transfer fee.
+    pub amount_a_received: u64,
+    pub amount_b_received: u64,
+}
+
+/// The handler for the `quote_amm_swap` instruction.
+///
+/// Prices a swap exactly as `swap_on_amm` would, without moving tokens or updating the
+/// pool, and returns the result as the instruction's return data.
+pub fn handle_quote_amm_swap(
+    ctx: Context<QuoteAmm>,
+    amount_in: u64,
+    is_a_to_b: bool,
+) -> Result<AmmSwapQuote> {
+    let amm_pool = &ctx.accounts.amm_pool;
+    if amm_pool.curve_type == CurveType::LiquidityBootstrapping {
+        require!(amm_pool.swaps_enabled, DloomError::SwapsDisabled);
+    }
+
+    let (source_mint, destination_mint) = if is_a_to_b {
+        (&ctx.accounts.token_a_mint, &ctx.accounts.token_b_mint)
+    } else {
+        (&ctx.accounts.token_b_mint, &ctx.accounts.token_a_mint)
+    };
+
+    let net_amount_in = token_extensions::calculate_net_received(source_mint, amount_in)?;
+    require!(net_amount_in > 0, DloomError::ZeroAmount);
+    let (amount_out, protocol_fee, lp_fee) = math::calculate_swap_on_curve(
+        amm_pool,
+        net_amount_in,
+        is_a_to_b,
+        source_mint.decimals,
+        destination_mint.decimals,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    Ok(AmmSwapQuote {
+        net_amount_in,
+        amount_out: token_extensions::calculate_net_received(destination_mint, amount_out)?,
+        protocol_fee,
+        lp_fee,
+    })
+}
+
+/// The handler for the `quote_add_liquidity` instruction.
+///
+/// Sizes a deposit exactly as `add_amm_liquidity` would and returns the result as the
+/// instruction's return data.
+pub fn handle_quote_add_liquidity(
+    ctx: Context<QuoteAmm>,
+    amount_a_desired: u64,
+    amount_b_desired: u64,
+) -> Result<AmmAddLiquidityQuote> {
+    require!(
+        amount_a_desired > 0 && amount_b_desired > 0,
+        DloomError::ZeroLiquidity
+    );
+
+    let net_amount_a_desired =
+        token_extensions::calculate_net_received(&ctx.accounts.token_a_mint, amount_a_desired)?;
+    let net_amount_b_desired =
+        token_extensions::calculate_net_received(&ctx.accounts.token_b_mint, amount_b_desired)?;
+
+    let (amount_a_deposited, amount_b_deposited, total_lp_tokens) =
+        if ctx.accounts.amm_pool.curve_type.is_weighted() {
+            weighted_math::calculate_weighted_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                net_amount_a_desired,
+                net_amount_b_desired,
+            )?
+        } else {
+            math::calculate_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                net_amount_a_desired,
+                net_amount_b_desired,
+            )?
+        };
+
+    let lp_tokens_to_lock = if ctx.accounts.lp_mint.supply == 0 {
+        MINIMUM_LIQUIDITY
+    } else {
+        0
+    };
+
+    Ok(AmmAddLiquidityQuote {
+        amount_a_to_send: token_extensions::calculate_gross_to_send(
+            &ctx.accounts.token_a_mint,
+            amount_a_deposited,
+        )?,
+        amount_b_to_send: token_extensions::calculate_gross_to_send(
+            &ctx.accounts.token_b_mint,
+            amount_b_deposited,
+        )?,
+        amount_a_deposited,
+        amount_b_deposited,
+        lp_tokens_to_mint: total_lp_tokens
+            .checked_sub(lp_tokens_to_lock)
+            .ok_or(DloomError::InsufficientInitialLiquidity)?,
+    })
+}
+
+/// The handler for the `quote_remove_liquidity` instruction.
+///
+/// Prices a withdrawal exactly as `remove_amm_liquidity` would and returns the result as
+/// the instruction's return data.
+pub fn handle_quote_remove_liquidity(
+    ctx: Context<QuoteAmm>,
+    lp_tokens_to_burn: u64,
+) -> Result<AmmRemoveLiquidityQuote> {
+    let amm_pool = &ctx.accounts.amm_pool;
+    if amm_pool.curve_type == CurveType::LiquidityBootstrapping {
+        require!(
+            Clock::get()?.unix_timestamp >= amm_pool.weight_end_timestamp,
+            DloomError::LaunchNotEnded
+        );
+    }
+
+    let (amount_a_withdrawn, amount_b_withdrawn) = math::calculate_assets_to_withdraw(
+        amm_pool.reserves_a,
+        amm_pool.reserves_b,
+        ctx.accounts.lp_mint.supply,
+        lp_tokens_to_burn,
+    )?;
+
+    Ok(AmmRemoveLiquidityQuote {
+        amount_a_withdrawn,
+        amount_b_withdrawn,
+        amount_a_received: token_extensions::calculate_net_received(
+            &ctx.accounts.token_a_mint,
+            amount_a_withdrawn,
+        )?,
+        amount_b_received: token_extensions::calculate_net_received(
+            &ctx.accounts.token_b_mint,
+            amount_b_withdrawn,
+        )?,
+    })
+}
+
+/// The read-only accounts shared by the AMM quote instructions.
+#[derive(Accounts)]
+pub struct QuoteAmm<'info> {
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(address = amm_pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+}
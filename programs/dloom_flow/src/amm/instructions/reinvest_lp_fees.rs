@@ -3,9 +3,11 @@
 use crate::{
     constants::PRECISION,
     errors::DloomError,
+    events::{emit_event, AmmLpFeesReinvested},
     amm::{
-        math,                              
-        state::{AmmPool, AmmPosition, FeePreference}, 
+        math,
+        state::{AmmPool, AmmPosition, FeePreference},
+        weighted_math,
     },
 };
 use anchor_lang::prelude::*;
@@ -49,50 +51,71 @@ pub fn handle_reinvest_lp_fees(ctx: Context<ReinvestLpFees>) -> Result<()> {
     // 4. Treat the fees as a new liquidity deposit to calculate LP tokens to mint.
     // This function correctly handles cases where one of the amounts is zero.
     let (_amount_a_to_deposit, _amount_b_to_deposit, lp_tokens_to_mint) =
-        math::calculate_lp_tokens_to_mint(
-            &ctx.accounts.amm_pool,
-            &ctx.accounts.lp_mint,
-            fees_to_reinvest_a,
-            fees_to_reinvest_b,
+        if ctx.accounts.amm_pool.curve_type.is_weighted() {
+            weighted_math::calculate_weighted_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                fees_to_reinvest_a,
+                fees_to_reinvest_b,
+            )?
+        } else {
+            math::calculate_lp_tokens_to_mint(
+                &ctx.accounts.amm_pool,
+                ctx.accounts.lp_mint.supply,
+                fees_to_reinvest_a,
+                fees_to_reinvest_b,
+            )?
+        };
+
+    // 5. Mint new LP tokens to the user. Nothing is minted if no fees accrued or they
+    // are too small, but the snapshots above still moved, so the event is always emitted.
+    if lp_tokens_to_mint > 0 {
+        let fee_tier_bytes = &ctx.accounts.amm_pool.fee_tier.to_le_bytes()[..];
+        let curve_type_seed = &ctx.accounts.amm_pool.curve_type.seed()[..];
+        let bump = &[ctx.accounts.amm_pool.bump][..];
+        let signer_seeds = &[
+            b"amm_pool",
+            ctx.accounts.amm_pool.token_a_mint.as_ref(),
+            ctx.accounts.amm_pool.token_b_mint.as_ref(),
+            fee_tier_bytes,
+            curve_type_seed,
+            bump,
+        ][..];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.user_lp_token_account.to_account_info(),
+                    authority: ctx.accounts.amm_pool.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            lp_tokens_to_mint,
         )?;
 
-    if lp_tokens_to_mint == 0 {
-        return Ok(()); // Nothing to do if no fees accrued or are too small
+        position.lp_token_amount = position.lp_token_amount.checked_add(lp_tokens_to_mint).ok_or(DloomError::MathOverflow)?;
     }
 
-    // 5. Mint new LP tokens to the user.
-    let bump = &[ctx.accounts.amm_pool.bump][..];
-    let signer_seeds = &[
-        b"amm_pool",
-        ctx.accounts.amm_pool.token_a_mint.as_ref(),
-        ctx.accounts.amm_pool.token_b_mint.as_ref(),
-        bump,
-    ][..];
-    token_interface::mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.lp_mint.to_account_info(),
-                to: ctx.accounts.user_lp_token_account.to_account_info(),
-                authority: ctx.accounts.amm_pool.to_account_info(),
-            },
-            &[signer_seeds],
-        ),
-        lp_tokens_to_mint,
-    )?;
-
-    position.lp_token_amount = position.lp_token_amount.checked_add(lp_tokens_to_mint).ok_or(DloomError::MathOverflow)?;
-
+    emit_event!(ctx.accounts, ctx.bumps, AmmLpFeesReinvested {
+        pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
+        user: ctx.accounts.owner.key(),
+        fees_reinvested_a: fees_to_reinvest_a,
+        fees_reinvested_b: fees_to_reinvest_b,
+        lp_tokens_minted: lp_tokens_to_mint,
+    });
 
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct ReinvestLpFees<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    #[account(mut, seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()], bump = amm_pool.bump)]
+    #[account(mut, seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref(), &amm_pool.fee_tier.to_le_bytes(), &amm_pool.curve_type.seed()], bump = amm_pool.bump, constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive)]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
     #[account(mut, has_one = owner, seeds = [b"amm_position", owner.key().as_ref(), amm_pool.key().as_ref()], bump)]
@@ -2,11 +2,13 @@
 
 use crate::{
     errors::DloomError,
-    events::AmmLiquidityRemoved,
+    events::{emit_event, AmmLiquidityRemoved},
+    native_sol::{NativeSol, WSOL_SEED},
+    token_extensions,
     amm::{
         instructions::swap::update_oracle,
         math,                              
-        state::{AmmPool, AmmPosition},
+        state::{AmmOracle, AmmPool, AmmPosition, CurveType},
     },
 };
 use anchor_lang::prelude::*;
@@ -14,13 +16,25 @@ use anchor_spl::token_interface::{
     self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
 };
 
-pub fn handle_remove_amm_liquidity(
-    ctx: Context<RemoveAmmLiquidity>,
+pub fn handle_remove_amm_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RemoveAmmLiquidity<'info>>,
     lp_tokens_to_burn: u64,
     min_amount_a_to_receive: u64,
     min_amount_b_to_receive: u64,
 ) -> Result<()> {
-    update_oracle(&mut ctx.accounts.amm_pool)?;
+    // Liquidity in a bootstrapping pool stays put until the sale has ended.
+    if ctx.accounts.amm_pool.curve_type == CurveType::LiquidityBootstrapping {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.amm_pool.weight_end_timestamp,
+            DloomError::LaunchNotEnded
+        );
+    }
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
     let amm_pool_state = &ctx.accounts.amm_pool;
     let lp_mint = &ctx.accounts.lp_mint;
 
@@ -31,30 +45,60 @@ pub fn handle_remove_amm_liquidity(
         lp_tokens_to_burn,
     )?;
 
+    // Slippage is checked against what the user receives after any transfer fees.
+    let net_amount_a_received =
+        token_extensions::calculate_net_received(&ctx.accounts.token_a_mint, amount_a_to_withdraw)?;
+    let net_amount_b_received =
+        token_extensions::calculate_net_received(&ctx.accounts.token_b_mint, amount_b_to_withdraw)?;
     require!(
-        amount_a_to_withdraw >= min_amount_a_to_receive,
+        net_amount_a_received >= min_amount_a_to_receive,
         DloomError::SlippageExceeded
     );
     require!(
-        amount_b_to_withdraw >= min_amount_b_to_receive,
+        net_amount_b_received >= min_amount_b_to_receive,
         DloomError::SlippageExceeded
     );
 
+    let fee_tier_bytes = &ctx.accounts.amm_pool.fee_tier.to_le_bytes()[..];
+
+    let curve_type_seed = &ctx.accounts.amm_pool.curve_type.seed()[..];
     let bump = &[ctx.accounts.amm_pool.bump][..];
     let signer_seeds = &[
         b"amm_pool",
         ctx.accounts.amm_pool.token_a_mint.as_ref(),
         ctx.accounts.amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
         bump,
     ][..];
 
+    // Either side may be paid out in native SOL, which is unwrapped once both transfers land.
+    let native_sol = NativeSol::new(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        ctx.accounts.system_program.as_ref().map(|a| a.to_account_info()),
+    );
+    let user_token_a_account = native_sol.resolve(
+        ctx.accounts.user_token_a_account.as_ref(),
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program.to_account_info(),
+        0,
+    )?;
+    let user_token_b_account = native_sol.resolve(
+        ctx.accounts.user_token_b_account.as_ref(),
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program.to_account_info(),
+        0,
+    )?;
+
     if amount_a_to_withdraw > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_a_program.to_account_info(),
                 TransferChecked {
                     from: ctx.accounts.token_a_vault.to_account_info(),
-                    to: ctx.accounts.user_token_a_account.to_account_info(),
+                    to: user_token_a_account,
                     authority: ctx.accounts.amm_pool.to_account_info(),
                     mint: ctx.accounts.token_a_mint.to_account_info(),
                 },
@@ -62,16 +106,17 @@ pub fn handle_remove_amm_liquidity(
             ),
             amount_a_to_withdraw,
             ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
 
     if amount_b_to_withdraw > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_b_program.to_account_info(),
                 TransferChecked {
                     from: ctx.accounts.token_b_vault.to_account_info(),
-                    to: ctx.accounts.user_token_b_account.to_account_info(),
+                    to: user_token_b_account,
                     authority: ctx.accounts.amm_pool.to_account_info(),
                     mint: ctx.accounts.token_b_mint.to_account_info(),
                 },
@@ -79,8 +124,10 @@ pub fn handle_remove_amm_liquidity(
             ),
             amount_b_to_withdraw,
             ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
+    native_sol.close()?;
 
     token_interface::burn(
         CpiContext::new(
@@ -113,8 +160,9 @@ pub fn handle_remove_amm_liquidity(
         .checked_sub(amount_b_to_withdraw)
         .ok_or(DloomError::MathOverflow)?;
 
-    emit!(AmmLiquidityRemoved {
+    emit_event!(ctx.accounts, ctx.bumps, AmmLiquidityRemoved {
         pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
         user: ctx.accounts.owner.key(),
         lp_tokens_burned: lp_tokens_to_burn,
         amount_a_received: amount_a_to_withdraw,
@@ -124,6 +172,7 @@ pub fn handle_remove_amm_liquidity(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct RemoveAmmLiquidity<'info> {
     #[account(mut)]
@@ -131,8 +180,15 @@ pub struct RemoveAmmLiquidity<'info> {
 
     #[account(
         mut,
-        seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()],
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
         bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
@@ -159,12 +215,22 @@ pub struct RemoveAmmLiquidity<'info> {
 
     #[account(mut, has_one = owner)]
     pub user_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Omit to receive the native SOL side of the pool as lamports.
     #[account(mut, has_one = owner)]
-    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_a_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Omit to receive the native SOL side of the pool as lamports.
     #[account(mut, has_one = owner)]
-    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_b_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, owner.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
 
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Option<Program<'info, System>>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
 }
\ No newline at end of file
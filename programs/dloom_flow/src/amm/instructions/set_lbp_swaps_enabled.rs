@@ -0,0 +1,46 @@
+// FILE: programs/dloom_flow/src/amm/instructions/set_lbp_swaps_enabled.rs
+
+use crate::{
+    amm::state::{AmmPool, CurveType},
+    errors::DloomError,
+    events::{emit_event, AmmLbpSwapStatusUpdated},
+    state::ProtocolConfig,
+};
+use anchor_lang::prelude::*;
+
+/// The handler for the `set_lbp_swaps_enabled` instruction.
+/// Lets the protocol authority open or pause trading on a liquidity bootstrapping pool.
+pub fn handle_set_lbp_swaps_enabled(ctx: Context<SetLbpSwapsEnabled>, enabled: bool) -> Result<()> {
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.swaps_enabled = enabled;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmLbpSwapStatusUpdated {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        swaps_enabled: enabled,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SetLbpSwapsEnabled<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"protocol_config"], bump, has_one = authority)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = amm_pool.curve_type == CurveType::LiquidityBootstrapping @ DloomError::InvalidCurveType,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+}
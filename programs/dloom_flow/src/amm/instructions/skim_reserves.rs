@@ -0,0 +1,124 @@
+// FILE: programs/dloom_flow/src/amm/instructions/skim_reserves.rs
+
+use crate::{
+    amm::state::AmmPool, errors::DloomError, events::{emit_event, AmmSurplusSkimmed}, token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the permissionless `skim_amm_reserves` instruction.
+///
+/// Sends any vault balance above the tracked reserves to the pool's protocol fee vaults,
+/// leaving the reserves untouched. The destination is fixed, so anyone may call it.
+pub fn handle_skim_amm_reserves<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SkimAmmReserves<'info>>,
+) -> Result<()> {
+    let amm_pool = &ctx.accounts.amm_pool;
+    let surplus_a = ctx
+        .accounts
+        .token_a_vault
+        .amount
+        .saturating_sub(amm_pool.reserves_a);
+    let surplus_b = ctx
+        .accounts
+        .token_b_vault
+        .amount
+        .saturating_sub(amm_pool.reserves_b);
+
+    let fee_tier_bytes = &amm_pool.fee_tier.to_le_bytes()[..];
+
+    let curve_type_seed = &amm_pool.curve_type.seed()[..];
+    let bump = &[amm_pool.bump][..];
+    let signer_seeds = &[
+        b"amm_pool",
+        amm_pool.token_a_mint.as_ref(),
+        amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
+        bump,
+    ][..];
+
+    if surplus_a > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_vault.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault_a.to_account_info(),
+                    authority: amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_a_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            surplus_a,
+            ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    if surplus_b > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_vault.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault_b.to_account_info(),
+                    authority: amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_b_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            surplus_b,
+            ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    emit_event!(ctx.accounts, ctx.bumps, AmmSurplusSkimmed {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        reserves_a: amm_pool.reserves_a,
+        reserves_b: amm_pool.reserves_b,
+        surplus_a,
+        surplus_b,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SkimAmmReserves<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = amm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = amm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = amm_pool.protocol_fee_vault_a)]
+    pub protocol_fee_vault_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = amm_pool.protocol_fee_vault_b)]
+    pub protocol_fee_vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
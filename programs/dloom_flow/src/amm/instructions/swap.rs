@@ -2,21 +2,73 @@
 
 use crate::{
     amm::{
-        math,           
-        state::AmmPool, 
+        math, weighted_math,
+        state::{AmmOracle, AmmPool, CurveType},
     },
     constants::*,
     errors::DloomError,
-    events::AmmSwap,
+    events::{emit_event, AmmSwap},
+    flash_loan,
+    native_sol::{is_native_mint, NativeSol, WSOL_SEED},
+    token_extensions,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::AccountDeserialize;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Returns the instantaneous `(price_a, price_b)` implied by the pool's raw reserves,
+/// scaled by 1e9. `price_a` is the price of token A in terms of token B.
+pub(crate) fn calculate_spot_prices(pool: &AmmPool, now: i64) -> Result<(u128, u128)> {
+    // Weighted pools quote (reserves_b / weight_b) / (reserves_a / weight_a); every
+    // other curve is treated as equally weighted.
+    let (weight_a, weight_b) = if pool.curve_type.is_weighted() {
+        let (weight_a, weight_b) = weighted_math::current_weights(pool, now)?;
+        (weight_a as u128, weight_b as u128)
+    } else {
+        (1, 1)
+    };
+
+    // price = reserves_b / reserves_a. We use u128 and scale for fixed-point math.
+    let price_a = (pool.reserves_b as u128)
+        .checked_mul(1_000_000_000) // Scale for precision
+        .ok_or(DloomError::MathOverflow)?
+        .checked_mul(weight_a)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(
+            (pool.reserves_a as u128)
+                .checked_mul(weight_b)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+
+    // Do the same for the inverse price
+    let price_b = (pool.reserves_a as u128)
+        .checked_mul(1_000_000_000) // Scale for precision
+        .ok_or(DloomError::MathOverflow)?
+        .checked_mul(weight_b)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(
+            (pool.reserves_b as u128)
+                .checked_mul(weight_a)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+
+    Ok((price_a, price_b))
+}
 
 /// Updates the oracle's cumulative price values.
 /// This is made public within the crate (`pub(crate)`) so that `add_liquidity` and
 /// `remove_liquidity` can also call it, ensuring the oracle is always up-to-date.
-pub(crate) fn update_oracle(pool: &mut Account<AmmPool>) -> Result<()> {
+///
+/// The mints are read only to accumulate the UI-adjusted prices alongside the raw ones.
+/// Once the pool has an `AmmOracle`, `oracle` is required and records the new values.
+pub(crate) fn update_oracle(
+    pool: &mut Account<AmmPool>,
+    token_a_mint: &InterfaceAccount<Mint>,
+    token_b_mint: &InterfaceAccount<Mint>,
+    oracle: Option<&mut Account<AmmOracle>>,
+) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
     // On the very first action, just set the initial timestamp.
@@ -31,12 +83,9 @@ pub(crate) fn update_oracle(pool: &mut Account<AmmPool>) -> Result<()> {
 
     // Only update if time has passed and there are reserves to calculate a price.
     if time_elapsed > 0 && pool.reserves_a > 0 && pool.reserves_b > 0 {
-        // price = reserves_b / reserves_a. We use u128 and scale for fixed-point math.
-        let price_a = (pool.reserves_b as u128)
-            .checked_mul(1_000_000_000) // Scale for precision
-            .ok_or(DloomError::MathOverflow)?
-            .checked_div(pool.reserves_a as u128)
-            .ok_or(DloomError::MathOverflow)?;
+        let (price_a, price_b) = calculate_spot_prices(pool, now)?;
+        let ui_price_a = token_extensions::calculate_ui_price(price_a, token_a_mint, token_b_mint, now)?;
+        let ui_price_b = token_extensions::calculate_ui_price(price_b, token_b_mint, token_a_mint, now)?;
 
         pool.price_a_cumulative = pool
             .price_a_cumulative
@@ -46,14 +95,6 @@ pub(crate) fn update_oracle(pool: &mut Account<AmmPool>) -> Result<()> {
                     .ok_or(DloomError::MathOverflow)?,
             )
             .ok_or(DloomError::MathOverflow)?;
-
-        // Do the same for the inverse price
-        let price_b = (pool.reserves_a as u128)
-            .checked_mul(1_000_000_000) // Scale for precision
-            .ok_or(DloomError::MathOverflow)?
-            .checked_div(pool.reserves_b as u128)
-            .ok_or(DloomError::MathOverflow)?;
-
         pool.price_b_cumulative = pool
             .price_b_cumulative
             .checked_add(
@@ -62,17 +103,70 @@ pub(crate) fn update_oracle(pool: &mut Account<AmmPool>) -> Result<()> {
                     .ok_or(DloomError::MathOverflow)?,
             )
             .ok_or(DloomError::MathOverflow)?;
+        pool.ui_price_a_cumulative = pool
+            .ui_price_a_cumulative
+            .checked_add(
+                ui_price_a
+                    .checked_mul(time_elapsed as u128)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+        pool.ui_price_b_cumulative = pool
+            .ui_price_b_cumulative
+            .checked_add(
+                ui_price_b
+                    .checked_mul(time_elapsed as u128)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
     }
 
     pool.last_update_timestamp = now;
+    if pool.has_oracle {
+        let oracle = oracle.ok_or(DloomError::OracleAccountRequired)?;
+        oracle.write(now, pool.price_a_cumulative, pool.price_b_cumulative);
+    }
     Ok(())
 }
 
-pub fn handle_swap_on_amm(
-    ctx: Context<SwapOnAmm>,
+pub fn handle_swap_on_amm<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapOnAmm<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    execute_swap(ctx, amount_in, min_amount_out, None)
+}
+
+/// Executes a swap. When `callback_data` is set this is a flash swap: the output is sent
+/// first and `callback_program` is invoked with `callback_data`, and it must pay the
+/// input into the source vault before the reserves and fees are updated.
+pub(crate) fn execute_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapOnAmm<'info>>,
     amount_in: u64,
     min_amount_out: u64,
+    callback_data: Option<Vec<u8>>,
 ) -> Result<()> {
+    let amm_pool = &ctx.accounts.amm_pool;
+    let source_mint_key = match (
+        &ctx.accounts.user_source_token_account,
+        &ctx.accounts.user_destination_token_account,
+    ) {
+        (Some(user_source_token_account), _) => user_source_token_account.mint,
+        // A flash swap is paid by its callback and needs no source account, so the input
+        // is the side opposite the output.
+        (None, Some(user_destination_token_account)) if callback_data.is_some() => {
+            if user_destination_token_account.mint == amm_pool.token_a_mint {
+                amm_pool.token_b_mint
+            } else {
+                amm_pool.token_a_mint
+            }
+        }
+        // Otherwise an omitted source account pays in native SOL, so the input is the
+        // native side.
+        (None, _) if is_native_mint(&amm_pool.token_a_mint) => amm_pool.token_a_mint,
+        (None, _) => amm_pool.token_b_mint,
+    };
+
     if let Some(referrer_account_info) = &ctx.accounts.referrer_fee_account {
         // Manually deserialize the account data.
         let data = referrer_account_info.try_borrow_data()?;
@@ -80,16 +174,24 @@ pub fn handle_swap_on_amm(
 
         // Now, perform the constraint check on the deserialized account
         require!(
-            referrer_token_account.mint == ctx.accounts.user_source_token_account.mint,
+            referrer_token_account.mint == source_mint_key,
             DloomError::InvalidMint
         );
     }
 
     // 1. Update the oracle with the pre-trade reserves.
-    update_oracle(&mut ctx.accounts.amm_pool)?;
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
 
     let amm_pool = &ctx.accounts.amm_pool;
-    let is_a_to_b = ctx.accounts.user_source_token_account.mint == amm_pool.token_a_mint;
+    if amm_pool.curve_type == CurveType::LiquidityBootstrapping {
+        require!(amm_pool.swaps_enabled, DloomError::SwapsDisabled);
+    }
+    let is_a_to_b = source_mint_key == amm_pool.token_a_mint;
 
     let (source_reserves, destination_reserves, source_mint_decimals, destination_mint_decimals) =
         if is_a_to_b {
@@ -101,7 +203,7 @@ pub fn handle_swap_on_amm(
             )
         } else {
             require_keys_eq!(
-                ctx.accounts.user_source_token_account.mint,
+                source_mint_key,
                 amm_pool.token_b_mint,
                 DloomError::InvalidMint
             );
@@ -118,17 +220,32 @@ pub fn handle_swap_on_amm(
     } else {
         (ctx.accounts.token_b_program.to_account_info(), ctx.accounts.token_a_program.to_account_info())
     };
+    let (source_mint, destination_mint) = if is_a_to_b {
+        (&ctx.accounts.token_a_mint, &ctx.accounts.token_b_mint)
+    } else {
+        (&ctx.accounts.token_b_mint, &ctx.accounts.token_a_mint)
+    };
+
+    // Token-2022 transfer fees are withheld from what the vault receives, so the swap is
+    // priced on the net input only.
+    let net_amount_in = token_extensions::calculate_net_received(source_mint, amount_in)?;
+    require!(net_amount_in > 0, DloomError::ZeroAmount);
 
-    // 2. Calculate swap results based on the current state.
-    let (amount_out, protocol_fee, lp_fee) = math::calculate_swap_out_amount(
+    // 2. Calculate swap results based on the current state and the pool's curve.
+    let (amount_out, protocol_fee, lp_fee) = math::calculate_swap_on_curve(
         amm_pool,
-        amount_in,
-        source_reserves,
-        destination_reserves,
+        net_amount_in,
+        is_a_to_b,
+        source_mint_decimals,
+        destination_mint_decimals,
+        Clock::get()?.unix_timestamp,
     )?;
-    require!(amount_out >= min_amount_out, DloomError::SlippageExceeded);
+    // Slippage is checked against what the trader actually receives.
+    let net_amount_out = token_extensions::calculate_net_received(destination_mint, amount_out)?;
+    require!(net_amount_out >= min_amount_out, DloomError::SlippageExceeded);
 
-    // 3. Transfer from user to the appropriate source vault.
+    // 3. Transfer from user to the appropriate source vault. A flash swap skips this and
+    // has its callback pay the vault instead, once the output has been sent.
     let (source_vault_info, source_mint_info) = if is_a_to_b {
         (
             ctx.accounts.token_a_vault.to_account_info(),
@@ -140,30 +257,130 @@ pub fn handle_swap_on_amm(
             ctx.accounts.token_b_mint.to_account_info(),
         )
     };
-    token_interface::transfer_checked(
-        CpiContext::new(
-            source_token_program.clone(),
-            TransferChecked {
-                from: ctx.accounts.user_source_token_account.to_account_info(),
-                to: source_vault_info,
-                authority: ctx.accounts.trader.to_account_info(),
-                mint: source_mint_info,
-            },
-        ),
-        amount_in,
-        source_mint_decimals,
-    )?;
+    let source_vault_before = if is_a_to_b {
+        ctx.accounts.token_a_vault.amount
+    } else {
+        ctx.accounts.token_b_vault.amount
+    };
+    let native_sol = NativeSol::new(
+        ctx.accounts.trader.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        ctx.accounts.system_program.as_ref().map(|a| a.to_account_info()),
+    );
+    if callback_data.is_none() {
+        let user_source_token_account = native_sol.resolve(
+            ctx.accounts.user_source_token_account.as_ref(),
+            source_mint,
+            &source_token_program,
+            amount_in,
+        )?;
+        token_extensions::transfer_checked(
+            CpiContext::new(
+                source_token_program.clone(),
+                TransferChecked {
+                    from: user_source_token_account,
+                    to: source_vault_info.clone(),
+                    authority: ctx.accounts.trader.to_account_info(),
+                    mint: source_mint_info.clone(),
+                },
+            ),
+            amount_in,
+            source_mint_decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
 
     // 4. Prepare signer seeds for all subsequent PDA-controlled transfers.
+    let token_a_mint_key = amm_pool.token_a_mint;
+    let token_b_mint_key = amm_pool.token_b_mint;
+    let fee_tier_bytes = &amm_pool.fee_tier.to_le_bytes()[..];
+    let curve_type_seed = &amm_pool.curve_type.seed()[..];
     let bump = &[amm_pool.bump][..];
     let signer_seeds = &[
         b"amm_pool",
-        amm_pool.token_a_mint.as_ref(),
-        amm_pool.token_b_mint.as_ref(),
+        token_a_mint_key.as_ref(),
+        token_b_mint_key.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
         bump,
     ][..];
 
-    // 5. Handle fee distribution (Referral and Protocol).
+    // 5. Transfer swapped amount to user, unwrapping it if they take native SOL.
+    let user_destination_token_account = native_sol.resolve(
+        ctx.accounts.user_destination_token_account.as_ref(),
+        destination_mint,
+        &destination_token_program,
+        0,
+    )?;
+    if amount_out > 0 {
+        let (dest_vault, dest_mint) = if is_a_to_b {
+            (
+                ctx.accounts.token_b_vault.to_account_info(),
+                ctx.accounts.token_b_mint.to_account_info(),
+            )
+        } else {
+            (
+                ctx.accounts.token_a_vault.to_account_info(),
+                ctx.accounts.token_a_mint.to_account_info(),
+            )
+        };
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                destination_token_program.clone(),
+                TransferChecked {
+                    from: dest_vault,
+                    to: user_destination_token_account,
+                    authority: amm_pool.to_account_info(),
+                    mint: dest_mint,
+                },
+                &[signer_seeds],
+            ),
+            amount_out,
+            destination_mint_decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+    native_sol.close()?;
+
+    // 6. For a flash swap, hand over control with the pool locked, then check that the
+    // callback paid the input. The swap was priced on the net input, so that is what the
+    // vault must have gained.
+    if let Some(callback_data) = callback_data {
+        let callback_program = ctx
+            .accounts
+            .callback_program
+            .as_ref()
+            .ok_or(DloomError::InvalidFlashLoanProgram)?;
+        ctx.accounts.amm_pool.flash_loan_active = true;
+        ctx.accounts.amm_pool.exit(ctx.program_id)?;
+        flash_loan::invoke_callback(
+            callback_program,
+            &ctx.accounts.trader.to_account_info(),
+            ctx.remaining_accounts,
+            callback_data,
+        )?;
+        ctx.accounts.amm_pool.reload()?;
+        ctx.accounts.amm_pool.flash_loan_active = false;
+
+        let source_vault_after = if is_a_to_b {
+            ctx.accounts.token_a_vault.reload()?;
+            ctx.accounts.token_a_vault.amount
+        } else {
+            ctx.accounts.token_b_vault.reload()?;
+            ctx.accounts.token_b_vault.amount
+        };
+        require!(
+            source_vault_after
+                >= source_vault_before
+                    .checked_add(net_amount_in)
+                    .ok_or(DloomError::MathOverflow)?,
+            DloomError::FlashSwapNotPaid
+        );
+    }
+    let amm_pool = &ctx.accounts.amm_pool;
+
+    // 7. Handle fee distribution (Referral and Protocol).
     let mut actual_protocol_fee = protocol_fee;
 
     if protocol_fee > 0 && amm_pool.referrer_fee_share > 0 {
@@ -179,101 +396,51 @@ pub fn handle_swap_on_amm(
                     .checked_sub(referral_fee)
                     .ok_or(DloomError::MathOverflow)?;
 
-                let (fee_source_vault, fee_mint) = if is_a_to_b {
-                    (
-                        ctx.accounts.token_a_vault.to_account_info(),
-                        ctx.accounts.token_a_mint.to_account_info(),
-                    )
-                } else {
-                    (
-                        ctx.accounts.token_b_vault.to_account_info(),
-                        ctx.accounts.token_b_mint.to_account_info(),
-                    )
-                };
-
-                token_interface::transfer_checked(
+                token_extensions::transfer_checked(
                     CpiContext::new_with_signer(
                         source_token_program.clone(),
                         TransferChecked {
-                            from: fee_source_vault,
+                            from: source_vault_info.clone(),
                             to: referrer_account.to_account_info(),
                             authority: amm_pool.to_account_info(),
-                            mint: fee_mint,
+                            mint: source_mint_info.clone(),
                         },
                         &[signer_seeds],
                     ),
                     referral_fee,
                     source_mint_decimals,
+                    ctx.remaining_accounts,
                 )?;
             }
         }
     }
 
     if actual_protocol_fee > 0 {
-        let (source_vault, fee_vault, mint) = if is_a_to_b {
-            (
-                ctx.accounts.token_a_vault.to_account_info(),
-                ctx.accounts.protocol_fee_vault_a.to_account_info(),
-                ctx.accounts.token_a_mint.to_account_info(),
-            )
+        let fee_vault = if is_a_to_b {
+            ctx.accounts.protocol_fee_vault_a.to_account_info()
         } else {
-            (
-                ctx.accounts.token_b_vault.to_account_info(),
-                ctx.accounts.protocol_fee_vault_b.to_account_info(),
-                ctx.accounts.token_b_mint.to_account_info(),
-            )
+            ctx.accounts.protocol_fee_vault_b.to_account_info()
         };
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 source_token_program.clone(),
                 TransferChecked {
-                    from: source_vault,
+                    from: source_vault_info,
                     to: fee_vault,
                     authority: amm_pool.to_account_info(),
-                    mint,
+                    mint: source_mint_info,
                 },
                 &[signer_seeds],
             ),
             actual_protocol_fee,
             source_mint_decimals,
+            ctx.remaining_accounts,
         )?;
     }
 
-    // 6. Transfer swapped amount to user.
-    if amount_out > 0 {
-        let (dest_vault, dest_mint) = if is_a_to_b {
-            (
-                ctx.accounts.token_b_vault.to_account_info(),
-                ctx.accounts.token_b_mint.to_account_info(),
-            )
-        } else {
-            (
-                ctx.accounts.token_a_vault.to_account_info(),
-                ctx.accounts.token_a_mint.to_account_info(),
-            )
-        };
-        token_interface::transfer_checked(
-            CpiContext::new_with_signer(
-                destination_token_program.clone(),
-                TransferChecked {
-                    from: dest_vault,
-                    to: ctx
-                        .accounts
-                        .user_destination_token_account
-                        .to_account_info(),
-                    authority: amm_pool.to_account_info(),
-                    mint: dest_mint,
-                },
-                &[signer_seeds],
-            ),
-            amount_out,
-            destination_mint_decimals,
-        )?;
-    }
-
-    // 7. Update reserves state.
+    // 8. Update reserves state.
     let amm_pool_mut = &mut ctx.accounts.amm_pool;
-    let amount_added_to_lp_reserves = amount_in
+    let amount_added_to_lp_reserves = net_amount_in
         .checked_sub(protocol_fee)
         .ok_or(DloomError::MathOverflow)?;
     if is_a_to_b {
@@ -292,7 +459,7 @@ pub fn handle_swap_on_amm(
             .ok_or(DloomError::MathOverflow)?;
     }
 
-    // 8. Update fee growth accumulators for LPs.
+    // 9. Update fee growth accumulators for LPs.
     let lp_mint_supply = ctx.accounts.lp_mint.supply;
     if lp_mint_supply > 0 && lp_fee > 0 {
         let fee_growth_update = (lp_fee as u128)
@@ -316,33 +483,54 @@ pub fn handle_swap_on_amm(
         }
     }
 
-    emit!(AmmSwap {
+    let now = Clock::get()?.unix_timestamp;
+    let (price_a, _) = calculate_spot_prices(&ctx.accounts.amm_pool, now)?;
+    let ui_price_a = token_extensions::calculate_ui_price(
+        price_a,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        now,
+    )?;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmSwap {
         pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
         trader: ctx.accounts.trader.key(),
-        input_mint: ctx.accounts.user_source_token_account.mint,
-        output_mint: ctx.accounts.user_destination_token_account.mint,
+        input_mint: source_mint.key(),
+        output_mint: destination_mint.key(),
         amount_in,
         amount_out,
         protocol_fee: actual_protocol_fee, // Use the final protocol fee after referral split
         lp_fee,
+        ui_price_a,
         referrer: ctx
             .accounts
             .referrer_fee_account
             .as_ref()
             .map(|acc| acc.key()),
+        reserves_a: ctx.accounts.amm_pool.reserves_a,
+        reserves_b: ctx.accounts.amm_pool.reserves_b,
     });
 
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct SwapOnAmm<'info> {
     #[account(mut)]
     pub trader: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"amm_pool", amm_pool.token_a_mint.as_ref(), amm_pool.token_b_mint.as_ref()],
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
         bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
@@ -353,10 +541,16 @@ pub struct SwapOnAmm<'info> {
     pub token_a_mint: InterfaceAccount<'info, Mint>,
     #[account(address = amm_pool.token_b_mint)]
     pub token_b_mint: InterfaceAccount<'info, Mint>,
+    /// Omit to pay in native SOL when the input is the pool's native mint.
     #[account(mut)]
-    pub user_source_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_source_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Omit to receive native SOL when the output is the pool's native mint.
     #[account(mut)]
-    pub user_destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, trader.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
     #[account(mut, address = amm_pool.token_a_vault)]
     pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, address = amm_pool.token_b_vault)]
@@ -374,6 +568,16 @@ pub struct SwapOnAmm<'info> {
     /// The client is responsible for passing the correct token account for the input token.
     pub referrer_fee_account: Option<AccountInfo<'info>>,
 
+    /// CHECK: Flash swaps only: the program invoked once the output has been sent. It
+    /// receives `trader` and the instruction's remaining accounts, and must pay the input
+    /// into the source vault before returning.
+    #[account(executable)]
+    pub callback_program: Option<UncheckedAccount<'info>>,
+
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Option<Program<'info, System>>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+// FILE: programs/dloom_flow/src/amm/instructions/sync_reserves.rs
+
+use crate::{
+    amm::{instructions::swap::update_oracle, state::{AmmOracle, AmmPool}},
+    errors::DloomError,
+    events::{emit_event, AmmReservesSynced},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/// The handler for the permissionless `sync_amm_reserves` instruction.
+///
+/// Overwrites the pool's tracked reserves with the actual vault balances, absorbing
+/// any drift from donations, transfer fees or rounding. The oracle is brought up to
+/// date at the old price first, so the new price only applies from this point on.
+pub fn handle_sync_amm_reserves(ctx: Context<SyncAmmReserves>) -> Result<()> {
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
+
+    let vault_a_balance = ctx.accounts.token_a_vault.amount;
+    let vault_b_balance = ctx.accounts.token_b_vault.amount;
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    let old_reserves_a = amm_pool.reserves_a;
+    let old_reserves_b = amm_pool.reserves_b;
+    amm_pool.reserves_a = vault_a_balance;
+    amm_pool.reserves_b = vault_b_balance;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmReservesSynced {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        old_reserves_a,
+        old_reserves_b,
+        new_reserves_a: vault_a_balance,
+        new_reserves_b: vault_b_balance,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SyncAmmReserves<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = amm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = amm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
+}
@@ -0,0 +1,195 @@
+// FILE: programs/dloom_flow/src/amm/instructions/withdraw_lbp_proceeds.rs
+
+use crate::{
+    amm::{
+        instructions::swap::update_oracle,
+        math,
+        state::{AmmOracle, AmmPool, AmmPosition, CurveType},
+    },
+    errors::DloomError,
+    events::{emit_event, AmmLbpProceedsWithdrawn},
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+/// The handler for the `withdraw_lbp_proceeds` instruction.
+///
+/// Once the sale window has ended, the launch owner burns all of the LP tokens it holds
+/// and receives their pro-rata share of both reserves: the unsold launch tokens and the
+/// raised proceeds. The share backing the locked `MINIMUM_LIQUIDITY`, and any LP tokens
+/// the owner has moved elsewhere, stays behind; their holders exit through
+/// `remove_amm_liquidity`. Swaps are disabled afterwards.
+pub fn handle_withdraw_lbp_proceeds<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawLbpProceeds<'info>>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= ctx.accounts.amm_pool.weight_end_timestamp,
+        DloomError::LaunchNotEnded
+    );
+
+    let lp_tokens_to_burn = ctx.accounts.launch_owner_lp_token_account.amount;
+    require!(lp_tokens_to_burn > 0, DloomError::ZeroLiquidity);
+
+    update_oracle(
+        &mut ctx.accounts.amm_pool,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        ctx.accounts.amm_oracle.as_deref_mut(),
+    )?;
+    let (amount_a_to_withdraw, amount_b_to_withdraw) = math::calculate_assets_to_withdraw(
+        ctx.accounts.amm_pool.reserves_a,
+        ctx.accounts.amm_pool.reserves_b,
+        ctx.accounts.lp_mint.supply,
+        lp_tokens_to_burn,
+    )?;
+
+    let fee_tier_bytes = &ctx.accounts.amm_pool.fee_tier.to_le_bytes()[..];
+
+    let curve_type_seed = &ctx.accounts.amm_pool.curve_type.seed()[..];
+    let bump = &[ctx.accounts.amm_pool.bump][..];
+    let signer_seeds = &[
+        b"amm_pool",
+        ctx.accounts.amm_pool.token_a_mint.as_ref(),
+        ctx.accounts.amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
+        bump,
+    ][..];
+
+    if amount_a_to_withdraw > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_vault.to_account_info(),
+                    to: ctx.accounts.launch_owner_token_a_account.to_account_info(),
+                    authority: ctx.accounts.amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_a_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_a_to_withdraw,
+            ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    if amount_b_to_withdraw > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_vault.to_account_info(),
+                    to: ctx.accounts.launch_owner_token_b_account.to_account_info(),
+                    authority: ctx.accounts.amm_pool.to_account_info(),
+                    mint: ctx.accounts.token_b_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_b_to_withdraw,
+            ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                from: ctx.accounts.launch_owner_lp_token_account.to_account_info(),
+                authority: ctx.accounts.launch_owner.to_account_info(),
+            },
+        ),
+        lp_tokens_to_burn,
+    )?;
+
+    ctx.accounts.amm_position.lp_token_amount = ctx
+        .accounts
+        .amm_position
+        .lp_token_amount
+        .saturating_sub(lp_tokens_to_burn);
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.reserves_a = amm_pool
+        .reserves_a
+        .checked_sub(amount_a_to_withdraw)
+        .ok_or(DloomError::MathOverflow)?;
+    amm_pool.reserves_b = amm_pool
+        .reserves_b
+        .checked_sub(amount_b_to_withdraw)
+        .ok_or(DloomError::MathOverflow)?;
+    amm_pool.swaps_enabled = false;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmLbpProceedsWithdrawn {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        launch_owner: ctx.accounts.launch_owner.key(),
+        lp_tokens_burned: lp_tokens_to_burn,
+        amount_a_received: amount_a_to_withdraw,
+        amount_b_received: amount_b_to_withdraw,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct WithdrawLbpProceeds<'info> {
+    #[account(mut)]
+    pub launch_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"amm_pool",
+            amm_pool.token_a_mint.as_ref(),
+            amm_pool.token_b_mint.as_ref(),
+            &amm_pool.fee_tier.to_le_bytes(),
+            &amm_pool.curve_type.seed()
+        ],
+        bump = amm_pool.bump,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+        has_one = launch_owner @ DloomError::Unauthorized,
+        constraint = amm_pool.curve_type == CurveType::LiquidityBootstrapping @ DloomError::InvalidCurveType,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_position", launch_owner.key().as_ref(), amm_pool.key().as_ref()],
+        bump,
+    )]
+    pub amm_position: Box<Account<'info, AmmPosition>>,
+
+    #[account(mut, address = amm_pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = amm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = amm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, constraint = launch_owner_lp_token_account.owner == launch_owner.key() @ DloomError::Unauthorized)]
+    pub launch_owner_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = launch_owner_token_a_account.owner == launch_owner.key() @ DloomError::Unauthorized)]
+    pub launch_owner_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = launch_owner_token_b_account.owner == launch_owner.key() @ DloomError::Unauthorized)]
+    pub launch_owner_token_b_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
+}
@@ -1,26 +1,41 @@
 // FILE: programs/dloom_flow/src/math/amm_math.rs
 
-use crate::{constants::BASIS_POINT_MAX, errors::DloomError, amm::{state::AmmPool}};
+use crate::{
+    constants::{BASIS_POINT_MAX, MINIMUM_LIQUIDITY},
+    errors::DloomError,
+    amm::{
+        stable_math,
+        state::{AmmPool, CurveType},
+        weighted_math,
+    },
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::Mint;
 
 /// Calculates the optimal deposit amounts and the number of LP tokens to mint.
+///
+/// On the first deposit the returned LP amount includes the `MINIMUM_LIQUIDITY` that the
+/// caller must mint to the pool's lock vault rather than to the depositor.
 pub fn calculate_lp_tokens_to_mint(
     amm_pool: &AmmPool,
-    lp_mint: &InterfaceAccount<Mint>, // Accept the lp_mint account
+    lp_supply: u64,
     amount_a_desired: u64,
     amount_b_desired: u64,
 ) -> Result<(u64, u64, u64)> {
     let reserves_a = amm_pool.reserves_a as u128;
     let reserves_b = amm_pool.reserves_b as u128;
-    let lp_total_supply = lp_mint.supply as u128; // Get supply from the account
+    let lp_total_supply = lp_supply as u128;
 
     if lp_total_supply == 0 {
         // This is the first deposit. The amount of LP tokens is the geometric mean of the two amounts.
+        require_initial_deposit(amount_a_desired, amount_b_desired)?;
         let lp_to_mint = (amount_a_desired as u128)
             .checked_mul(amount_b_desired as u128)
             .ok_or(DloomError::MathOverflow)?
             .sqrt();
+        require!(
+            lp_to_mint > MINIMUM_LIQUIDITY as u128,
+            DloomError::InsufficientInitialLiquidity
+        );
 
         Ok((amount_a_desired, amount_b_desired, lp_to_mint as u64))
     } else {
@@ -61,24 +76,19 @@ pub fn calculate_lp_tokens_to_mint(
     }
 }
 
-/// Calculates the result of a swap.
-pub fn calculate_swap_out_amount(
-    amm_pool: &AmmPool,
-    amount_in: u64,
-    source_reserves: u64,
-    destination_reserves: u64,
-) -> Result<(u64, u64, u64)> {
-    require!(amount_in > 0, DloomError::ZeroAmount);
+/// Rejects first deposits too small to set a meaningful starting price. Dust on either
+/// side would let the depositor pick an arbitrarily skewed price at almost no cost.
+pub fn require_initial_deposit(amount_a: u64, amount_b: u64) -> Result<()> {
     require!(
-        source_reserves > 0 && destination_reserves > 0,
-        DloomError::InsufficientLiquidityForSwap
+        amount_a >= MINIMUM_LIQUIDITY && amount_b >= MINIMUM_LIQUIDITY,
+        DloomError::InsufficientInitialLiquidity
     );
+    Ok(())
+}
 
-    let amount_in_u128 = amount_in as u128;
-    let source_reserves_u128 = source_reserves as u128;
-    let destination_reserves_u128 = destination_reserves as u128;
-
-    let total_fee = amount_in_u128
+/// Splits the fee charged on `amount_in` into its total, protocol and LP parts.
+pub fn calculate_swap_fees(amm_pool: &AmmPool, amount_in: u64) -> Result<(u128, u128, u128)> {
+    let total_fee = (amount_in as u128)
         .checked_mul(amm_pool.fee_rate as u128)
         .ok_or(DloomError::MathOverflow)?
         .checked_div(BASIS_POINT_MAX)
@@ -94,6 +104,28 @@ pub fn calculate_swap_out_amount(
         .checked_sub(protocol_fee)
         .ok_or(DloomError::MathOverflow)?;
 
+    Ok((total_fee, protocol_fee, lp_fee))
+}
+
+/// Calculates the result of a swap.
+pub fn calculate_swap_out_amount(
+    amm_pool: &AmmPool,
+    amount_in: u64,
+    source_reserves: u64,
+    destination_reserves: u64,
+) -> Result<(u64, u64, u64)> {
+    require!(amount_in > 0, DloomError::ZeroAmount);
+    require!(
+        source_reserves > 0 && destination_reserves > 0,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    let amount_in_u128 = amount_in as u128;
+    let source_reserves_u128 = source_reserves as u128;
+    let destination_reserves_u128 = destination_reserves as u128;
+
+    let (total_fee, protocol_fee, lp_fee) = calculate_swap_fees(amm_pool, amount_in)?;
+
     let amount_in_after_fees = amount_in_u128
         .checked_sub(total_fee)
         .ok_or(DloomError::MathOverflow)?;
@@ -156,4 +188,55 @@ impl U128Sqrt for u128 {
         }
         x
     }
-}
\ No newline at end of file
+}
+
+/// Prices a swap of `net_amount_in` against the pool's reserves on its curve, returning
+/// `(amount_out, protocol_fee, lp_fee)`. Shared by the swap, its read-only quote and
+/// off-chain quoting, so `now` is passed in rather than read from the clock.
+pub fn calculate_swap_on_curve(
+    amm_pool: &AmmPool,
+    net_amount_in: u64,
+    is_a_to_b: bool,
+    source_mint_decimals: u8,
+    destination_mint_decimals: u8,
+    now: i64,
+) -> Result<(u64, u64, u64)> {
+    let (source_reserves, destination_reserves) = if is_a_to_b {
+        (amm_pool.reserves_a, amm_pool.reserves_b)
+    } else {
+        (amm_pool.reserves_b, amm_pool.reserves_a)
+    };
+    Ok(match amm_pool.curve_type {
+        CurveType::ConstantProduct => calculate_swap_out_amount(
+            amm_pool,
+            net_amount_in,
+            source_reserves,
+            destination_reserves,
+        )?,
+        CurveType::StableSwap => stable_math::calculate_stable_swap_out_amount(
+            amm_pool,
+            net_amount_in,
+            source_reserves,
+            destination_reserves,
+            source_mint_decimals,
+            destination_mint_decimals,
+            now,
+        )?,
+        CurveType::Weighted | CurveType::LiquidityBootstrapping => {
+            let (weight_a, weight_b) = weighted_math::current_weights(amm_pool, now)?;
+            let (source_weight, destination_weight) = if is_a_to_b {
+                (weight_a, weight_b)
+            } else {
+                (weight_b, weight_a)
+            };
+            weighted_math::calculate_weighted_swap_out_amount(
+                amm_pool,
+                net_amount_in,
+                source_reserves,
+                destination_reserves,
+                source_weight,
+                destination_weight,
+            )?
+        }
+    })
+}
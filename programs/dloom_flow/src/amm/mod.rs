@@ -1,8 +1,12 @@
 // FILE: programs/dloom_flow/src/amm/mod.rs
 pub mod instructions;
 pub mod math;
+pub mod stable_math;
 pub mod state;
+pub mod weighted_math;
 
 pub use instructions::*;
 pub use math::*;
-pub use state::*;
\ No newline at end of file
+pub use stable_math::*;
+pub use state::*;
+pub use weighted_math::*;
\ No newline at end of file
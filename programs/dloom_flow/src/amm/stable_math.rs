@@ -0,0 +1,268 @@
+// FILE: programs/dloom_flow/src/amm/stable_math.rs
+
+use crate::{
+    amm::{math::calculate_swap_fees, state::AmmPool},
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// The number of tokens in a pool. The StableSwap formulas below are specialised for two.
+const N_COINS: u128 = 2;
+/// Newton's method normally converges in a handful of rounds; this is a safety cap.
+const MAX_ITERATIONS: usize = 256;
+
+mod u256 {
+    #![allow(clippy::assign_op_pattern, clippy::manual_div_ceil, clippy::ptr_offset_with_cast)]
+
+    uint::construct_uint! {
+        /// The width `compute_d` and `compute_y` take their intermediate products in.
+        pub struct U256(4);
+    }
+}
+use u256::U256;
+
+/// Returns the amplification coefficient in effect at `now`, linearly interpolating
+/// between `amp_initial` and `amp_target` while a ramp is in progress.
+pub fn current_amp(pool: &AmmPool, now: i64) -> Result<u64> {
+    if now >= pool.amp_ramp_end_timestamp || pool.amp_ramp_end_timestamp <= pool.amp_ramp_start_timestamp {
+        return Ok(pool.amp_target);
+    }
+    if now <= pool.amp_ramp_start_timestamp {
+        return Ok(pool.amp_initial);
+    }
+
+    let time_elapsed = now
+        .checked_sub(pool.amp_ramp_start_timestamp)
+        .ok_or(DloomError::MathOverflow)? as u128;
+    let ramp_duration = pool
+        .amp_ramp_end_timestamp
+        .checked_sub(pool.amp_ramp_start_timestamp)
+        .ok_or(DloomError::MathOverflow)? as u128;
+
+    let amp_initial = pool.amp_initial as u128;
+    let amp_target = pool.amp_target as u128;
+
+    let amp = if amp_target >= amp_initial {
+        let delta = (amp_target - amp_initial)
+            .checked_mul(time_elapsed)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_div(ramp_duration)
+            .ok_or(DloomError::MathOverflow)?;
+        amp_initial.checked_add(delta).ok_or(DloomError::MathOverflow)?
+    } else {
+        let delta = (amp_initial - amp_target)
+            .checked_mul(time_elapsed)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_div(ramp_duration)
+            .ok_or(DloomError::MathOverflow)?;
+        amp_initial.checked_sub(delta).ok_or(DloomError::MathOverflow)?
+    };
+
+    Ok(amp as u64)
+}
+
+/// Computes the StableSwap invariant `D` for the normalized balances `x` and `y`.
+///
+/// The intermediate products reach `D^3` and `Ann * S * D`, which overflow `u128` for
+/// large pools at high amplification, so they are taken in 256 bits.
+pub fn compute_d(amp: u64, x: u128, y: u128) -> Result<u128> {
+    let sum = x.checked_add(y).ok_or(DloomError::MathOverflow)?;
+    if sum == 0 {
+        return Ok(0);
+    }
+    require!(x > 0 && y > 0, DloomError::InsufficientLiquidityForSwap);
+
+    let (x, y, sum) = (U256::from(x), U256::from(y), U256::from(sum));
+    let n_coins = U256::from(N_COINS);
+    let amp_times_coins = U256::from(amp)
+        .checked_mul(n_coins)
+        .ok_or(DloomError::MathOverflow)?;
+    let leverage = amp_times_coins
+        .checked_mul(sum)
+        .ok_or(DloomError::MathOverflow)?;
+
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // d_product = D^(n+1) / (n^n * x * y)
+        let d_product = d
+            .checked_mul(d)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_div(x.checked_mul(n_coins).ok_or(DloomError::MathOverflow)?)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_mul(d)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_div(y.checked_mul(n_coins).ok_or(DloomError::MathOverflow)?)
+            .ok_or(DloomError::MathOverflow)?;
+
+        let d_prev = d;
+        // D = (Ann * S + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let numerator = leverage
+            .checked_add(d_product.checked_mul(n_coins).ok_or(DloomError::MathOverflow)?)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_mul(d)
+            .ok_or(DloomError::MathOverflow)?;
+        let denominator = amp_times_coins
+            .checked_sub(U256::one())
+            .ok_or(DloomError::MathOverflow)?
+            .checked_mul(d)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_add(
+                d_product
+                    .checked_mul(n_coins + 1)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+        d = numerator
+            .checked_div(denominator)
+            .ok_or(DloomError::MathOverflow)?;
+
+        if abs_diff(d, d_prev) <= U256::one() {
+            return to_u128(d);
+        }
+    }
+
+    err!(DloomError::InvariantNotConverged)
+}
+
+/// Computes the new balance of the other token given the new balance `x` of one
+/// token, such that the invariant `d` is preserved. Like `compute_d`, it works in 256
+/// bits.
+pub fn compute_y(amp: u64, x: u128, d: u128) -> Result<u128> {
+    require!(x > 0, DloomError::InsufficientLiquidityForSwap);
+
+    let (x, d) = (U256::from(x), U256::from(d));
+    let n_coins = U256::from(N_COINS);
+    let amp_times_coins = U256::from(amp)
+        .checked_mul(n_coins)
+        .ok_or(DloomError::MathOverflow)?;
+
+    // c = D^(n+1) / (n^n * x * Ann)
+    let c = d
+        .checked_mul(d)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(x.checked_mul(n_coins).ok_or(DloomError::MathOverflow)?)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_mul(d)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(
+            amp_times_coins
+                .checked_mul(n_coins)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+    // b = x + D / Ann
+    let b = x
+        .checked_add(d.checked_div(amp_times_coins).ok_or(DloomError::MathOverflow)?)
+        .ok_or(DloomError::MathOverflow)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2y + b - D)
+        let numerator = y
+            .checked_mul(y)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_add(c)
+            .ok_or(DloomError::MathOverflow)?;
+        let denominator = y
+            .checked_mul(U256::from(2))
+            .ok_or(DloomError::MathOverflow)?
+            .checked_add(b)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_sub(d)
+            .ok_or(DloomError::MathOverflow)?;
+        y = numerator
+            .checked_div(denominator)
+            .ok_or(DloomError::MathOverflow)?;
+
+        if abs_diff(y, y_prev) <= U256::one() {
+            return to_u128(y);
+        }
+    }
+
+    err!(DloomError::InvariantNotConverged)
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    u128::try_from(value).map_err(|_| DloomError::MathOverflow.into())
+}
+
+/// Returns the multipliers that scale both tokens to the larger of the two decimal counts,
+/// so the invariant treats one whole unit of each token as equal.
+fn decimal_multipliers(token_a_decimals: u8, token_b_decimals: u8) -> Result<(u128, u128)> {
+    let max_decimals = token_a_decimals.max(token_b_decimals);
+    let multiplier_a = 10u128
+        .checked_pow((max_decimals - token_a_decimals) as u32)
+        .ok_or(DloomError::MathOverflow)?;
+    let multiplier_b = 10u128
+        .checked_pow((max_decimals - token_b_decimals) as u32)
+        .ok_or(DloomError::MathOverflow)?;
+    Ok((multiplier_a, multiplier_b))
+}
+
+/// Calculates the result of a swap against the StableSwap invariant.
+///
+/// Fees are split exactly as in `math::calculate_swap_out_amount`, so the same
+/// reserve, referral and fee-growth bookkeeping applies to both curve types.
+pub fn calculate_stable_swap_out_amount(
+    amm_pool: &AmmPool,
+    amount_in: u64,
+    source_reserves: u64,
+    destination_reserves: u64,
+    source_decimals: u8,
+    destination_decimals: u8,
+    now: i64,
+) -> Result<(u64, u64, u64)> {
+    require!(amount_in > 0, DloomError::ZeroAmount);
+    require!(
+        source_reserves > 0 && destination_reserves > 0,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    let (total_fee, protocol_fee, lp_fee) = calculate_swap_fees(amm_pool, amount_in)?;
+    let amount_in_after_fees = (amount_in as u128)
+        .checked_sub(total_fee)
+        .ok_or(DloomError::MathOverflow)?;
+
+    let (source_multiplier, destination_multiplier) =
+        decimal_multipliers(source_decimals, destination_decimals)?;
+    let amp = current_amp(amm_pool, now)?;
+
+    let x = (source_reserves as u128)
+        .checked_mul(source_multiplier)
+        .ok_or(DloomError::MathOverflow)?;
+    let y = (destination_reserves as u128)
+        .checked_mul(destination_multiplier)
+        .ok_or(DloomError::MathOverflow)?;
+    let d = compute_d(amp, x, y)?;
+
+    let new_x = x
+        .checked_add(
+            amount_in_after_fees
+                .checked_mul(source_multiplier)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+    let new_y = compute_y(amp, new_x, d)?;
+
+    // Subtract one unit to round in the pool's favour.
+    let amount_out = y
+        .saturating_sub(new_y)
+        .saturating_sub(1)
+        .checked_div(destination_multiplier)
+        .ok_or(DloomError::MathOverflow)?;
+
+    Ok((
+        u64::try_from(amount_out).map_err(|_| DloomError::MathOverflow)?,
+        protocol_fee as u64,
+        lp_fee as u64,
+    ))
+}
@@ -1,6 +1,8 @@
 // FILE: programs/dloom_flow/src/amm/state/mod.rs
+pub mod oracle;
 pub mod pool;
 pub mod position;
 
+pub use oracle::*;
 pub use pool::*;
 pub use position::*;
\ No newline at end of file
@@ -0,0 +1,127 @@
+// FILE: programs/dloom_flow/src/amm/state/oracle.rs
+
+use crate::errors::DloomError;
+use anchor_lang::prelude::*;
+
+/// A snapshot of an AMM pool's cumulative prices.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct AmmObservation {
+    /// When the snapshot was taken. Zero for a slot that has never been written.
+    pub timestamp: i64,
+    /// The pool's `price_a_cumulative` at `timestamp`.
+    pub price_a_cumulative: u128,
+    /// The pool's `price_b_cumulative` at `timestamp`.
+    pub price_b_cumulative: u128,
+}
+
+impl AmmObservation {
+    pub const SIZE: usize = 8 + 16 + 16;
+}
+
+/// A ring buffer of observations of an AMM pool's cumulative prices, written each time
+/// the pool's oracle is updated, at most once per second.
+///
+/// The time-weighted average price over any window the buffer covers is the difference
+/// between the cumulative prices `observe` returns for its ends, divided by its length.
+#[account]
+#[derive(Default, Debug)]
+pub struct AmmOracle {
+    /// The pool this oracle observes.
+    pub pool: Pubkey,
+    /// The PDA bump.
+    pub bump: u8,
+    /// The slot of the most recent observation.
+    pub index: u16,
+    /// The number of slots the ring buffer currently cycles through.
+    pub cardinality: u16,
+    /// The number of allocated slots. The buffer grows into them once it next wraps, so
+    /// the observations already written stay in order.
+    pub cardinality_next: u16,
+    /// The allocated slots, `cardinality_next` of them.
+    pub observations: Vec<AmmObservation>,
+}
+
+impl AmmOracle {
+    /// The account space for an oracle with `cardinality` slots.
+    pub fn space(cardinality: u16) -> usize {
+        8 + 32 + 1 + 2 + 2 + 2 + 4 + cardinality as usize * AmmObservation::SIZE
+    }
+
+    /// Records the pool's cumulative prices at `timestamp`. Later updates within the same
+    /// second are skipped, as no time has passed for the prices to accumulate over.
+    pub fn write(&mut self, timestamp: i64, price_a_cumulative: u128, price_b_cumulative: u128) {
+        if self.observations[self.index as usize].timestamp == timestamp {
+            return;
+        }
+        if self.index + 1 == self.cardinality && self.cardinality_next > self.cardinality {
+            self.cardinality = self.cardinality_next;
+        }
+        self.index = (self.index + 1) % self.cardinality;
+        self.observations[self.index as usize] = AmmObservation {
+            timestamp,
+            price_a_cumulative,
+            price_b_cumulative,
+        };
+    }
+
+    /// Returns the cumulative prices at `target`, interpolated linearly between the
+    /// observations either side of it. `current` is the pool's cumulative prices now, and
+    /// `target` must lie between the oldest observation and `current`.
+    pub fn observe(&self, current: &AmmObservation, target: i64) -> Result<AmmObservation> {
+        require!(target <= current.timestamp, DloomError::InvalidOracleQuery);
+        let newest = &self.observations[self.index as usize];
+        if target >= newest.timestamp {
+            return interpolate(newest, current, target);
+        }
+
+        // The slot after the newest is the oldest, unless the buffer has yet to fill it.
+        let next = (self.index + 1) % self.cardinality;
+        let (oldest, len) = if self.observations[next as usize].timestamp != 0 {
+            (next as usize, self.cardinality as usize)
+        } else {
+            (0, self.index as usize + 1)
+        };
+        let at = |position: usize| {
+            &self.observations[(oldest + position) % self.cardinality as usize]
+        };
+        require!(target >= at(0).timestamp, DloomError::OracleObservationTooOld);
+
+        // Binary search for the observations either side of `target`; the newest is after it.
+        let (mut before, mut after) = (0, len - 1);
+        while after - before > 1 {
+            let middle = (before + after) / 2;
+            if at(middle).timestamp <= target {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        interpolate(at(before), at(after), target)
+    }
+}
+
+/// Interpolates the cumulative prices at `target`, which lies between `before` and `after`.
+fn interpolate(
+    before: &AmmObservation,
+    after: &AmmObservation,
+    target: i64,
+) -> Result<AmmObservation> {
+    if target == before.timestamp {
+        return Ok(*before);
+    }
+    let elapsed = (target - before.timestamp) as u128;
+    let duration = (after.timestamp - before.timestamp) as u128;
+    let step = |before: u128, after: u128| -> Result<u128> {
+        after
+            .checked_sub(before)
+            .and_then(|delta| delta.checked_mul(elapsed))
+            .map(|delta| delta / duration)
+            .and_then(|delta| before.checked_add(delta))
+            .ok_or_else(|| DloomError::MathOverflow.into())
+    };
+    Ok(AmmObservation {
+        timestamp: target,
+        price_a_cumulative: step(before.price_a_cumulative, after.price_a_cumulative)?,
+        price_b_cumulative: step(before.price_b_cumulative, after.price_b_cumulative)?,
+    })
+}
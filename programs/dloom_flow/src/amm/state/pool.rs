@@ -2,9 +2,38 @@
 
 use anchor_lang::prelude::*;
 
-/// State for a permissionless, constant-product AMM pool.
+/// The invariant an `AmmPool` trades against.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CurveType {
+    /// The classic x * y = k curve.
+    #[default]
+    ConstantProduct,
+    /// A Curve-style StableSwap invariant for pegged pairs, tuned by an amplification coefficient.
+    StableSwap,
+    /// A Balancer-style weighted constant-mean invariant, x^w_a * y^w_b = k.
+    Weighted,
+    /// A liquidity bootstrapping pool: the weighted invariant with weights that shift
+    /// linearly over a launch window.
+    LiquidityBootstrapping,
+}
+
+impl CurveType {
+    /// Returns true for the curves that trade against the weighted constant-mean invariant.
+    pub fn is_weighted(&self) -> bool {
+        matches!(self, CurveType::Weighted | CurveType::LiquidityBootstrapping)
+    }
+
+    /// The byte an `AmmPool`'s PDA is seeded with, so a pair can hold one pool of each
+    /// curve type per fee tier.
+    pub fn seed(&self) -> [u8; 1] {
+        [*self as u8]
+    }
+}
+
+/// State for a permissionless AMM pool.
 ///
-/// Follows the x * y = k model.
+/// Follows the x * y = k model by default, or the StableSwap, weighted or
+/// liquidity bootstrapping invariant selected by `curve_type`.
 #[account]
 #[derive(Default, Debug)]
 pub struct AmmPool {
@@ -23,6 +52,9 @@ pub struct AmmPool {
     pub lp_mint: Pubkey,
 
     // --- Fee Parameters ---
+    /// The fee tier this pool was created under. Part of the PDA seeds, so it never
+    /// changes even if `fee_rate` is later updated.
+    pub fee_tier: u16,
     pub fee_rate: u16,
     pub protocol_fee_share: u16,
     pub referrer_fee_share: u16,
@@ -44,9 +76,70 @@ pub struct AmmPool {
     pub price_a_cumulative: u128,
     /// The cumulative price of token B in terms of token A.
     pub price_b_cumulative: u128,
+    /// `price_a_cumulative` measured in UI amounts, for interest-bearing or
+    /// scaled-UI-amount mints whose displayed balance drifts from the raw amount.
+    /// Equal to `price_a_cumulative` when neither mint has such an extension.
+    pub ui_price_a_cumulative: u128,
+    /// `price_b_cumulative` measured in UI amounts.
+    pub ui_price_b_cumulative: u128,
     /// The timestamp of the last update to the reserves and oracle.
     pub last_update_timestamp: i64,
     pub last_fee_update_timestamp: i64,
     /// Snapshot of the cumulative price at the last fee update, used for volatility calculation.
     pub price_a_cumulative_last_fee_update: u128,
+
+    // --- Curve Parameters ---
+    /// The invariant this pool uses for swaps.
+    pub curve_type: CurveType,
+    /// StableSwap only: the amplification coefficient at the start of the current ramp.
+    pub amp_initial: u64,
+    /// StableSwap only: the amplification coefficient at the end of the current ramp.
+    pub amp_target: u64,
+    /// StableSwap only: the timestamp the current amplification ramp started.
+    pub amp_ramp_start_timestamp: i64,
+    /// StableSwap only: the timestamp the current amplification ramp ends.
+    pub amp_ramp_end_timestamp: i64,
+    /// Weighted only: the weight of token A, in basis points.
+    pub weight_a: u16,
+    /// Weighted only: the weight of token B, in basis points. Always `BASIS_POINT_MAX - weight_a`.
+    pub weight_b: u16,
+
+    // --- Liquidity Bootstrapping Parameters ---
+    /// LBP only: the account that created the launch. Only it can provide liquidity,
+    /// toggle swaps and withdraw the proceeds.
+    pub launch_owner: Pubkey,
+    /// LBP only: the weight of token A, in basis points, at `weight_start_timestamp`.
+    pub start_weight_a: u16,
+    /// LBP only: the weight of token A, in basis points, at `weight_end_timestamp`.
+    pub end_weight_a: u16,
+    /// LBP only: the timestamp the weights start shifting.
+    pub weight_start_timestamp: i64,
+    /// LBP only: the timestamp the weights stop shifting and the sale ends.
+    pub weight_end_timestamp: i64,
+    /// LBP only: whether swaps are currently allowed. Starts disabled so the launch owner
+    /// can seed liquidity before the protocol authority opens trading.
+    pub swaps_enabled: bool,
+
+    // --- Flash Loans ---
+    /// Set while a flash loan's callback runs, so the callback cannot trade against or
+    /// otherwise touch the pool's vaults until the loan is repaid.
+    pub flash_loan_active: bool,
+
+    // --- Events ---
+    /// The sequence number of the latest event about this pool. Each event about it
+    /// carries the next number, so an indexer can tell when it has missed one.
+    pub event_sequence: u64,
+
+    // --- Observations ---
+    /// Whether the pool has an `AmmOracle`. Once it does, every instruction that updates
+    /// the oracle must pass it, so that no update goes unrecorded.
+    pub has_oracle: bool,
+}
+
+impl AmmPool {
+    /// Advances the event sequence and returns the number for the event being emitted.
+    pub fn next_event_sequence(&mut self) -> u64 {
+        self.event_sequence += 1;
+        self.event_sequence
+    }
 }
\ No newline at end of file
@@ -0,0 +1,229 @@
+// FILE: programs/dloom_flow/src/amm/weighted_math.rs
+
+use crate::{
+    amm::{
+        math::{self, calculate_swap_fees},
+        state::{AmmPool, CurveType},
+    },
+    constants::{BASIS_POINT_MAX, INITIAL_WEIGHTED_LP_SUPPLY},
+    errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// Fixed-point "one" used by the weighted-pool power functions (18 decimals).
+const BONE: u128 = 1_000_000_000_000_000_000;
+/// `bpow` only converges for bases in (0, 2).
+const MAX_BPOW_BASE: u128 = 2 * BONE - 1;
+/// The approximation series stops once a term drops below this value.
+const BPOW_PRECISION: u128 = BONE / 10_000_000_000;
+
+fn bmul(a: u128, b: u128) -> Result<u128> {
+    Ok(a
+        .checked_mul(b)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_add(BONE / 2)
+        .ok_or(DloomError::MathOverflow)?
+        / BONE)
+}
+
+fn bdiv(a: u128, b: u128) -> Result<u128> {
+    require!(b > 0, DloomError::MathOverflow);
+    Ok(a
+        .checked_mul(BONE)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_add(b / 2)
+        .ok_or(DloomError::MathOverflow)?
+        / b)
+}
+
+/// Raises a fixed-point `base` to a whole-number power.
+fn bpowi(base: u128, exp: u128) -> Result<u128> {
+    let mut result = if exp & 1 == 1 { base } else { BONE };
+    let mut base = base;
+    let mut exp = exp / 2;
+    while exp != 0 {
+        base = bmul(base, base)?;
+        if exp & 1 == 1 {
+            result = bmul(result, base)?;
+        }
+        exp /= 2;
+    }
+    Ok(result)
+}
+
+/// Approximates `base^exp` for a fractional `exp` with the binomial series of (1 + x)^exp.
+fn bpow_approx(base: u128, exp: u128) -> Result<u128> {
+    let (x, x_negative) = if base >= BONE {
+        (base - BONE, false)
+    } else {
+        (BONE - base, true)
+    };
+
+    let mut term = BONE;
+    let mut sum = BONE;
+    let mut negative = false;
+    let mut i: u128 = 1;
+    while term >= BPOW_PRECISION {
+        let big_k = i.checked_mul(BONE).ok_or(DloomError::MathOverflow)?;
+        let k_minus_one = big_k - BONE;
+        let (c, c_negative) = if exp >= k_minus_one {
+            (exp - k_minus_one, false)
+        } else {
+            (k_minus_one - exp, true)
+        };
+        term = bdiv(bmul(term, bmul(c, x)?)?, big_k)?;
+        if term == 0 {
+            break;
+        }
+        if x_negative {
+            negative = !negative;
+        }
+        if c_negative {
+            negative = !negative;
+        }
+        sum = if negative {
+            sum.checked_sub(term).ok_or(DloomError::MathOverflow)?
+        } else {
+            sum.checked_add(term).ok_or(DloomError::MathOverflow)?
+        };
+        i += 1;
+    }
+    Ok(sum)
+}
+
+/// Raises a fixed-point `base` in (0, 2) to a fixed-point power.
+fn bpow(base: u128, exp: u128) -> Result<u128> {
+    require!(base > 0 && base <= MAX_BPOW_BASE, DloomError::MathOverflow);
+
+    let whole = exp / BONE;
+    let remain = exp % BONE;
+    let whole_pow = bpowi(base, whole)?;
+    if remain == 0 {
+        return Ok(whole_pow);
+    }
+    bmul(whole_pow, bpow_approx(base, remain)?)
+}
+
+/// Returns the `(weight_a, weight_b)` in effect at `now`, in basis points.
+///
+/// Liquidity bootstrapping pools interpolate token A's weight linearly from
+/// `start_weight_a` to `end_weight_a` across the launch window; other weighted
+/// pools return their fixed weights.
+pub fn current_weights(pool: &AmmPool, now: i64) -> Result<(u16, u16)> {
+    if pool.curve_type != CurveType::LiquidityBootstrapping {
+        return Ok((pool.weight_a, pool.weight_b));
+    }
+
+    let weight_a = if now <= pool.weight_start_timestamp {
+        pool.start_weight_a
+    } else if now >= pool.weight_end_timestamp {
+        pool.end_weight_a
+    } else {
+        let time_elapsed = now
+            .checked_sub(pool.weight_start_timestamp)
+            .ok_or(DloomError::MathOverflow)? as u128;
+        let duration = pool
+            .weight_end_timestamp
+            .checked_sub(pool.weight_start_timestamp)
+            .ok_or(DloomError::MathOverflow)? as u128;
+
+        let start = pool.start_weight_a as u128;
+        let end = pool.end_weight_a as u128;
+        let weight = if end >= start {
+            let delta = (end - start)
+                .checked_mul(time_elapsed)
+                .ok_or(DloomError::MathOverflow)?
+                .checked_div(duration)
+                .ok_or(DloomError::MathOverflow)?;
+            start.checked_add(delta).ok_or(DloomError::MathOverflow)?
+        } else {
+            let delta = (start - end)
+                .checked_mul(time_elapsed)
+                .ok_or(DloomError::MathOverflow)?
+                .checked_div(duration)
+                .ok_or(DloomError::MathOverflow)?;
+            start.checked_sub(delta).ok_or(DloomError::MathOverflow)?
+        };
+        weight as u16
+    };
+
+    Ok((weight_a, (BASIS_POINT_MAX as u16) - weight_a))
+}
+
+/// Calculates the result of a swap against the weighted constant-mean invariant
+/// `reserves_a^weight_a * reserves_b^weight_b = k`.
+///
+/// Fees are split exactly as in `math::calculate_swap_out_amount`, so the same
+/// reserve, referral and fee-growth bookkeeping applies to every curve type.
+pub fn calculate_weighted_swap_out_amount(
+    amm_pool: &AmmPool,
+    amount_in: u64,
+    source_reserves: u64,
+    destination_reserves: u64,
+    source_weight: u16,
+    destination_weight: u16,
+) -> Result<(u64, u64, u64)> {
+    require!(amount_in > 0, DloomError::ZeroAmount);
+    require!(
+        source_reserves > 0 && destination_reserves > 0,
+        DloomError::InsufficientLiquidityForSwap
+    );
+    require!(
+        source_weight > 0 && destination_weight > 0,
+        DloomError::InvalidWeights
+    );
+
+    let (total_fee, protocol_fee, lp_fee) = calculate_swap_fees(amm_pool, amount_in)?;
+    let amount_in_after_fees = (amount_in as u128)
+        .checked_sub(total_fee)
+        .ok_or(DloomError::MathOverflow)?;
+
+    // Large trades push the power series towards the edge of its convergence range,
+    // so cap a single swap at half of the input reserves.
+    require!(
+        amount_in_after_fees <= (source_reserves as u128) / 2,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    // amount_out = reserves_out * (1 - (reserves_in / (reserves_in + amount_in))^(w_in / w_out))
+    let weight_ratio = bdiv(source_weight as u128, destination_weight as u128)?;
+    let base = bdiv(
+        source_reserves as u128,
+        (source_reserves as u128)
+            .checked_add(amount_in_after_fees)
+            .ok_or(DloomError::MathOverflow)?,
+    )?;
+    let retained_fraction = bpow(base, weight_ratio)?;
+    let sold_fraction = BONE.saturating_sub(retained_fraction);
+
+    // Floor the result rather than rounding, so the pool never pays out too much.
+    let amount_out = (destination_reserves as u128)
+        .checked_mul(sold_fraction)
+        .ok_or(DloomError::MathOverflow)?
+        / BONE;
+
+    Ok((amount_out as u64, protocol_fee as u64, lp_fee as u64))
+}
+
+/// Calculates the deposit amounts and LP tokens to mint for a weighted pool.
+///
+/// The first deposit mints a fixed `INITIAL_WEIGHTED_LP_SUPPLY` (including the locked
+/// `MINIMUM_LIQUIDITY`) and sets the starting price from the deposited ratio. Later
+/// deposits must be proportional to the current reserves, which is identical to the
+/// constant-product rule, as is withdrawal via `math::calculate_assets_to_withdraw`.
+pub fn calculate_weighted_lp_tokens_to_mint(
+    amm_pool: &AmmPool,
+    lp_supply: u64,
+    amount_a_desired: u64,
+    amount_b_desired: u64,
+) -> Result<(u64, u64, u64)> {
+    if lp_supply == 0 {
+        math::require_initial_deposit(amount_a_desired, amount_b_desired)?;
+        return Ok((
+            amount_a_desired,
+            amount_b_desired,
+            INITIAL_WEIGHTED_LP_SUPPLY,
+        ));
+    }
+    math::calculate_lp_tokens_to_mint(amm_pool, lp_supply, amount_a_desired, amount_b_desired)
+}
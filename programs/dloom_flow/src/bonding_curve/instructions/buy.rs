@@ -0,0 +1,162 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/buy.rs
+
+use crate::{
+    bonding_curve::{
+        math,
+        state::{BondingCurve, CurveStatus},
+    },
+    errors::DloomError,
+    events::{emit_event, BondingCurveCompleted, BondingCurveTrade},
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the `buy_on_bonding_curve` instruction.
+///
+/// The buy that lifts the raised quote reserves to the graduation threshold completes
+/// the curve and closes trading until the reserves are migrated.
+pub fn handle_buy_on_bonding_curve<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TradeOnBondingCurve<'info>>,
+    quote_in: u64,
+    min_tokens_out: u64,
+) -> Result<()> {
+    let (tokens_out, quote_to_reserves, fee) =
+        math::calculate_buy(&ctx.accounts.bonding_curve, quote_in)?;
+    require!(tokens_out > 0, DloomError::ZeroAmount);
+    let net_tokens_out =
+        token_extensions::calculate_net_received(&ctx.accounts.token_mint, tokens_out)?;
+    require!(net_tokens_out >= min_tokens_out, DloomError::SlippageExceeded);
+
+    // 1. Transfer the quote tokens (net of fees) and the fee from the trader. The reserve
+    // transfer is grossed up for any Token-2022 transfer fee so the vault is credited in full.
+    let quote_to_send =
+        token_extensions::calculate_gross_to_send(&ctx.accounts.quote_mint, quote_to_reserves)?;
+    token_extensions::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.quote_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.trader_quote_account.to_account_info(),
+                to: ctx.accounts.quote_vault.to_account_info(),
+                authority: ctx.accounts.trader.to_account_info(),
+                mint: ctx.accounts.quote_mint.to_account_info(),
+            },
+        ),
+        quote_to_send,
+        ctx.accounts.quote_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+    if fee > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.quote_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.trader_quote_account.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault.to_account_info(),
+                    authority: ctx.accounts.trader.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
+                },
+            ),
+            fee,
+            ctx.accounts.quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 2. Transfer the launch tokens to the trader.
+    let token_mint_key = ctx.accounts.bonding_curve.token_mint;
+    let bump = &[ctx.accounts.bonding_curve.bump][..];
+    let signer_seeds = &[b"bonding_curve", token_mint_key.as_ref(), bump][..];
+    token_extensions::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.token_vault.to_account_info(),
+                to: ctx.accounts.trader_token_account.to_account_info(),
+                authority: ctx.accounts.bonding_curve.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+            },
+            &[signer_seeds],
+        ),
+        tokens_out,
+        ctx.accounts.token_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    // 3. Move along the curve.
+    let curve = &mut ctx.accounts.bonding_curve;
+    curve.virtual_quote_reserves = curve
+        .virtual_quote_reserves
+        .checked_add(quote_to_reserves)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.virtual_token_reserves = curve
+        .virtual_token_reserves
+        .checked_sub(tokens_out)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.real_quote_reserves = curve
+        .real_quote_reserves
+        .checked_add(quote_to_reserves)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.real_token_reserves = curve
+        .real_token_reserves
+        .checked_sub(tokens_out)
+        .ok_or(DloomError::MathOverflow)?;
+
+    emit_event!(ctx.accounts, ctx.bumps, BondingCurveTrade {
+        curve_address: curve.key(),
+        sequence: curve.next_event_sequence(),
+        trader: ctx.accounts.trader.key(),
+        is_buy: true,
+        token_amount: tokens_out,
+        quote_amount: quote_in,
+        fee,
+    });
+
+    let curve = &mut ctx.accounts.bonding_curve;
+    if curve.real_quote_reserves >= curve.graduation_quote_threshold {
+        curve.status = CurveStatus::Complete;
+        emit_event!(ctx.accounts, ctx.bumps, BondingCurveCompleted {
+            curve_address: curve.key(),
+            sequence: curve.next_event_sequence(),
+            real_token_reserves: curve.real_token_reserves,
+            real_quote_reserves: curve.real_quote_reserves,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct TradeOnBondingCurve<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"bonding_curve", bonding_curve.token_mint.as_ref()],
+        bump = bonding_curve.bump,
+        constraint = bonding_curve.status == CurveStatus::Trading @ DloomError::CurveNotTrading,
+    )]
+    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(address = bonding_curve.token_mint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = bonding_curve.quote_mint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = bonding_curve.token_vault)]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = bonding_curve.quote_vault)]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = bonding_curve.protocol_fee_vault)]
+    pub protocol_fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = token_mint, token::authority = trader)]
+    pub trader_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = quote_mint, token::authority = trader)]
+    pub trader_quote_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,173 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/create_curve.rs
+
+use crate::{
+    bonding_curve::state::{BondingCurve, CurveStatus, GraduationFees},
+    constants::BASIS_POINT_MAX,
+    errors::DloomError,
+    events::{emit_event, BondingCurveCreated},
+    state::ProtocolConfig,
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the `create_bonding_curve` instruction.
+///
+/// The creator deposits `token_amount` launch tokens, and whatever arrives after any
+/// Token-2022 transfer fee is put up for sale. The starting price is
+/// `virtual_quote_reserves / virtual_token_reserves`, and the curve completes once
+/// `graduation_quote_threshold` quote tokens have been raised, and graduates into a pool
+/// charging `graduation_fees`.
+pub fn handle_create_bonding_curve<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateBondingCurve<'info>>,
+    virtual_token_reserves: u64,
+    virtual_quote_reserves: u64,
+    token_amount: u64,
+    graduation_quote_threshold: u64,
+    fee_rate: u16,
+    graduation_fees: GraduationFees,
+) -> Result<()> {
+    require!(
+        fee_rate as u128 <= BASIS_POINT_MAX,
+        DloomError::InvalidFeeRates
+    );
+    graduation_fees.validate()?;
+    token_extensions::validate_mint_extensions(&ctx.accounts.token_mint, &ctx.accounts.protocol_config)?;
+    token_extensions::validate_mint_extensions(&ctx.accounts.quote_mint, &ctx.accounts.protocol_config)?;
+    // The curve can only sell what the vault is credited with.
+    let net_token_amount =
+        token_extensions::calculate_net_received(&ctx.accounts.token_mint, token_amount)?;
+    require!(
+        net_token_amount > 0
+            && net_token_amount < virtual_token_reserves
+            && virtual_quote_reserves > 0
+            && graduation_quote_threshold > 0,
+        DloomError::InvalidBondingCurveParameters
+    );
+
+    // Selling the whole allocation raises virtual_quote * token_amount / (virtual_token - token_amount),
+    // so the threshold must be reachable before the curve runs out of tokens.
+    let max_quote_raised = (virtual_quote_reserves as u128)
+        .checked_mul(net_token_amount as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div((virtual_token_reserves - net_token_amount) as u128)
+        .ok_or(DloomError::MathOverflow)?;
+    require!(
+        graduation_quote_threshold as u128 <= max_quote_raised,
+        DloomError::InvalidBondingCurveParameters
+    );
+
+    token_extensions::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.creator_token_account.to_account_info(),
+                to: ctx.accounts.token_vault.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+            },
+        ),
+        token_amount,
+        ctx.accounts.token_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    let curve = &mut ctx.accounts.bonding_curve;
+    curve.bump = ctx.bumps.bonding_curve;
+    curve.creator = ctx.accounts.creator.key();
+    curve.token_mint = ctx.accounts.token_mint.key();
+    curve.quote_mint = ctx.accounts.quote_mint.key();
+    curve.token_vault = ctx.accounts.token_vault.key();
+    curve.quote_vault = ctx.accounts.quote_vault.key();
+    curve.protocol_fee_vault = ctx.accounts.protocol_fee_vault.key();
+    curve.fee_rate = fee_rate;
+    curve.virtual_token_reserves = virtual_token_reserves;
+    curve.virtual_quote_reserves = virtual_quote_reserves;
+    curve.real_token_reserves = net_token_amount;
+    curve.real_quote_reserves = 0;
+    curve.graduation_quote_threshold = graduation_quote_threshold;
+    curve.status = CurveStatus::Trading;
+    curve.graduation_fees = graduation_fees;
+
+    emit_event!(ctx.accounts, ctx.bumps, BondingCurveCreated {
+        curve_address: curve.key(),
+        sequence: curve.next_event_sequence(),
+        creator: curve.creator,
+        token_mint: curve.token_mint,
+        quote_mint: curve.quote_mint,
+        virtual_token_reserves,
+        virtual_quote_reserves,
+        real_token_reserves: net_token_amount,
+        graduation_quote_threshold,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CreateBondingCurve<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: The protocol authority, which owns the fee vault. Checked against the protocol config.
+    #[account(address = protocol_config.authority)]
+    pub authority: AccountInfo<'info>,
+
+    #[account(constraint = token_mint.key() != quote_mint.key() @ DloomError::InvalidMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + 320,
+        seeds = [b"bonding_curve", token_mint.key().as_ref()],
+        bump
+    )]
+    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", bonding_curve.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = bonding_curve,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", bonding_curve.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = bonding_curve,
+        token::token_program = quote_token_program
+    )]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"protocol_fee_vault", bonding_curve.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = authority,
+        token::token_program = quote_token_program
+    )]
+    pub protocol_fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = token_mint, token::authority = creator)]
+    pub creator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,267 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/graduate_to_amm.rs
+
+use crate::{
+    amm::{
+        // The glob brings in the client modules generated for the nested accounts struct.
+        instructions::{create_pool::*, swap::update_oracle},
+        math as amm_math,
+    },
+    bonding_curve::{
+        math,
+        state::{BondingCurve, CurveStatus},
+    },
+    constants::GRADUATION_FEE_TIER,
+    errors::DloomError,
+    events::{emit_event, BondingCurveGraduated},
+    state::ProtocolConfig,
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, MintTo, TokenAccount, TransferChecked};
+
+/// The handler for the `graduate_bonding_curve_to_amm` instruction.
+///
+/// Creates a constant-product `AmmPool` for the launch pair and migrates the completed
+/// curve's reserves into it at the curve's final price, in a single instruction. The
+/// pool sits in the reserved `GRADUATION_FEE_TIER` and charges the fees fixed when the
+/// curve was created. All LP tokens are minted to the pool's own lock vault, so the
+/// liquidity is locked permanently. The LP fees it earns are deliberately forfeited: no
+/// position holds the locked LP tokens, so the fees are never claimed and stay in the
+/// pool's reserves, deepening the locked liquidity. Unpaired launch tokens are burned and
+/// any unpaired quote tokens are returned to the creator.
+pub fn handle_graduate_bonding_curve_to_amm<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GraduateBondingCurveToAmm<'info>>,
+) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.create_pool.authority.key(),
+        ctx.accounts.protocol_config.authority,
+        DloomError::Unauthorized
+    );
+
+    let curve = &ctx.accounts.bonding_curve;
+    let token_is_a = ctx.accounts.create_pool.token_a_mint.key() == curve.token_mint;
+    let (launch_mint_key, quote_mint_key) = if token_is_a {
+        (
+            ctx.accounts.create_pool.token_a_mint.key(),
+            ctx.accounts.create_pool.token_b_mint.key(),
+        )
+    } else {
+        (
+            ctx.accounts.create_pool.token_b_mint.key(),
+            ctx.accounts.create_pool.token_a_mint.key(),
+        )
+    };
+    require_keys_eq!(launch_mint_key, curve.token_mint, DloomError::InvalidMint);
+    require_keys_eq!(quote_mint_key, curve.quote_mint, DloomError::InvalidMint);
+
+    // 1. Create the pool through the same path as `create_amm_pool`.
+    let fees = curve.graduation_fees;
+    ctx.accounts.create_pool.initialize_pool(
+        &ctx.bumps.create_pool,
+        GRADUATION_FEE_TIER,
+        fees.fee_rate,
+        fees.protocol_fee_share,
+        fees.referrer_fee_share,
+    )?;
+    let create_pool = &mut ctx.accounts.create_pool;
+    update_oracle(
+        &mut create_pool.amm_pool,
+        &create_pool.token_a_mint,
+        &create_pool.token_b_mint,
+        None,
+    )?;
+
+    // 2. Work out the deposit at the curve's final price.
+    let curve = &ctx.accounts.bonding_curve;
+    let (token_amount, quote_amount) = math::calculate_migration_amounts(curve)?;
+    let token_left = curve
+        .real_token_reserves
+        .checked_sub(token_amount)
+        .ok_or(DloomError::MathOverflow)?;
+    let quote_left = curve
+        .real_quote_reserves
+        .checked_sub(quote_amount)
+        .ok_or(DloomError::MathOverflow)?;
+    let (sent_a, sent_b) = if token_is_a {
+        (token_amount, quote_amount)
+    } else {
+        (quote_amount, token_amount)
+    };
+    // The pool is credited only what arrives after any Token-2022 transfer fee.
+    let amount_a =
+        token_extensions::calculate_net_received(&ctx.accounts.create_pool.token_a_mint, sent_a)?;
+    let amount_b =
+        token_extensions::calculate_net_received(&ctx.accounts.create_pool.token_b_mint, sent_b)?;
+    let (_, _, lp_tokens_to_mint) = amm_math::calculate_lp_tokens_to_mint(
+        &ctx.accounts.create_pool.amm_pool,
+        ctx.accounts.create_pool.lp_mint.supply,
+        amount_a,
+        amount_b,
+    )?;
+    require!(lp_tokens_to_mint > 0, DloomError::ZeroLiquidity);
+
+    // 3. Move the paired reserves from the curve's vaults into the pool's vaults.
+    let token_mint_key = curve.token_mint;
+    let curve_bump = &[curve.bump][..];
+    let curve_seeds = &[b"bonding_curve", token_mint_key.as_ref(), curve_bump][..];
+
+    let create_pool = &ctx.accounts.create_pool;
+    let (
+        (launch_mint, launch_program, launch_pool_vault),
+        (quote_mint, quote_program, quote_pool_vault),
+    ) = if token_is_a {
+        (
+            (&create_pool.token_a_mint, &create_pool.token_a_program, &create_pool.token_a_vault),
+            (&create_pool.token_b_mint, &create_pool.token_b_program, &create_pool.token_b_vault),
+        )
+    } else {
+        (
+            (&create_pool.token_b_mint, &create_pool.token_b_program, &create_pool.token_b_vault),
+            (&create_pool.token_a_mint, &create_pool.token_a_program, &create_pool.token_a_vault),
+        )
+    };
+
+    token_extensions::transfer_checked(
+        CpiContext::new_with_signer(
+            launch_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.curve_token_vault.to_account_info(),
+                to: launch_pool_vault.to_account_info(),
+                authority: ctx.accounts.bonding_curve.to_account_info(),
+                mint: launch_mint.to_account_info(),
+            },
+            &[curve_seeds],
+        ),
+        token_amount,
+        launch_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+    token_extensions::transfer_checked(
+        CpiContext::new_with_signer(
+            quote_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.curve_quote_vault.to_account_info(),
+                to: quote_pool_vault.to_account_info(),
+                authority: ctx.accounts.bonding_curve.to_account_info(),
+                mint: quote_mint.to_account_info(),
+            },
+            &[curve_seeds],
+        ),
+        quote_amount,
+        quote_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    // 4. Release whatever could not be paired.
+    if token_left > 0 {
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                launch_program.to_account_info(),
+                Burn {
+                    mint: launch_mint.to_account_info(),
+                    from: ctx.accounts.curve_token_vault.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            token_left,
+        )?;
+    }
+    if quote_left > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                quote_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.curve_quote_vault.to_account_info(),
+                    to: ctx.accounts.creator_quote_account.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: quote_mint.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            quote_left,
+            quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 5. Mint the LP tokens into the lock vault.
+    let amm_pool = &create_pool.amm_pool;
+    let fee_tier_bytes = &amm_pool.fee_tier.to_le_bytes()[..];
+    let curve_type_seed = &amm_pool.curve_type.seed()[..];
+    let pool_bump = &[amm_pool.bump][..];
+    let pool_seeds = &[
+        b"amm_pool",
+        amm_pool.token_a_mint.as_ref(),
+        amm_pool.token_b_mint.as_ref(),
+        fee_tier_bytes,
+        curve_type_seed,
+        pool_bump,
+    ][..];
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            create_pool.token_program.to_account_info(),
+            MintTo {
+                mint: create_pool.lp_mint.to_account_info(),
+                to: create_pool.lp_lock_vault.to_account_info(),
+                authority: amm_pool.to_account_info(),
+            },
+            &[pool_seeds],
+        ),
+        lp_tokens_to_mint,
+    )?;
+
+    // 6. Record the new state.
+    let pool_address = ctx.accounts.create_pool.amm_pool.key();
+    let amm_pool = &mut ctx.accounts.create_pool.amm_pool;
+    amm_pool.reserves_a = amount_a;
+    amm_pool.reserves_b = amount_b;
+    let pool_sequence = amm_pool.next_event_sequence();
+
+    let curve = &mut ctx.accounts.bonding_curve;
+    curve.real_token_reserves = 0;
+    curve.real_quote_reserves = 0;
+    curve.status = CurveStatus::Graduated;
+    curve.graduated_pool = pool_address;
+
+    emit_event!(ctx.accounts.create_pool, ctx.bumps.create_pool, BondingCurveGraduated {
+        curve_address: curve.key(),
+        sequence: curve.next_event_sequence(),
+        pool_address,
+        pool_sequence,
+        token_amount,
+        quote_amount,
+        liquidity: lp_tokens_to_mint as u128,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GraduateBondingCurveToAmm<'info> {
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bonding_curve", bonding_curve.token_mint.as_ref()],
+        bump = bonding_curve.bump,
+        constraint = bonding_curve.status == CurveStatus::Complete @ DloomError::CurveNotComplete,
+    )]
+    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(mut, address = bonding_curve.token_vault)]
+    pub curve_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = bonding_curve.quote_vault)]
+    pub curve_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = creator_quote_account.owner == bonding_curve.creator @ DloomError::Unauthorized,
+        constraint = creator_quote_account.mint == bonding_curve.quote_mint @ DloomError::InvalidMint,
+    )]
+    pub creator_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The accounts for the new pool. Anyone may crank the graduation and pay its rent.
+    pub create_pool: CreateGraduatedAmmPool<'info>,
+}
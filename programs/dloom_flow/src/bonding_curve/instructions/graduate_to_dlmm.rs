@@ -0,0 +1,295 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/graduate_to_dlmm.rs
+
+use crate::{
+    bonding_curve::{
+        math,
+        state::{BondingCurve, CurveStatus},
+    },
+    constants::PRECISION,
+    dlmm::{
+        // The glob brings in the client modules generated for the nested accounts struct.
+        instructions::create_community_pool::*,
+        math as dlmm_math,
+        state::Bin,
+    },
+    errors::DloomError,
+    events::{emit_event, BondingCurveGraduated},
+    state::ProtocolConfig,
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, TokenAccount, TransferChecked};
+
+/// The handler for the `graduate_bonding_curve_to_dlmm` instruction.
+///
+/// Creates a community `DlmmPool` whose `initial_bin_id` holds the curve's final price,
+/// charging the fees fixed when the curve was created, and deposits the completed
+/// curve's reserves into that active bin. The liquidity is not assigned to any position,
+/// so it can never be withdrawn and is effectively burned. Its share of swap fees is
+/// forfeited the same way: it raises the bin's fee growth, but with no position to claim
+/// it, it stays in the pool's vaults. Unpaired launch tokens are burned and any unpaired
+/// quote tokens are returned to the creator.
+pub fn handle_graduate_bonding_curve_to_dlmm<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GraduateBondingCurveToDlmm<'info>>,
+    bin_step: u16,
+    initial_bin_id: i32,
+) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.create_pool.authority.key(),
+        ctx.accounts.protocol_config.authority,
+        DloomError::Unauthorized
+    );
+
+    let curve = &ctx.accounts.bonding_curve;
+    let token_is_a = ctx.accounts.create_pool.token_a_mint.key() == curve.token_mint;
+    let (launch_mint_key, quote_mint_key) = if token_is_a {
+        (
+            ctx.accounts.create_pool.token_a_mint.key(),
+            ctx.accounts.create_pool.token_b_mint.key(),
+        )
+    } else {
+        (
+            ctx.accounts.create_pool.token_b_mint.key(),
+            ctx.accounts.create_pool.token_a_mint.key(),
+        )
+    };
+    require_keys_eq!(launch_mint_key, curve.token_mint, DloomError::InvalidMint);
+    require_keys_eq!(quote_mint_key, curve.quote_mint, DloomError::InvalidMint);
+
+    // 1. The initial bin must contain the curve's final price (token B per token A).
+    let (price_numerator, price_denominator) = if token_is_a {
+        (curve.virtual_quote_reserves, curve.virtual_token_reserves)
+    } else {
+        (curve.virtual_token_reserves, curve.virtual_quote_reserves)
+    };
+    let curve_price = (price_numerator as u128)
+        .checked_mul(PRECISION)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(price_denominator as u128)
+        .ok_or(DloomError::MathOverflow)?;
+    let bin_price = dlmm_math::get_price_at_bin(initial_bin_id, bin_step)?;
+    let next_bin_price = dlmm_math::get_price_at_bin(
+        initial_bin_id.checked_add(1).ok_or(DloomError::MathOverflow)?,
+        bin_step,
+    )?;
+    require!(
+        bin_price <= curve_price && curve_price < next_bin_price,
+        DloomError::GraduationPriceMismatch
+    );
+
+    // 2. Create the pool through the same path as `create_dlmm_community_pool`. The
+    // protocol, not whoever cranked the graduation, becomes its authority.
+    let fees = curve.graduation_fees;
+    ctx.accounts.create_pool.initialize_pool(
+        &ctx.bumps.create_pool,
+        bin_step,
+        fees.fee_rate,
+        fees.protocol_fee_share,
+        fees.referrer_fee_share,
+        initial_bin_id,
+    )?;
+    ctx.accounts.create_pool.dlmm_pool.authority = ctx.accounts.protocol_config.authority;
+
+    // 3. Size the active-bin deposit from the reserves available at the curve's price.
+    // Token-2022 transfer fees are withheld from what the pool's vaults receive, so the
+    // bin is sized on the net amounts and the transfers are grossed back up.
+    let (token_amount, quote_amount) = math::calculate_migration_amounts(curve)?;
+    let (available_a, available_b) = if token_is_a {
+        (token_amount, quote_amount)
+    } else {
+        (quote_amount, token_amount)
+    };
+    let mint_a = &ctx.accounts.create_pool.token_a_mint;
+    let mint_b = &ctx.accounts.create_pool.token_b_mint;
+    let net_available_a = token_extensions::calculate_net_received(mint_a, available_a)? as u128;
+    let net_available_b = token_extensions::calculate_net_received(mint_b, available_b)? as u128;
+    let liquidity = net_available_a.min(
+        net_available_b
+            .checked_mul(PRECISION)
+            .ok_or(DloomError::MathOverflow)?
+            .checked_div(bin_price)
+            .ok_or(DloomError::MathOverflow)?,
+    );
+    require!(liquidity > 0, DloomError::ZeroLiquidity);
+    let (required_a, required_b) =
+        dlmm_math::calculate_required_for_bin(initial_bin_id, initial_bin_id, bin_step, liquidity)?;
+    let (amount_a, amount_b) = (required_a as u64, required_b as u64);
+    let amount_a_to_send = token_extensions::calculate_gross_to_send(mint_a, amount_a)?;
+    let amount_b_to_send = token_extensions::calculate_gross_to_send(mint_b, amount_b)?;
+    let (token_deposited, quote_deposited) = if token_is_a {
+        (amount_a_to_send, amount_b_to_send)
+    } else {
+        (amount_b_to_send, amount_a_to_send)
+    };
+    let token_left = curve
+        .real_token_reserves
+        .checked_sub(token_deposited)
+        .ok_or(DloomError::MathOverflow)?;
+    let quote_left = curve
+        .real_quote_reserves
+        .checked_sub(quote_deposited)
+        .ok_or(DloomError::MathOverflow)?;
+
+    // 4. Move the paired reserves from the curve's vaults into the pool's vaults.
+    let token_mint_key = curve.token_mint;
+    let curve_bump = &[curve.bump][..];
+    let curve_seeds = &[b"bonding_curve", token_mint_key.as_ref(), curve_bump][..];
+
+    let create_pool = &ctx.accounts.create_pool;
+    let (
+        (launch_mint, launch_program, launch_pool_vault),
+        (quote_mint, quote_program, quote_pool_vault),
+    ) = if token_is_a {
+        (
+            (&create_pool.token_a_mint, &create_pool.token_a_program, &create_pool.token_a_vault),
+            (&create_pool.token_b_mint, &create_pool.token_b_program, &create_pool.token_b_vault),
+        )
+    } else {
+        (
+            (&create_pool.token_b_mint, &create_pool.token_b_program, &create_pool.token_b_vault),
+            (&create_pool.token_a_mint, &create_pool.token_a_program, &create_pool.token_a_vault),
+        )
+    };
+
+    if token_deposited > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                launch_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.curve_token_vault.to_account_info(),
+                    to: launch_pool_vault.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: launch_mint.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            token_deposited,
+            launch_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+    if quote_deposited > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                quote_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.curve_quote_vault.to_account_info(),
+                    to: quote_pool_vault.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: quote_mint.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            quote_deposited,
+            quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 5. Release whatever could not be paired.
+    if token_left > 0 {
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                launch_program.to_account_info(),
+                Burn {
+                    mint: launch_mint.to_account_info(),
+                    from: ctx.accounts.curve_token_vault.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            token_left,
+        )?;
+    }
+    if quote_left > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                quote_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.curve_quote_vault.to_account_info(),
+                    to: ctx.accounts.creator_quote_account.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: quote_mint.to_account_info(),
+                },
+                &[curve_seeds],
+            ),
+            quote_left,
+            quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 6. Record the new state.
+    let mut active_bin = ctx.accounts.active_bin.load_init()?;
+    active_bin.liquidity = liquidity;
+    drop(active_bin);
+
+    let pool_address = ctx.accounts.create_pool.dlmm_pool.key();
+    let dlmm_pool = &mut ctx.accounts.create_pool.dlmm_pool;
+    dlmm_pool.reserves_a = amount_a;
+    dlmm_pool.reserves_b = amount_b;
+    let pool_sequence = dlmm_pool.next_event_sequence();
+
+    let curve = &mut ctx.accounts.bonding_curve;
+    curve.real_token_reserves = 0;
+    curve.real_quote_reserves = 0;
+    curve.status = CurveStatus::Graduated;
+    curve.graduated_pool = pool_address;
+
+    emit_event!(ctx.accounts.create_pool, ctx.bumps.create_pool, BondingCurveGraduated {
+        curve_address: curve.key(),
+        sequence: curve.next_event_sequence(),
+        pool_address,
+        pool_sequence,
+        token_amount: token_deposited,
+        quote_amount: quote_deposited,
+        liquidity,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(bin_step: u16, initial_bin_id: i32)]
+pub struct GraduateBondingCurveToDlmm<'info> {
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bonding_curve", bonding_curve.token_mint.as_ref()],
+        bump = bonding_curve.bump,
+        constraint = bonding_curve.status == CurveStatus::Complete @ DloomError::CurveNotComplete,
+    )]
+    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(mut, address = bonding_curve.token_vault)]
+    pub curve_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = bonding_curve.quote_vault)]
+    pub curve_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = creator_quote_account.owner == bonding_curve.creator @ DloomError::Unauthorized,
+        constraint = creator_quote_account.mint == bonding_curve.quote_mint @ DloomError::InvalidMint,
+    )]
+    pub creator_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The accounts for the new pool. Anyone may crank the graduation and pay its rent.
+    pub create_pool: CreateDlmmCommunityPool<'info>,
+
+    #[account(
+        init,
+        payer = create_pool.payer,
+        space = 8 + std::mem::size_of::<Bin>(),
+        seeds = [
+            b"bin",
+            create_pool.dlmm_pool.key().as_ref(),
+            &initial_bin_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub active_bin: AccountLoader<'info, Bin>,
+
+    pub system_program: Program<'info, System>,
+}
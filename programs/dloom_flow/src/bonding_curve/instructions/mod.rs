@@ -0,0 +1,12 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/mod.rs
+pub mod buy;
+pub mod create_curve;
+pub mod graduate_to_amm;
+pub mod graduate_to_dlmm;
+pub mod sell;
+
+pub use buy::*;
+pub use create_curve::*;
+pub use graduate_to_amm::*;
+pub use graduate_to_dlmm::*;
+pub use sell::*;
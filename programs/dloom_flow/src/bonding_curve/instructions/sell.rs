@@ -0,0 +1,113 @@
+// FILE: programs/dloom_flow/src/bonding_curve/instructions/sell.rs
+
+use crate::{
+    bonding_curve::{instructions::buy::TradeOnBondingCurve, math},
+    errors::DloomError,
+    events::{emit_event, BondingCurveTrade},
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TransferChecked;
+
+/// The handler for the `sell_on_bonding_curve` instruction.
+pub fn handle_sell_on_bonding_curve<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TradeOnBondingCurve<'info>>,
+    tokens_in: u64,
+    min_quote_out: u64,
+) -> Result<()> {
+    // The curve is only credited what arrives after any Token-2022 transfer fee.
+    let net_tokens_in =
+        token_extensions::calculate_net_received(&ctx.accounts.token_mint, tokens_in)?;
+    require!(net_tokens_in > 0, DloomError::ZeroAmount);
+    let (quote_out, quote_from_reserves, fee) =
+        math::calculate_sell(&ctx.accounts.bonding_curve, net_tokens_in)?;
+    let net_quote_out =
+        token_extensions::calculate_net_received(&ctx.accounts.quote_mint, quote_out)?;
+    require!(net_quote_out >= min_quote_out, DloomError::SlippageExceeded);
+
+    // 1. Transfer the launch tokens from the trader back to the curve.
+    token_extensions::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.trader_token_account.to_account_info(),
+                to: ctx.accounts.token_vault.to_account_info(),
+                authority: ctx.accounts.trader.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+            },
+        ),
+        tokens_in,
+        ctx.accounts.token_mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    // 2. Pay out the quote tokens and the fee from the curve's reserves.
+    let token_mint_key = ctx.accounts.bonding_curve.token_mint;
+    let bump = &[ctx.accounts.bonding_curve.bump][..];
+    let signer_seeds = &[b"bonding_curve", token_mint_key.as_ref(), bump][..];
+    if quote_out > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.quote_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.quote_vault.to_account_info(),
+                    to: ctx.accounts.trader_quote_account.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            quote_out,
+            ctx.accounts.quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+    if fee > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.quote_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.quote_vault.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            fee,
+            ctx.accounts.quote_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 3. Move back down the curve.
+    let curve = &mut ctx.accounts.bonding_curve;
+    curve.virtual_token_reserves = curve
+        .virtual_token_reserves
+        .checked_add(net_tokens_in)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.virtual_quote_reserves = curve
+        .virtual_quote_reserves
+        .checked_sub(quote_from_reserves)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.real_token_reserves = curve
+        .real_token_reserves
+        .checked_add(net_tokens_in)
+        .ok_or(DloomError::MathOverflow)?;
+    curve.real_quote_reserves = curve
+        .real_quote_reserves
+        .checked_sub(quote_from_reserves)
+        .ok_or(DloomError::MathOverflow)?;
+
+    emit_event!(ctx.accounts, ctx.bumps, BondingCurveTrade {
+        curve_address: curve.key(),
+        sequence: curve.next_event_sequence(),
+        trader: ctx.accounts.trader.key(),
+        is_buy: false,
+        token_amount: tokens_in,
+        quote_amount: quote_out,
+        fee,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+// FILE: programs/dloom_flow/src/bonding_curve/math.rs
+
+use crate::{
+    bonding_curve::state::BondingCurve, constants::BASIS_POINT_MAX, errors::DloomError,
+};
+use anchor_lang::prelude::*;
+
+/// Calculates the fee on a quote-token amount.
+fn calculate_curve_fee(curve: &BondingCurve, quote_amount: u128) -> Result<u128> {
+    quote_amount
+        .checked_mul(curve.fee_rate as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(BASIS_POINT_MAX)
+        .ok_or(DloomError::MathOverflow.into())
+}
+
+/// Calculates a buy of `quote_in` against the curve.
+///
+/// The fee is taken from the input. Returns `(tokens_out, quote_added_to_reserves, fee)`.
+pub fn calculate_buy(curve: &BondingCurve, quote_in: u64) -> Result<(u64, u64, u64)> {
+    require!(quote_in > 0, DloomError::ZeroAmount);
+
+    let fee = calculate_curve_fee(curve, quote_in as u128)?;
+    let net_quote_in = (quote_in as u128)
+        .checked_sub(fee)
+        .ok_or(DloomError::MathOverflow)?;
+
+    // tokens_out = virtual_token * net_quote_in / (virtual_quote + net_quote_in)
+    let tokens_out = (curve.virtual_token_reserves as u128)
+        .checked_mul(net_quote_in)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(
+            (curve.virtual_quote_reserves as u128)
+                .checked_add(net_quote_in)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+
+    require!(
+        tokens_out <= curve.real_token_reserves as u128,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    Ok((tokens_out as u64, net_quote_in as u64, fee as u64))
+}
+
+/// Calculates a sell of `tokens_in` against the curve.
+///
+/// The fee is taken from the output. Returns `(quote_out, quote_removed_from_reserves, fee)`.
+pub fn calculate_sell(curve: &BondingCurve, tokens_in: u64) -> Result<(u64, u64, u64)> {
+    require!(tokens_in > 0, DloomError::ZeroAmount);
+
+    // gross_quote_out = virtual_quote * tokens_in / (virtual_token + tokens_in)
+    let gross_quote_out = (curve.virtual_quote_reserves as u128)
+        .checked_mul(tokens_in as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(
+            (curve.virtual_token_reserves as u128)
+                .checked_add(tokens_in as u128)
+                .ok_or(DloomError::MathOverflow)?,
+        )
+        .ok_or(DloomError::MathOverflow)?;
+
+    require!(
+        gross_quote_out <= curve.real_quote_reserves as u128,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    let fee = calculate_curve_fee(curve, gross_quote_out)?;
+    let quote_out = gross_quote_out
+        .checked_sub(fee)
+        .ok_or(DloomError::MathOverflow)?;
+
+    Ok((quote_out as u64, gross_quote_out as u64, fee as u64))
+}
+
+/// Calculates how many launch and quote tokens migrate into the graduated pool.
+///
+/// All raised quote tokens are paired with launch tokens at the curve's final price,
+/// `virtual_quote_reserves / virtual_token_reserves`, so the new pool opens where the
+/// curve closed. Returns `(token_amount, quote_amount)`.
+pub fn calculate_migration_amounts(curve: &BondingCurve) -> Result<(u64, u64)> {
+    require!(
+        curve.virtual_quote_reserves > 0 && curve.virtual_token_reserves > 0,
+        DloomError::InsufficientLiquidityForSwap
+    );
+
+    let quote_amount = curve.real_quote_reserves as u128;
+    let token_amount = quote_amount
+        .checked_mul(curve.virtual_token_reserves as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(curve.virtual_quote_reserves as u128)
+        .ok_or(DloomError::MathOverflow)?;
+
+    if token_amount <= curve.real_token_reserves as u128 {
+        return Ok((token_amount as u64, quote_amount as u64));
+    }
+
+    // Not enough launch tokens left: pair all of them and leave the excess quote.
+    let token_amount = curve.real_token_reserves as u128;
+    let quote_amount = token_amount
+        .checked_mul(curve.virtual_quote_reserves as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(curve.virtual_token_reserves as u128)
+        .ok_or(DloomError::MathOverflow)?;
+    Ok((token_amount as u64, quote_amount as u64))
+}
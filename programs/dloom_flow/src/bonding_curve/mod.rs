@@ -0,0 +1,8 @@
+// FILE: programs/dloom_flow/src/bonding_curve/mod.rs
+pub mod instructions;
+pub mod math;
+pub mod state;
+
+pub use instructions::*;
+pub use math::*;
+pub use state::*;
@@ -0,0 +1,101 @@
+// FILE: programs/dloom_flow/src/bonding_curve/state/bonding_curve.rs
+
+use crate::{constants::BASIS_POINT_MAX, errors::DloomError};
+use anchor_lang::prelude::*;
+
+/// The lifecycle of a bonding-curve launch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CurveStatus {
+    /// Buys and sells follow the curve.
+    #[default]
+    Trading,
+    /// The graduation threshold was reached. Trading is closed until the reserves migrate.
+    Complete,
+    /// The reserves have been migrated into `graduated_pool`.
+    Graduated,
+}
+
+/// The fees of the pool a curve graduates into, fixed when the curve is created so
+/// whoever cranks the graduation cannot choose them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct GraduationFees {
+    /// The pool's swap fee, in basis points.
+    pub fee_rate: u16,
+    /// The protocol's share of the swap fee, in basis points.
+    pub protocol_fee_share: u16,
+    /// The referrer's share of the swap fee, in basis points.
+    pub referrer_fee_share: u16,
+}
+
+impl GraduationFees {
+    /// Checks the fees against the limits pool creation enforces, so a curve cannot be
+    /// created that would fail to graduate.
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.fee_rate as u128 <= BASIS_POINT_MAX,
+            DloomError::InvalidFeeRates
+        );
+        require!(
+            self.protocol_fee_share as u128 + self.referrer_fee_share as u128 <= BASIS_POINT_MAX,
+            DloomError::FeeShareExceedsTotal
+        );
+        Ok(())
+    }
+}
+
+/// State for a bonding-curve launch pool.
+///
+/// Prices follow a constant-product curve over virtual reserves,
+/// `virtual_token_reserves * virtual_quote_reserves = k`, while the real reserves track
+/// what the vaults actually hold.
+#[account]
+#[derive(Default, Debug)]
+pub struct BondingCurve {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The account that launched the token and funded the sale allocation.
+    pub creator: Pubkey,
+
+    // --- Mint and Vault Keys ---
+    /// The token being launched.
+    pub token_mint: Pubkey,
+    /// The token buyers pay with, e.g. wrapped SOL.
+    pub quote_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub protocol_fee_vault: Pubkey,
+
+    // --- Fee Parameters ---
+    /// The fee charged on every trade, in basis points of the quote amount.
+    pub fee_rate: u16,
+
+    // --- Curve State ---
+    pub virtual_token_reserves: u64,
+    pub virtual_quote_reserves: u64,
+    /// Launch tokens still held by the curve.
+    pub real_token_reserves: u64,
+    /// Quote tokens raised by the curve, net of fees.
+    pub real_quote_reserves: u64,
+    /// The curve completes once `real_quote_reserves` reaches this amount.
+    pub graduation_quote_threshold: u64,
+
+    // --- Lifecycle ---
+    pub status: CurveStatus,
+    /// The AMM or DLMM pool the reserves were migrated into.
+    pub graduated_pool: Pubkey,
+    /// The fees of the pool the curve graduates into.
+    pub graduation_fees: GraduationFees,
+
+    // --- Events ---
+    /// The sequence number of the latest event about this curve. Each event about it
+    /// carries the next number, so an indexer can tell when it has missed one.
+    pub event_sequence: u64,
+}
+
+impl BondingCurve {
+    /// Advances the event sequence and returns the number for the event being emitted.
+    pub fn next_event_sequence(&mut self) -> u64 {
+        self.event_sequence += 1;
+        self.event_sequence
+    }
+}
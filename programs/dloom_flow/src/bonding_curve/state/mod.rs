@@ -0,0 +1,4 @@
+// FILE: programs/dloom_flow/src/bonding_curve/state/mod.rs
+pub mod bonding_curve;
+
+pub use bonding_curve::*;
@@ -1,3 +1,49 @@
 pub const BASIS_POINT_MAX: u128 = 10000;
 pub const PRECISION: u128 = 1_000_000_000_000;
 pub const MAX_BINS_PER_POSITION: i32 = 500;
+
+// --- AMM Liquidity ---
+/// LP tokens permanently locked on the first deposit into an AMM pool, so the LP supply
+/// can never return to zero and a single LP unit can never be priced at the whole pool.
+/// Each side of the first deposit must also be at least this large.
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// --- StableSwap Amplification ---
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+/// The amplification coefficient may change by at most this factor in a single ramp.
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// A ramp must last at least one day so LPs can react to the change.
+pub const MIN_AMP_RAMP_DURATION: i64 = 86_400;
+
+// --- Weighted Pools ---
+/// The smallest weight, in basis points, either token of a weighted pool may have.
+pub const MIN_WEIGHT: u16 = 100;
+/// LP tokens minted by the first deposit into a weighted pool (100 LP at 6 decimals).
+pub const INITIAL_WEIGHTED_LP_SUPPLY: u64 = 100_000_000;
+
+// --- Bonding Curves ---
+/// The fee tier graduated AMM pools are created in. It lies outside the range of fee
+/// rates, so no other pool can take a launch pair's address before the curve graduates.
+pub const GRADUATION_FEE_TIER: u16 = u16::MAX;
+
+// --- Mint Extensions ---
+/// The most mints the protocol authority can allowlist in `ProtocolConfig`.
+pub const MAX_ALLOWED_MINTS: usize = 50;
+
+// --- Routing ---
+/// The most hops a single `route_swap` may chain.
+pub const MAX_ROUTE_HOPS: usize = 4;
+/// The most pools a single `split_swap` may divide an order across.
+pub const MAX_SPLIT_LEGS: usize = 4;
+
+// --- AMM Oracle ---
+/// The most observations an `AmmOracle` may hold.
+pub const MAX_ORACLE_CARDINALITY: u16 = 1_000;
+/// The most observations `initialize_amm_oracle` may allocate. An account created through
+/// a CPI may be at most 10 KiB, which `AmmOracle::space` reaches at 254 slots; larger
+/// oracles are reached with `grow_amm_oracle`.
+pub const MAX_INITIAL_ORACLE_CARDINALITY: u16 = 254;
+/// The most points a single `observe_amm_oracle` may return, so that the result fits in
+/// the 1024 bytes of return data.
+pub const MAX_ORACLE_QUERIES: usize = 25;
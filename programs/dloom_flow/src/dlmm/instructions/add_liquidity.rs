@@ -6,11 +6,13 @@ use crate::{
         state::{Bin, DlmmPool, Position}, 
     },
     errors::DloomError,
-    events::DlmmLiquidityUpdate,
+    events::{emit_event, DlmmLiquidityUpdate},
+    native_sol::{NativeSol, WSOL_SEED},
     state::TransactionBins, // This is a top-level state now
+    token_extensions,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 use std::collections::HashMap; // Added for the validation step
 
 pub fn handle_dlmm_add_liquidity<'info>(
@@ -70,21 +72,52 @@ pub fn handle_dlmm_add_liquidity<'info>(
             .ok_or(DloomError::MathOverflow)?;
     }
 
-    // 2. Transfer the calculated total tokens for this chunk. (Logic preserved)
+    // 2. Transfer the calculated total tokens for this chunk, grossed up for any
+    // Token-2022 transfer fee so the vaults receive the full required amounts. Either
+    // side may be paid in native SOL, which is wrapped just for the transfer.
+    let native_sol = NativeSol::new(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        ctx.accounts.system_program.as_ref().map(|a| a.to_account_info()),
+    );
     if total_required_a > 0 {
-        token_interface::transfer_checked(
-            ctx.accounts.transfer_a_context(),
+        let amount_a_to_send = token_extensions::calculate_gross_to_send(
+            &ctx.accounts.token_a_mint,
             total_required_a as u64,
+        )?;
+        let user_token_a_account = native_sol.resolve(
+            ctx.accounts.user_token_a_account.as_ref(),
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program.to_account_info(),
+            amount_a_to_send,
+        )?;
+        token_extensions::transfer_checked(
+            ctx.accounts.transfer_a_context(user_token_a_account),
+            amount_a_to_send,
             ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
     if total_required_b > 0 {
-        token_interface::transfer_checked(
-            ctx.accounts.transfer_b_context(),
+        let amount_b_to_send = token_extensions::calculate_gross_to_send(
+            &ctx.accounts.token_b_mint,
             total_required_b as u64,
+        )?;
+        let user_token_b_account = native_sol.resolve(
+            ctx.accounts.user_token_b_account.as_ref(),
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program.to_account_info(),
+            amount_b_to_send,
+        )?;
+        token_extensions::transfer_checked(
+            ctx.accounts.transfer_b_context(user_token_b_account),
+            amount_b_to_send,
             ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
+    native_sol.close()?;
 
     // Defer mutable borrows until after CPIs to satisfy the borrow checker.
     let dlmm_pool = &mut ctx.accounts.dlmm_pool;
@@ -145,7 +178,9 @@ pub fn handle_dlmm_add_liquidity<'info>(
         .checked_add(total_liquidity_added_in_chunk)
         .ok_or(DloomError::MathOverflow)?;
 
-        emit!(DlmmLiquidityUpdate {
+        emit_event!(ctx.accounts, ctx.bumps, DlmmLiquidityUpdate {
+    pool_address: ctx.accounts.dlmm_pool.key(),
+    sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
     position_address: ctx.accounts.position.key(),
     liquidity_added: total_liquidity_added_in_chunk as i128,
     amount_a: total_required_a as u64,
@@ -154,6 +189,7 @@ pub fn handle_dlmm_add_liquidity<'info>(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmAddLiquidity<'info> {
     #[account(mut)]
@@ -167,7 +203,8 @@ pub struct DlmmAddLiquidity<'info> {
             dlmm_pool.token_b_mint.as_ref(),
             &dlmm_pool.bin_step.to_le_bytes()
         ],
-        bump = dlmm_pool.bump
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive
     )]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 
@@ -194,11 +231,18 @@ pub struct DlmmAddLiquidity<'info> {
     #[account(address = dlmm_pool.token_b_mint)]
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 
+    /// Omit to pay the native SOL side of the pool straight from `owner`'s lamports.
     #[account(mut, token::mint = token_a_mint, has_one = owner)]
-    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_a_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Omit to pay the native SOL side of the pool straight from `owner`'s lamports.
     #[account(mut, token::mint = token_b_mint, has_one = owner)]
-    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_b_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, owner.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
 
     #[account(mut, address = dlmm_pool.token_a_vault)]
     pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
@@ -208,15 +252,19 @@ pub struct DlmmAddLiquidity<'info> {
 
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Option<Program<'info, System>>,
 }
 
 // impl block with transfer contexts is preserved
 impl<'info> DlmmAddLiquidity<'info> {
-    fn transfer_a_context(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+    fn transfer_a_context(
+        &self,
+        from: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         CpiContext::new(
             self.token_a_program.to_account_info(),
             TransferChecked {
-                from: self.user_token_a_account.to_account_info(),
+                from,
                 to: self.token_a_vault.to_account_info(),
                 authority: self.owner.to_account_info(),
                 mint: self.token_a_mint.to_account_info(),
@@ -224,11 +272,14 @@ impl<'info> DlmmAddLiquidity<'info> {
         )
     }
 
-    fn transfer_b_context(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+    fn transfer_b_context(
+        &self,
+        from: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         CpiContext::new(
             self.token_b_program.to_account_info(),
             TransferChecked {
-                from: self.user_token_b_account.to_account_info(),
+                from,
                 to: self.token_b_vault.to_account_info(),
                 authority: self.owner.to_account_info(),
                 mint: self.token_b_mint.to_account_info(),
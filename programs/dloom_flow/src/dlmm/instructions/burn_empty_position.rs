@@ -1,6 +1,6 @@
 // FILE: programs/dloom_flow/src/instructions/dlmm_burn_empty_position.rs
 
-use crate::{errors::DloomError, dlmm::{state::Position}, events::DlmmPositionBurned};
+use crate::{errors::DloomError, dlmm::{state::{DlmmPool, Position}}, events::{emit_event, DlmmPositionBurned}};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{self, Burn, CloseAccount, Mint, TokenAccount, TokenInterface};
 
@@ -31,13 +31,16 @@ pub fn handle_dlmm_burn_empty_position(ctx: Context<DlmmBurnEmptyPosition>) -> R
     // 3. The `position` account is automatically closed by Anchor's `close = owner`
     // constraint, and its lamports are also sent to the owner.
 
-    emit!(DlmmPositionBurned {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmPositionBurned {
+    pool_address: ctx.accounts.dlmm_pool.key(),
+    sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
     position_address: ctx.accounts.position.key(),
     owner: ctx.accounts.owner.key(),
 });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmBurnEmptyPosition<'info> {
     #[account(mut)]
@@ -53,6 +56,14 @@ pub struct DlmmBurnEmptyPosition<'info> {
     )]
     pub position: Box<Account<'info, Position>>,
 
+    /// The position's pool, whose event sequence the burn advances.
+    #[account(
+        mut,
+        address = position.pool @ DloomError::InvalidPool,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub dlmm_pool: Box<Account<'info, DlmmPool>>,
+
     #[account(
         mut,
         address = position.position_mint
@@ -1,6 +1,6 @@
 // FILE: programs/dloom_flow/src/dlmm/instructions/dlmm_create_community_pool.rs
 
-use crate::{constants::*, errors::DloomError, dlmm::{state::{DlmmPool, PoolType}}, state::DlmmParameters, events::DlmmPoolCreated};
+use crate::{constants::*, errors::DloomError, dlmm::{state::{DlmmPool, PoolType}}, state::{DlmmParameters, ProtocolConfig}, events::{emit_event, DlmmPoolCreated}, token_extensions};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
@@ -12,46 +12,17 @@ pub fn handle_create_dlmm_community_pool(
     referrer_fee_share: u16,
     initial_bin_id: i32,
 ) -> Result<()> {
-    let is_allowed = ctx.accounts.dlmm_parameters.community_parameters.iter()
-        .any(|p| p.bin_step == bin_step && p.fee_rate == fee_rate);
-    require!(is_allowed, DloomError::InvalidParameters);
-    require!(protocol_fee_share as u128 <= BASIS_POINT_MAX, DloomError::InvalidFeeRates);
-    require!(referrer_fee_share as u128 <= BASIS_POINT_MAX, DloomError::InvalidFeeRates);
-
-    let dlmm_pool = &mut ctx.accounts.dlmm_pool;
-    let clock = Clock::get()?;
-
-    // Set all fields for the new community pool
-    dlmm_pool.bump = ctx.bumps.dlmm_pool;
-    dlmm_pool.authority = ctx.accounts.payer.key(); 
-    dlmm_pool.pool_type = PoolType::Community;
-    dlmm_pool.token_a_mint = ctx.accounts.token_a_mint.key();
-    dlmm_pool.token_b_mint = ctx.accounts.token_b_mint.key();
-    dlmm_pool.token_a_vault = ctx.accounts.token_a_vault.key();
-    dlmm_pool.token_b_vault = ctx.accounts.token_b_vault.key();
-    dlmm_pool.protocol_fee_vault_a = ctx.accounts.protocol_fee_vault_a.key();
-    dlmm_pool.protocol_fee_vault_b = ctx.accounts.protocol_fee_vault_b.key();
-    dlmm_pool.active_bin_id = initial_bin_id;
-    dlmm_pool.bin_step = bin_step;
-    dlmm_pool.fee_rate = fee_rate;
-    dlmm_pool.protocol_fee_share = protocol_fee_share;
-    dlmm_pool.referrer_fee_share = referrer_fee_share;
-    dlmm_pool.reserves_a = 0;
-    dlmm_pool.reserves_b = 0;
-    dlmm_pool.volatility_accumulator = 0;
-    dlmm_pool.last_fee_update_timestamp = clock.unix_timestamp;
-
-    emit!(DlmmPoolCreated {
-        pool_address: dlmm_pool.key(),
-        token_a_mint: dlmm_pool.token_a_mint,
-        token_b_mint: dlmm_pool.token_b_mint,
+    ctx.accounts.initialize_pool(
+        &ctx.bumps,
         bin_step,
         fee_rate,
-    });
-
-    Ok(())
+        protocol_fee_share,
+        referrer_fee_share,
+        initial_bin_id,
+    )
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(bin_step: u16)]
 pub struct CreateDlmmCommunityPool<'info> {
@@ -61,6 +32,12 @@ pub struct CreateDlmmCommunityPool<'info> {
     /// CHECK: This is the authority that will own the protocol fee vaults.
     pub authority: AccountInfo<'info>,
 
+    #[account(
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
     #[account(
         seeds = [b"dlmm_parameters"],
         bump
@@ -74,7 +51,7 @@ pub struct CreateDlmmCommunityPool<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 256,
+        space = 8 + 320,
         seeds = [
             b"dlmm_pool",
             token_a_mint.key().as_ref(),
@@ -130,4 +107,59 @@ pub struct CreateDlmmCommunityPool<'info> {
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
-}
\ No newline at end of file
+}
+
+impl<'info> CreateDlmmCommunityPool<'info> {
+    /// Validates the parameters against the community list and writes the new pool's state.
+    pub(crate) fn initialize_pool(
+        &mut self,
+        bumps: &CreateDlmmCommunityPoolBumps,
+        bin_step: u16,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        initial_bin_id: i32,
+    ) -> Result<()> {
+        let is_allowed = self.dlmm_parameters.community_parameters.iter()
+            .any(|p| p.bin_step == bin_step && p.fee_rate == fee_rate);
+        require!(is_allowed, DloomError::InvalidParameters);
+        token_extensions::validate_mint_extensions(&self.token_a_mint, &self.protocol_config)?;
+        token_extensions::validate_mint_extensions(&self.token_b_mint, &self.protocol_config)?;
+        require!(protocol_fee_share as u128 <= BASIS_POINT_MAX, DloomError::InvalidFeeRates);
+        require!(referrer_fee_share as u128 <= BASIS_POINT_MAX, DloomError::InvalidFeeRates);
+
+        let dlmm_pool = &mut self.dlmm_pool;
+        let clock = Clock::get()?;
+
+        // Set all fields for the new community pool
+        dlmm_pool.bump = bumps.dlmm_pool;
+        dlmm_pool.authority = self.payer.key(); 
+        dlmm_pool.pool_type = PoolType::Community;
+        dlmm_pool.token_a_mint = self.token_a_mint.key();
+        dlmm_pool.token_b_mint = self.token_b_mint.key();
+        dlmm_pool.token_a_vault = self.token_a_vault.key();
+        dlmm_pool.token_b_vault = self.token_b_vault.key();
+        dlmm_pool.protocol_fee_vault_a = self.protocol_fee_vault_a.key();
+        dlmm_pool.protocol_fee_vault_b = self.protocol_fee_vault_b.key();
+        dlmm_pool.active_bin_id = initial_bin_id;
+        dlmm_pool.bin_step = bin_step;
+        dlmm_pool.fee_rate = fee_rate;
+        dlmm_pool.protocol_fee_share = protocol_fee_share;
+        dlmm_pool.referrer_fee_share = referrer_fee_share;
+        dlmm_pool.reserves_a = 0;
+        dlmm_pool.reserves_b = 0;
+        dlmm_pool.volatility_accumulator = 0;
+        dlmm_pool.last_fee_update_timestamp = clock.unix_timestamp;
+
+        emit_event!(self, bumps, DlmmPoolCreated {
+            pool_address: dlmm_pool.key(),
+            sequence: dlmm_pool.next_event_sequence(),
+            token_a_mint: dlmm_pool.token_a_mint,
+            token_b_mint: dlmm_pool.token_b_mint,
+            bin_step,
+            fee_rate,
+        });
+
+        Ok(())
+    }
+}
@@ -3,9 +3,10 @@
 use crate::{
     constants::*,
     errors::DloomError,
-    events::DlmmPoolCreated,
+    events::{emit_event, DlmmPoolCreated},
     dlmm::{state::{DlmmPool, PoolType}},
     state::{ProtocolConfig, DlmmParameters},
+    token_extensions,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
@@ -25,6 +26,8 @@ pub fn handle_create_dlmm_pool(
         .iter()
         .any(|p| p.bin_step == bin_step && p.fee_rate == fee_rate);
     require!(is_allowed, DloomError::InvalidParameters);
+    token_extensions::validate_mint_extensions(&ctx.accounts.token_a_mint, &ctx.accounts.protocol_config)?;
+    token_extensions::validate_mint_extensions(&ctx.accounts.token_b_mint, &ctx.accounts.protocol_config)?;
     require!(
         protocol_fee_share as u128 <= BASIS_POINT_MAX,
         DloomError::InvalidFeeRates
@@ -57,8 +60,9 @@ pub fn handle_create_dlmm_pool(
     dlmm_pool.volatility_accumulator = 0;
     dlmm_pool.last_fee_update_timestamp = clock.unix_timestamp;
 
-    emit!(DlmmPoolCreated {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmPoolCreated {
         pool_address: dlmm_pool.key(),
+        sequence: dlmm_pool.next_event_sequence(),
         token_a_mint: dlmm_pool.token_a_mint,
         token_b_mint: dlmm_pool.token_b_mint,
         bin_step,
@@ -68,6 +72,7 @@ pub fn handle_create_dlmm_pool(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(bin_step: u16)]
 pub struct CreateDlmmPool<'info> {
@@ -95,7 +100,7 @@ pub struct CreateDlmmPool<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 256,
+        space = 8 + 320,
         seeds = [
             b"dlmm_pool",
             token_a_mint.key().as_ref(),
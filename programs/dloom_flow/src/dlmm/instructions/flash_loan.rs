@@ -0,0 +1,214 @@
+// FILE: programs/dloom_flow/src/dlmm/instructions/flash_loan.rs
+
+use crate::{
+    constants::PRECISION,
+    dlmm::state::{Bin, DlmmPool},
+    errors::DloomError,
+    events::{emit_event, DlmmFlashLoanRepaid},
+    flash_loan, token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the `dlmm_flash_loan` instruction.
+///
+/// Lends `amount_a` and `amount_b` out of the pool's vaults, invokes `borrower_program`
+/// with `callback_data`, and then requires each vault to hold at least its starting
+/// balance plus a fee at the pool's `fee_rate`. The fee is paid to the liquidity in the
+/// active bin, so the loan is refused while that bin is empty. While the callback runs
+/// the pool is locked, so it cannot be traded against or drained.
+pub fn handle_dlmm_flash_loan<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DlmmFlashLoan<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    require!(amount_a > 0 || amount_b > 0, DloomError::ZeroAmount);
+    require!(
+        ctx.accounts.active_bin.load()?.liquidity > 0,
+        DloomError::FlashLoanNoActiveLiquidity
+    );
+
+    let fee_a = flash_loan::calculate_flash_loan_fee(amount_a, ctx.accounts.dlmm_pool.fee_rate)?;
+    let fee_b = flash_loan::calculate_flash_loan_fee(amount_b, ctx.accounts.dlmm_pool.fee_rate)?;
+    let vault_a_before = ctx.accounts.token_a_vault.amount;
+    let vault_b_before = ctx.accounts.token_b_vault.amount;
+
+    // 1. Lend the tokens out.
+    let dlmm_pool = &ctx.accounts.dlmm_pool;
+    let bin_step_bytes = &dlmm_pool.bin_step.to_le_bytes()[..];
+    let bump = &[dlmm_pool.bump][..];
+    let signer_seeds = &[
+        b"dlmm_pool",
+        dlmm_pool.token_a_mint.as_ref(),
+        dlmm_pool.token_b_mint.as_ref(),
+        bin_step_bytes,
+        bump,
+    ][..];
+
+    if amount_a > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_vault.to_account_info(),
+                    to: ctx.accounts.borrower_token_a_account.to_account_info(),
+                    authority: dlmm_pool.to_account_info(),
+                    mint: ctx.accounts.token_a_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_a,
+            ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+    if amount_b > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_vault.to_account_info(),
+                    to: ctx.accounts.borrower_token_b_account.to_account_info(),
+                    authority: dlmm_pool.to_account_info(),
+                    mint: ctx.accounts.token_b_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount_b,
+            ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    // 2. Lock the pool and write the lock to the account before handing over control,
+    // since any instruction the callback invokes reads the account data, not our copy.
+    ctx.accounts.dlmm_pool.flash_loan_active = true;
+    ctx.accounts.dlmm_pool.exit(ctx.program_id)?;
+    flash_loan::invoke_callback(
+        &ctx.accounts.borrower_program,
+        &ctx.accounts.borrower.to_account_info(),
+        ctx.remaining_accounts,
+        callback_data,
+    )?;
+
+    // 3. Reload the pool, so the final updates start from the account rather than the
+    // copy taken before the callback, and check the repayment against the vaults' actual
+    // balances.
+    ctx.accounts.dlmm_pool.reload()?;
+    ctx.accounts.token_a_vault.reload()?;
+    ctx.accounts.token_b_vault.reload()?;
+    require!(
+        ctx.accounts.token_a_vault.amount
+            >= vault_a_before.checked_add(fee_a).ok_or(DloomError::MathOverflow)?,
+        DloomError::FlashLoanNotRepaid
+    );
+    require!(
+        ctx.accounts.token_b_vault.amount
+            >= vault_b_before.checked_add(fee_b).ok_or(DloomError::MathOverflow)?,
+        DloomError::FlashLoanNotRepaid
+    );
+
+    // 4. Unlock the pool and pay the fees to the active bin, as swap fees are.
+    {
+        let mut bin = ctx.accounts.active_bin.load_mut()?;
+        bin.fee_growth_per_unit_a = bin
+            .fee_growth_per_unit_a
+            .checked_add(
+                (fee_a as u128)
+                    .checked_mul(PRECISION)
+                    .ok_or(DloomError::MathOverflow)?
+                    .checked_div(bin.liquidity)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+        bin.fee_growth_per_unit_b = bin
+            .fee_growth_per_unit_b
+            .checked_add(
+                (fee_b as u128)
+                    .checked_mul(PRECISION)
+                    .ok_or(DloomError::MathOverflow)?
+                    .checked_div(bin.liquidity)
+                    .ok_or(DloomError::MathOverflow)?,
+            )
+            .ok_or(DloomError::MathOverflow)?;
+    }
+    let dlmm_pool = &mut ctx.accounts.dlmm_pool;
+    dlmm_pool.flash_loan_active = false;
+    dlmm_pool.reserves_a = dlmm_pool
+        .reserves_a
+        .checked_add(fee_a)
+        .ok_or(DloomError::MathOverflow)?;
+    dlmm_pool.reserves_b = dlmm_pool
+        .reserves_b
+        .checked_add(fee_b)
+        .ok_or(DloomError::MathOverflow)?;
+
+    emit_event!(ctx.accounts, ctx.bumps, DlmmFlashLoanRepaid {
+        pool_address: dlmm_pool.key(),
+        sequence: dlmm_pool.next_event_sequence(),
+        borrower: ctx.accounts.borrower.key(),
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b,
+        active_bin_id: dlmm_pool.active_bin_id,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DlmmFlashLoan<'info> {
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"dlmm_pool",
+            dlmm_pool.token_a_mint.as_ref(),
+            dlmm_pool.token_b_mint.as_ref(),
+            &dlmm_pool.bin_step.to_le_bytes()
+        ],
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub dlmm_pool: Box<Account<'info, DlmmPool>>,
+
+    /// The bin at the pool's current price, whose liquidity earns the fees.
+    #[account(
+        mut,
+        seeds = [
+            b"bin",
+            dlmm_pool.key().as_ref(),
+            &dlmm_pool.active_bin_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub active_bin: AccountLoader<'info, Bin>,
+
+    #[account(address = dlmm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = dlmm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = dlmm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = dlmm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the borrowed token A. Any account of the right mint may be used.
+    #[account(mut, token::mint = token_a_mint)]
+    pub borrower_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    /// Receives the borrowed token B. Any account of the right mint may be used.
+    #[account(mut, token::mint = token_b_mint)]
+    pub borrower_token_b_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The program invoked with the borrowed funds. It receives `borrower` and the
+    /// instruction's remaining accounts, and must repay the vaults before returning.
+    #[account(executable)]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
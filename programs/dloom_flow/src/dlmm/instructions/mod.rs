@@ -3,16 +3,24 @@ pub mod add_liquidity;
 pub mod burn_empty_position;
 pub mod create_community_pool;
 pub mod create_pool;
+pub mod flash_loan;
 pub mod modify_liquidity;
 pub mod open_position;
+pub mod quote;
 pub mod remove_liquidity;
+pub mod skim_reserves;
 pub mod swap;
+pub mod sync_reserves;
 
 pub use add_liquidity::*;
 pub use burn_empty_position::*;
 pub use create_community_pool::*;
 pub use create_pool::*;
+pub use flash_loan::*;
 pub use modify_liquidity::*;
 pub use open_position::*;
+pub use quote::*;
 pub use remove_liquidity::*;
-pub use swap::*;
\ No newline at end of file
+pub use skim_reserves::*;
+pub use swap::*;
+pub use sync_reserves::*;
\ No newline at end of file
@@ -6,11 +6,12 @@ use crate::{
         state::{Bin, DlmmPool, Position},
     },
     errors::DloomError,
-    events::DlmmLiquidityModified,
+    events::{emit_event, DlmmLiquidityModified},
     state::TransactionBins,
+    token_extensions,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 use std::collections::HashMap; // Added for validation
 
 pub fn handle_dlmm_modify_liquidity<'info>(
@@ -104,8 +105,13 @@ pub fn handle_dlmm_modify_liquidity<'info>(
     let surplus_b = total_claimable_b
         .checked_sub(required_b)
         .ok_or(DloomError::MathOverflow)?;
+    // Slippage is checked against what the user receives after any transfer fees.
+    let net_surplus_a =
+        token_extensions::calculate_net_received(&ctx.accounts.token_a_mint, surplus_a as u64)?;
+    let net_surplus_b =
+        token_extensions::calculate_net_received(&ctx.accounts.token_b_mint, surplus_b as u64)?;
     require!(
-        surplus_a >= min_surplus_a_out as u128 && surplus_b >= min_surplus_b_out as u128,
+        net_surplus_a >= min_surplus_a_out && net_surplus_b >= min_surplus_b_out,
         DloomError::SlippageExceeded
     );
 
@@ -123,7 +129,7 @@ pub fn handle_dlmm_modify_liquidity<'info>(
 
     // 7. Transfer surplus tokens back to the user.
     if surplus_a > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_a_program.to_account_info(),
                 TransferChecked {
@@ -136,10 +142,11 @@ pub fn handle_dlmm_modify_liquidity<'info>(
             ),
             surplus_a as u64,
             ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
     if surplus_b > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_b_program.to_account_info(),
                 TransferChecked {
@@ -152,6 +159,7 @@ pub fn handle_dlmm_modify_liquidity<'info>(
             ),
             surplus_b as u64,
             ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
 
@@ -194,9 +202,10 @@ pub fn handle_dlmm_modify_liquidity<'info>(
     new_position.fee_growth_snapshot_a = snapshot_a;
     new_position.fee_growth_snapshot_b = snapshot_b;
 
-    emit!(DlmmLiquidityModified {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmLiquidityModified {
         owner: ctx.accounts.owner.key(),
         pool_address: ctx.accounts.dlmm_pool.key(),
+        sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
         old_position_address: ctx.accounts.old_position.key(),
         new_position_address: ctx.accounts.new_position.key(),
         liquidity_to_move,
@@ -207,12 +216,13 @@ pub fn handle_dlmm_modify_liquidity<'info>(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmModifyLiquidity<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    #[account(mut)]
+    #[account(mut, constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive)]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 
     #[account(
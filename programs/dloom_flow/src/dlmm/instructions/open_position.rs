@@ -3,7 +3,7 @@
 use crate::{
     constants::MAX_BINS_PER_POSITION,
     errors::DloomError,
-    events::DlmmPositionOpened,
+    events::{emit_event, DlmmPositionOpened},
     dlmm::{state::{DlmmPool, Position}},
 };
 use anchor_lang::prelude::*;
@@ -114,8 +114,9 @@ pub fn handle_dlmm_open_position(
     )
     .invoke()?;
 
-    emit!(DlmmPositionOpened {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmPositionOpened {
         pool_address: ctx.accounts.dlmm_pool.key(),
+        sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
         owner: ctx.accounts.owner.key(),
         position_address: ctx.accounts.position.key(),
         position_mint: ctx.accounts.position_mint.key(),
@@ -126,12 +127,14 @@ pub fn handle_dlmm_open_position(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmOpenPosition<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    // Use the DlmmPool struct
+    // Use the DlmmPool struct. Mutable for its event sequence.
+    #[account(mut, constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive)]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 
     #[account(
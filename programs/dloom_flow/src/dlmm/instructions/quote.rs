@@ -0,0 +1,69 @@
+// FILE: programs/dloom_flow/src/dlmm/instructions/quote.rs
+
+use crate::{
+    dlmm::{math, state::DlmmPool},
+    errors::DloomError,
+    token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// The result of `quote_dlmm_swap`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DlmmSwapQuote {
+    /// The input the swap is priced on, after any Token-2022 transfer fee.
+    pub net_amount_in: u64,
+    /// What the trader would receive, after any Token-2022 transfer fee.
+    pub amount_out: u64,
+    pub protocol_fee: u64,
+    pub lp_fee: u64,
+    pub bins_crossed: u32,
+    pub final_active_bin_id: i32,
+}
+
+/// The handler for the `quote_dlmm_swap` instruction.
+///
+/// Walks the bins exactly as `dlmm_swap` would, without moving tokens or writing to the
+/// pool or its bins, and returns the result as the instruction's return data. The bins
+/// to walk are passed as remaining accounts; no `TransactionBins` account is needed.
+pub fn handle_quote_dlmm_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, QuoteDlmmSwap<'info>>,
+    amount_in: u64,
+    is_a_to_b: bool,
+) -> Result<DlmmSwapQuote> {
+    let dlmm_pool = &ctx.accounts.dlmm_pool;
+    let (source_mint, destination_mint) = if is_a_to_b {
+        (&ctx.accounts.token_a_mint, &ctx.accounts.token_b_mint)
+    } else {
+        (&ctx.accounts.token_b_mint, &ctx.accounts.token_a_mint)
+    };
+
+    let net_amount_in = token_extensions::calculate_net_received(source_mint, amount_in)?;
+    require!(net_amount_in > 0, DloomError::ZeroAmount);
+    let (amount_out, protocol_fee, lp_fee, final_active_bin_id) = math::quote_swap(
+        dlmm_pool,
+        net_amount_in,
+        is_a_to_b,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        &dlmm_pool.key(),
+    )?;
+
+    Ok(DlmmSwapQuote {
+        net_amount_in,
+        amount_out: token_extensions::calculate_net_received(destination_mint, amount_out)?,
+        protocol_fee,
+        lp_fee,
+        bins_crossed: final_active_bin_id.abs_diff(dlmm_pool.active_bin_id),
+        final_active_bin_id,
+    })
+}
+
+#[derive(Accounts)]
+pub struct QuoteDlmmSwap<'info> {
+    pub dlmm_pool: Box<Account<'info, DlmmPool>>,
+    #[account(address = dlmm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = dlmm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+}
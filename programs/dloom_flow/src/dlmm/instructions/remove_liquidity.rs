@@ -7,10 +7,12 @@ use crate::{
         state::{Bin, DlmmPool, Position}, 
     },
     state::{TransactionBins},
-    events::DlmmLiquidityUpdate 
+    events::{emit_event, DlmmLiquidityUpdate},
+    native_sol::{NativeSol, WSOL_SEED},
+    token_extensions,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 use std::collections::HashMap; 
 
 pub fn handle_dlmm_remove_liquidity<'info>(
@@ -89,12 +91,17 @@ pub fn handle_dlmm_remove_liquidity<'info>(
     let total_withdrawal_b = (principal_b as u64)
         .checked_add(total_fees_b)
         .ok_or(DloomError::MathOverflow)?;
+    // Slippage is checked against what the user receives after any transfer fees.
+    let net_withdrawal_a =
+        token_extensions::calculate_net_received(&ctx.accounts.token_a_mint, total_withdrawal_a)?;
+    let net_withdrawal_b =
+        token_extensions::calculate_net_received(&ctx.accounts.token_b_mint, total_withdrawal_b)?;
     require!(
-        total_withdrawal_a >= min_amount_a,
+        net_withdrawal_a >= min_amount_a,
         DloomError::SlippageExceeded
     );
     require!(
-        total_withdrawal_b >= min_amount_b,
+        net_withdrawal_b >= min_amount_b,
         DloomError::SlippageExceeded
     );
 
@@ -109,13 +116,33 @@ pub fn handle_dlmm_remove_liquidity<'info>(
         bump,
     ][..];
 
+    // Either side may be paid out in native SOL, which is unwrapped once both transfers land.
+    let native_sol = NativeSol::new(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        ctx.accounts.system_program.as_ref().map(|a| a.to_account_info()),
+    );
+    let user_token_a_account = native_sol.resolve(
+        ctx.accounts.user_token_a_account.as_ref(),
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program.to_account_info(),
+        0,
+    )?;
+    let user_token_b_account = native_sol.resolve(
+        ctx.accounts.user_token_b_account.as_ref(),
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program.to_account_info(),
+        0,
+    )?;
+
     if total_withdrawal_a > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_a_program.to_account_info(),
                 TransferChecked {
                     from: ctx.accounts.token_a_vault.to_account_info(),
-                    to: ctx.accounts.user_token_a_account.to_account_info(),
+                    to: user_token_a_account,
                     authority: ctx.accounts.dlmm_pool.to_account_info(),
                     mint: ctx.accounts.token_a_mint.to_account_info(),
                 },
@@ -123,15 +150,16 @@ pub fn handle_dlmm_remove_liquidity<'info>(
             ),
             total_withdrawal_a,
             ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
     if total_withdrawal_b > 0 {
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_b_program.to_account_info(),
                 TransferChecked {
                     from: ctx.accounts.token_b_vault.to_account_info(),
-                    to: ctx.accounts.user_token_b_account.to_account_info(),
+                    to: user_token_b_account,
                     authority: ctx.accounts.dlmm_pool.to_account_info(),
                     mint: ctx.accounts.token_b_mint.to_account_info(),
                 },
@@ -139,8 +167,10 @@ pub fn handle_dlmm_remove_liquidity<'info>(
             ),
             total_withdrawal_b,
             ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
         )?;
     }
+    native_sol.close()?;
 
     // --- Update State ---
     let dlmm_pool = &mut ctx.accounts.dlmm_pool;
@@ -165,7 +195,9 @@ pub fn handle_dlmm_remove_liquidity<'info>(
     position.fee_growth_snapshot_a = final_fee_growth_a;
     position.fee_growth_snapshot_b = final_fee_growth_b;
 
-    emit!(DlmmLiquidityUpdate {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmLiquidityUpdate {
+    pool_address: ctx.accounts.dlmm_pool.key(),
+    sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
     position_address: ctx.accounts.position.key(),
     liquidity_added: -(liquidity_to_remove as i128), // This is a removal
     amount_a: total_withdrawal_a,
@@ -175,6 +207,7 @@ pub fn handle_dlmm_remove_liquidity<'info>(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmRemoveLiquidity<'info> {
     #[account(mut)]
@@ -188,7 +221,8 @@ pub struct DlmmRemoveLiquidity<'info> {
             dlmm_pool.token_b_mint.as_ref(),
             &dlmm_pool.bin_step.to_le_bytes()
         ],
-        bump = dlmm_pool.bump
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive
     )]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 
@@ -211,11 +245,18 @@ pub struct DlmmRemoveLiquidity<'info> {
     #[account(address = dlmm_pool.token_b_mint)]
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 
+    /// Omit to receive the native SOL side of the pool as lamports.
     #[account(mut, token::mint = dlmm_pool.token_a_mint, has_one = owner)]
-    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_a_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Omit to receive the native SOL side of the pool as lamports.
     #[account(mut, token::mint = dlmm_pool.token_b_mint, has_one = owner)]
-    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_b_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, owner.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
 
     #[account(mut, address = dlmm_pool.token_a_vault)]
     pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
@@ -225,4 +266,5 @@ pub struct DlmmRemoveLiquidity<'info> {
 
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Option<Program<'info, System>>,
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+// FILE: programs/dloom_flow/src/dlmm/instructions/skim_reserves.rs
+
+use crate::{
+    dlmm::state::DlmmPool, errors::DloomError, events::{emit_event, DlmmSurplusSkimmed}, token_extensions,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// The handler for the permissionless `dlmm_skim_reserves` instruction.
+///
+/// Sends any vault balance above the tracked reserves to the pool's protocol fee vaults,
+/// leaving the reserves untouched. The destination is fixed, so anyone may call it.
+pub fn handle_dlmm_skim_reserves<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DlmmSkimReserves<'info>>,
+) -> Result<()> {
+    let dlmm_pool = &ctx.accounts.dlmm_pool;
+    let surplus_a = ctx
+        .accounts
+        .token_a_vault
+        .amount
+        .saturating_sub(dlmm_pool.reserves_a);
+    let surplus_b = ctx
+        .accounts
+        .token_b_vault
+        .amount
+        .saturating_sub(dlmm_pool.reserves_b);
+
+    let bin_step_bytes = &dlmm_pool.bin_step.to_le_bytes()[..];
+    let bump = &[dlmm_pool.bump][..];
+    let signer_seeds = &[
+        b"dlmm_pool",
+        dlmm_pool.token_a_mint.as_ref(),
+        dlmm_pool.token_b_mint.as_ref(),
+        bin_step_bytes,
+        bump,
+    ][..];
+
+    if surplus_a > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_vault.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault_a.to_account_info(),
+                    authority: dlmm_pool.to_account_info(),
+                    mint: ctx.accounts.token_a_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            surplus_a,
+            ctx.accounts.token_a_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    if surplus_b > 0 {
+        token_extensions::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_vault.to_account_info(),
+                    to: ctx.accounts.protocol_fee_vault_b.to_account_info(),
+                    authority: dlmm_pool.to_account_info(),
+                    mint: ctx.accounts.token_b_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            surplus_b,
+            ctx.accounts.token_b_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    let dlmm_pool = &mut ctx.accounts.dlmm_pool;
+    emit_event!(ctx.accounts, ctx.bumps, DlmmSurplusSkimmed {
+        pool_address: dlmm_pool.key(),
+        sequence: dlmm_pool.next_event_sequence(),
+        reserves_a: dlmm_pool.reserves_a,
+        reserves_b: dlmm_pool.reserves_b,
+        surplus_a,
+        surplus_b,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DlmmSkimReserves<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"dlmm_pool",
+            dlmm_pool.token_a_mint.as_ref(),
+            dlmm_pool.token_b_mint.as_ref(),
+            &dlmm_pool.bin_step.to_le_bytes()
+        ],
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive
+    )]
+    pub dlmm_pool: Box<Account<'info, DlmmPool>>,
+
+    #[account(address = dlmm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = dlmm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = dlmm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = dlmm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = dlmm_pool.protocol_fee_vault_a)]
+    pub protocol_fee_vault_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = dlmm_pool.protocol_fee_vault_b)]
+    pub protocol_fee_vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
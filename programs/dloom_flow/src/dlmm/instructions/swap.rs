@@ -3,12 +3,14 @@ use crate::{
     constants::*,
     dlmm::{math, state::DlmmPool},
     errors::DloomError,
-    events::DlmmSwapResult, // Added TransactionBins
+    events::{emit_event, DlmmSwapResult}, // Added TransactionBins
+    native_sol::{is_native_mint, NativeSol, WSOL_SEED},
     state::TransactionBins,
+    token_extensions,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::AccountDeserialize;
-use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 
 // Updated function signature and logic
 pub fn handle_dlmm_swap<'info>(
@@ -16,6 +18,15 @@ pub fn handle_dlmm_swap<'info>(
     amount_in: u64,
     min_amount_out: u64,
 ) -> Result<()> {
+    let source_mint_key = match &ctx.accounts.user_source_token_account {
+        Some(user_source_token_account) => user_source_token_account.mint,
+        // An omitted source account pays in native SOL, so the input is the native side.
+        None if is_native_mint(&ctx.accounts.dlmm_pool.token_a_mint) => {
+            ctx.accounts.dlmm_pool.token_a_mint
+        }
+        None => ctx.accounts.dlmm_pool.token_b_mint,
+    };
+
     if let Some(referrer_account_info) = &ctx.accounts.referrer_fee_account {
         // Manually deserialize the account data.
         let data = referrer_account_info.try_borrow_data()?;
@@ -23,12 +34,12 @@ pub fn handle_dlmm_swap<'info>(
 
         // Now, perform the constraint check on the deserialized account
         require!(
-            referrer_token_account.mint == ctx.accounts.user_source_token_account.mint,
+            referrer_token_account.mint == source_mint_key,
             DloomError::InvalidMint
         );
     }
     let dlmm_pool = &ctx.accounts.dlmm_pool;
-    let is_a_to_b = ctx.accounts.user_source_token_account.mint == dlmm_pool.token_a_mint;
+    let is_a_to_b = source_mint_key == dlmm_pool.token_a_mint;
     let initial_active_bin_id = dlmm_pool.active_bin_id;
 
     let (source_token_program, destination_token_program) = if is_a_to_b {
@@ -43,12 +54,22 @@ pub fn handle_dlmm_swap<'info>(
         )
     };
 
+    let (source_mint, destination_mint) = if is_a_to_b {
+        (&ctx.accounts.token_a_mint, &ctx.accounts.token_b_mint)
+    } else {
+        (&ctx.accounts.token_b_mint, &ctx.accounts.token_a_mint)
+    };
+    // Token-2022 transfer fees are withheld from what the vault receives, so the bins
+    // only see the net input.
+    let net_amount_in = token_extensions::calculate_net_received(source_mint, amount_in)?;
+    require!(net_amount_in > 0, DloomError::ZeroAmount);
+
     // 1. Calculate swap results. We now pass the transaction_bins account and the
     // remaining_accounts directly to the math functions, which will handle validation.
-    let (amount_out, protocol_fee, final_active_bin_id) = if is_a_to_b {
+    let (amount_out, protocol_fee, lp_fee, final_active_bin_id) = if is_a_to_b {
         math::swap_a_to_b(
             &ctx.accounts.dlmm_pool, // Pass directly from the context
-            amount_in,
+            net_amount_in,
             &ctx.accounts.transaction_bins,
             ctx.remaining_accounts,
             ctx.program_id,
@@ -57,14 +78,16 @@ pub fn handle_dlmm_swap<'info>(
     } else {
         math::swap_b_to_a(
             &ctx.accounts.dlmm_pool, // Pass directly from the context
-            amount_in,
+            net_amount_in,
             &ctx.accounts.transaction_bins,
             ctx.remaining_accounts,
             ctx.program_id,
             &ctx.accounts.dlmm_pool.key(), // Pass directly from the context
         )?
     };
-    require!(amount_out >= min_amount_out, DloomError::SlippageExceeded);
+    // Slippage is checked against what the trader actually receives.
+    let net_amount_out = token_extensions::calculate_net_received(destination_mint, amount_out)?;
+    require!(net_amount_out >= min_amount_out, DloomError::SlippageExceeded);
 
     // 2. Transfer from user to the appropriate source vault. (Logic preserved)
     let source_mint_decimals = if is_a_to_b {
@@ -83,11 +106,23 @@ pub fn handle_dlmm_swap<'info>(
             ctx.accounts.token_b_mint.to_account_info(),
         )
     };
-    token_interface::transfer_checked(
+    let native_sol = NativeSol::new(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.wsol_account.as_ref().map(|a| a.to_account_info()),
+        ctx.bumps.wsol_account,
+        ctx.accounts.system_program.as_ref().map(|a| a.to_account_info()),
+    );
+    let user_source_token_account = native_sol.resolve(
+        ctx.accounts.user_source_token_account.as_ref(),
+        source_mint,
+        &source_token_program,
+        amount_in,
+    )?;
+    token_extensions::transfer_checked(
         CpiContext::new(
             source_token_program.clone(),
             TransferChecked {
-                from: ctx.accounts.user_source_token_account.to_account_info(),
+                from: user_source_token_account,
                 to: dest_vault,
                 authority: ctx.accounts.owner.to_account_info(),
                 mint: source_mint_info,
@@ -95,6 +130,7 @@ pub fn handle_dlmm_swap<'info>(
         ),
         amount_in,
         source_mint_decimals,
+        ctx.remaining_accounts,
     )?;
 
     // 3. Prepare signer seeds for all subsequent PDA-controlled transfers. (Logic preserved)
@@ -136,7 +172,7 @@ pub fn handle_dlmm_swap<'info>(
                     )
                 };
 
-                token_interface::transfer_checked(
+                token_extensions::transfer_checked(
                     CpiContext::new_with_signer(
                         source_token_program.clone(),
                         TransferChecked {
@@ -149,6 +185,7 @@ pub fn handle_dlmm_swap<'info>(
                     ),
                     referral_fee,
                     source_mint_decimals,
+                    ctx.remaining_accounts,
                 )?;
             }
         }
@@ -168,7 +205,7 @@ pub fn handle_dlmm_swap<'info>(
                 ctx.accounts.token_b_mint.to_account_info(),
             )
         };
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 source_token_program.clone(),
                 TransferChecked {
@@ -181,10 +218,17 @@ pub fn handle_dlmm_swap<'info>(
             ),
             actual_protocol_fee,
             source_mint_decimals,
+            ctx.remaining_accounts,
         )?;
     }
 
-    // 5. Transfer swapped amount to user. (Logic preserved)
+    // 5. Transfer swapped amount to user, unwrapping it if they take native SOL.
+    let user_destination_token_account = native_sol.resolve(
+        ctx.accounts.user_destination_token_account.as_ref(),
+        destination_mint,
+        &destination_token_program,
+        0,
+    )?;
     if amount_out > 0 {
         let (source_vault, mint, dest_mint_decimals) = if is_a_to_b {
             (
@@ -199,15 +243,12 @@ pub fn handle_dlmm_swap<'info>(
                 ctx.accounts.token_a_mint.decimals,
             )
         };
-        token_interface::transfer_checked(
+        token_extensions::transfer_checked(
             CpiContext::new_with_signer(
                 destination_token_program.clone(),
                 TransferChecked {
                     from: source_vault,
-                    to: ctx
-                        .accounts
-                        .user_destination_token_account
-                        .to_account_info(),
+                    to: user_destination_token_account,
                     authority: dlmm_pool.to_account_info(),
                     mint,
                 },
@@ -215,8 +256,10 @@ pub fn handle_dlmm_swap<'info>(
             ),
             amount_out,
             dest_mint_decimals,
+            ctx.remaining_accounts,
         )?;
     }
+    native_sol.close()?;
 
     // 6. Update DLMM pool state, now including the new volatility accumulator.
     let dlmm_pool_mut = &mut ctx.accounts.dlmm_pool;
@@ -229,7 +272,7 @@ pub fn handle_dlmm_swap<'info>(
         .checked_add(bins_crossed as u64)
         .ok_or(DloomError::MathOverflow)?;
 
-    let amount_for_lps = amount_in
+    let amount_for_lps = net_amount_in
         .checked_sub(protocol_fee)
         .ok_or(DloomError::MathOverflow)?;
     if is_a_to_b {
@@ -254,16 +297,36 @@ pub fn handle_dlmm_swap<'info>(
 
     // ... (rest of the function logic) ...
 
+    let now = Clock::get()?.unix_timestamp;
+    let ui_price_a_before = token_extensions::calculate_ui_price(
+        math::get_price_at_bin(initial_active_bin_id, ctx.accounts.dlmm_pool.bin_step)?,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        now,
+    )?;
+    let ui_price_a = token_extensions::calculate_ui_price(
+        math::get_price_at_bin(final_active_bin_id, ctx.accounts.dlmm_pool.bin_step)?,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_b_mint,
+        now,
+    )?;
+
     // Replace the old emit! with this new one at the end
-    emit!(DlmmSwapResult {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmSwapResult {
         pool_address: ctx.accounts.dlmm_pool.key(),
+        sequence: ctx.accounts.dlmm_pool.next_event_sequence(),
         trader: ctx.accounts.owner.key(),
-        input_mint: ctx.accounts.user_source_token_account.mint,
-        output_mint: ctx.accounts.user_destination_token_account.mint,
+        input_mint: source_mint.key(),
+        output_mint: destination_mint.key(),
         amount_in,
         amount_out,
         protocol_fee: actual_protocol_fee, // Use the final protocol fee after referral split
+        lp_fee,
+        bins_crossed: final_active_bin_id.abs_diff(initial_active_bin_id),
+        initial_active_bin_id,
         final_active_bin_id,
+        ui_price_a_before,
+        ui_price_a,
         referrer: ctx
             .accounts
             .referrer_fee_account
@@ -275,6 +338,7 @@ pub fn handle_dlmm_swap<'info>(
 }
 
 // Updated Accounts struct
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct DlmmSwap<'info> {
     #[account(mut)]
@@ -288,7 +352,8 @@ pub struct DlmmSwap<'info> {
             dlmm_pool.token_b_mint.as_ref(),
             &dlmm_pool.bin_step.to_le_bytes()
         ],
-        bump = dlmm_pool.bump
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive
     )]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 
@@ -309,11 +374,18 @@ pub struct DlmmSwap<'info> {
     #[account(address = dlmm_pool.token_b_mint)]
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 
+    /// Omit to pay in native SOL when the input is the pool's native mint.
     #[account(mut, has_one = owner)]
-    pub user_source_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_source_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Omit to receive native SOL when the output is the pool's native mint.
     #[account(mut, has_one = owner)]
-    pub user_destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The temporary wrapped SOL account, created and closed within the instruction.
+    /// Only needed when a user token account is omitted.
+    #[account(mut, seeds = [WSOL_SEED, owner.key().as_ref()], bump)]
+    pub wsol_account: Option<UncheckedAccount<'info>>,
 
     #[account(mut, address = dlmm_pool.token_a_vault)]
     pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
@@ -333,4 +405,5 @@ pub struct DlmmSwap<'info> {
 
     pub token_a_program: Interface<'info, TokenInterface>,
     pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Option<Program<'info, System>>,
 }
@@ -0,0 +1,53 @@
+// FILE: programs/dloom_flow/src/dlmm/instructions/sync_reserves.rs
+
+use crate::{dlmm::state::DlmmPool, errors::DloomError, events::{emit_event, DlmmReservesSynced}};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+/// The handler for the permissionless `dlmm_sync_reserves` instruction.
+///
+/// Overwrites the pool's tracked reserves with the actual vault balances. DLMM prices
+/// come from the active bin rather than the reserves, so only the accounting changes.
+pub fn handle_dlmm_sync_reserves(ctx: Context<DlmmSyncReserves>) -> Result<()> {
+    let vault_a_balance = ctx.accounts.token_a_vault.amount;
+    let vault_b_balance = ctx.accounts.token_b_vault.amount;
+
+    let dlmm_pool = &mut ctx.accounts.dlmm_pool;
+    let old_reserves_a = dlmm_pool.reserves_a;
+    let old_reserves_b = dlmm_pool.reserves_b;
+    dlmm_pool.reserves_a = vault_a_balance;
+    dlmm_pool.reserves_b = vault_b_balance;
+
+    emit_event!(ctx.accounts, ctx.bumps, DlmmReservesSynced {
+        pool_address: dlmm_pool.key(),
+        sequence: dlmm_pool.next_event_sequence(),
+        old_reserves_a,
+        old_reserves_b,
+        new_reserves_a: vault_a_balance,
+        new_reserves_b: vault_b_balance,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DlmmSyncReserves<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"dlmm_pool",
+            dlmm_pool.token_a_mint.as_ref(),
+            dlmm_pool.token_b_mint.as_ref(),
+            &dlmm_pool.bin_step.to_le_bytes()
+        ],
+        bump = dlmm_pool.bump,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive
+    )]
+    pub dlmm_pool: Box<Account<'info, DlmmPool>>,
+
+    #[account(address = dlmm_pool.token_a_vault)]
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = dlmm_pool.token_b_vault)]
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
+}
@@ -259,38 +259,44 @@ pub fn swap_a_to_b<'info>(
     bin_account_infos: &'info [AccountInfo<'info>],
     program_id: &Pubkey,
     pool_key: &Pubkey,
-) -> Result<(u64, u64, i32)> {
+) -> Result<(u64, u64, u64, i32)> {
     // 1. Validate that the provided accounts match the cached list of bin pubkeys.
     let validated_bins = get_validated_bin_map(transaction_bins, bin_account_infos)?;
 
+    walk_a_to_b(
+        pool,
+        amount_in,
+        transaction_bins.bins.len(),
+        &mut AccountBins::new(&validated_bins, program_id, pool_key, true),
+    )
+}
+
+/// Walks at most `max_bins` bins for a Token A -> Token B swap, starting at the pool's
+/// active bin. Each bin is read from `bins` and written back to it once it has been
+/// swapped against.
+///
+/// Returns `(amount_out, protocol_fee, lp_fee, final_active_bin_id)`, where `lp_fee` is
+/// the part of the fee credited to the bins' liquidity providers.
+pub fn walk_a_to_b<B: BinSource>(
+    pool: &DlmmPool,
+    amount_in: u64,
+    max_bins: usize,
+    bins: &mut B,
+) -> Result<(u64, u64, u64, i32)> {
     let mut amount_remaining_in = amount_in as u128;
     let mut total_amount_out: u128 = 0;
     let mut total_protocol_fee: u128 = 0;
+    let mut total_lp_fee: u128 = 0;
     let mut current_bin_id = pool.active_bin_id;
 
     // 2. Iterate through the bins in the expected swap order.
-    for _ in 0..transaction_bins.bins.len() {
+    for _ in 0..max_bins {
         if amount_remaining_in == 0 {
             break;
         }
 
-        // 3. Find the PDA for the current price bin we are processing.
-        let (expected_pda, _) = Pubkey::find_program_address(
-            &[b"bin", pool_key.as_ref(), &current_bin_id.to_le_bytes()],
-            program_id,
-        );
-
-        // 4. Safely retrieve the validated account info for this PDA.
-        let bin_info = validated_bins
-            .get(&expected_pda)
-            .ok_or(DloomError::BinCacheMismatch)?;
-
-        if *bin_info.owner != *program_id {
-            return err!(DloomError::InvalidBinAccount);
-        }
-
-        let bin_loader = AccountLoader::<'_, Bin>::try_from(bin_info)?;
-        let mut bin = bin_loader.load_mut()?;
+        // 3. Load the current price bin we are processing.
+        let mut bin = bins.load(current_bin_id)?;
         let price = get_price_at_bin(current_bin_id, pool.bin_step)?;
 
         let available_b_in_bin = bin
@@ -347,6 +353,9 @@ pub fn swap_a_to_b<'info>(
                     .ok_or(DloomError::MathOverflow)?
                     .checked_div(bin.liquidity)
                     .ok_or(DloomError::MathOverflow)?;
+                total_lp_fee = total_lp_fee
+                    .checked_add(lp_fee_for_chunk)
+                    .ok_or(DloomError::MathOverflow)?;
                 bin.fee_growth_per_unit_b = bin
                     .fee_growth_per_unit_b
                     .checked_add(fee_growth_update)
@@ -365,6 +374,8 @@ pub fn swap_a_to_b<'info>(
                 .ok_or(DloomError::MathOverflow)?;
         }
 
+        bins.store(current_bin_id, &bin)?;
+
         // 6. Move to the next bin in the swap direction.
         current_bin_id = current_bin_id.checked_sub(1).ok_or(DloomError::MathOverflow)?;
     }
@@ -379,6 +390,7 @@ pub fn swap_a_to_b<'info>(
     Ok((
         total_amount_out as u64,
         total_protocol_fee as u64,
+        total_lp_fee as u64,
         current_bin_id,
     ))
 }
@@ -391,38 +403,41 @@ pub fn swap_b_to_a<'info>(
     bin_account_infos: &'info [AccountInfo<'info>],
     program_id: &Pubkey,
     pool_key: &Pubkey,
-) -> Result<(u64, u64, i32)> {
+) -> Result<(u64, u64, u64, i32)> {
     // 1. Validate that the provided accounts match the cached list of bin pubkeys.
     let validated_bins = get_validated_bin_map(transaction_bins, bin_account_infos)?;
 
+    walk_b_to_a(
+        pool,
+        amount_in,
+        transaction_bins.bins.len(),
+        &mut AccountBins::new(&validated_bins, program_id, pool_key, true),
+    )
+}
+
+/// Walks at most `max_bins` bins for a Token B -> Token A swap, starting at the pool's
+/// active bin. Each bin is read from `bins` and written back to it once it has been
+/// swapped against. Returns the same as [`walk_a_to_b`].
+pub fn walk_b_to_a<B: BinSource>(
+    pool: &DlmmPool,
+    amount_in: u64,
+    max_bins: usize,
+    bins: &mut B,
+) -> Result<(u64, u64, u64, i32)> {
     let mut amount_remaining_in = amount_in as u128;
     let mut total_amount_out: u128 = 0;
     let mut total_protocol_fee: u128 = 0;
+    let mut total_lp_fee: u128 = 0;
     let mut current_bin_id = pool.active_bin_id;
 
     // 2. Iterate through the bins in the expected swap order.
-    for _ in 0..transaction_bins.bins.len() {
+    for _ in 0..max_bins {
         if amount_remaining_in == 0 {
             break;
         }
 
-        // 3. Find the PDA for the current price bin we are processing.
-        let (expected_pda, _) = Pubkey::find_program_address(
-            &[b"bin", pool_key.as_ref(), &current_bin_id.to_le_bytes()],
-            program_id,
-        );
-
-        // 4. Safely retrieve the validated account info for this PDA.
-        let bin_info = validated_bins
-            .get(&expected_pda)
-            .ok_or(DloomError::BinCacheMismatch)?;
-
-        if *bin_info.owner != *program_id {
-            return err!(DloomError::InvalidBinAccount);
-        }
-
-        let bin_loader = AccountLoader::<'_, Bin>::try_from(bin_info)?;
-        let mut bin = bin_loader.load_mut()?;
+        // 3. Load the current price bin we are processing.
+        let mut bin = bins.load(current_bin_id)?;
         let price = get_price_at_bin(current_bin_id, pool.bin_step)?;
 
         let available_a_in_bin = bin.liquidity;
@@ -474,6 +489,9 @@ pub fn swap_b_to_a<'info>(
                     .ok_or(DloomError::MathOverflow)?
                     .checked_div(bin.liquidity)
                     .ok_or(DloomError::MathOverflow)?;
+                total_lp_fee = total_lp_fee
+                    .checked_add(lp_fee_for_chunk)
+                    .ok_or(DloomError::MathOverflow)?;
                 bin.fee_growth_per_unit_a = bin
                     .fee_growth_per_unit_a
                     .checked_add(fee_growth_update)
@@ -492,6 +510,8 @@ pub fn swap_b_to_a<'info>(
                 .ok_or(DloomError::MathOverflow)?;
         }
 
+        bins.store(current_bin_id, &bin)?;
+
         // 6. Move to the next bin in the swap direction.
         current_bin_id = current_bin_id.checked_add(1).ok_or(DloomError::MathOverflow)?;
     }
@@ -506,6 +526,113 @@ pub fn swap_b_to_a<'info>(
     Ok((
         total_amount_out as u64,
         total_protocol_fee as u64,
+        total_lp_fee as u64,
         current_bin_id,
     ))
 }
+
+/// Prices a swap against the pool's bins without changing them. `bin_account_infos` are
+/// the bins to walk, in any order; the walk fails if it runs past them.
+///
+/// Returns `(amount_out, protocol_fee, lp_fee, final_active_bin_id)`, as the swap would.
+pub fn quote_swap<'info>(
+    pool: &DlmmPool,
+    amount_in: u64,
+    is_a_to_b: bool,
+    bin_account_infos: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    pool_key: &Pubkey,
+) -> Result<(u64, u64, u64, i32)> {
+    let bins: HashMap<Pubkey, &'info AccountInfo<'info>> = bin_account_infos
+        .iter()
+        .map(|bin_info| (bin_info.key(), bin_info))
+        .collect();
+    simulate_swap(
+        pool,
+        amount_in,
+        is_a_to_b,
+        bin_account_infos.len(),
+        &mut AccountBins::new(&bins, program_id, pool_key, false),
+    )
+}
+
+/// Walks the bins for a swap in either direction; see [`walk_a_to_b`] and [`walk_b_to_a`].
+pub fn simulate_swap<B: BinSource>(
+    pool: &DlmmPool,
+    amount_in: u64,
+    is_a_to_b: bool,
+    max_bins: usize,
+    bins: &mut B,
+) -> Result<(u64, u64, u64, i32)> {
+    if is_a_to_b {
+        walk_a_to_b(pool, amount_in, max_bins, bins)
+    } else {
+        walk_b_to_a(pool, amount_in, max_bins, bins)
+    }
+}
+
+/// Where a bin walk reads its bins from and writes them back to. On-chain the bins are
+/// program accounts; off-chain they can be snapshots of those accounts.
+pub trait BinSource {
+    /// Returns a copy of the bin with `bin_id`, or `BinCacheMismatch` if it is unavailable.
+    fn load(&mut self, bin_id: i32) -> Result<Bin>;
+    /// Writes back the bin most recently returned by `load`.
+    fn store(&mut self, bin_id: i32, bin: &Bin) -> Result<()>;
+}
+
+/// A pool's bins as program accounts, keyed by their PDA. Writes only reach the accounts
+/// when `commit` is set.
+struct AccountBins<'a, 'info> {
+    bins: &'a HashMap<Pubkey, &'info AccountInfo<'info>>,
+    program_id: &'a Pubkey,
+    pool_key: &'a Pubkey,
+    commit: bool,
+    current: Option<&'info AccountInfo<'info>>,
+}
+
+impl<'a, 'info> AccountBins<'a, 'info> {
+    fn new(
+        bins: &'a HashMap<Pubkey, &'info AccountInfo<'info>>,
+        program_id: &'a Pubkey,
+        pool_key: &'a Pubkey,
+        commit: bool,
+    ) -> Self {
+        Self {
+            bins,
+            program_id,
+            pool_key,
+            commit,
+            current: None,
+        }
+    }
+}
+
+impl BinSource for AccountBins<'_, '_> {
+    fn load(&mut self, bin_id: i32) -> Result<Bin> {
+        // Find the PDA for the bin and safely retrieve its validated account info.
+        let (expected_pda, _) = Pubkey::find_program_address(
+            &[b"bin", self.pool_key.as_ref(), &bin_id.to_le_bytes()],
+            self.program_id,
+        );
+        let bin_info = *self
+            .bins
+            .get(&expected_pda)
+            .ok_or(DloomError::BinCacheMismatch)?;
+
+        if *bin_info.owner != *self.program_id {
+            return err!(DloomError::InvalidBinAccount);
+        }
+
+        let bin = *AccountLoader::<'_, Bin>::try_from(bin_info)?.load()?;
+        self.current = Some(bin_info);
+        Ok(bin)
+    }
+
+    fn store(&mut self, _bin_id: i32, bin: &Bin) -> Result<()> {
+        if self.commit {
+            let bin_info = self.current.ok_or(DloomError::BinCacheMismatch)?;
+            *AccountLoader::<'_, Bin>::try_from(bin_info)?.load_mut()? = *bin;
+        }
+        Ok(())
+    }
+}
@@ -52,4 +52,22 @@ pub struct DlmmPool {
     // --- State Tracking ---
     pub reserves_a: u64,
     pub reserves_b: u64,
+
+    // --- Flash Loans ---
+    /// Set while a flash loan's callback runs, so the callback cannot trade against or
+    /// otherwise touch the pool's vaults until the loan is repaid.
+    pub flash_loan_active: bool,
+
+    // --- Events ---
+    /// The sequence number of the latest event about this pool. Each event about it
+    /// carries the next number, so an indexer can tell when it has missed one.
+    pub event_sequence: u64,
+}
+
+impl DlmmPool {
+    /// Advances the event sequence and returns the number for the event being emitted.
+    pub fn next_event_sequence(&mut self) -> u64 {
+        self.event_sequence += 1;
+        self.event_sequence
+    }
 }
\ No newline at end of file
@@ -54,4 +54,60 @@ pub enum DloomError {
     FeeShareExceedsTotal,
     #[msg("The trader cannot be the referrer.")]
     ReferrerIsTrader,
+    #[msg("The amplification coefficient is out of range or changes too quickly.")]
+    InvalidAmplification,
+    #[msg("The amplification ramp must end at least the minimum ramp duration from now.")]
+    InvalidRampSchedule,
+    #[msg("This operation is not supported by the pool's curve type.")]
+    InvalidCurveType,
+    #[msg("The StableSwap invariant calculation did not converge.")]
+    InvariantNotConverged,
+    #[msg("Pool weights must each be at least the minimum weight and sum to 100%.")]
+    InvalidWeights,
+    #[msg("The weight schedule must start in the future and end after it starts.")]
+    InvalidWeightSchedule,
+    #[msg("Swaps are currently disabled for this pool.")]
+    SwapsDisabled,
+    #[msg("The liquidity bootstrapping sale has not ended yet.")]
+    LaunchNotEnded,
+    #[msg("The bonding curve parameters are invalid.")]
+    InvalidBondingCurveParameters,
+    #[msg("The bonding curve is not open for trading.")]
+    CurveNotTrading,
+    #[msg("The bonding curve has not reached its graduation threshold.")]
+    CurveNotComplete,
+    #[msg("The initial bin does not contain the bonding curve's final price.")]
+    GraduationPriceMismatch,
+    #[msg("The first deposit into a pool is too small to lock the minimum liquidity.")]
+    InsufficientInitialLiquidity,
+    #[msg("The mint has a Token-2022 extension that is not allowed unless the mint is allowlisted.")]
+    MintExtensionNotAllowed,
+    #[msg("The mint allowlist is full.")]
+    MintAllowlistFull,
+    #[msg("A user token account may only be omitted for the native SOL side of the pool.")]
+    NativeMintRequired,
+    #[msg("The temporary wrapped SOL account is missing or has already been used.")]
+    InvalidNativeSolAccount,
+    #[msg("The pool is locked while a flash loan is outstanding.")]
+    FlashLoanActive,
+    #[msg("The flash loan was not repaid with its fee.")]
+    FlashLoanNotRepaid,
+    #[msg("The flash loan callback program is not allowed.")]
+    InvalidFlashLoanProgram,
+    #[msg("The flash swap's callback did not pay the swap input.")]
+    FlashSwapNotPaid,
+    #[msg("The route is empty, too long, or its hops are not chained through the trader's accounts.")]
+    InvalidRoute,
+    #[msg("The split is empty, has too many legs, or its legs trade different pairs or accounts.")]
+    InvalidSplit,
+    #[msg("The pool's oracle account must be passed once it has one.")]
+    OracleAccountRequired,
+    #[msg("An oracle's cardinality must grow, and may not exceed the maximum.")]
+    InvalidOracleCardinality,
+    #[msg("The oracle query is empty, too long, or asks about the future.")]
+    InvalidOracleQuery,
+    #[msg("The oracle has no observation that old.")]
+    OracleObservationTooOld,
+    #[msg("The active bin has no liquidity to pay the flash loan fee to.")]
+    FlashLoanNoActiveLiquidity,
 }
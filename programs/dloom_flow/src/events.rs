@@ -1,28 +1,135 @@
 // FILE: programs/dloom_flow/src/events.rs
 
 use anchor_lang::prelude::*;
-use crate::{ParameterAction, ParameterList};
+use crate::{
+    amm::state::{CurveType, FeePreference},
+    state::DlmmParameter,
+    ParameterAction, ParameterList,
+};
+
+/// Emits an event. With the `event-cpi` feature it goes through Anchor's `emit_cpi!`: a
+/// self-CPI signed by the event authority, which indexers read from the transaction's
+/// inner instructions and which, unlike the program log, cannot be truncated. Otherwise
+/// it is written to the program log with `emit!`.
+///
+/// `$accounts` and `$bumps` are the instruction's accounts and bumps; with `event-cpi`,
+/// the accounts struct carries the `event_authority` added by `#[event_cpi]`.
+macro_rules! emit_event {
+    ($accounts:expr, $bumps:expr, $event:expr $(,)?) => {{
+        #[cfg(feature = "event-cpi")]
+        {
+            // The event is built first, as it may read accounts that are still mutably
+            // borrowed.
+            let event = $event;
+            let ctx = $crate::events::EventCpiContext {
+                accounts: &$accounts,
+                bumps: &$bumps,
+            };
+            anchor_lang::prelude::emit_cpi!(event);
+        }
+        #[cfg(not(feature = "event-cpi"))]
+        anchor_lang::prelude::emit!($event);
+    }};
+}
+pub(crate) use emit_event;
+
+/// The `ctx` that `emit_cpi!` reads the event authority and its bump from.
+#[cfg(feature = "event-cpi")]
+pub struct EventCpiContext<'a, A, B> {
+    pub accounts: &'a A,
+    pub bumps: &'a B,
+}
+
+// Events about a pool or bonding curve carry its next `sequence` number; see
+// `AmmPool::event_sequence`.
+
+// --- Protocol Events ---
+
+#[event]
+pub struct ProtocolInitialized {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct DlmmParametersInitialized {
+    pub authority: Pubkey,
+    pub official_parameters: Vec<DlmmParameter>,
+    pub community_parameters: Vec<DlmmParameter>,
+}
 
 // --- AMM Events ---
 
 #[event]
 pub struct AmmPoolCreated {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub token_a_mint: Pubkey,
     pub token_b_mint: Pubkey,
     pub lp_mint: Pubkey,
+    pub fee_tier: u16,
     pub fee_rate: u16,
+    pub curve_type: CurveType,
 }
 
 #[event]
 pub struct AmmFeesUpdated {
     pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub old_fee_rate: u16,
     pub new_fee_rate: u16,
+    /// Whether the rate was derived from recent volatility rather than set by the authority.
+    pub is_automatic: bool,
+}
+
+#[event]
+pub struct AmmAmpRampStarted {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_timestamp: i64,
+    pub ramp_end_timestamp: i64,
+}
+
+#[event]
+pub struct AmmLbpSwapStatusUpdated {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub swaps_enabled: bool,
+}
+
+#[event]
+pub struct AmmLbpProceedsWithdrawn {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub launch_owner: Pubkey,
+    pub lp_tokens_burned: u64,
+    pub amount_a_received: u64,
+    pub amount_b_received: u64,
+}
+
+#[event]
+pub struct AmmPositionOpened {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub owner: Pubkey,
+    pub position_address: Pubkey,
+    pub fee_preference: FeePreference,
+}
+
+#[event]
+pub struct AmmFeePreferenceUpdated {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub owner: Pubkey,
+    pub position_address: Pubkey,
+    pub fee_preference: FeePreference,
 }
 
 #[event]
 pub struct AmmLiquidityAdded {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub user: Pubkey,
     pub lp_tokens_minted: u64,
     pub amount_a_deposited: u64,
@@ -32,6 +139,7 @@ pub struct AmmLiquidityAdded {
 #[event]
 pub struct AmmLiquidityRemoved {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub user: Pubkey,
     pub lp_tokens_burned: u64,
     pub amount_a_received: u64,
@@ -41,6 +149,7 @@ pub struct AmmLiquidityRemoved {
 #[event]
 pub struct AmmSwap {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub trader: Pubkey,
     pub input_mint: Pubkey,
     pub output_mint: Pubkey,
@@ -48,23 +157,87 @@ pub struct AmmSwap {
     pub amount_out: u64,
     pub protocol_fee: u64,
     pub lp_fee: u64,
+    /// The post-trade price of token A in terms of token B, in UI amounts, scaled by 1e9.
+    pub ui_price_a: u128,
     pub referrer: Option<Pubkey>,
+    /// The pool's reserves after the trade.
+    pub reserves_a: u64,
+    pub reserves_b: u64,
 }
 
 #[event]
 pub struct AmmFeesClaimed {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub user: Pubkey,
     pub fees_claimed_a: u64,
     pub fees_claimed_b: u64,
 }
 
+#[event]
+pub struct AmmLpFeesReinvested {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub user: Pubkey,
+    pub fees_reinvested_a: u64,
+    pub fees_reinvested_b: u64,
+    pub lp_tokens_minted: u64,
+}
+
+#[event]
+pub struct AmmReservesSynced {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub old_reserves_a: u64,
+    pub old_reserves_b: u64,
+    pub new_reserves_a: u64,
+    pub new_reserves_b: u64,
+}
+
+#[event]
+pub struct AmmSurplusSkimmed {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub reserves_a: u64,
+    pub reserves_b: u64,
+    pub surplus_a: u64,
+    pub surplus_b: u64,
+}
+
+#[event]
+pub struct AmmFlashLoanRepaid {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub borrower: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+}
+
+#[event]
+pub struct AmmOracleInitialized {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub oracle_address: Pubkey,
+    pub cardinality: u16,
+}
+
+#[event]
+pub struct AmmOracleGrown {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub old_cardinality_next: u16,
+    pub new_cardinality_next: u16,
+}
+
 
 // --- DLMM Events ---
 
 #[event]
 pub struct DlmmPoolCreated {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub token_a_mint: Pubkey,
     pub token_b_mint: Pubkey,
     pub bin_step: u16,
@@ -74,12 +247,17 @@ pub struct DlmmPoolCreated {
 #[event]
 pub struct DlmmFeesUpdated {
     pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub old_fee_rate: u16,
     pub new_fee_rate: u16,
+    /// Whether the rate was derived from recent volatility rather than set by the authority.
+    pub is_automatic: bool,
 }
 
 #[event]
 pub struct DlmmPositionOpened {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub owner: Pubkey,
     pub position_address: Pubkey,
     pub position_mint: Pubkey,
@@ -89,6 +267,8 @@ pub struct DlmmPositionOpened {
 
 #[event]
 pub struct DlmmLiquidityUpdate {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
     pub position_address: Pubkey,
     pub liquidity_added: i128, // Can be positive (add) or zero (for modify/remove)
     pub amount_a: u64,
@@ -98,19 +278,31 @@ pub struct DlmmLiquidityUpdate {
 #[event]
 pub struct DlmmSwapResult {
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub trader: Pubkey,
     pub input_mint: Pubkey,
     pub output_mint: Pubkey,
     pub amount_in: u64,
     pub amount_out: u64,
     pub protocol_fee: u64,
-    // Note: LP fee in DLMM is implicitly part of the price improvement. We log the protocol fee.
+    /// The fee credited to the liquidity providers of the bins swapped against.
+    pub lp_fee: u64,
+    pub bins_crossed: u32,
+    pub initial_active_bin_id: i32,
     pub final_active_bin_id: i32,
+    /// The price of token A in terms of token B at the initial active bin, in UI amounts,
+    /// scaled by `PRECISION`.
+    pub ui_price_a_before: u128,
+    /// The price of token A in terms of token B at the final active bin, in UI amounts,
+    /// scaled by `PRECISION`.
+    pub ui_price_a: u128,
     pub referrer: Option<Pubkey>,
 }
 
 #[event]
 pub struct DlmmPositionBurned {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
     pub position_address: Pubkey,
     pub owner: Pubkey,
 }
@@ -123,13 +315,129 @@ pub struct DlmmParametersUpdated {
     pub fee_rate: u16,
 }
 
+#[event]
+pub struct MintAllowlistUpdated {
+    pub action: ParameterAction,
+    pub mint: Pubkey,
+}
+
 #[event]
 pub struct DlmmLiquidityModified {
     pub owner: Pubkey,
     pub pool_address: Pubkey,
+    pub sequence: u64,
     pub old_position_address: Pubkey,
     pub new_position_address: Pubkey,
     pub liquidity_to_move: u128,
     pub surplus_a_out: u64,
     pub surplus_b_out: u64,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct DlmmReservesSynced {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub old_reserves_a: u64,
+    pub old_reserves_b: u64,
+    pub new_reserves_a: u64,
+    pub new_reserves_b: u64,
+}
+
+#[event]
+pub struct DlmmSurplusSkimmed {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub reserves_a: u64,
+    pub reserves_b: u64,
+    pub surplus_a: u64,
+    pub surplus_b: u64,
+}
+
+#[event]
+pub struct DlmmFlashLoanRepaid {
+    pub pool_address: Pubkey,
+    pub sequence: u64,
+    pub borrower: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+    /// The active bin whose liquidity providers earned the fees.
+    pub active_bin_id: i32,
+}
+
+// --- Routing Events ---
+
+#[event]
+pub struct RouteSwapCompleted {
+    pub trader: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    /// What the trader's final destination account received.
+    pub amount_out: u64,
+    /// The pools traded against, in route order.
+    pub pools: Vec<Pubkey>,
+}
+
+#[event]
+pub struct SplitSwapCompleted {
+    pub trader: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// The total input across all legs.
+    pub amount_in: u64,
+    /// The total output the trader's destination account received.
+    pub amount_out: u64,
+    /// The pools traded against, with each leg's input and output at the same index.
+    pub pools: Vec<Pubkey>,
+    pub amounts_in: Vec<u64>,
+    pub amounts_out: Vec<u64>,
+}
+
+// --- Bonding Curve Events ---
+
+#[event]
+pub struct BondingCurveCreated {
+    pub curve_address: Pubkey,
+    pub sequence: u64,
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub virtual_token_reserves: u64,
+    pub virtual_quote_reserves: u64,
+    pub real_token_reserves: u64,
+    pub graduation_quote_threshold: u64,
+}
+
+#[event]
+pub struct BondingCurveTrade {
+    pub curve_address: Pubkey,
+    pub sequence: u64,
+    pub trader: Pubkey,
+    pub is_buy: bool,
+    pub token_amount: u64,
+    pub quote_amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct BondingCurveCompleted {
+    pub curve_address: Pubkey,
+    pub sequence: u64,
+    pub real_token_reserves: u64,
+    pub real_quote_reserves: u64,
+}
+
+#[event]
+pub struct BondingCurveGraduated {
+    pub curve_address: Pubkey,
+    pub sequence: u64,
+    pub pool_address: Pubkey,
+    /// The pool's sequence number for the deposit of the curve's reserves.
+    pub pool_sequence: u64,
+    pub token_amount: u64,
+    pub quote_amount: u64,
+    /// LP tokens locked for AMM pools, or bin liquidity burned for DLMM pools.
+    pub liquidity: u128,
+}
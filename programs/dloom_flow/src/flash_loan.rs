@@ -0,0 +1,60 @@
+// FILE: programs/dloom_flow/src/flash_loan.rs
+
+use crate::{constants::BASIS_POINT_MAX, errors::DloomError};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke,
+    },
+};
+
+/// Returns the fee owed on a flash loan of `amount` at `fee_rate` basis points, rounded
+/// up so that borrowing in small slices never costs less than one large loan.
+pub fn calculate_flash_loan_fee(amount: u64, fee_rate: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_rate as u128)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_add(BASIS_POINT_MAX - 1)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(BASIS_POINT_MAX)
+        .ok_or(DloomError::MathOverflow)?;
+    u64::try_from(fee).map_err(|_| DloomError::MathOverflow.into())
+}
+
+/// Invokes the borrower's callback with `data` as its instruction data.
+///
+/// The callback receives the borrower first, as a signer, followed by every remaining
+/// account of the flash loan instruction in order. The pool never signs the callback,
+/// so the borrower can only repay out of accounts it controls itself.
+pub fn invoke_callback<'info>(
+    borrower_program: &AccountInfo<'info>,
+    borrower: &AccountInfo<'info>,
+    callback_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+) -> Result<()> {
+    require_keys_neq!(*borrower_program.key, crate::ID, DloomError::InvalidFlashLoanProgram);
+
+    let mut accounts = Vec::with_capacity(callback_accounts.len() + 1);
+    accounts.push(AccountMeta::new_readonly(*borrower.key, true));
+    accounts.extend(callback_accounts.iter().map(|account| AccountMeta {
+        pubkey: *account.key,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    }));
+
+    let mut account_infos = Vec::with_capacity(callback_accounts.len() + 2);
+    account_infos.push(borrower.clone());
+    account_infos.extend_from_slice(callback_accounts);
+    account_infos.push(borrower_program.clone());
+
+    invoke(
+        &Instruction {
+            program_id: *borrower_program.key,
+            accounts,
+            data,
+        },
+        &account_infos,
+    )
+    .map_err(Into::into)
+}
@@ -1,6 +1,9 @@
 // FILE: programs/dloom_flow/src/instructions/initialize_dlmm_parameters.rs
 
-use crate::state::{DlmmParameter, DlmmParameters};
+use crate::{
+    events::{emit_event, DlmmParametersInitialized},
+    state::{DlmmParameter, DlmmParameters},
+};
 use anchor_lang::prelude::*;
 
 pub fn handle_initialize_dlmm_parameters(
@@ -14,9 +17,15 @@ pub fn handle_initialize_dlmm_parameters(
     params_account.official_parameters = official_params;
     params_account.community_parameters = community_params;
 
+    emit_event!(ctx.accounts, ctx.bumps, DlmmParametersInitialized {
+        authority: params_account.authority,
+        official_parameters: params_account.official_parameters.clone(),
+        community_parameters: params_account.community_parameters.clone(),
+    });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct InitializeDlmmParameters<'info> {
     #[account(
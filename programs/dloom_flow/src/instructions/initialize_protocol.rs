@@ -1,6 +1,10 @@
 // FILE: programs/dloom_flow/src/instructions/initialize_protocol.rs
 
-use crate::state::ProtocolConfig;
+use crate::{
+    constants::MAX_ALLOWED_MINTS,
+    events::{emit_event, ProtocolInitialized},
+    state::ProtocolConfig,
+};
 use anchor_lang::prelude::*;
 
 /// This instruction should be called only once to initialize the protocol's
@@ -8,15 +12,21 @@ use anchor_lang::prelude::*;
 pub fn handle_initialize_protocol(ctx: Context<InitializeProtocol>) -> Result<()> {
     let config = &mut ctx.accounts.protocol_config;
     config.authority = ctx.accounts.authority.key();
+    config.allowed_mints = Vec::new();
+
+    emit_event!(ctx.accounts, ctx.bumps, ProtocolInitialized {
+        authority: config.authority,
+    });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct InitializeProtocol<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32, 
+        space = 8 + 32 + 4 + (MAX_ALLOWED_MINTS * 32),
         seeds = [b"protocol_config"],
         bump
     )]
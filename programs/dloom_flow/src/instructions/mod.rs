@@ -1,16 +1,24 @@
 // FILE: programs/dloom_flow/src/instructions/mod.rs
 pub mod initialize_dlmm_parameters;
 pub mod initialize_protocol;
+pub mod ramp_amm_amp;
+pub mod route_swap;
 pub mod setup_bins;
+pub mod split_swap;
 pub mod update_amm_fees;
 pub mod update_dlmm_fees;
 pub mod update_dlmm_parameters;
 pub mod update_fee_preference;
+pub mod update_mint_allowlist;
 
 pub use initialize_dlmm_parameters::*;
 pub use initialize_protocol::*;
+pub use ramp_amm_amp::*;
+pub use route_swap::*;
 pub use setup_bins::*;
+pub use split_swap::*;
 pub use update_amm_fees::*;
 pub use update_dlmm_fees::*;
 pub use update_dlmm_parameters::*;
-pub use update_fee_preference::*;
\ No newline at end of file
+pub use update_fee_preference::*;
+pub use update_mint_allowlist::*;
\ No newline at end of file
@@ -0,0 +1,80 @@
+// FILE: programs/dloom_flow/src/instructions/ramp_amm_amp.rs
+
+use crate::{
+    amm::{
+        stable_math,
+        state::{AmmPool, CurveType},
+    },
+    constants::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_AMP_RAMP_DURATION},
+    errors::DloomError,
+    events::{emit_event, AmmAmpRampStarted},
+    state::ProtocolConfig,
+};
+use anchor_lang::prelude::*;
+
+/// Starts a linear ramp of a StableSwap pool's amplification coefficient from its
+/// current value to `target_amp`, ending at `ramp_end_timestamp`.
+///
+/// Passing the current amplification as the target effectively stops an ongoing ramp.
+pub fn handle_ramp_amm_amp(
+    ctx: Context<RampAmmAmp>,
+    target_amp: u64,
+    ramp_end_timestamp: i64,
+) -> Result<()> {
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        (MIN_AMP..=MAX_AMP).contains(&target_amp),
+        DloomError::InvalidAmplification
+    );
+    let earliest_end = now
+        .checked_add(MIN_AMP_RAMP_DURATION)
+        .ok_or(DloomError::MathOverflow)?;
+    require!(
+        ramp_end_timestamp >= earliest_end,
+        DloomError::InvalidRampSchedule
+    );
+
+    // Limit how far A can move in one ramp, in either direction.
+    let current_amp = stable_math::current_amp(amm_pool, now)?;
+    let max_amp = current_amp
+        .checked_mul(MAX_AMP_CHANGE)
+        .ok_or(DloomError::MathOverflow)?;
+    let min_amp = current_amp / MAX_AMP_CHANGE;
+    require!(
+        target_amp <= max_amp && target_amp >= min_amp,
+        DloomError::InvalidAmplification
+    );
+
+    amm_pool.amp_initial = current_amp;
+    amm_pool.amp_target = target_amp;
+    amm_pool.amp_ramp_start_timestamp = now;
+    amm_pool.amp_ramp_end_timestamp = ramp_end_timestamp;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmAmpRampStarted {
+        pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        initial_amp: current_amp,
+        target_amp,
+        ramp_start_timestamp: now,
+        ramp_end_timestamp,
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct RampAmmAmp<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"protocol_config"], bump, has_one = authority)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        mut,
+        constraint = amm_pool.authority == authority.key() @ DloomError::Unauthorized,
+        constraint = amm_pool.curve_type == CurveType::StableSwap @ DloomError::InvalidCurveType,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+}
@@ -0,0 +1,250 @@
+// FILE: programs/dloom_flow/src/instructions/route_swap.rs
+
+use crate::{
+    amm::instructions::swap::{execute_swap, SwapOnAmm, SwapOnAmmBumps},
+    constants::MAX_ROUTE_HOPS,
+    dlmm::instructions::swap::{handle_dlmm_swap, DlmmSwap, DlmmSwapBumps},
+    errors::DloomError,
+    events::{emit_event, RouteSwapCompleted},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use std::collections::BTreeSet;
+
+/// The kind of pool a route hop trades against.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RoutePool {
+    Amm,
+    Dlmm,
+}
+
+/// One hop of a route. Its accounts are read from the instruction's remaining accounts:
+/// first the accounts of `swap_on_amm` or `dlmm_swap` in their usual order (omitted
+/// optional accounts are passed as this program's ID), then `extra_accounts` accounts
+/// for that swap's own remaining accounts, such as bins and transfer-hook accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RouteHop {
+    pub pool: RoutePool,
+    pub extra_accounts: u8,
+}
+
+/// What a single hop did, as seen from the trader's accounts.
+pub(crate) struct HopResult {
+    pub pool_address: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub source_account: Option<Pubkey>,
+    pub destination_account: Pubkey,
+    pub amount_out: u64,
+}
+
+/// The handler for the `route_swap` instruction.
+///
+/// Executes `hops` in order, each exactly as its standalone swap instruction would,
+/// feeding the amount actually received by one hop's destination account into the next
+/// hop. Every hop after the first must spend from the previous hop's destination account,
+/// and only the final output is checked against `min_amount_out`.
+///
+/// DLMM hops share the trader's `TransactionBins` account, which must list the bins of
+/// every DLMM pool on the route and is closed after the last DLMM hop. Each DLMM hop must
+/// pass all of those bins among its extra accounts.
+pub fn handle_route_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    hops: Vec<RouteHop>,
+) -> Result<()> {
+    require!(amount_in > 0, DloomError::ZeroAmount);
+    require!(
+        !hops.is_empty() && hops.len() <= MAX_ROUTE_HOPS,
+        DloomError::InvalidRoute
+    );
+
+    let trader = ctx.accounts.trader.key();
+    let mut accounts: &'info [AccountInfo<'info>] = ctx.remaining_accounts;
+    let mut pools = Vec::with_capacity(hops.len());
+    let mut input_mint = Pubkey::default();
+    let mut output_mint = Pubkey::default();
+    let mut amount_out = amount_in;
+    let mut previous_destination: Option<Pubkey> = None;
+    let last_dlmm_hop = hops.iter().rposition(|hop| hop.pool == RoutePool::Dlmm);
+
+    for (index, hop) in hops.iter().enumerate() {
+        let result = match hop.pool {
+            RoutePool::Amm => execute_amm_hop(
+                ctx.program_id,
+                &mut accounts,
+                hop.extra_accounts,
+                &trader,
+                amount_out,
+            )?,
+            RoutePool::Dlmm => execute_dlmm_hop(
+                ctx.program_id,
+                &mut accounts,
+                hop.extra_accounts,
+                &trader,
+                amount_out,
+                Some(index) == last_dlmm_hop,
+            )?,
+        };
+
+        // Intermediate amounts stay in the trader's accounts, so each hop must spend what
+        // the one before it received.
+        match previous_destination {
+            Some(previous_destination) => require!(
+                result.source_account == Some(previous_destination),
+                DloomError::InvalidRoute
+            ),
+            None => input_mint = result.input_mint,
+        }
+
+        pools.push(result.pool_address);
+        previous_destination = Some(result.destination_account);
+        output_mint = result.output_mint;
+        amount_out = result.amount_out;
+    }
+    require!(accounts.is_empty(), DloomError::InvalidRoute);
+    require!(amount_out >= min_amount_out, DloomError::SlippageExceeded);
+
+    emit_event!(ctx.accounts, ctx.bumps, RouteSwapCompleted {
+        trader,
+        input_mint,
+        output_mint,
+        amount_in,
+        amount_out,
+        pools,
+    });
+
+    Ok(())
+}
+
+/// Splits off the `extra_accounts` that follow a hop's swap accounts.
+fn take_extra_accounts<'info>(
+    accounts: &mut &'info [AccountInfo<'info>],
+    extra_accounts: u8,
+) -> Result<&'info [AccountInfo<'info>]> {
+    let extra_accounts = extra_accounts as usize;
+    require!(accounts.len() >= extra_accounts, DloomError::InvalidRoute);
+    let (extra, rest) = accounts.split_at(extra_accounts);
+    *accounts = rest;
+    Ok(extra)
+}
+
+/// Returns the hop's destination account and its balance before the swap. Every hop
+/// must pay out to a token account so that its output can be measured and chained.
+fn destination_before(
+    destination: &Option<InterfaceAccount<TokenAccount>>,
+) -> Result<(Pubkey, Pubkey, u64)> {
+    let destination = destination.as_ref().ok_or(DloomError::InvalidRoute)?;
+    Ok((destination.key(), destination.mint, destination.amount))
+}
+
+/// Reads a `swap_on_amm` hop from `accounts` and executes it, spending `amount_in`.
+pub(crate) fn execute_amm_hop<'info>(
+    program_id: &Pubkey,
+    accounts: &mut &'info [AccountInfo<'info>],
+    extra_accounts: u8,
+    trader: &Pubkey,
+    amount_in: u64,
+) -> Result<HopResult> {
+    let mut bumps = SwapOnAmmBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut swap = SwapOnAmm::try_accounts(program_id, accounts, &[], &mut bumps, &mut reallocs)?;
+    let extra = take_extra_accounts(accounts, extra_accounts)?;
+    require_keys_eq!(swap.trader.key(), *trader, DloomError::InvalidRoute);
+
+    let (destination_account, output_mint, balance_before) =
+        destination_before(&swap.user_destination_token_account)?;
+    let input_mint = if output_mint == swap.amm_pool.token_a_mint {
+        swap.amm_pool.token_b_mint
+    } else {
+        swap.amm_pool.token_a_mint
+    };
+    let source_account = swap.user_source_token_account.as_ref().map(|a| a.key());
+
+    execute_swap(
+        Context::new(program_id, &mut swap, extra, bumps),
+        amount_in,
+        0,
+        None,
+    )?;
+    swap.exit(program_id)?;
+
+    let destination = swap
+        .user_destination_token_account
+        .as_mut()
+        .ok_or(DloomError::InvalidRoute)?;
+    destination.reload()?;
+    Ok(HopResult {
+        pool_address: swap.amm_pool.key(),
+        input_mint,
+        output_mint,
+        source_account,
+        destination_account,
+        amount_out: destination
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(DloomError::MathOverflow)?,
+    })
+}
+
+/// Reads a `dlmm_swap` hop from `accounts` and executes it, spending `amount_in`. The
+/// trader's `TransactionBins` account is only closed when `close_transaction_bins` is set,
+/// so that later DLMM hops in the same instruction can still use it.
+pub(crate) fn execute_dlmm_hop<'info>(
+    program_id: &Pubkey,
+    accounts: &mut &'info [AccountInfo<'info>],
+    extra_accounts: u8,
+    trader: &Pubkey,
+    amount_in: u64,
+    close_transaction_bins: bool,
+) -> Result<HopResult> {
+    let mut bumps = DlmmSwapBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut swap = DlmmSwap::try_accounts(program_id, accounts, &[], &mut bumps, &mut reallocs)?;
+    let extra = take_extra_accounts(accounts, extra_accounts)?;
+    require_keys_eq!(swap.owner.key(), *trader, DloomError::InvalidRoute);
+
+    let (destination_account, output_mint, balance_before) =
+        destination_before(&swap.user_destination_token_account)?;
+    let input_mint = if output_mint == swap.dlmm_pool.token_a_mint {
+        swap.dlmm_pool.token_b_mint
+    } else {
+        swap.dlmm_pool.token_a_mint
+    };
+    let source_account = swap.user_source_token_account.as_ref().map(|a| a.key());
+
+    handle_dlmm_swap(
+        Context::new(program_id, &mut swap, extra, bumps),
+        amount_in,
+        0,
+    )?;
+    swap.dlmm_pool.exit(program_id)?;
+    if close_transaction_bins {
+        swap.transaction_bins.close(swap.owner.to_account_info())?;
+    }
+
+    let destination = swap
+        .user_destination_token_account
+        .as_mut()
+        .ok_or(DloomError::InvalidRoute)?;
+    destination.reload()?;
+    Ok(HopResult {
+        pool_address: swap.dlmm_pool.key(),
+        input_mint,
+        output_mint,
+        source_account,
+        destination_account,
+        amount_out: destination
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(DloomError::MathOverflow)?,
+    })
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+}
@@ -0,0 +1,111 @@
+// FILE: programs/dloom_flow/src/instructions/split_swap.rs
+
+use crate::{
+    constants::MAX_SPLIT_LEGS,
+    errors::DloomError,
+    events::{emit_event, SplitSwapCompleted},
+    instructions::route_swap::{execute_amm_hop, execute_dlmm_hop, HopResult, RoutePool},
+};
+use anchor_lang::prelude::*;
+
+/// One leg of a split order. Its accounts are laid out as for a `route_swap` hop: the
+/// accounts of `swap_on_amm` or `dlmm_swap`, then `extra_accounts` accounts for that
+/// swap's own remaining accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SplitLeg {
+    pub pool: RoutePool,
+    /// The share of the order's input sent to this leg's pool.
+    pub amount_in: u64,
+    pub extra_accounts: u8,
+}
+
+/// The handler for the `split_swap` instruction.
+///
+/// Splits one order across several pools of the same pair, such as an AMM pool and DLMM
+/// pools of different bin steps, using the input allocations chosen by the client. Every
+/// leg must spend from the same source account and pay out to the same destination
+/// account, and only the total output is checked against `min_amount_out`.
+///
+/// DLMM legs share the trader's `TransactionBins` account, which must list the bins of
+/// every DLMM pool in the split and is closed after the last DLMM leg.
+pub fn handle_split_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplitSwap<'info>>,
+    min_amount_out: u64,
+    legs: Vec<SplitLeg>,
+) -> Result<()> {
+    require!(
+        !legs.is_empty() && legs.len() <= MAX_SPLIT_LEGS,
+        DloomError::InvalidSplit
+    );
+    require!(legs.iter().all(|leg| leg.amount_in > 0), DloomError::ZeroAmount);
+
+    let trader = ctx.accounts.trader.key();
+    let mut accounts: &'info [AccountInfo<'info>] = ctx.remaining_accounts;
+    let last_dlmm_leg = legs.iter().rposition(|leg| leg.pool == RoutePool::Dlmm);
+    let mut results: Vec<HopResult> = Vec::with_capacity(legs.len());
+
+    for (index, leg) in legs.iter().enumerate() {
+        let result = match leg.pool {
+            RoutePool::Amm => execute_amm_hop(
+                ctx.program_id,
+                &mut accounts,
+                leg.extra_accounts,
+                &trader,
+                leg.amount_in,
+            )?,
+            RoutePool::Dlmm => execute_dlmm_hop(
+                ctx.program_id,
+                &mut accounts,
+                leg.extra_accounts,
+                &trader,
+                leg.amount_in,
+                Some(index) == last_dlmm_leg,
+            )?,
+        };
+
+        // Every leg trades the same pair between the same two accounts.
+        if let Some(first) = results.first() {
+            require!(
+                result.input_mint == first.input_mint
+                    && result.output_mint == first.output_mint
+                    && result.source_account == first.source_account
+                    && result.destination_account == first.destination_account,
+                DloomError::InvalidSplit
+            );
+        }
+        results.push(result);
+    }
+    require!(accounts.is_empty(), DloomError::InvalidSplit);
+
+    let mut amount_in: u64 = 0;
+    let mut amount_out: u64 = 0;
+    for (leg, result) in legs.iter().zip(&results) {
+        amount_in = amount_in
+            .checked_add(leg.amount_in)
+            .ok_or(DloomError::MathOverflow)?;
+        amount_out = amount_out
+            .checked_add(result.amount_out)
+            .ok_or(DloomError::MathOverflow)?;
+    }
+    require!(amount_out >= min_amount_out, DloomError::SlippageExceeded);
+
+    emit_event!(ctx.accounts, ctx.bumps, SplitSwapCompleted {
+        trader,
+        input_mint: results[0].input_mint,
+        output_mint: results[0].output_mint,
+        amount_in,
+        amount_out,
+        pools: results.iter().map(|result| result.pool_address).collect(),
+        amounts_in: legs.iter().map(|leg| leg.amount_in).collect(),
+        amounts_out: results.iter().map(|result| result.amount_out).collect(),
+    });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SplitSwap<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+}
@@ -1,13 +1,14 @@
 // FILE: programs/dloom_flow/src/instructions/update_amm_fees.rs
 
 use crate::{
+    amm::state::{AmmOracle, AmmPool},
     constants::BASIS_POINT_MAX,
     errors::DloomError,
-    events::AmmFeesUpdated,
-    state::{ProtocolConfig},
-    amm::{state::{AmmPool}}
+    events::{emit_event, AmmFeesUpdated},
+    state::ProtocolConfig,
 };
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
 
 pub fn handle_update_amm_fees(
     ctx: Context<UpdateAmmFees>,
@@ -15,6 +16,7 @@ pub fn handle_update_amm_fees(
 ) -> Result<()> {
     let amm_pool = &mut ctx.accounts.amm_pool;
     let now = Clock::get()?.unix_timestamp;
+    let old_fee_rate = amm_pool.fee_rate;
 
     if let Some(manual_fee_rate) = new_fee_rate {
         // MANUAL UPDATE: The authority provides a specific new fee rate.
@@ -33,7 +35,12 @@ pub fn handle_update_amm_fees(
         require!(time_elapsed > 3600, DloomError::UpdateNotNeeded);
 
         // Update the oracle to get the latest cumulative price.
-        crate::amm::instructions::swap::update_oracle(amm_pool)?;
+        crate::amm::instructions::swap::update_oracle(
+            amm_pool,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_b_mint,
+            ctx.accounts.amm_oracle.as_deref_mut(),
+        )?;
 
         // Simple metric for volatility: change in cumulative price over time.
         // This now uses the snapshot taken at the last fee update.
@@ -60,19 +67,34 @@ pub fn handle_update_amm_fees(
     amm_pool.last_fee_update_timestamp = now;
     amm_pool.price_a_cumulative_last_fee_update = amm_pool.price_a_cumulative;
 
-    emit!(AmmFeesUpdated {
+    emit_event!(ctx.accounts, ctx.bumps, AmmFeesUpdated {
         pool_address: amm_pool.key(),
+        sequence: amm_pool.next_event_sequence(),
+        old_fee_rate,
         new_fee_rate: amm_pool.fee_rate,
+        is_automatic: new_fee_rate.is_none(),
     });
 
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct UpdateAmmFees<'info> {
     pub authority: Signer<'info>,
     #[account(seeds = [b"protocol_config"], bump, has_one = authority)]
     pub protocol_config: Account<'info, ProtocolConfig>,
-    #[account(mut, constraint = amm_pool.authority == authority.key() @ DloomError::Unauthorized)]
+    #[account(
+        mut,
+        constraint = amm_pool.authority == authority.key() @ DloomError::Unauthorized,
+        constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive,
+    )]
     pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(address = amm_pool.token_a_mint)]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = amm_pool.token_b_mint)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+    /// Required once the pool has an oracle, which records this instruction's update.
+    #[account(mut, seeds = [b"amm_oracle", amm_pool.key().as_ref()], bump = amm_oracle.bump)]
+    pub amm_oracle: Option<Box<Account<'info, AmmOracle>>>,
 }
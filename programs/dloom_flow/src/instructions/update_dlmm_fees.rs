@@ -1,11 +1,12 @@
 // FILE: programs/dloom_flow/src/instructions/update_dlmm_fees.rs
 
-use crate::{errors::DloomError, state::{ProtocolConfig}, dlmm::{state::{DlmmPool}}, events::DlmmFeesUpdated};
+use crate::{errors::DloomError, state::{ProtocolConfig}, dlmm::{state::{DlmmPool}}, events::{emit_event, DlmmFeesUpdated}};
 use anchor_lang::prelude::*;
 
 pub fn handle_update_dlmm_fees(ctx: Context<UpdateDlmmFees>, new_fee_rate: Option<u16>) -> Result<()> {
     let dlmm_pool = &mut ctx.accounts.dlmm_pool;
     let now = Clock::get()?.unix_timestamp;
+    let old_fee_rate = dlmm_pool.fee_rate;
 
     if let Some(manual_fee_rate) = new_fee_rate {
         // MANUAL UPDATE:
@@ -42,13 +43,17 @@ pub fn handle_update_dlmm_fees(ctx: Context<UpdateDlmmFees>, new_fee_rate: Optio
     dlmm_pool.volatility_accumulator = 0;
     dlmm_pool.last_fee_update_timestamp = now;
 
-    emit!(DlmmFeesUpdated {
+    emit_event!(ctx.accounts, ctx.bumps, DlmmFeesUpdated {
     pool_address: dlmm_pool.key(),
+    sequence: dlmm_pool.next_event_sequence(),
+    old_fee_rate,
     new_fee_rate: dlmm_pool.fee_rate,
+    is_automatic: new_fee_rate.is_none(),
 });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct UpdateDlmmFees<'info> {
     /// The authority must sign for any fee update.
@@ -68,6 +73,7 @@ pub struct UpdateDlmmFees<'info> {
         // to be eligible for this type of update.
         constraint = dlmm_pool.authority == authority.key() @ DloomError::Unauthorized,
         constraint = dlmm_pool.pool_type == crate::dlmm::state::PoolType::Official @ DloomError::Unauthorized,
+        constraint = !dlmm_pool.flash_loan_active @ DloomError::FlashLoanActive,
     )]
     pub dlmm_pool: Box<Account<'info, DlmmPool>>,
 }
\ No newline at end of file
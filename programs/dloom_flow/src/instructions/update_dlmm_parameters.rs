@@ -31,7 +31,7 @@ pub fn handle_update_dlmm_parameters(
         }
     }
 
-    emit!(crate::events::DlmmParametersUpdated {
+    crate::events::emit_event!(ctx.accounts, ctx.bumps, crate::events::DlmmParametersUpdated {
     list,
     action,
     bin_step,
@@ -41,6 +41,7 @@ pub fn handle_update_dlmm_parameters(
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct UpdateDlmmParameters<'info> {
     pub authority: Signer<'info>,
@@ -1,6 +1,10 @@
 // FILE: programs/dloom_flow/src/instructions/update_fee_preference.rs
 
-use crate::amm::{state::{AmmPool, AmmPosition, FeePreference}};
+use crate::{
+    amm::state::{AmmPool, AmmPosition, FeePreference},
+    errors::DloomError,
+    events::{emit_event, AmmFeePreferenceUpdated},
+};
 use anchor_lang::prelude::*;
 
 pub fn handle_update_fee_preference(
@@ -8,14 +12,24 @@ pub fn handle_update_fee_preference(
     new_preference: FeePreference,
 ) -> Result<()> {
     ctx.accounts.amm_position.fee_preference = new_preference;
+
+    emit_event!(ctx.accounts, ctx.bumps, AmmFeePreferenceUpdated {
+        pool_address: ctx.accounts.amm_pool.key(),
+        sequence: ctx.accounts.amm_pool.next_event_sequence(),
+        owner: ctx.accounts.owner.key(),
+        position_address: ctx.accounts.amm_position.key(),
+        fee_preference: new_preference,
+    });
     Ok(())
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct UpdateFeePreference<'info> {
     pub owner: Signer<'info>,
 
-    // This is needed just to validate the position PDA seeds
+    // This is needed to validate the position PDA seeds, and carries the event sequence.
+    #[account(mut, constraint = !amm_pool.flash_loan_active @ DloomError::FlashLoanActive)]
     pub amm_pool: Box<Account<'info, AmmPool>>,
 
     #[account(
@@ -0,0 +1,54 @@
+// FILE: programs/dloom_flow/src/instructions/update_mint_allowlist.rs
+
+use crate::{
+    constants::MAX_ALLOWED_MINTS,
+    errors::DloomError,
+    events::{emit_event, MintAllowlistUpdated},
+    state::ProtocolConfig,
+    ParameterAction,
+};
+use anchor_lang::prelude::*;
+
+/// Adds a mint to, or removes it from, the allowlist of Token-2022 mints that may be
+/// pooled despite carrying an extension pool creation otherwise rejects.
+pub fn handle_update_mint_allowlist(
+    ctx: Context<UpdateMintAllowlist>,
+    action: ParameterAction,
+    mint: Pubkey,
+) -> Result<()> {
+    let allowed_mints = &mut ctx.accounts.protocol_config.allowed_mints;
+
+    match action {
+        ParameterAction::Add => {
+            // Prevent duplicates
+            if !allowed_mints.contains(&mint) {
+                require!(
+                    allowed_mints.len() < MAX_ALLOWED_MINTS,
+                    DloomError::MintAllowlistFull
+                );
+                allowed_mints.push(mint);
+            }
+        }
+        ParameterAction::Remove => {
+            allowed_mints.retain(|allowed| *allowed != mint);
+        }
+    }
+
+    emit_event!(ctx.accounts, ctx.bumps, MintAllowlistUpdated { action, mint });
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateMintAllowlist<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump,
+        has_one = authority @ DloomError::Unauthorized
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
@@ -3,23 +3,29 @@
 use anchor_lang::prelude::*;
 
 pub mod amm;
+pub mod bonding_curve;
 pub mod constants;
 pub mod dlmm;
 pub mod errors;
 pub mod events;
+pub mod flash_loan;
 pub mod instructions;
+pub mod native_sol;
 pub mod state;
+pub mod token_extensions;
 
 use crate::state::{DlmmParameter};
 use crate::{
     amm::{
-        state::{FeePreference},
+        state::{AmmObservation, FeePreference},
     },
+    bonding_curve::state::GraduationFees,
 };
 
 use instructions::*; // For protocol-level instructions
 use amm::instructions::*; // For AMM instructions
 use dlmm::instructions::*; // For DLMM instructions
+use bonding_curve::instructions::*; // For bonding-curve launch instructions
 
 declare_id!("8VryDeNca4LCF7ivjQ5mNwMik6ugTtmwfTrg6Qfta23X");
 
@@ -72,6 +78,14 @@ pub mod dloom_flow {
         )
     }
 
+    pub fn update_mint_allowlist(
+        ctx: Context<UpdateMintAllowlist>,
+        action: ParameterAction,
+        mint: Pubkey,
+    ) -> Result<()> {
+        instructions::update_mint_allowlist::handle_update_mint_allowlist(ctx, action, mint)
+    }
+
     pub fn update_fee_preference(
         ctx: Context<UpdateFeePreference>,
         new_preference: FeePreference,
@@ -97,6 +111,72 @@ pub mod dloom_flow {
         )
     }
 
+    pub fn create_stable_amm_pool(
+        ctx: Context<CreateStableAmmPool>,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        amp: u64,
+    ) -> Result<()> {
+        amm::instructions::create_stable_pool::handle_create_stable_amm_pool(
+            ctx,
+            fee_rate,
+            protocol_fee_share,
+            referrer_fee_share,
+            amp,
+        )
+    }
+
+    pub fn create_weighted_amm_pool(
+        ctx: Context<CreateWeightedAmmPool>,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        weight_a: u16,
+    ) -> Result<()> {
+        amm::instructions::create_weighted_pool::handle_create_weighted_amm_pool(
+            ctx,
+            fee_rate,
+            protocol_fee_share,
+            referrer_fee_share,
+            weight_a,
+        )
+    }
+
+    pub fn create_lbp_amm_pool(
+        ctx: Context<CreateLbpAmmPool>,
+        fee_rate: u16,
+        protocol_fee_share: u16,
+        referrer_fee_share: u16,
+        params: LbpParams,
+    ) -> Result<()> {
+        amm::instructions::create_lbp_pool::handle_create_lbp_amm_pool(
+            ctx,
+            fee_rate,
+            protocol_fee_share,
+            referrer_fee_share,
+            params,
+        )
+    }
+
+    pub fn set_lbp_swaps_enabled(ctx: Context<SetLbpSwapsEnabled>, enabled: bool) -> Result<()> {
+        amm::instructions::set_lbp_swaps_enabled::handle_set_lbp_swaps_enabled(ctx, enabled)
+    }
+
+    pub fn withdraw_lbp_proceeds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawLbpProceeds<'info>>,
+    ) -> Result<()> {
+        amm::instructions::withdraw_lbp_proceeds::handle_withdraw_lbp_proceeds(ctx)
+    }
+
+    pub fn ramp_amm_amp(
+        ctx: Context<RampAmmAmp>,
+        target_amp: u64,
+        ramp_end_timestamp: i64,
+    ) -> Result<()> {
+        instructions::ramp_amm_amp::handle_ramp_amm_amp(ctx, target_amp, ramp_end_timestamp)
+    }
+
     pub fn open_amm_position(
         ctx: Context<OpenAmmPosition>,
         fee_preference: FeePreference,
@@ -104,8 +184,8 @@ pub mod dloom_flow {
         amm::instructions::open_position::handle_open_amm_position(ctx, fee_preference)
     }
 
-    pub fn add_amm_liquidity(
-        ctx: Context<AddAmmLiquidity>,
+    pub fn add_amm_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddAmmLiquidity<'info>>,
         amount_a_desired: u64,
         amount_b_desired: u64,
         min_lp_tokens_to_mint: u64,
@@ -118,16 +198,74 @@ pub mod dloom_flow {
         )
     }
 
-    pub fn swap_on_amm(
-        ctx: Context<SwapOnAmm>,
+    pub fn swap_on_amm<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapOnAmm<'info>>,
         amount_in: u64,
         min_amount_out: u64,
     ) -> Result<()> {
         amm::instructions::swap::handle_swap_on_amm(ctx, amount_in, min_amount_out)
     }
 
-    pub fn remove_amm_liquidity(
-        ctx: Context<RemoveAmmLiquidity>,
+    pub fn flash_swap_on_amm<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapOnAmm<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        amm::instructions::flash_swap::handle_flash_swap_on_amm(
+            ctx,
+            amount_in,
+            min_amount_out,
+            callback_data,
+        )
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        hops: Vec<RouteHop>,
+    ) -> Result<()> {
+        instructions::route_swap::handle_route_swap(ctx, amount_in, min_amount_out, hops)
+    }
+
+    pub fn split_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplitSwap<'info>>,
+        min_amount_out: u64,
+        legs: Vec<SplitLeg>,
+    ) -> Result<()> {
+        instructions::split_swap::handle_split_swap(ctx, min_amount_out, legs)
+    }
+
+    pub fn quote_amm_swap(
+        ctx: Context<QuoteAmm>,
+        amount_in: u64,
+        is_a_to_b: bool,
+    ) -> Result<AmmSwapQuote> {
+        amm::instructions::quote::handle_quote_amm_swap(ctx, amount_in, is_a_to_b)
+    }
+
+    pub fn quote_add_liquidity(
+        ctx: Context<QuoteAmm>,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+    ) -> Result<AmmAddLiquidityQuote> {
+        amm::instructions::quote::handle_quote_add_liquidity(
+            ctx,
+            amount_a_desired,
+            amount_b_desired,
+        )
+    }
+
+    pub fn quote_remove_liquidity(
+        ctx: Context<QuoteAmm>,
+        lp_tokens_to_burn: u64,
+    ) -> Result<AmmRemoveLiquidityQuote> {
+        amm::instructions::quote::handle_quote_remove_liquidity(ctx, lp_tokens_to_burn)
+    }
+
+    pub fn remove_amm_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveAmmLiquidity<'info>>,
         lp_tokens_to_burn: u64,
         min_amount_a_to_receive: u64,
         min_amount_b_to_receive: u64,
@@ -140,7 +278,9 @@ pub mod dloom_flow {
         )
     }
 
-    pub fn claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
+    pub fn claim_lp_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimLpFees<'info>>,
+    ) -> Result<()> {
         amm::instructions::claim_lp_fees::handle_claim_lp_fees(ctx)
     }
 
@@ -148,6 +288,40 @@ pub mod dloom_flow {
         amm::instructions::reinvest_lp_fees::handle_reinvest_lp_fees(ctx)
     }
 
+    pub fn sync_amm_reserves(ctx: Context<SyncAmmReserves>) -> Result<()> {
+        amm::instructions::sync_reserves::handle_sync_amm_reserves(ctx)
+    }
+
+    pub fn skim_amm_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SkimAmmReserves<'info>>,
+    ) -> Result<()> {
+        amm::instructions::skim_reserves::handle_skim_amm_reserves(ctx)
+    }
+
+    pub fn amm_flash_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AmmFlashLoan<'info>>,
+        amount_a: u64,
+        amount_b: u64,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        amm::instructions::flash_loan::handle_amm_flash_loan(ctx, amount_a, amount_b, callback_data)
+    }
+
+    pub fn initialize_amm_oracle(ctx: Context<InitializeAmmOracle>, cardinality: u16) -> Result<()> {
+        amm::instructions::initialize_oracle::handle_initialize_amm_oracle(ctx, cardinality)
+    }
+
+    pub fn grow_amm_oracle(ctx: Context<GrowAmmOracle>, cardinality_next: u16) -> Result<()> {
+        amm::instructions::grow_oracle::handle_grow_amm_oracle(ctx, cardinality_next)
+    }
+
+    pub fn observe_amm_oracle(
+        ctx: Context<ObserveAmmOracle>,
+        seconds_ago: Vec<u32>,
+    ) -> Result<Vec<AmmObservation>> {
+        amm::instructions::observe_oracle::handle_observe_amm_oracle(ctx, seconds_ago)
+    }
+
     // --- DLMM Instructions ---
     // FIX: Simplified the Context<> paths from `dlmm::instructions::StructName` to just `StructName`
     pub fn create_dlmm_pool(
@@ -210,6 +384,10 @@ pub mod dloom_flow {
         )
     }
 
+    pub fn setup_bins(ctx: Context<SetupBins>, bin_pubkeys: Vec<Pubkey>) -> Result<()> {
+        instructions::setup_bins::handle_setup_bins(ctx, bin_pubkeys)
+    }
+
     pub fn dlmm_swap<'info>(
         ctx: Context<'_, '_, 'info, 'info, DlmmSwap<'info>>,
         amount_in: u64,
@@ -218,6 +396,14 @@ pub mod dloom_flow {
         dlmm::instructions::swap::handle_dlmm_swap(ctx, amount_in, min_amount_out)
     }
 
+    pub fn quote_dlmm_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, QuoteDlmmSwap<'info>>,
+        amount_in: u64,
+        is_a_to_b: bool,
+    ) -> Result<DlmmSwapQuote> {
+        dlmm::instructions::quote::handle_quote_dlmm_swap(ctx, amount_in, is_a_to_b)
+    }
+
     pub fn dlmm_remove_liquidity<'info>(
         ctx: Context<'_, '_, 'info, 'info, DlmmRemoveLiquidity<'info>>,
         liquidity_to_remove: u128,
@@ -249,4 +435,83 @@ pub mod dloom_flow {
     ) -> Result<()> {
         dlmm::instructions::burn_empty_position::handle_dlmm_burn_empty_position(ctx)
     }
-}
\ No newline at end of file
+
+    pub fn dlmm_sync_reserves(ctx: Context<DlmmSyncReserves>) -> Result<()> {
+        dlmm::instructions::sync_reserves::handle_dlmm_sync_reserves(ctx)
+    }
+
+    pub fn dlmm_skim_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DlmmSkimReserves<'info>>,
+    ) -> Result<()> {
+        dlmm::instructions::skim_reserves::handle_dlmm_skim_reserves(ctx)
+    }
+
+    pub fn dlmm_flash_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DlmmFlashLoan<'info>>,
+        amount_a: u64,
+        amount_b: u64,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        dlmm::instructions::flash_loan::handle_dlmm_flash_loan(
+            ctx,
+            amount_a,
+            amount_b,
+            callback_data,
+        )
+    }
+
+    // --- Bonding Curve Instructions ---
+    pub fn create_bonding_curve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateBondingCurve<'info>>,
+        virtual_token_reserves: u64,
+        virtual_quote_reserves: u64,
+        token_amount: u64,
+        graduation_quote_threshold: u64,
+        fee_rate: u16,
+        graduation_fees: GraduationFees,
+    ) -> Result<()> {
+        bonding_curve::instructions::create_curve::handle_create_bonding_curve(
+            ctx,
+            virtual_token_reserves,
+            virtual_quote_reserves,
+            token_amount,
+            graduation_quote_threshold,
+            fee_rate,
+            graduation_fees,
+        )
+    }
+
+    pub fn buy_on_bonding_curve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TradeOnBondingCurve<'info>>,
+        quote_in: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        bonding_curve::instructions::buy::handle_buy_on_bonding_curve(ctx, quote_in, min_tokens_out)
+    }
+
+    pub fn sell_on_bonding_curve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TradeOnBondingCurve<'info>>,
+        tokens_in: u64,
+        min_quote_out: u64,
+    ) -> Result<()> {
+        bonding_curve::instructions::sell::handle_sell_on_bonding_curve(ctx, tokens_in, min_quote_out)
+    }
+
+    pub fn graduate_bonding_curve_to_amm<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GraduateBondingCurveToAmm<'info>>,
+    ) -> Result<()> {
+        bonding_curve::instructions::graduate_to_amm::handle_graduate_bonding_curve_to_amm(ctx)
+    }
+
+    pub fn graduate_bonding_curve_to_dlmm<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GraduateBondingCurveToDlmm<'info>>,
+        bin_step: u16,
+        initial_bin_id: i32,
+    ) -> Result<()> {
+        bonding_curve::instructions::graduate_to_dlmm::handle_graduate_bonding_curve_to_dlmm(
+            ctx,
+            bin_step,
+            initial_bin_id,
+        )
+    }
+}
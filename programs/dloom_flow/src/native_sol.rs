@@ -0,0 +1,177 @@
+// FILE: programs/dloom_flow/src/native_sol.rs
+
+use crate::errors::DloomError;
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Allocate, Assign, CreateAccount, Transfer},
+};
+use anchor_spl::{
+    token::{spl_token, TokenAccount as SplTokenAccount},
+    token_2022::spl_token_2022,
+    token_interface::{self, CloseAccount, InitializeAccount3, Mint, TokenAccount},
+};
+use std::cell::RefCell;
+
+/// Seed of the temporary wrapped-SOL account, `["wsol", owner]`, that an instruction
+/// creates and closes again when its user pays or is paid in native SOL.
+pub const WSOL_SEED: &[u8] = b"wsol";
+
+/// Returns true for the native mint of either token program.
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+/// The accounts an instruction needs to wrap and unwrap native SOL for its user.
+///
+/// Instructions that accept native SOL take each user token account as optional. When
+/// the account for the native side is omitted, `resolve` wraps the lamports into the
+/// temporary account instead, and `close` unwraps whatever is left back to the user
+/// before the instruction returns.
+pub struct NativeSol<'info> {
+    owner: AccountInfo<'info>,
+    wsol_account: Option<AccountInfo<'info>>,
+    wsol_bump: Option<u8>,
+    system_program: Option<AccountInfo<'info>>,
+    /// The token program of the temporary account, once `resolve` has created it.
+    wrapped_token_program: RefCell<Option<AccountInfo<'info>>>,
+}
+
+impl<'info> NativeSol<'info> {
+    pub fn new(
+        owner: AccountInfo<'info>,
+        wsol_account: Option<AccountInfo<'info>>,
+        wsol_bump: Option<u8>,
+        system_program: Option<AccountInfo<'info>>,
+    ) -> Self {
+        Self {
+            owner,
+            wsol_account,
+            wsol_bump,
+            system_program,
+            wrapped_token_program: RefCell::new(None),
+        }
+    }
+
+    /// Returns the account to move the user's `mint` tokens through.
+    ///
+    /// This is `user_token_account` when it was provided. Otherwise `mint` must be a native
+    /// mint, and the temporary wrapped-SOL account is created, funded with
+    /// `lamports_to_wrap` of the owner's SOL, and returned in its place.
+    pub fn resolve(
+        &self,
+        user_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        mint: &InterfaceAccount<'info, Mint>,
+        token_program: &AccountInfo<'info>,
+        lamports_to_wrap: u64,
+    ) -> Result<AccountInfo<'info>> {
+        if let Some(user_token_account) = user_token_account {
+            return Ok(user_token_account.to_account_info());
+        }
+
+        require!(is_native_mint(&mint.key()), DloomError::NativeMintRequired);
+        require!(
+            self.wrapped_token_program.borrow().is_none(),
+            DloomError::InvalidNativeSolAccount
+        );
+        let (Some(wsol_account), Some(bump), Some(system_program)) = (
+            self.wsol_account.as_ref(),
+            self.wsol_bump,
+            self.system_program.as_ref(),
+        ) else {
+            return err!(DloomError::InvalidNativeSolAccount);
+        };
+
+        let bump = &[bump][..];
+        let wsol_seeds = &[WSOL_SEED, self.owner.key.as_ref(), bump][..];
+        let required_lamports = Rent::get()?
+            .minimum_balance(SplTokenAccount::LEN)
+            .checked_add(lamports_to_wrap)
+            .ok_or(DloomError::MathOverflow)?;
+
+        // Anyone can send lamports to the address, so fall back to funding, allocating and
+        // assigning it separately rather than letting `create_account` fail.
+        let current_lamports = wsol_account.lamports();
+        if current_lamports == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    CreateAccount {
+                        from: self.owner.clone(),
+                        to: wsol_account.clone(),
+                    },
+                    &[wsol_seeds],
+                ),
+                required_lamports,
+                SplTokenAccount::LEN as u64,
+                token_program.key,
+            )?;
+        } else {
+            let top_up = required_lamports.saturating_sub(current_lamports);
+            if top_up > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.clone(),
+                        Transfer {
+                            from: self.owner.clone(),
+                            to: wsol_account.clone(),
+                        },
+                    ),
+                    top_up,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    Allocate {
+                        account_to_allocate: wsol_account.clone(),
+                    },
+                    &[wsol_seeds],
+                ),
+                SplTokenAccount::LEN as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    Assign {
+                        account_to_assign: wsol_account.clone(),
+                    },
+                    &[wsol_seeds],
+                ),
+                token_program.key,
+            )?;
+        }
+
+        // Initializing a native account sets its token balance to the lamports above rent.
+        token_interface::initialize_account3(CpiContext::new(
+            token_program.clone(),
+            InitializeAccount3 {
+                account: wsol_account.clone(),
+                mint: mint.to_account_info(),
+                authority: self.owner.clone(),
+            },
+        ))?;
+        *self.wrapped_token_program.borrow_mut() = Some(token_program.clone());
+
+        Ok(wsol_account.clone())
+    }
+
+    /// Closes the temporary wrapped-SOL account if `resolve` created one, returning its
+    /// remaining balance and rent to the owner as lamports.
+    pub fn close(&self) -> Result<()> {
+        let (Some(token_program), Some(wsol_account)) = (
+            self.wrapped_token_program.borrow_mut().take(),
+            self.wsol_account.as_ref(),
+        ) else {
+            return Ok(());
+        };
+
+        token_interface::close_account(CpiContext::new(
+            token_program,
+            CloseAccount {
+                account: wsol_account.clone(),
+                destination: self.owner.clone(),
+                authority: self.owner.clone(),
+            },
+        ))
+    }
+}
@@ -10,4 +10,7 @@ pub struct ProtocolConfig {
     /// The master authority that can perform admin actions, like creating
     /// official pools or updating protocol-level parameters.
     pub authority: Pubkey,
+    /// Token-2022 mints that may be pooled even though they carry an extension that
+    /// pool creation otherwise rejects, such as a permanent delegate.
+    pub allowed_mints: Vec<Pubkey>,
 }
\ No newline at end of file
@@ -0,0 +1,203 @@
+// FILE: programs/dloom_flow/src/token_extensions.rs
+
+use crate::{
+    constants::{BASIS_POINT_MAX, PRECISION},
+    errors::DloomError,
+    state::ProtocolConfig,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            default_account_state::DefaultAccountState,
+            interest_bearing_mint::InterestBearingConfig, scaled_ui_amount::ScaledUiAmountConfig,
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        onchain,
+        state::AccountState,
+    },
+    token_interface::{Mint, TransferChecked},
+};
+
+/// The year length Token-2022 uses to compound interest-bearing mints.
+const SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+
+/// Rejects mints whose Token-2022 extensions could drain or brick a pool, unless the
+/// protocol authority has allowlisted the mint in `ProtocolConfig`.
+///
+/// A permanent delegate can move tokens out of the pool's vaults, a mint close authority
+/// can close the mint after its supply is burned, and a mint whose accounts are frozen by
+/// default would leave freshly created vaults unusable.
+pub fn validate_mint_extensions(
+    mint: &InterfaceAccount<Mint>,
+    protocol_config: &ProtocolConfig,
+) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID
+        || protocol_config.allowed_mints.contains(mint_info.key)
+    {
+        return Ok(());
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    for extension_type in mint_state.get_extension_types()? {
+        let is_dangerous = match extension_type {
+            ExtensionType::PermanentDelegate | ExtensionType::MintCloseAuthority => true,
+            ExtensionType::DefaultAccountState => {
+                let default_state = mint_state.get_extension::<DefaultAccountState>()?;
+                default_state.state == AccountState::Frozen as u8
+            }
+            _ => false,
+        };
+        require!(!is_dangerous, DloomError::MintExtensionNotAllowed);
+    }
+    Ok(())
+}
+
+/// Returns the fee the token program withholds when `amount` of `mint` is transferred.
+///
+/// Token-2022 withholds transfer fees in the destination account, so the sender is
+/// debited the full `amount` while the recipient is credited `amount - fee`. Mints owned
+/// by the legacy token program, and Token-2022 mints without a `TransferFeeConfig`,
+/// never charge a fee.
+pub fn calculate_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or_else(|| DloomError::MathOverflow.into()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Returns the amount the recipient is credited when `amount` of `mint` is sent.
+pub fn calculate_net_received(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let fee = calculate_transfer_fee(mint, amount)?;
+    amount
+        .checked_sub(fee)
+        .ok_or_else(|| DloomError::MathOverflow.into())
+}
+
+/// Returns the amount that must be sent so the recipient is credited `net_amount`.
+pub fn calculate_gross_to_send(mint: &InterfaceAccount<Mint>, net_amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID || net_amount == 0 {
+        return Ok(net_amount);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, net_amount)
+            .ok_or(DloomError::MathOverflow)?,
+        Err(_) => 0,
+    };
+    net_amount
+        .checked_add(fee)
+        .ok_or_else(|| DloomError::MathOverflow.into())
+}
+
+/// Performs a `transfer_checked` CPI that also works for Token-2022 mints with a transfer hook.
+///
+/// When the mint has a `TransferHook` extension, the hook program, its extra-account-metas
+/// validation account and every account listed there are looked up by key in
+/// `additional_accounts` and forwarded to the token program. Callers pass the instruction's
+/// `remaining_accounts`; accounts the hook does not need (such as DLMM bin arrays) are
+/// ignored, and mints without a hook behave exactly like `token_interface::transfer_checked`.
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+    additional_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        additional_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Returns the factor, scaled by `PRECISION`, that converts a raw amount of `mint` into
+/// the amount wallets display for it at `unix_timestamp`, ignoring decimals.
+///
+/// Interest-bearing mints compound their configured rate on top of the raw amount and
+/// scaled-UI-amount mints apply the issuer's multiplier; every other mint returns exactly
+/// `PRECISION`. Only prices shown to users are adjusted: reserves, invariants and fee
+/// accounting always stay on raw amounts.
+pub fn ui_amount_multiplier(mint: &InterfaceAccount<Mint>, unix_timestamp: i64) -> Result<u128> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(PRECISION);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let mut multiplier = 1f64;
+    if let Ok(config) = mint_state.get_extension::<InterestBearingConfig>() {
+        // Mirrors the token program: the average rate up to the last rate change, then
+        // the current rate since.
+        let last_update_timestamp = i64::from(config.last_update_timestamp);
+        let pre_update_timespan =
+            last_update_timestamp.saturating_sub(i64::from(config.initialization_timestamp));
+        let post_update_timespan = unix_timestamp.saturating_sub(last_update_timestamp);
+        let exponent = (i16::from(config.pre_update_average_rate) as f64
+            * pre_update_timespan as f64
+            + i16::from(config.current_rate) as f64 * post_update_timespan as f64)
+            / SECONDS_PER_YEAR
+            / BASIS_POINT_MAX as f64;
+        multiplier *= exponent.exp();
+    }
+    if let Ok(config) = mint_state.get_extension::<ScaledUiAmountConfig>() {
+        let current_multiplier: f64 =
+            if unix_timestamp >= i64::from(config.new_multiplier_effective_timestamp) {
+                config.new_multiplier.into()
+            } else {
+                config.multiplier.into()
+            };
+        multiplier *= current_multiplier;
+    }
+
+    let scaled = multiplier * PRECISION as f64;
+    require!(
+        scaled.is_finite() && scaled > 0.0 && scaled < u128::MAX as f64,
+        DloomError::MathOverflow
+    );
+    Ok(scaled as u128)
+}
+
+/// Converts a raw price of `base_mint` in `quote_mint` into the price between the
+/// amounts wallets display, keeping the raw price's fixed-point scale.
+pub fn calculate_ui_price(
+    raw_price: u128,
+    base_mint: &InterfaceAccount<Mint>,
+    quote_mint: &InterfaceAccount<Mint>,
+    unix_timestamp: i64,
+) -> Result<u128> {
+    let base_multiplier = ui_amount_multiplier(base_mint, unix_timestamp)?;
+    let quote_multiplier = ui_amount_multiplier(quote_mint, unix_timestamp)?;
+    if base_multiplier == quote_multiplier {
+        return Ok(raw_price);
+    }
+    raw_price
+        .checked_mul(quote_multiplier)
+        .ok_or(DloomError::MathOverflow)?
+        .checked_div(base_multiplier)
+        .ok_or_else(|| DloomError::MathOverflow.into())
+}
@@ -0,0 +1,100 @@
+// FILE: programs/test_transfer_hook/src/lib.rs
+
+//! A minimal Token-2022 transfer hook for the test suite. Its single extra account is a
+//! counter the hook bumps on every transfer, so a test can tell that a transfer made
+//! through `dloom_flow` reached the hook with its extra accounts.
+
+use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+declare_id!("E5Hm8u1puJ1rYQxUWiFxqAvUERtJx7A4jijfgLEzJcXE");
+
+#[program]
+pub mod test_transfer_hook {
+    use super::*;
+
+    /// Writes the mint's extra-account-metas list, naming the transfer counter, and
+    /// creates the counter if no earlier mint has.
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        let mut data = ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_account_metas()?)?;
+        Ok(())
+    }
+
+    /// The hook Token-2022 invokes on every transfer of a hooked mint. It takes the
+    /// interface's `Execute` discriminator in place of Anchor's.
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        ctx.accounts.counter.transfers = ctx.accounts.counter.transfers.saturating_add(1);
+        Ok(())
+    }
+}
+
+/// The accounts the hook needs beyond the transfer's own: the counter PDA.
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[Seed::Literal {
+            bytes: b"counter".to_vec(),
+        }],
+        false,
+        true,
+    )?])
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TransferCounter {
+    pub transfers: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Written by `ExtraAccountMetaList::init`.
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1)?,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// CHECK: Only used as a seed.
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TransferCounter::INIT_SPACE,
+        seeds = [b"counter"],
+        bump
+    )]
+    pub counter: Account<'info, TransferCounter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The accounts of the transfer-hook interface's `Execute` instruction, in its order.
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    /// CHECK: The transfer's source account.
+    pub source: UncheckedAccount<'info>,
+    /// CHECK: The mint being transferred.
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: The transfer's destination account.
+    pub destination: UncheckedAccount<'info>,
+    /// CHECK: The source account's owner or delegate.
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: The mint's extra-account-metas list.
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"counter"], bump)]
+    pub counter: Account<'info, TransferCounter>,
+}
@@ -11,6 +11,7 @@ import {
   AmmFeesClaimed,
   AmmLiquidityRemoved,
 } from "../target/types/dloom";
+import { TestTransferHook } from "../target/types/test_transfer_hook";
 import {
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountInstruction,
@@ -19,7 +20,19 @@ import {
   TOKEN_PROGRAM_ID,
   TOKEN_2022_PROGRAM_ID,
   getAccount,
+  getMint,
+  transferChecked,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferHookInstruction,
+  createInitializeInterestBearingMintInstruction,
+  createInitializeScaledUiAmountConfigInstruction,
+  getInterestBearingMintConfigState,
+  NATIVE_MINT,
 } from "@solana/spl-token";
 import {
   Keypair,
@@ -27,6 +40,7 @@ import {
   PublicKey,
   SystemProgram,
   Transaction,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import { expect } from "chai";
 import chai from "chai";
@@ -56,6 +70,20 @@ describe("dloom_flow AMM Tests", () => {
   const FEE_RATE = 25; // 0.25%
   const PROTOCOL_FEE_SHARE = 2000; // 20%
   const REFERRER_FEE_SHARE = 1000; // 10%
+  const MINIMUM_LIQUIDITY = 1000;
+  // The most slots `initialize_amm_oracle` may allocate, given the 10 KiB CPI create limit.
+  const MAX_INITIAL_ORACLE_CARDINALITY = 254;
+  // The curve-type byte the program seeds each AMM pool's PDA with.
+  const CURVE_TYPE_SEED = {
+    constantProduct: 0,
+    stableSwap: 1,
+    weighted: 2,
+    liquidityBootstrapping: 3,
+  };
+
+  // Protocol configuration
+  let protocolConfigPda: PublicKey;
+  let protocolAuthority: PublicKey;
 
   // Wallets & Keypairs
   const user = loadKeypairFromFile("./target/test-wallets/user.json");
@@ -157,6 +185,30 @@ describe("dloom_flow AMM Tests", () => {
       }
     }
 
+    // Pool creation reads the mint policy from the protocol config singleton,
+    // which another suite may have created already.
+    [protocolConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    const existingConfig = await program.account.protocolConfig.fetchNullable(
+      protocolConfigPda
+    );
+    if (existingConfig) {
+      protocolAuthority = existingConfig.authority;
+    } else {
+      await program.methods
+        .initializeProtocol()
+        .accounts({
+          protocolConfig: protocolConfigPda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      protocolAuthority = user.publicKey;
+    }
+
     // Create mints for all test cases
     [mintA_Token, mintB_Token] = await Promise.all([
       createMintHelper(TOKEN_PROGRAM_ID),
@@ -174,7 +226,13 @@ describe("dloom_flow AMM Tests", () => {
         a.toBuffer().compare(b.toBuffer())
       );
       const [poolPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("amm_pool"), mint1.toBuffer(), mint2.toBuffer()],
+        [
+          Buffer.from("amm_pool"),
+          mint1.toBuffer(),
+          mint2.toBuffer(),
+          new anchor.BN(FEE_RATE).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+        ],
         program.programId
       );
 
@@ -199,12 +257,56 @@ describe("dloom_flow AMM Tests", () => {
       expect(poolAccount.authority.equals(user.publicKey)).to.be.true;
     });
 
+    it("Creates a second pool for the same pair with a different fee tier", async () => {
+      const [mint1, mint2] = [mintA_Token, mintB_Token].sort((a, b) =>
+        a.toBuffer().compare(b.toBuffer())
+      );
+      const HIGH_FEE_TIER = 100; // 1%
+      const [poolPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_pool"),
+          mint1.toBuffer(),
+          mint2.toBuffer(),
+          new anchor.BN(HIGH_FEE_TIER).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createAmmPool(HIGH_FEE_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: mint1,
+          tokenBMint: mint2,
+          ammPool: poolPda,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolAccount = await program.account.ammPool.fetch(poolPda);
+      expect(poolAccount.feeTier).to.equal(HIGH_FEE_TIER);
+      expect(poolAccount.feeRate).to.equal(HIGH_FEE_TIER);
+    });
+
     it("Creates a new AMM pool (Token-2022 / Token-2022)", async () => {
       const [mint1, mint2] = [mintC_T22, mintD_T22].sort((a, b) =>
         a.toBuffer().compare(b.toBuffer())
       );
       const [poolPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("amm_pool"), mint1.toBuffer(), mint2.toBuffer()],
+        [
+          Buffer.from("amm_pool"),
+          mint1.toBuffer(),
+          mint2.toBuffer(),
+          new anchor.BN(FEE_RATE).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+        ],
         program.programId
       );
 
@@ -242,7 +344,13 @@ describe("dloom_flow AMM Tests", () => {
 
       // Set global PDAs
       [ammPoolPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("amm_pool"), mintA.toBuffer(), mintB.toBuffer()],
+        [
+          Buffer.from("amm_pool"),
+          mintA.toBuffer(),
+          mintB.toBuffer(),
+          new anchor.BN(FEE_RATE).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+        ],
         program.programId
       );
       [lpMintPda] = PublicKey.findProgramAddressSync(
@@ -311,7 +419,13 @@ describe("dloom_flow AMM Tests", () => {
 
     it("Fails to create a pool with invalid mint order", async () => {
       const [tempPoolPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("amm_pool"), mintB.toBuffer(), mintA.toBuffer()],
+        [
+          Buffer.from("amm_pool"),
+          mintB.toBuffer(),
+          mintA.toBuffer(),
+          new anchor.BN(FEE_RATE).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+        ],
         program.programId
       );
 
@@ -513,6 +627,7 @@ describe("dloom_flow AMM Tests", () => {
           protocolFeeVaultB: protocolFeeVaultBPda,
           authority: user.publicKey,
           referrerFeeAccount: null,
+          callbackProgram: null,
           tokenAProgram: tokenAProgram,
           tokenBProgram: tokenBProgram,
         })
@@ -551,6 +666,7 @@ describe("dloom_flow AMM Tests", () => {
             protocolFeeVaultB: protocolFeeVaultBPda,
             authority: user.publicKey,
             referrerFeeAccount: null,
+            callbackProgram: null,
             tokenAProgram: tokenAProgram,
             tokenBProgram: tokenBProgram,
           })
@@ -584,6 +700,7 @@ describe("dloom_flow AMM Tests", () => {
           protocolFeeVaultB: protocolFeeVaultBPda,
           authority: user.publicKey,
           referrerFeeAccount: referrerTokenA,
+          callbackProgram: null,
           tokenAProgram: tokenAProgram,
           tokenBProgram: tokenBProgram,
         })
@@ -617,6 +734,7 @@ describe("dloom_flow AMM Tests", () => {
           protocolFeeVaultB: protocolFeeVaultBPda,
           authority: user.publicKey,
           referrerFeeAccount: null,
+          callbackProgram: null,
           tokenAProgram: tokenAProgram,
           tokenBProgram: tokenBProgram,
         })
@@ -770,6 +888,7 @@ describe("dloom_flow AMM Tests", () => {
           protocolFeeVaultB: protocolFeeVaultBPda,
           authority: user.publicKey,
           referrerFeeAccount: null,
+          callbackProgram: null,
           tokenAProgram: tokenAProgram,
           tokenBProgram: tokenBProgram,
         })
@@ -779,6 +898,2676 @@ describe("dloom_flow AMM Tests", () => {
       const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
       expect(poolAfter.priceACumulative.gt(poolBefore.priceACumulative)).to.be
         .true;
+      // Neither mint rescales its UI amount, so the UI-adjusted oracle matches the raw one.
+      expect(
+        poolAfter.uiPriceACumulative
+          .sub(poolBefore.uiPriceACumulative)
+          .eq(poolAfter.priceACumulative.sub(poolBefore.priceACumulative))
+      ).to.be.true;
+    });
+  });
+
+  describe("Stable Swap Pools", () => {
+    const STABLE_FEE_TIER = 4; // 0.04%
+    const AMP = 100;
+
+    let stablePoolPda: PublicKey;
+    let stableLpMintPda: PublicKey;
+    let stableVaultAPda: PublicKey;
+    let stableVaultBPda: PublicKey;
+    let stableFeeVaultAPda: PublicKey;
+    let stableFeeVaultBPda: PublicKey;
+    let stablePositionPda: PublicKey;
+
+    before(async () => {
+      [stablePoolPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_pool"),
+          mintA.toBuffer(),
+          mintB.toBuffer(),
+          new anchor.BN(STABLE_FEE_TIER).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.stableSwap]),
+        ],
+        program.programId
+      );
+      [stableLpMintPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_mint"), stablePoolPda.toBuffer()],
+        program.programId
+      );
+      [stableVaultAPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), stablePoolPda.toBuffer(), mintA.toBuffer()],
+        program.programId
+      );
+      [stableVaultBPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), stablePoolPda.toBuffer(), mintB.toBuffer()],
+        program.programId
+      );
+      [stableFeeVaultAPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          stablePoolPda.toBuffer(),
+          mintA.toBuffer(),
+        ],
+        program.programId
+      );
+      [stableFeeVaultBPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          stablePoolPda.toBuffer(),
+          mintB.toBuffer(),
+        ],
+        program.programId
+      );
+      [stablePositionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_position"),
+          user.publicKey.toBuffer(),
+          stablePoolPda.toBuffer(),
+        ],
+        program.programId
+      );
+    });
+
+    it("Creates a StableSwap pool", async () => {
+      await program.methods
+        .createStableAmmPool(
+          STABLE_FEE_TIER,
+          PROTOCOL_FEE_SHARE,
+          REFERRER_FEE_SHARE,
+          new anchor.BN(AMP)
+        )
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: stablePoolPda,
+          lpMint: stableLpMintPda,
+          tokenAVault: stableVaultAPda,
+          tokenBVault: stableVaultBPda,
+          protocolFeeVaultA: stableFeeVaultAPda,
+          protocolFeeVaultB: stableFeeVaultBPda,
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolAccount = await program.account.ammPool.fetch(stablePoolPda);
+      expect(poolAccount.curveType).to.deep.equal({ stableSwap: {} });
+      expect(poolAccount.ampTarget.toNumber()).to.equal(AMP);
+    });
+
+    it("Swaps near the peg with low slippage", async () => {
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: stablePoolPda,
+          ammPosition: stablePositionPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: stablePoolPda,
+          ammPosition: stablePositionPda,
+          lpMint: stableLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: stableVaultAPda,
+          tokenBVault: stableVaultBPda,
+          userTokenAAccount: userTokenA,
+          userTokenBAccount: userTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            stableLpMintPda,
+            user.publicKey
+          ),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const amountIn = 10 * 10 ** 6;
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+
+      await program.methods
+        .swapOnAmm(new anchor.BN(amountIn), new anchor.BN(1))
+        .accounts({
+          trader: user.publicKey,
+          ammPool: stablePoolPda,
+          lpMint: stableLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userTokenB,
+          tokenAVault: stableVaultAPda,
+          tokenBVault: stableVaultBPda,
+          protocolFeeVaultA: stableFeeVaultAPda,
+          protocolFeeVaultB: stableFeeVaultBPda,
+          authority: user.publicKey,
+          referrerFeeAccount: null,
+          callbackProgram: null,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+        })
+        .signers([user])
+        .rpc();
+
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      const received = Number(userBAfter.amount - userBBefore.amount);
+      // A constant-product pool of the same depth would return ~9.09 tokens.
+      expect(received).to.be.greaterThan(9.9 * 10 ** 6);
+    });
+
+    it("Records TWAP observations in a growable ring buffer", async () => {
+      const [stableOraclePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("amm_oracle"), stablePoolPda.toBuffer()],
+        program.programId
+      );
+      const initializeAccounts = {
+        payer: user.publicKey,
+        ammPool: stablePoolPda,
+        tokenAMint: mintA,
+        tokenBMint: mintB,
+        ammOracle: stableOraclePda,
+        systemProgram: SystemProgram.programId,
+      };
+      // Anything past the CPI create limit has to be reached by growing the oracle.
+      await expect(
+        program.methods
+          .initializeAmmOracle(MAX_INITIAL_ORACLE_CARDINALITY + 1)
+          .accounts(initializeAccounts)
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/InvalidOracleCardinality/);
+      await program.methods
+        .initializeAmmOracle(2)
+        .accounts(initializeAccounts)
+        .signers([user])
+        .rpc();
+      await program.methods
+        .growAmmOracle(4)
+        .accounts({
+          payer: user.publicKey,
+          ammPool: stablePoolPda,
+          ammOracle: stableOraclePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      let oracle = await program.account.ammOracle.fetch(stableOraclePda);
+      expect(oracle.cardinality).to.equal(1);
+      expect(oracle.cardinalityNext).to.equal(4);
+      expect(oracle.observations.length).to.equal(4);
+
+      const swapAccounts = (ammOracle: PublicKey | null) => ({
+        trader: user.publicKey,
+        ammPool: stablePoolPda,
+        lpMint: stableLpMintPda,
+        tokenAMint: mintA,
+        tokenBMint: mintB,
+        userSourceTokenAccount: userTokenA,
+        userDestinationTokenAccount: userTokenB,
+        tokenAVault: stableVaultAPda,
+        tokenBVault: stableVaultBPda,
+        protocolFeeVaultA: stableFeeVaultAPda,
+        protocolFeeVaultB: stableFeeVaultBPda,
+        authority: user.publicKey,
+        referrerFeeAccount: null,
+        callbackProgram: null,
+        tokenAProgram: tokenAProgram,
+        tokenBProgram: tokenBProgram,
+        ammOracle,
+      });
+
+      // Once the pool has an oracle, no update may skip it.
+      await expect(
+        program.methods
+          .swapOnAmm(new anchor.BN(10 ** 6), new anchor.BN(1))
+          .accounts(swapAccounts(null))
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/OracleAccountRequired/);
+
+      await sleep(2000);
+      await program.methods
+        .swapOnAmm(new anchor.BN(10 ** 6), new anchor.BN(1))
+        .accounts(swapAccounts(stableOraclePda))
+        .signers([user])
+        .rpc();
+
+      const pool = await program.account.ammPool.fetch(stablePoolPda);
+      oracle = await program.account.ammOracle.fetch(stableOraclePda);
+      expect(oracle.cardinality).to.equal(4);
+      expect(oracle.index).to.equal(1);
+      const latest = oracle.observations[1];
+      expect(latest.timestamp.eq(pool.lastUpdateTimestamp)).to.be.true;
+      expect(latest.priceACumulative.eq(pool.priceACumulative)).to.be.true;
+
+      const [now, then] = await program.methods
+        .observeAmmOracle([0, 1])
+        .accounts({ ammPool: stablePoolPda, ammOracle: stableOraclePda })
+        .view();
+      expect(now.priceACumulative.gte(latest.priceACumulative)).to.be.true;
+      expect(then.priceACumulative.lte(now.priceACumulative)).to.be.true;
+
+      await expect(
+        program.methods
+          .observeAmmOracle([86_400])
+          .accounts({ ammPool: stablePoolPda, ammOracle: stableOraclePda })
+          .view()
+      ).to.be.rejectedWith(/OracleObservationTooOld/);
+    });
+  });
+
+  describe("Weighted Pools", () => {
+    const WEIGHTED_FEE_TIER = 30; // 0.30%
+    const WEIGHT_A = 8000; // 80/20 pool
+
+    let weightedPoolPda: PublicKey;
+    let weightedLpMintPda: PublicKey;
+    let weightedVaultAPda: PublicKey;
+    let weightedVaultBPda: PublicKey;
+    let weightedFeeVaultAPda: PublicKey;
+    let weightedFeeVaultBPda: PublicKey;
+    let weightedPositionPda: PublicKey;
+
+    before(async () => {
+      [weightedPoolPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_pool"),
+          mintA.toBuffer(),
+          mintB.toBuffer(),
+          new anchor.BN(WEIGHTED_FEE_TIER).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.weighted]),
+        ],
+        program.programId
+      );
+      [weightedLpMintPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_mint"), weightedPoolPda.toBuffer()],
+        program.programId
+      );
+      [weightedVaultAPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          weightedPoolPda.toBuffer(),
+          mintA.toBuffer(),
+        ],
+        program.programId
+      );
+      [weightedVaultBPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          weightedPoolPda.toBuffer(),
+          mintB.toBuffer(),
+        ],
+        program.programId
+      );
+      [weightedFeeVaultAPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          weightedPoolPda.toBuffer(),
+          mintA.toBuffer(),
+        ],
+        program.programId
+      );
+      [weightedFeeVaultBPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          weightedPoolPda.toBuffer(),
+          mintB.toBuffer(),
+        ],
+        program.programId
+      );
+      [weightedPositionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_position"),
+          user.publicKey.toBuffer(),
+          weightedPoolPda.toBuffer(),
+        ],
+        program.programId
+      );
+    });
+
+    it("Creates an 80/20 weighted pool", async () => {
+      await program.methods
+        .createWeightedAmmPool(
+          WEIGHTED_FEE_TIER,
+          PROTOCOL_FEE_SHARE,
+          REFERRER_FEE_SHARE,
+          WEIGHT_A
+        )
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: weightedPoolPda,
+          lpMint: weightedLpMintPda,
+          tokenAVault: weightedVaultAPda,
+          tokenBVault: weightedVaultBPda,
+          protocolFeeVaultA: weightedFeeVaultAPda,
+          protocolFeeVaultB: weightedFeeVaultBPda,
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolAccount = await program.account.ammPool.fetch(
+        weightedPoolPda
+      );
+      expect(poolAccount.curveType).to.deep.equal({ weighted: {} });
+      expect(poolAccount.weightA).to.equal(WEIGHT_A);
+      expect(poolAccount.weightB).to.equal(10000 - WEIGHT_A);
+    });
+
+    it("Swaps against the weighted invariant", async () => {
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: weightedPoolPda,
+          ammPosition: weightedPositionPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .addAmmLiquidity(
+          // 80/20 value split at a 1:1 price.
+          new anchor.BN(80 * 10 ** 6),
+          new anchor.BN(20 * 10 ** 6),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: weightedPoolPda,
+          ammPosition: weightedPositionPda,
+          lpMint: weightedLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: weightedVaultAPda,
+          tokenBVault: weightedVaultBPda,
+          userTokenAAccount: userTokenA,
+          userTokenBAccount: userTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            weightedLpMintPda,
+            user.publicKey
+          ),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const amountIn = 1 * 10 ** 6;
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+
+      await program.methods
+        .swapOnAmm(new anchor.BN(amountIn), new anchor.BN(1))
+        .accounts({
+          trader: user.publicKey,
+          ammPool: weightedPoolPda,
+          lpMint: weightedLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userTokenB,
+          tokenAVault: weightedVaultAPda,
+          tokenBVault: weightedVaultBPda,
+          protocolFeeVaultA: weightedFeeVaultAPda,
+          protocolFeeVaultB: weightedFeeVaultBPda,
+          authority: user.publicKey,
+          referrerFeeAccount: null,
+          callbackProgram: null,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+        })
+        .signers([user])
+        .rpc();
+
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      const received = Number(userBAfter.amount - userBBefore.amount);
+      // out = 20 * (1 - (80 / (80 + 0.997))^4) ~= 0.967 tokens.
+      expect(received).to.be.greaterThan(0.96 * 10 ** 6);
+      expect(received).to.be.lessThan(0.97 * 10 ** 6);
+    });
+  });
+
+  describe("Liquidity Bootstrapping Pools", () => {
+    const LBP_FEE_TIER = 50; // 0.50%
+    const START_WEIGHT_A = 9500; // 95/5 at launch
+    const END_WEIGHT_A = 5000; // 50/50 at the end
+
+    let lbpPoolPda: PublicKey;
+    let lbpLpMintPda: PublicKey;
+    let lbpVaultAPda: PublicKey;
+    let lbpVaultBPda: PublicKey;
+    let lbpFeeVaultAPda: PublicKey;
+    let lbpFeeVaultBPda: PublicKey;
+    let lbpPositionPda: PublicKey;
+    let saleEnd: number;
+
+    before(async () => {
+      [lbpPoolPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_pool"),
+          mintA.toBuffer(),
+          mintB.toBuffer(),
+          new anchor.BN(LBP_FEE_TIER).toBuffer("le", 2),
+          Buffer.from([CURVE_TYPE_SEED.liquidityBootstrapping]),
+        ],
+        program.programId
+      );
+      [lbpLpMintPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_mint"), lbpPoolPda.toBuffer()],
+        program.programId
+      );
+      [lbpVaultAPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          lbpPoolPda.toBuffer(),
+          mintA.toBuffer(),
+        ],
+        program.programId
+      );
+      [lbpVaultBPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          lbpPoolPda.toBuffer(),
+          mintB.toBuffer(),
+        ],
+        program.programId
+      );
+      [lbpFeeVaultAPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          lbpPoolPda.toBuffer(),
+          mintA.toBuffer(),
+        ],
+        program.programId
+      );
+      [lbpFeeVaultBPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("protocol_fee_vault"),
+          lbpPoolPda.toBuffer(),
+          mintB.toBuffer(),
+        ],
+        program.programId
+      );
+      [lbpPositionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("amm_position"),
+          user.publicKey.toBuffer(),
+          lbpPoolPda.toBuffer(),
+        ],
+        program.programId
+      );
+    });
+
+    const swapAccounts = () => ({
+      trader: user.publicKey,
+      ammPool: lbpPoolPda,
+      lpMint: lbpLpMintPda,
+      tokenAMint: mintA,
+      tokenBMint: mintB,
+      userSourceTokenAccount: userTokenA,
+      userDestinationTokenAccount: userTokenB,
+      tokenAVault: lbpVaultAPda,
+      tokenBVault: lbpVaultBPda,
+      protocolFeeVaultA: lbpFeeVaultAPda,
+      protocolFeeVaultB: lbpFeeVaultBPda,
+      authority: user.publicKey,
+      referrerFeeAccount: null,
+      callbackProgram: null,
+      tokenAProgram: tokenAProgram,
+      tokenBProgram: tokenBProgram,
+    });
+
+    it("Creates a 95/5 -> 50/50 liquidity bootstrapping pool", async () => {
+      const saleStart = Math.floor(Date.now() / 1000) + 2;
+      saleEnd = saleStart + 6;
+
+      await program.methods
+        .createLbpAmmPool(
+          LBP_FEE_TIER,
+          PROTOCOL_FEE_SHARE,
+          REFERRER_FEE_SHARE,
+          {
+            startWeightA: START_WEIGHT_A,
+            endWeightA: END_WEIGHT_A,
+            startTimestamp: new anchor.BN(saleStart),
+            endTimestamp: new anchor.BN(saleEnd),
+          }
+        )
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: lbpPoolPda,
+          lpMint: lbpLpMintPda,
+          tokenAVault: lbpVaultAPda,
+          tokenBVault: lbpVaultBPda,
+          protocolFeeVaultA: lbpFeeVaultAPda,
+          protocolFeeVaultB: lbpFeeVaultBPda,
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolAccount = await program.account.ammPool.fetch(lbpPoolPda);
+      expect(poolAccount.curveType).to.deep.equal({
+        liquidityBootstrapping: {},
+      });
+      expect(poolAccount.launchOwner.toBase58()).to.equal(
+        user.publicKey.toBase58()
+      );
+      expect(poolAccount.swapsEnabled).to.be.false;
+    });
+
+    it("Seeds liquidity and rejects swaps until enabled", async () => {
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: lbpPoolPda,
+          ammPosition: lbpPositionPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(95 * 10 ** 6),
+          new anchor.BN(5 * 10 ** 6),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: lbpPoolPda,
+          ammPosition: lbpPositionPda,
+          lpMint: lbpLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: lbpVaultAPda,
+          tokenBVault: lbpVaultBPda,
+          userTokenAAccount: userTokenA,
+          userTokenBAccount: userTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            lbpLpMintPda,
+            user.publicKey
+          ),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      await expect(
+        program.methods
+          .swapOnAmm(new anchor.BN(10 ** 6), new anchor.BN(1))
+          .accounts(swapAccounts())
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/SwapsDisabled/);
+    });
+
+    it("Swaps once the protocol authority enables trading", async () => {
+      // Only the protocol authority may open trading, not just any signer.
+      const stranger = Keypair.generate();
+      await expect(
+        program.methods
+          .setLbpSwapsEnabled(true)
+          .accounts({
+            authority: stranger.publicKey,
+            protocolConfig: protocolConfigPda,
+            ammPool: lbpPoolPda,
+          })
+          .signers([stranger])
+          .rpc()
+      ).to.be.rejectedWith(/ConstraintHasOne/);
+
+      await program.methods
+        .setLbpSwapsEnabled(true)
+        .accounts({
+          authority: user.publicKey,
+          protocolConfig: protocolConfigPda,
+          ammPool: lbpPoolPda,
+        })
+        .signers([user])
+        .rpc();
+
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      await program.methods
+        .swapOnAmm(new anchor.BN(10 ** 6), new anchor.BN(1))
+        .accounts(swapAccounts())
+        .signers([user])
+        .rpc();
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      const received = Number(userBAfter.amount - userBBefore.amount);
+      expect(received).to.be.greaterThan(0);
+    });
+
+    it("Withdraws the proceeds after the sale ends", async () => {
+      const lpAccount = getAssociatedTokenAddressSync(
+        lbpLpMintPda,
+        user.publicKey
+      );
+      const withdrawAccounts = {
+        launchOwner: user.publicKey,
+        ammPool: lbpPoolPda,
+        ammPosition: lbpPositionPda,
+        lpMint: lbpLpMintPda,
+        tokenAMint: mintA,
+        tokenBMint: mintB,
+        tokenAVault: lbpVaultAPda,
+        tokenBVault: lbpVaultBPda,
+        launchOwnerLpTokenAccount: lpAccount,
+        launchOwnerTokenAAccount: userTokenA,
+        launchOwnerTokenBAccount: userTokenB,
+        tokenAProgram: tokenAProgram,
+        tokenBProgram: tokenBProgram,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      await expect(
+        program.methods
+          .withdrawLbpProceeds()
+          .accounts(withdrawAccounts)
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/LaunchNotEnded/);
+
+      // LP tokens the owner has moved elsewhere must not hold up the withdrawal.
+      const MOVED_LP = 1_000;
+      const holder = Keypair.generate();
+      const holderLpAccount = getAssociatedTokenAddressSync(
+        lbpLpMintPda,
+        holder.publicKey
+      );
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            user.publicKey,
+            holderLpAccount,
+            holder.publicKey,
+            lbpLpMintPda
+          )
+        ),
+        [user]
+      );
+      await transferChecked(
+        connection,
+        user,
+        lpAccount,
+        lbpLpMintPda,
+        holderLpAccount,
+        user,
+        MOVED_LP,
+        6
+      );
+
+      await sleep((saleEnd + 2) * 1000 - Date.now());
+
+      await program.methods
+        .withdrawLbpProceeds()
+        .accounts(withdrawAccounts)
+        .signers([user])
+        .rpc();
+
+      // Only the dust backing the locked minimum liquidity and the moved LP tokens stays
+      // in the pool.
+      const poolAccount = await program.account.ammPool.fetch(lbpPoolPda);
+      const remainingLp = MINIMUM_LIQUIDITY + MOVED_LP;
+      expect(poolAccount.reservesA.toNumber()).to.be.lessThan(remainingLp);
+      expect(poolAccount.reservesB.toNumber()).to.be.lessThan(remainingLp);
+      expect(poolAccount.swapsEnabled).to.be.false;
+      const lpMintAccount = await getMint(
+        connection,
+        lbpLpMintPda,
+        undefined,
+        TOKEN_PROGRAM_ID
+      );
+      expect(Number(lpMintAccount.supply)).to.equal(remainingLp);
+    });
+  });
+
+  describe("Minimum Liquidity Lock", () => {
+    const LOCK_FEE_TIER = 40; // 0.40%
+    const DONATION = 10 * 10 ** 6;
+
+    const victim = Keypair.generate();
+    let victimTokenA: PublicKey;
+    let victimTokenB: PublicKey;
+
+    let lockPoolPda: PublicKey;
+    let lockLpMintPda: PublicKey;
+    let lockVaultPda: PublicKey;
+    let lockVaultAPda: PublicKey;
+    let lockVaultBPda: PublicKey;
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const addLiquidityAccounts = (
+      owner: Keypair,
+      ownerTokenA: PublicKey,
+      ownerTokenB: PublicKey
+    ) => ({
+      owner: owner.publicKey,
+      ammPool: lockPoolPda,
+      ammPosition: findPda([
+        Buffer.from("amm_position"),
+        owner.publicKey.toBuffer(),
+        lockPoolPda.toBuffer(),
+      ]),
+      lpMint: lockLpMintPda,
+      tokenAMint: mintA,
+      tokenBMint: mintB,
+      tokenAVault: lockVaultAPda,
+      tokenBVault: lockVaultBPda,
+      userTokenAAccount: ownerTokenA,
+      userTokenBAccount: ownerTokenB,
+      userLpTokenAccount: getAssociatedTokenAddressSync(
+        lockLpMintPda,
+        owner.publicKey
+      ),
+      lpLockVault: lockVaultPda,
+      systemProgram: SystemProgram.programId,
+      tokenAProgram: tokenAProgram,
+      tokenBProgram: tokenBProgram,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    });
+
+    const openPosition = async (owner: Keypair) => {
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: owner.publicKey,
+          ammPool: lockPoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            owner.publicKey.toBuffer(),
+            lockPoolPda.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    };
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(victim.publicKey, LAMPORTS_PER_SOL)
+      );
+      [victimTokenA, victimTokenB] = await Promise.all([
+        createUserAndAssociatedWallet(
+          mintA,
+          victim,
+          BigInt(100 * 10 ** 6),
+          tokenAProgram
+        ),
+        createUserAndAssociatedWallet(
+          mintB,
+          victim,
+          BigInt(100 * 10 ** 6),
+          tokenBProgram
+        ),
+      ]);
+
+      lockPoolPda = findPda([
+        Buffer.from("amm_pool"),
+        mintA.toBuffer(),
+        mintB.toBuffer(),
+        new anchor.BN(LOCK_FEE_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      lockLpMintPda = findPda([Buffer.from("lp_mint"), lockPoolPda.toBuffer()]);
+      lockVaultPda = findPda([Buffer.from("lp_lock"), lockPoolPda.toBuffer()]);
+      lockVaultAPda = findPda([
+        Buffer.from("vault"),
+        lockPoolPda.toBuffer(),
+        mintA.toBuffer(),
+      ]);
+      lockVaultBPda = findPda([
+        Buffer.from("vault"),
+        lockPoolPda.toBuffer(),
+        mintB.toBuffer(),
+      ]);
+
+      await program.methods
+        .createAmmPool(LOCK_FEE_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: lockPoolPda,
+          lpMint: lockLpMintPda,
+          lpLockVault: lockVaultPda,
+          tokenAVault: lockVaultAPda,
+          tokenBVault: lockVaultBPda,
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            lockPoolPda.toBuffer(),
+            mintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            lockPoolPda.toBuffer(),
+            mintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      await openPosition(user);
+      await openPosition(victim);
+    });
+
+    it("Rejects a dust first deposit", async () => {
+      await expect(
+        program.methods
+          .addAmmLiquidity(
+            new anchor.BN(MINIMUM_LIQUIDITY - 1),
+            new anchor.BN(10 * 10 ** 6),
+            new anchor.BN(0)
+          )
+          .accounts(addLiquidityAccounts(user, userTokenA, userTokenB))
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/InsufficientInitialLiquidity/);
+    });
+
+    it("Locks the minimum liquidity on the first deposit", async () => {
+      // The smallest first deposit allowed: the depositor keeps a single LP unit.
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(MINIMUM_LIQUIDITY + 1),
+          new anchor.BN(MINIMUM_LIQUIDITY + 1),
+          new anchor.BN(1)
+        )
+        .accounts(addLiquidityAccounts(user, userTokenA, userTokenB))
+        .signers([user])
+        .rpc();
+
+      const lockVault = await getAccount(connection, lockVaultPda);
+      expect(Number(lockVault.amount)).to.equal(MINIMUM_LIQUIDITY);
+      expect(lockVault.owner.equals(lockPoolPda)).to.be.true;
+      const attackerLp = await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(lockLpMintPda, user.publicKey)
+      );
+      expect(Number(attackerLp.amount)).to.equal(1);
+    });
+
+    it("Makes a donation attack on the next depositor unprofitable", async () => {
+      const attackerABefore = await getAccount(
+        connection,
+        userTokenA,
+        undefined,
+        tokenAProgram
+      );
+      const attackerBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+
+      // The attacker donates straight to the vaults to inflate the value of its LP unit.
+      await transferChecked(
+        connection,
+        user,
+        userTokenA,
+        mintA,
+        lockVaultAPda,
+        user,
+        DONATION,
+        6,
+        [],
+        undefined,
+        tokenAProgram
+      );
+      await transferChecked(
+        connection,
+        user,
+        userTokenB,
+        mintB,
+        lockVaultBPda,
+        user,
+        DONATION,
+        6,
+        [],
+        undefined,
+        tokenBProgram
+      );
+
+      const victimDeposit = 5 * 10 ** 6;
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(victimDeposit),
+          new anchor.BN(victimDeposit),
+          new anchor.BN(1)
+        )
+        .accounts(addLiquidityAccounts(victim, victimTokenA, victimTokenB))
+        .signers([victim])
+        .rpc();
+
+      // The victim is not rounded down to zero shares.
+      const victimLp = await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(lockLpMintPda, victim.publicKey)
+      );
+      expect(Number(victimLp.amount)).to.equal(victimDeposit);
+
+      // The attacker exits with its single LP unit and recovers almost nothing.
+      await program.methods
+        .removeAmmLiquidity(new anchor.BN(1), new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          owner: user.publicKey,
+          ammPool: lockPoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            lockPoolPda.toBuffer(),
+          ]),
+          lpMint: lockLpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: lockVaultAPda,
+          tokenBVault: lockVaultBPda,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            lockLpMintPda,
+            user.publicKey
+          ),
+          userTokenAAccount: userTokenA,
+          userTokenBAccount: userTokenB,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const attackerAAfter = await getAccount(
+        connection,
+        userTokenA,
+        undefined,
+        tokenAProgram
+      );
+      const attackerBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      const lossA = Number(attackerABefore.amount - attackerAAfter.amount);
+      const lossB = Number(attackerBBefore.amount - attackerBAfter.amount);
+      expect(lossA).to.be.greaterThan(DONATION - 10);
+      expect(lossB).to.be.greaterThan(DONATION - 10);
+    });
+  });
+
+  describe("Reserve Reconciliation", () => {
+    const DONATION = 1 * 10 ** 6;
+
+    it("Skims a donated surplus to the protocol fee vault", async () => {
+      await transferChecked(
+        connection,
+        user,
+        userTokenA,
+        mintA,
+        tokenAVaultPda,
+        user,
+        DONATION,
+        6,
+        [],
+        undefined,
+        tokenAProgram
+      );
+      const poolBefore = await program.account.ammPool.fetch(ammPoolPda);
+      const vaultBefore = await getAccount(
+        connection,
+        tokenAVaultPda,
+        undefined,
+        tokenAProgram
+      );
+      const feeVaultBefore = await getAccount(
+        connection,
+        protocolFeeVaultAPda,
+        undefined,
+        tokenAProgram
+      );
+      const surplus =
+        vaultBefore.amount - BigInt(poolBefore.reservesA.toString());
+
+      await program.methods
+        .skimAmmReserves()
+        .accounts({
+          ammPool: ammPoolPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: tokenAVaultPda,
+          tokenBVault: tokenBVaultPda,
+          protocolFeeVaultA: protocolFeeVaultAPda,
+          protocolFeeVaultB: protocolFeeVaultBPda,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+        })
+        .rpc();
+
+      const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
+      const feeVaultAfter = await getAccount(
+        connection,
+        protocolFeeVaultAPda,
+        undefined,
+        tokenAProgram
+      );
+      expect(surplus >= BigInt(DONATION)).to.be.true;
+      expect(feeVaultAfter.amount - feeVaultBefore.amount).to.equal(surplus);
+      expect(poolAfter.reservesA.eq(poolBefore.reservesA)).to.be.true;
+    });
+
+    it("Syncs tracked reserves to the vault balances", async () => {
+      await transferChecked(
+        connection,
+        user,
+        userTokenB,
+        mintB,
+        tokenBVaultPda,
+        user,
+        DONATION,
+        6,
+        [],
+        undefined,
+        tokenBProgram
+      );
+      const poolBefore = await program.account.ammPool.fetch(ammPoolPda);
+
+      await program.methods
+        .syncAmmReserves()
+        .accounts({
+          ammPool: ammPoolPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: tokenAVaultPda,
+          tokenBVault: tokenBVaultPda,
+        })
+        .rpc();
+
+      const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
+      const vaultB = await getAccount(
+        connection,
+        tokenBVaultPda,
+        undefined,
+        tokenBProgram
+      );
+      expect(poolAfter.reservesB.toString()).to.equal(vaultB.amount.toString());
+      expect(
+        poolAfter.reservesB.sub(poolBefore.reservesB).gten(DONATION)
+      ).to.be.true;
+    });
+  });
+
+  describe("Flash Loans", () => {
+    // SPL Memo, which the local validator ships with: a callback that never repays.
+    const MEMO_PROGRAM_ID = new PublicKey(
+      "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+    );
+
+    const flashLoanAccounts = (borrowerProgram: PublicKey) => ({
+      borrower: user.publicKey,
+      ammPool: ammPoolPda,
+      lpMint: lpMintPda,
+      tokenAMint: mintA,
+      tokenBMint: mintB,
+      tokenAVault: tokenAVaultPda,
+      tokenBVault: tokenBVaultPda,
+      borrowerTokenAAccount: userTokenA,
+      borrowerTokenBAccount: userTokenB,
+      borrowerProgram,
+      tokenAProgram: tokenAProgram,
+      tokenBProgram: tokenBProgram,
+    });
+
+    it("Reverts a flash loan that is not repaid", async () => {
+      const poolBefore = await program.account.ammPool.fetch(ammPoolPda);
+      await expect(
+        program.methods
+          .ammFlashLoan(
+            new anchor.BN(10 ** 6),
+            new anchor.BN(0),
+            Buffer.from("no repayment")
+          )
+          .accounts(flashLoanAccounts(MEMO_PROGRAM_ID))
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/FlashLoanNotRepaid/);
+
+      const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
+      expect(poolAfter.flashLoanActive).to.be.false;
+      expect(poolAfter.reservesA.eq(poolBefore.reservesA)).to.be.true;
+    });
+
+    it("Rejects a callback into the pool program itself", async () => {
+      await expect(
+        program.methods
+          .ammFlashLoan(new anchor.BN(10 ** 6), new anchor.BN(0), Buffer.from([]))
+          .accounts(flashLoanAccounts(program.programId))
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/InvalidFlashLoanProgram/);
+    });
+
+    it("Reverts a flash swap whose callback does not pay the input", async () => {
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      await expect(
+        program.methods
+          .flashSwapOnAmm(
+            new anchor.BN(10 ** 6),
+            new anchor.BN(1),
+            Buffer.from("no payment")
+          )
+          .accounts({
+            trader: user.publicKey,
+            ammPool: ammPoolPda,
+            lpMint: lpMintPda,
+            tokenAMint: mintA,
+            tokenBMint: mintB,
+            userSourceTokenAccount: null,
+            userDestinationTokenAccount: userTokenB,
+            tokenAVault: tokenAVaultPda,
+            tokenBVault: tokenBVaultPda,
+            protocolFeeVaultA: protocolFeeVaultAPda,
+            protocolFeeVaultB: protocolFeeVaultBPda,
+            authority: user.publicKey,
+            referrerFeeAccount: null,
+            callbackProgram: MEMO_PROGRAM_ID,
+            tokenAProgram: tokenAProgram,
+            tokenBProgram: tokenBProgram,
+          })
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/FlashSwapNotPaid/);
+
+      // The optimistic output was rolled back with the rest of the transaction.
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      expect(userBAfter.amount).to.equal(userBBefore.amount);
+    });
+  });
+
+  describe("Routed and Split Swaps", () => {
+    // The accounts of one `swapOnAmm` hop, in instruction order. Omitted optional
+    // accounts are passed as the program ID.
+    const ammHopAccounts = (source: PublicKey, destination: PublicKey) =>
+      [
+        { pubkey: user.publicKey, isSigner: true, isWritable: true },
+        { pubkey: ammPoolPda, isSigner: false, isWritable: true },
+        { pubkey: lpMintPda, isSigner: false, isWritable: true },
+        { pubkey: mintA, isSigner: false, isWritable: false },
+        { pubkey: mintB, isSigner: false, isWritable: false },
+        { pubkey: source, isSigner: false, isWritable: true },
+        { pubkey: destination, isSigner: false, isWritable: true },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+        { pubkey: tokenAVaultPda, isSigner: false, isWritable: true },
+        { pubkey: tokenBVaultPda, isSigner: false, isWritable: true },
+        { pubkey: protocolFeeVaultAPda, isSigner: false, isWritable: true },
+        { pubkey: protocolFeeVaultBPda, isSigner: false, isWritable: true },
+        { pubkey: user.publicKey, isSigner: false, isWritable: false },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+        { pubkey: tokenAProgram, isSigner: false, isWritable: false },
+        { pubkey: tokenBProgram, isSigner: false, isWritable: false },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+      ];
+    const ammHop = { pool: { amm: {} }, extraAccounts: 0 };
+
+    it("Routes A -> B -> A and checks only the final output", async () => {
+      const amountIn = new anchor.BN(10 ** 6);
+      const userABefore = await getAccount(
+        connection,
+        userTokenA,
+        undefined,
+        tokenAProgram
+      );
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+
+      await program.methods
+        .routeSwap(amountIn, new anchor.BN(1), [ammHop, ammHop])
+        .accounts({ trader: user.publicKey })
+        .remainingAccounts([
+          ...ammHopAccounts(userTokenA, userTokenB),
+          ...ammHopAccounts(userTokenB, userTokenA),
+        ])
+        .signers([user])
+        .rpc();
+
+      // Everything the first hop paid out was spent by the second, and the round trip
+      // cost the trader the two hops' fees.
+      const userAAfter = await getAccount(
+        connection,
+        userTokenA,
+        undefined,
+        tokenAProgram
+      );
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      expect(userBAfter.amount).to.equal(userBBefore.amount);
+      expect(userAAfter.amount < userABefore.amount).to.be.true;
+      const roundTripCost = userABefore.amount - userAAfter.amount;
+      expect(roundTripCost < BigInt(amountIn.toString())).to.be.true;
+    });
+
+    it("Fails when the final output is below the minimum", async () => {
+      const amountIn = new anchor.BN(10 ** 6);
+      await expect(
+        program.methods
+          .routeSwap(amountIn, amountIn, [ammHop, ammHop])
+          .accounts({ trader: user.publicKey })
+          .remainingAccounts([
+            ...ammHopAccounts(userTokenA, userTokenB),
+            ...ammHopAccounts(userTokenB, userTokenA),
+          ])
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/SlippageExceeded/);
+    });
+
+    it("Rejects hops that are not chained through the trader's accounts", async () => {
+      await expect(
+        program.methods
+          .routeSwap(new anchor.BN(10 ** 6), new anchor.BN(1), [ammHop, ammHop])
+          .accounts({ trader: user.publicKey })
+          .remainingAccounts([
+            ...ammHopAccounts(userTokenA, userTokenB),
+            ...ammHopAccounts(userTokenA, userTokenB),
+          ])
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/InvalidRoute/);
+    });
+
+    it("Splits an order across legs with one aggregate slippage check", async () => {
+      const legs = [
+        { pool: { amm: {} }, amountIn: new anchor.BN(2 * 10 ** 6), extraAccounts: 0 },
+        { pool: { amm: {} }, amountIn: new anchor.BN(10 ** 6), extraAccounts: 0 },
+      ];
+      const splitAccounts = [
+        ...ammHopAccounts(userTokenA, userTokenB),
+        ...ammHopAccounts(userTokenA, userTokenB),
+      ];
+
+      await expect(
+        program.methods
+          .splitSwap(new anchor.BN(3 * 10 ** 6), legs)
+          .accounts({ trader: user.publicKey })
+          .remainingAccounts(splitAccounts)
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/SlippageExceeded/);
+
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      await program.methods
+        .splitSwap(new anchor.BN(1), legs)
+        .accounts({ trader: user.publicKey })
+        .remainingAccounts(splitAccounts)
+        .signers([user])
+        .rpc();
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      expect(userBAfter.amount > userBBefore.amount).to.be.true;
+    });
+
+    it("Rejects split legs that trade in different directions", async () => {
+      await expect(
+        program.methods
+          .splitSwap(new anchor.BN(1), [
+            { pool: { amm: {} }, amountIn: new anchor.BN(10 ** 6), extraAccounts: 0 },
+            { pool: { amm: {} }, amountIn: new anchor.BN(10 ** 6), extraAccounts: 0 },
+          ])
+          .accounts({ trader: user.publicKey })
+          .remainingAccounts([
+            ...ammHopAccounts(userTokenA, userTokenB),
+            ...ammHopAccounts(userTokenB, userTokenA),
+          ])
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/InvalidSplit/);
+    });
+  });
+
+  describe("Quotes", () => {
+    const quoteAccounts = () => ({
+      ammPool: ammPoolPda,
+      lpMint: lpMintPda,
+      tokenAMint: mintA,
+      tokenBMint: mintB,
+    });
+
+    it("Quotes a swap that matches its execution", async () => {
+      const amountIn = new anchor.BN(10 ** 6);
+      const quote = await program.methods
+        .quoteAmmSwap(amountIn, true)
+        .accounts(quoteAccounts())
+        .view();
+      expect(quote.amountOut.gtn(0)).to.be.true;
+
+      const userBBefore = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      await program.methods
+        .swapOnAmm(amountIn, quote.amountOut)
+        .accounts({
+          trader: user.publicKey,
+          ammPool: ammPoolPda,
+          lpMint: lpMintPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userTokenB,
+          tokenAVault: tokenAVaultPda,
+          tokenBVault: tokenBVaultPda,
+          protocolFeeVaultA: protocolFeeVaultAPda,
+          protocolFeeVaultB: protocolFeeVaultBPda,
+          authority: user.publicKey,
+          referrerFeeAccount: null,
+          callbackProgram: null,
+          tokenAProgram: tokenAProgram,
+          tokenBProgram: tokenBProgram,
+        })
+        .signers([user])
+        .rpc();
+      const userBAfter = await getAccount(
+        connection,
+        userTokenB,
+        undefined,
+        tokenBProgram
+      );
+      expect(userBAfter.amount - userBBefore.amount).to.equal(
+        BigInt(quote.amountOut.toString())
+      );
+    });
+
+    it("Quotes deposits and withdrawals without changing the pool", async () => {
+      const poolBefore = await program.account.ammPool.fetch(ammPoolPda);
+      const depositQuote = await program.methods
+        .quoteAddLiquidity(new anchor.BN(10 ** 6), new anchor.BN(10 ** 6))
+        .accounts(quoteAccounts())
+        .view();
+      expect(depositQuote.lpTokensToMint.gtn(0)).to.be.true;
+
+      const withdrawalQuote = await program.methods
+        .quoteRemoveLiquidity(depositQuote.lpTokensToMint)
+        .accounts(quoteAccounts())
+        .view();
+      const { amountAWithdrawn } = withdrawalQuote;
+      expect(amountAWithdrawn.lte(depositQuote.amountADeposited)).to.be.true;
+
+      const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
+      expect(poolAfter.reservesA.eq(poolBefore.reservesA)).to.be.true;
+      expect(poolAfter.reservesB.eq(poolBefore.reservesB)).to.be.true;
+    });
+  });
+
+  describe("Token-2022 Transfer Fees", () => {
+    const FEE_POOL_TIER = 45; // 0.45%
+    const TRANSFER_FEE_BPS = 100; // 1%
+
+    let feeMint: PublicKey;
+    let plainMint: PublicKey;
+    let feePoolMintA: PublicKey;
+    let feePoolMintB: PublicKey;
+    let feePoolProgramA: PublicKey;
+    let feePoolProgramB: PublicKey;
+    let feeUserTokenA: PublicKey;
+    let feeUserTokenB: PublicKey;
+
+    let feePoolPda: PublicKey;
+    let feeLpMintPda: PublicKey;
+    let feeVaultAPda: PublicKey;
+    let feeVaultBPda: PublicKey;
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const createTransferFeeMint = async (): Promise<PublicKey> => {
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        mintLen
+      );
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mintKeypair.publicKey,
+          user.publicKey,
+          user.publicKey,
+          TRANSFER_FEE_BPS,
+          BigInt(1_000_000 * 10 ** 6),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          mintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await provider.sendAndConfirm(tx, [user, mintKeypair]);
+      return mintKeypair.publicKey;
+    };
+
+    const vaultBalances = async () => {
+      const [vaultA, vaultB] = await Promise.all([
+        getAccount(connection, feeVaultAPda, undefined, feePoolProgramA),
+        getAccount(connection, feeVaultBPda, undefined, feePoolProgramB),
+      ]);
+      return [vaultA.amount, vaultB.amount];
+    };
+
+    before(async () => {
+      feeMint = await createTransferFeeMint();
+      plainMint = await createMintHelper(TOKEN_PROGRAM_ID);
+      const programOf = (mint: PublicKey) =>
+        mint.equals(feeMint) ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+      [feePoolMintA, feePoolMintB] = [feeMint, plainMint].sort((a, b) =>
+        a.toBuffer().compare(b.toBuffer())
+      );
+      feePoolProgramA = programOf(feePoolMintA);
+      feePoolProgramB = programOf(feePoolMintB);
+
+      [feeUserTokenA, feeUserTokenB] = await Promise.all([
+        createUserAndAssociatedWallet(
+          feePoolMintA,
+          user,
+          1000n * 1000000n,
+          feePoolProgramA
+        ),
+        createUserAndAssociatedWallet(
+          feePoolMintB,
+          user,
+          1000n * 1000000n,
+          feePoolProgramB
+        ),
+      ]);
+
+      feePoolPda = findPda([
+        Buffer.from("amm_pool"),
+        feePoolMintA.toBuffer(),
+        feePoolMintB.toBuffer(),
+        new anchor.BN(FEE_POOL_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      feeLpMintPda = findPda([Buffer.from("lp_mint"), feePoolPda.toBuffer()]);
+      feeVaultAPda = findPda([
+        Buffer.from("vault"),
+        feePoolPda.toBuffer(),
+        feePoolMintA.toBuffer(),
+      ]);
+      feeVaultBPda = findPda([
+        Buffer.from("vault"),
+        feePoolPda.toBuffer(),
+        feePoolMintB.toBuffer(),
+      ]);
+
+      await program.methods
+        .createAmmPool(FEE_POOL_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: feePoolMintA,
+          tokenBMint: feePoolMintB,
+          ammPool: feePoolPda,
+          lpMint: feeLpMintPda,
+          lpLockVault: findPda([Buffer.from("lp_lock"), feePoolPda.toBuffer()]),
+          tokenAVault: feeVaultAPda,
+          tokenBVault: feeVaultBPda,
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            feePoolPda.toBuffer(),
+            feePoolMintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            feePoolPda.toBuffer(),
+            feePoolMintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: feePoolProgramA,
+          tokenBProgram: feePoolProgramB,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: feePoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            feePoolPda.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Credits deposits net of the transfer fee", async () => {
+      const amount = 100 * 10 ** 6;
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(amount),
+          new anchor.BN(amount),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: feePoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            feePoolPda.toBuffer(),
+          ]),
+          lpMint: feeLpMintPda,
+          tokenAMint: feePoolMintA,
+          tokenBMint: feePoolMintB,
+          tokenAVault: feeVaultAPda,
+          tokenBVault: feeVaultBPda,
+          userTokenAAccount: feeUserTokenA,
+          userTokenBAccount: feeUserTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            feeLpMintPda,
+            user.publicKey
+          ),
+          lpLockVault: findPda([Buffer.from("lp_lock"), feePoolPda.toBuffer()]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: feePoolProgramA,
+          tokenBProgram: feePoolProgramB,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const pool = await program.account.ammPool.fetch(feePoolPda);
+      const [vaultA, vaultB] = await vaultBalances();
+      const feeReserves = feePoolMintA.equals(feeMint)
+        ? pool.reservesA
+        : pool.reservesB;
+      // The fee side is credited what the vault keeps after the 1% fee.
+      expect(feeReserves.toNumber()).to.equal(amount * 0.99);
+      expect(vaultA >= BigInt(pool.reservesA.toString())).to.be.true;
+      expect(vaultB >= BigInt(pool.reservesB.toString())).to.be.true;
+    });
+
+    it("Prices swaps on the net input and keeps reserves backed", async () => {
+      const feeIsA = feePoolMintA.equals(feeMint);
+      const amountIn = 10 * 10 ** 6;
+      const poolBefore = await program.account.ammPool.fetch(feePoolPda);
+      const [vaultABefore, vaultBBefore] = await vaultBalances();
+
+      await program.methods
+        .swapOnAmm(new anchor.BN(amountIn), new anchor.BN(1))
+        .accounts({
+          trader: user.publicKey,
+          ammPool: feePoolPda,
+          lpMint: feeLpMintPda,
+          tokenAMint: feePoolMintA,
+          tokenBMint: feePoolMintB,
+          userSourceTokenAccount: feeIsA ? feeUserTokenA : feeUserTokenB,
+          userDestinationTokenAccount: feeIsA ? feeUserTokenB : feeUserTokenA,
+          tokenAVault: feeVaultAPda,
+          tokenBVault: feeVaultBPda,
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            feePoolPda.toBuffer(),
+            feePoolMintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            feePoolPda.toBuffer(),
+            feePoolMintB.toBuffer(),
+          ]),
+          authority: user.publicKey,
+          referrerFeeAccount: null,
+          callbackProgram: null,
+          tokenAProgram: feePoolProgramA,
+          tokenBProgram: feePoolProgramB,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolAfter = await program.account.ammPool.fetch(feePoolPda);
+      const [vaultAAfter, vaultBAfter] = await vaultBalances();
+      // Every token the reserves gained or lost moved through the vault.
+      expect(
+        BigInt(poolAfter.reservesA.sub(poolBefore.reservesA).toString())
+      ).to.equal(vaultAAfter - vaultABefore);
+      expect(
+        BigInt(poolAfter.reservesB.sub(poolBefore.reservesB).toString())
+      ).to.equal(vaultBAfter - vaultBBefore);
+
+      const sourceGain = feeIsA
+        ? poolAfter.reservesA.sub(poolBefore.reservesA)
+        : poolAfter.reservesB.sub(poolBefore.reservesB);
+      expect(sourceGain.toNumber()).to.be.lessThan(amountIn * 0.99);
+    });
+  });
+
+  describe("Token-2022 Transfer Hooks", () => {
+    const HOOK_POOL_TIER = 30; // 0.30%
+    const hookProgram = anchor.workspace
+      .TestTransferHook as Program<TestTransferHook>;
+
+    let hookMint: PublicKey;
+    let hookPoolMintA: PublicKey;
+    let hookPoolMintB: PublicKey;
+    let hookPoolProgramA: PublicKey;
+    let hookPoolProgramB: PublicKey;
+    let hookUserTokenA: PublicKey;
+    let hookUserTokenB: PublicKey;
+
+    let hookPoolPda: PublicKey;
+    let hookLpMintPda: PublicKey;
+    let hookVaultAPda: PublicKey;
+    let hookVaultBPda: PublicKey;
+    let counterPda: PublicKey;
+    // The hook program, the mint's extra-account-metas list and the accounts it names.
+    let hookAccounts: anchor.web3.AccountMeta[];
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const transferCount = async () =>
+      (await hookProgram.account.transferCounter.fetch(counterPda)).transfers;
+
+    before(async () => {
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferHook]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        mintLen
+      );
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          mintKeypair.publicKey,
+          user.publicKey,
+          hookProgram.programId,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          mintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await provider.sendAndConfirm(tx, [user, mintKeypair]);
+      hookMint = mintKeypair.publicKey;
+
+      const [extraAccountMetaListPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("extra-account-metas"), hookMint.toBuffer()],
+        hookProgram.programId
+      );
+      [counterPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("counter")],
+        hookProgram.programId
+      );
+      await hookProgram.methods
+        .initializeExtraAccountMetaList()
+        .accounts({
+          payer: user.publicKey,
+          extraAccountMetaList: extraAccountMetaListPda,
+          mint: hookMint,
+          counter: counterPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      hookAccounts = [
+        { pubkey: hookProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: extraAccountMetaListPda, isSigner: false, isWritable: false },
+        { pubkey: counterPda, isSigner: false, isWritable: true },
+      ];
+
+      const plainMint = await createMintHelper(TOKEN_PROGRAM_ID);
+      const programOf = (mint: PublicKey) =>
+        mint.equals(hookMint) ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+      [hookPoolMintA, hookPoolMintB] = [hookMint, plainMint].sort((a, b) =>
+        a.toBuffer().compare(b.toBuffer())
+      );
+      hookPoolProgramA = programOf(hookPoolMintA);
+      hookPoolProgramB = programOf(hookPoolMintB);
+
+      [hookUserTokenA, hookUserTokenB] = await Promise.all([
+        createUserAndAssociatedWallet(
+          hookPoolMintA,
+          user,
+          1000n * 1000000n,
+          hookPoolProgramA
+        ),
+        createUserAndAssociatedWallet(
+          hookPoolMintB,
+          user,
+          1000n * 1000000n,
+          hookPoolProgramB
+        ),
+      ]);
+
+      hookPoolPda = findPda([
+        Buffer.from("amm_pool"),
+        hookPoolMintA.toBuffer(),
+        hookPoolMintB.toBuffer(),
+        new anchor.BN(HOOK_POOL_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      hookLpMintPda = findPda([Buffer.from("lp_mint"), hookPoolPda.toBuffer()]);
+      hookVaultAPda = findPda([
+        Buffer.from("vault"),
+        hookPoolPda.toBuffer(),
+        hookPoolMintA.toBuffer(),
+      ]);
+      hookVaultBPda = findPda([
+        Buffer.from("vault"),
+        hookPoolPda.toBuffer(),
+        hookPoolMintB.toBuffer(),
+      ]);
+
+      await program.methods
+        .createAmmPool(HOOK_POOL_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: hookPoolMintA,
+          tokenBMint: hookPoolMintB,
+          ammPool: hookPoolPda,
+          lpMint: hookLpMintPda,
+          lpLockVault: findPda([Buffer.from("lp_lock"), hookPoolPda.toBuffer()]),
+          tokenAVault: hookVaultAPda,
+          tokenBVault: hookVaultBPda,
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            hookPoolPda.toBuffer(),
+            hookPoolMintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            hookPoolPda.toBuffer(),
+            hookPoolMintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: hookPoolProgramA,
+          tokenBProgram: hookPoolProgramB,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: hookPoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            hookPoolPda.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Deposits a hooked mint with the hook's extra accounts", async () => {
+      const amount = 100 * 10 ** 6;
+      const transfersBefore = await transferCount();
+
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(amount),
+          new anchor.BN(amount),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: hookPoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            hookPoolPda.toBuffer(),
+          ]),
+          lpMint: hookLpMintPda,
+          tokenAMint: hookPoolMintA,
+          tokenBMint: hookPoolMintB,
+          tokenAVault: hookVaultAPda,
+          tokenBVault: hookVaultBPda,
+          userTokenAAccount: hookUserTokenA,
+          userTokenBAccount: hookUserTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            hookLpMintPda,
+            user.publicKey
+          ),
+          lpLockVault: findPda([Buffer.from("lp_lock"), hookPoolPda.toBuffer()]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: hookPoolProgramA,
+          tokenBProgram: hookPoolProgramB,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(hookAccounts)
+        .signers([user])
+        .rpc();
+
+      const pool = await program.account.ammPool.fetch(hookPoolPda);
+      expect(pool.reservesA.toNumber()).to.equal(amount);
+      expect(pool.reservesB.toNumber()).to.equal(amount);
+      // The hooked side moved once, and the hook saw it.
+      expect((await transferCount()).sub(transfersBefore).toNumber()).to.equal(1);
+    });
+
+    it("Swaps a hooked mint with the hook's extra accounts", async () => {
+      const hookIsA = hookPoolMintA.equals(hookMint);
+      const swap = () =>
+        program.methods
+          .swapOnAmm(new anchor.BN(10 * 10 ** 6), new anchor.BN(1))
+          .accounts({
+            trader: user.publicKey,
+            ammPool: hookPoolPda,
+            lpMint: hookLpMintPda,
+            tokenAMint: hookPoolMintA,
+            tokenBMint: hookPoolMintB,
+            userSourceTokenAccount: hookIsA ? hookUserTokenA : hookUserTokenB,
+            userDestinationTokenAccount: hookIsA
+              ? hookUserTokenB
+              : hookUserTokenA,
+            tokenAVault: hookVaultAPda,
+            tokenBVault: hookVaultBPda,
+            protocolFeeVaultA: findPda([
+              Buffer.from("protocol_fee_vault"),
+              hookPoolPda.toBuffer(),
+              hookPoolMintA.toBuffer(),
+            ]),
+            protocolFeeVaultB: findPda([
+              Buffer.from("protocol_fee_vault"),
+              hookPoolPda.toBuffer(),
+              hookPoolMintB.toBuffer(),
+            ]),
+            authority: user.publicKey,
+            referrerFeeAccount: null,
+            callbackProgram: null,
+            tokenAProgram: hookPoolProgramA,
+            tokenBProgram: hookPoolProgramB,
+          });
+
+      // Token-2022 refuses to move a hooked mint without the hook's accounts.
+      await expect(swap().signers([user]).rpc()).to.be.rejected;
+
+      const transfersBefore = await transferCount();
+      const poolBefore = await program.account.ammPool.fetch(hookPoolPda);
+      await swap().remainingAccounts(hookAccounts).signers([user]).rpc();
+
+      const poolAfter = await program.account.ammPool.fetch(hookPoolPda);
+      const hookedReservesBefore = hookIsA
+        ? poolBefore.reservesA
+        : poolBefore.reservesB;
+      const hookedReservesAfter = hookIsA
+        ? poolAfter.reservesA
+        : poolAfter.reservesB;
+      expect(hookedReservesAfter.gt(hookedReservesBefore)).to.be.true;
+      expect((await transferCount()).gt(transfersBefore)).to.be.true;
+    });
+  });
+
+  describe("UI-Adjusted Oracle Prices", () => {
+    const UI_POOL_TIER = 35; // 0.35%
+    // The year length Token-2022 compounds interest-bearing mints over.
+    const SECONDS_PER_YEAR = 60 * 60 * 24 * 365.24;
+    const INTEREST_RATE_BPS = 30_000; // 300% a year, so seconds of interest show
+    const UI_MULTIPLIER = 2;
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const createExtensionMint = async (
+      extension: ExtensionType,
+      initializeExtension: (mint: PublicKey) => TransactionInstruction
+    ): Promise<PublicKey> => {
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen([extension]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        mintLen
+      );
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        initializeExtension(mintKeypair.publicKey),
+        createInitializeMintInstruction(
+          mintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await provider.sendAndConfirm(tx, [user, mintKeypair]);
+      return mintKeypair.publicKey;
+    };
+
+    // Pairs `rescaledMint` with a plain mint in a new pool, swaps once the clock has
+    // moved, and returns how far the raw and UI-adjusted price-A cumulatives grew.
+    const measureOracleGrowth = async (rescaledMint: PublicKey) => {
+      const plainMint = await createMintHelper(TOKEN_PROGRAM_ID);
+      const [poolMintA, poolMintB] = [rescaledMint, plainMint].sort((a, b) =>
+        a.toBuffer().compare(b.toBuffer())
+      );
+      const rescaledIsA = poolMintA.equals(rescaledMint);
+      const programOf = (mint: PublicKey) =>
+        mint.equals(rescaledMint) ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+      const [poolTokenA, poolTokenB] = await Promise.all([
+        createUserAndAssociatedWallet(
+          poolMintA,
+          user,
+          1000n * 1000000n,
+          programOf(poolMintA)
+        ),
+        createUserAndAssociatedWallet(
+          poolMintB,
+          user,
+          1000n * 1000000n,
+          programOf(poolMintB)
+        ),
+      ]);
+
+      const poolPda = findPda([
+        Buffer.from("amm_pool"),
+        poolMintA.toBuffer(),
+        poolMintB.toBuffer(),
+        new anchor.BN(UI_POOL_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      const poolLpMintPda = findPda([Buffer.from("lp_mint"), poolPda.toBuffer()]);
+      const poolPositionPda = findPda([
+        Buffer.from("amm_position"),
+        user.publicKey.toBuffer(),
+        poolPda.toBuffer(),
+      ]);
+      const vaultAPda = findPda([
+        Buffer.from("vault"),
+        poolPda.toBuffer(),
+        poolMintA.toBuffer(),
+      ]);
+      const vaultBPda = findPda([
+        Buffer.from("vault"),
+        poolPda.toBuffer(),
+        poolMintB.toBuffer(),
+      ]);
+      const feeVaultAPda = findPda([
+        Buffer.from("protocol_fee_vault"),
+        poolPda.toBuffer(),
+        poolMintA.toBuffer(),
+      ]);
+      const feeVaultBPda = findPda([
+        Buffer.from("protocol_fee_vault"),
+        poolPda.toBuffer(),
+        poolMintB.toBuffer(),
+      ]);
+
+      await program.methods
+        .createAmmPool(UI_POOL_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          tokenAMint: poolMintA,
+          tokenBMint: poolMintB,
+          ammPool: poolPda,
+          lpMint: poolLpMintPda,
+          lpLockVault: findPda([Buffer.from("lp_lock"), poolPda.toBuffer()]),
+          tokenAVault: vaultAPda,
+          tokenBVault: vaultBPda,
+          protocolFeeVaultA: feeVaultAPda,
+          protocolFeeVaultB: feeVaultBPda,
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: programOf(poolMintA),
+          tokenBProgram: programOf(poolMintB),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: poolPda,
+          ammPosition: poolPositionPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: poolPda,
+          ammPosition: poolPositionPda,
+          lpMint: poolLpMintPda,
+          tokenAMint: poolMintA,
+          tokenBMint: poolMintB,
+          tokenAVault: vaultAPda,
+          tokenBVault: vaultBPda,
+          userTokenAAccount: poolTokenA,
+          userTokenBAccount: poolTokenB,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            poolLpMintPda,
+            user.publicKey
+          ),
+          lpLockVault: findPda([Buffer.from("lp_lock"), poolPda.toBuffer()]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: programOf(poolMintA),
+          tokenBProgram: programOf(poolMintB),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const poolBefore = await program.account.ammPool.fetch(poolPda);
+      await sleep(2000); // Let the clock move so the swap accumulates a price.
+      await program.methods
+        .swapOnAmm(new anchor.BN(1 * 10 ** 6), new anchor.BN(1))
+        .accounts({
+          trader: user.publicKey,
+          ammPool: poolPda,
+          lpMint: poolLpMintPda,
+          tokenAMint: poolMintA,
+          tokenBMint: poolMintB,
+          userSourceTokenAccount: poolTokenA,
+          userDestinationTokenAccount: poolTokenB,
+          tokenAVault: vaultAPda,
+          tokenBVault: vaultBPda,
+          protocolFeeVaultA: feeVaultAPda,
+          protocolFeeVaultB: feeVaultBPda,
+          authority: user.publicKey,
+          referrerFeeAccount: null,
+          callbackProgram: null,
+          tokenAProgram: programOf(poolMintA),
+          tokenBProgram: programOf(poolMintB),
+        })
+        .signers([user])
+        .rpc();
+      const poolAfter = await program.account.ammPool.fetch(poolPda);
+
+      return {
+        rescaledIsA,
+        swapTimestamp: poolAfter.lastUpdateTimestamp.toNumber(),
+        rawGrowth: Number(
+          poolAfter.priceACumulative.sub(poolBefore.priceACumulative).toString()
+        ),
+        uiGrowth: Number(
+          poolAfter.uiPriceACumulative
+            .sub(poolBefore.uiPriceACumulative)
+            .toString()
+        ),
+      };
+    };
+
+    it("Scales the UI price by a scaled-UI-amount mint's multiplier", async () => {
+      const scaledMint = await createExtensionMint(
+        ExtensionType.ScaledUiAmountConfig,
+        (mint) =>
+          createInitializeScaledUiAmountConfigInstruction(
+            mint,
+            user.publicKey,
+            UI_MULTIPLIER,
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+      const { rescaledIsA, rawGrowth, uiGrowth } = await measureOracleGrowth(
+        scaledMint
+      );
+
+      // Price A is quoted in B, so the multiplier divides it when the scaled mint is A
+      // and multiplies it when it is B.
+      const expected = rescaledIsA ? 1 / UI_MULTIPLIER : UI_MULTIPLIER;
+      expect(rawGrowth).to.be.greaterThan(0);
+      expect(uiGrowth / rawGrowth).to.be.closeTo(expected, 1e-6);
+    });
+
+    it("Compounds an interest-bearing mint's rate into the UI price", async () => {
+      const interestMint = await createExtensionMint(
+        ExtensionType.InterestBearingConfig,
+        (mint) =>
+          createInitializeInterestBearingMintInstruction(
+            mint,
+            user.publicKey,
+            INTEREST_RATE_BPS,
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+      const { rescaledIsA, swapTimestamp, rawGrowth, uiGrowth } =
+        await measureOracleGrowth(interestMint);
+
+      const mint = await getMint(
+        connection,
+        interestMint,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const { initializationTimestamp } = getInterestBearingMintConfigState(mint);
+      const multiplier = Math.exp(
+        ((INTEREST_RATE_BPS / BASIS_POINT_MAX) *
+          (swapTimestamp - Number(initializationTimestamp))) /
+          SECONDS_PER_YEAR
+      );
+      const expected = rescaledIsA ? 1 / multiplier : multiplier;
+      expect(uiGrowth).to.not.equal(rawGrowth);
+      expect(uiGrowth / rawGrowth).to.be.closeTo(expected, 1e-8);
+    });
+  });
+
+  describe("Mint Extension Policy", () => {
+    const POLICY_FEE_TIER = 50; // 0.50%
+
+    let delegatedMint: PublicKey;
+    let pairedMint: PublicKey;
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const createPool = (mint1: PublicKey, mint2: PublicKey) => {
+      const [mintA, mintB] = [mint1, mint2].sort((a, b) =>
+        a.toBuffer().compare(b.toBuffer())
+      );
+      const programOf = (mint: PublicKey) =>
+        mint.equals(delegatedMint) ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+      const poolPda = findPda([
+        Buffer.from("amm_pool"),
+        mintA.toBuffer(),
+        mintB.toBuffer(),
+        new anchor.BN(POLICY_FEE_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      return program.methods
+        .createAmmPool(POLICY_FEE_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          protocolConfig: protocolConfigPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: poolPda,
+          lpMint: findPda([Buffer.from("lp_mint"), poolPda.toBuffer()]),
+          lpLockVault: findPda([Buffer.from("lp_lock"), poolPda.toBuffer()]),
+          tokenAVault: findPda([
+            Buffer.from("vault"),
+            poolPda.toBuffer(),
+            mintA.toBuffer(),
+          ]),
+          tokenBVault: findPda([
+            Buffer.from("vault"),
+            poolPda.toBuffer(),
+            mintB.toBuffer(),
+          ]),
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            poolPda.toBuffer(),
+            mintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            poolPda.toBuffer(),
+            mintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: programOf(mintA),
+          tokenBProgram: programOf(mintB),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+    };
+
+    before(async () => {
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.PermanentDelegate]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        mintLen
+      );
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializePermanentDelegateInstruction(
+          mintKeypair.publicKey,
+          user.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          mintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await provider.sendAndConfirm(tx, [user, mintKeypair]);
+      delegatedMint = mintKeypair.publicKey;
+      pairedMint = await createMintHelper(TOKEN_PROGRAM_ID);
+    });
+
+    it("Rejects a pool for a mint with a permanent delegate", async () => {
+      await expect(createPool(delegatedMint, pairedMint)).to.be.rejectedWith(
+        /MintExtensionNotAllowed/
+      );
+    });
+
+    it("Creates the pool once the authority allowlists the mint", async function () {
+      if (!protocolAuthority.equals(user.publicKey)) {
+        this.skip();
+      }
+
+      await program.methods
+        .updateMintAllowlist({ add: {} }, delegatedMint)
+        .accounts({
+          authority: user.publicKey,
+          protocolConfig: protocolConfigPda,
+        })
+        .signers([user])
+        .rpc();
+      const config = await program.account.protocolConfig.fetch(
+        protocolConfigPda
+      );
+      expect(config.allowedMints.some((mint) => mint.equals(delegatedMint))).to
+        .be.true;
+
+      await createPool(delegatedMint, pairedMint);
+
+      await program.methods
+        .updateMintAllowlist({ remove: {} }, delegatedMint)
+        .accounts({
+          authority: user.publicKey,
+          protocolConfig: protocolConfigPda,
+        })
+        .signers([user])
+        .rpc();
+    });
+  });
+
+  describe("Native SOL", () => {
+    const NATIVE_FEE_TIER = 55; // 0.55%
+    const SOL_DEPOSIT = LAMPORTS_PER_SOL / 2;
+    const TOKEN_DEPOSIT = 50 * 10 ** 6;
+
+    let tokenMint: PublicKey;
+    let userToken: PublicKey;
+    let solIsA: boolean;
+    let nativePoolMintA: PublicKey;
+    let nativePoolMintB: PublicKey;
+    let nativePoolPda: PublicKey;
+    let nativeLpMintPda: PublicKey;
+    let wsolPda: PublicKey;
+
+    const findPda = (seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const swapAccounts = (
+      source: PublicKey | null,
+      destination: PublicKey | null
+    ) => ({
+      trader: user.publicKey,
+      ammPool: nativePoolPda,
+      lpMint: nativeLpMintPda,
+      tokenAMint: nativePoolMintA,
+      tokenBMint: nativePoolMintB,
+      userSourceTokenAccount: source,
+      userDestinationTokenAccount: destination,
+      wsolAccount: wsolPda,
+      tokenAVault: findPda([
+        Buffer.from("vault"),
+        nativePoolPda.toBuffer(),
+        nativePoolMintA.toBuffer(),
+      ]),
+      tokenBVault: findPda([
+        Buffer.from("vault"),
+        nativePoolPda.toBuffer(),
+        nativePoolMintB.toBuffer(),
+      ]),
+      protocolFeeVaultA: findPda([
+        Buffer.from("protocol_fee_vault"),
+        nativePoolPda.toBuffer(),
+        nativePoolMintA.toBuffer(),
+      ]),
+      protocolFeeVaultB: findPda([
+        Buffer.from("protocol_fee_vault"),
+        nativePoolPda.toBuffer(),
+        nativePoolMintB.toBuffer(),
+      ]),
+      authority: user.publicKey,
+      referrerFeeAccount: null,
+      callbackProgram: null,
+      tokenAProgram: TOKEN_PROGRAM_ID,
+      tokenBProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+      tokenMint = await createMintHelper(TOKEN_PROGRAM_ID);
+      userToken = await createUserAndAssociatedWallet(
+        tokenMint,
+        user,
+        1000n * 1000000n,
+        TOKEN_PROGRAM_ID
+      );
+      [nativePoolMintA, nativePoolMintB] = [NATIVE_MINT, tokenMint].sort(
+        (a, b) => a.toBuffer().compare(b.toBuffer())
+      );
+      solIsA = nativePoolMintA.equals(NATIVE_MINT);
+      nativePoolPda = findPda([
+        Buffer.from("amm_pool"),
+        nativePoolMintA.toBuffer(),
+        nativePoolMintB.toBuffer(),
+        new anchor.BN(NATIVE_FEE_TIER).toBuffer("le", 2),
+        Buffer.from([CURVE_TYPE_SEED.constantProduct]),
+      ]);
+      nativeLpMintPda = findPda([
+        Buffer.from("lp_mint"),
+        nativePoolPda.toBuffer(),
+      ]);
+      wsolPda = findPda([Buffer.from("wsol"), user.publicKey.toBuffer()]);
+
+      await program.methods
+        .createAmmPool(NATIVE_FEE_TIER, PROTOCOL_FEE_SHARE, REFERRER_FEE_SHARE)
+        .accounts({
+          payer: user.publicKey,
+          authority: user.publicKey,
+          protocolConfig: protocolConfigPda,
+          tokenAMint: nativePoolMintA,
+          tokenBMint: nativePoolMintB,
+          ammPool: nativePoolPda,
+          lpMint: nativeLpMintPda,
+          lpLockVault: findPda([
+            Buffer.from("lp_lock"),
+            nativePoolPda.toBuffer(),
+          ]),
+          tokenAVault: findPda([
+            Buffer.from("vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintA.toBuffer(),
+          ]),
+          tokenBVault: findPda([
+            Buffer.from("vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintB.toBuffer(),
+          ]),
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .openAmmPosition({ manualClaim: {} })
+        .accounts({
+          owner: user.publicKey,
+          ammPool: nativePoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            nativePoolPda.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Adds liquidity paying the SOL side in lamports", async () => {
+      const [amountA, amountB] = solIsA
+        ? [SOL_DEPOSIT, TOKEN_DEPOSIT]
+        : [TOKEN_DEPOSIT, SOL_DEPOSIT];
+      const lamportsBefore = await connection.getBalance(user.publicKey);
+
+      await program.methods
+        .addAmmLiquidity(
+          new anchor.BN(amountA),
+          new anchor.BN(amountB),
+          new anchor.BN(1)
+        )
+        .accounts({
+          owner: user.publicKey,
+          ammPool: nativePoolPda,
+          ammPosition: findPda([
+            Buffer.from("amm_position"),
+            user.publicKey.toBuffer(),
+            nativePoolPda.toBuffer(),
+          ]),
+          lpMint: nativeLpMintPda,
+          tokenAMint: nativePoolMintA,
+          tokenBMint: nativePoolMintB,
+          tokenAVault: findPda([
+            Buffer.from("vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintA.toBuffer(),
+          ]),
+          tokenBVault: findPda([
+            Buffer.from("vault"),
+            nativePoolPda.toBuffer(),
+            nativePoolMintB.toBuffer(),
+          ]),
+          userTokenAAccount: solIsA ? null : userToken,
+          userTokenBAccount: solIsA ? userToken : null,
+          wsolAccount: wsolPda,
+          userLpTokenAccount: getAssociatedTokenAddressSync(
+            nativeLpMintPda,
+            user.publicKey
+          ),
+          lpLockVault: findPda([
+            Buffer.from("lp_lock"),
+            nativePoolPda.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const pool = await program.account.ammPool.fetch(nativePoolPda);
+      const solReserves = solIsA ? pool.reservesA : pool.reservesB;
+      expect(solReserves.toNumber()).to.equal(SOL_DEPOSIT);
+      const lamportsAfter = await connection.getBalance(user.publicKey);
+      expect(lamportsBefore - lamportsAfter).to.be.at.least(SOL_DEPOSIT);
+      // The temporary wrapped SOL account is closed again.
+      expect(await connection.getAccountInfo(wsolPda)).to.be.null;
+    });
+
+    it("Swaps native SOL in and out", async () => {
+      const solIn = LAMPORTS_PER_SOL / 20;
+      const tokenBefore = await getAccount(connection, userToken);
+      await program.methods
+        .swapOnAmm(new anchor.BN(solIn), new anchor.BN(1))
+        .accounts(swapAccounts(null, userToken))
+        .signers([user])
+        .rpc();
+      const tokenAfter = await getAccount(connection, userToken);
+      expect(tokenAfter.amount > tokenBefore.amount).to.be.true;
+      expect(await connection.getAccountInfo(wsolPda)).to.be.null;
+
+      const lamportsBefore = await connection.getBalance(user.publicKey);
+      await program.methods
+        .swapOnAmm(new anchor.BN(2 * 10 ** 6), new anchor.BN(1))
+        .accounts(swapAccounts(userToken, null))
+        .signers([user])
+        .rpc();
+      const lamportsAfter = await connection.getBalance(user.publicKey);
+      expect(lamportsAfter).to.be.greaterThan(lamportsBefore);
+      expect(await connection.getAccountInfo(wsolPda)).to.be.null;
+    });
+
+    it("Rejects an omitted account on the non-native side", async () => {
+      await expect(
+        program.methods
+          .swapOnAmm(new anchor.BN(10 ** 6), new anchor.BN(1))
+          .accounts(swapAccounts(null, null))
+          .signers([user])
+          .rpc()
+      ).to.be.rejectedWith(/NativeMintRequired/);
     });
   });
 });
@@ -0,0 +1,652 @@
+// FILE: tests/bonding-curve-test.ts
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { DloomFlow } from "../target/types/dloom";
+import {
+  getAssociatedTokenAddressSync,
+  createAssociatedTokenAccountInstruction,
+  createMint,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  getAccount,
+  getMint,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializePermanentDelegateInstruction,
+} from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { expect } from "chai";
+import chai from "chai";
+import chaiAsPromised from "chai-as-promised";
+import * as fs from "fs";
+
+chai.use(chaiAsPromised);
+
+// Helper function to load a Keypair from a JSON file
+const loadKeypairFromFile = (filepath: string): Keypair => {
+  const secretKeyString = fs.readFileSync(filepath, { encoding: "utf8" });
+  const secretKey = Uint8Array.from(JSON.parse(secretKeyString));
+  return Keypair.fromSecretKey(secretKey);
+};
+
+describe("dloom_flow Bonding Curve Tests", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DloomFlow as Program<DloomFlow>;
+  const connection = provider.connection;
+
+  // Curve parameters (6-decimal mints)
+  const VIRTUAL_TOKEN_RESERVES = 1_000 * 10 ** 6;
+  const VIRTUAL_QUOTE_RESERVES = 30 * 10 ** 6;
+  const SALE_ALLOCATION = 800 * 10 ** 6;
+  const GRADUATION_THRESHOLD = 50 * 10 ** 6;
+  const CURVE_FEE_RATE = 100; // 1%
+  const GRADUATION_FEES = {
+    feeRate: 25, // 0.25%
+    protocolFeeShare: 2000, // 20%
+    referrerFeeShare: 1000, // 10%
+  };
+  // The reserved fee tier graduated AMM pools are created in (u16::MAX).
+  const GRADUATION_FEE_TIER = 65535;
+
+  const user = loadKeypairFromFile("./target/test-wallets/user.json");
+
+  let protocolConfigPda: PublicKey;
+  let protocolAuthority: PublicKey;
+  let launchMint: PublicKey;
+  let quoteMint: PublicKey;
+  let userLaunchAccount: PublicKey;
+  let userQuoteAccount: PublicKey;
+
+  let curvePda: PublicKey;
+  let curveTokenVaultPda: PublicKey;
+  let curveQuoteVaultPda: PublicKey;
+  let curveFeeVaultPda: PublicKey;
+
+  const findPda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const createUserAndAssociatedWallet = async (
+    mint: PublicKey,
+    amount: bigint
+  ): Promise<PublicKey> => {
+    const ata = getAssociatedTokenAddressSync(mint, user.publicKey);
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountInstruction(
+        user.publicKey,
+        ata,
+        user.publicKey,
+        mint
+      )
+    );
+    await provider.sendAndConfirm(tx, [user]);
+    if (amount > 0) {
+      await mintTo(connection, user, mint, ata, user, amount);
+    }
+    return ata;
+  };
+
+  // The constant-product pool the curve graduates into, in the reserved graduation tier.
+  const graduatedAmmPdas = () => {
+    const [mintA, mintB] = [launchMint, quoteMint].sort((a, b) =>
+      a.toBuffer().compare(b.toBuffer())
+    );
+    const ammPoolPda = findPda([
+      Buffer.from("amm_pool"),
+      mintA.toBuffer(),
+      mintB.toBuffer(),
+      new BN(GRADUATION_FEE_TIER).toBuffer("le", 2),
+      Buffer.from([0]), // The constant-product curve type.
+    ]);
+    return {
+      mintA,
+      mintB,
+      ammPoolPda,
+      lpMintPda: findPda([Buffer.from("lp_mint"), ammPoolPda.toBuffer()]),
+      lpLockVaultPda: findPda([Buffer.from("lp_lock"), ammPoolPda.toBuffer()]),
+    };
+  };
+
+  const tradeAccounts = () => ({
+    trader: user.publicKey,
+    bondingCurve: curvePda,
+    tokenMint: launchMint,
+    quoteMint: quoteMint,
+    tokenVault: curveTokenVaultPda,
+    quoteVault: curveQuoteVaultPda,
+    protocolFeeVault: curveFeeVaultPda,
+    traderTokenAccount: userLaunchAccount,
+    traderQuoteAccount: userQuoteAccount,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    quoteTokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  before(async () => {
+    const balance = await connection.getBalance(user.publicKey);
+    if (balance < 2 * LAMPORTS_PER_SOL) {
+      await connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    }
+
+    // The protocol config is a singleton; another suite may have created it already.
+    protocolConfigPda = findPda([Buffer.from("protocol_config")]);
+    const existingConfig = await program.account.protocolConfig.fetchNullable(
+      protocolConfigPda
+    );
+    if (existingConfig) {
+      protocolAuthority = existingConfig.authority;
+    } else {
+      await program.methods
+        .initializeProtocol()
+        .accounts({
+          protocolConfig: protocolConfigPda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      protocolAuthority = user.publicKey;
+    }
+
+    launchMint = await createMint(connection, user, user.publicKey, null, 6);
+    quoteMint = await createMint(connection, user, user.publicKey, null, 6);
+    [userLaunchAccount, userQuoteAccount] = await Promise.all([
+      createUserAndAssociatedWallet(launchMint, 1_000n * 1_000_000n),
+      createUserAndAssociatedWallet(quoteMint, 1_000n * 1_000_000n),
+    ]);
+
+    curvePda = findPda([Buffer.from("bonding_curve"), launchMint.toBuffer()]);
+    curveTokenVaultPda = findPda([
+      Buffer.from("vault"),
+      curvePda.toBuffer(),
+      launchMint.toBuffer(),
+    ]);
+    curveQuoteVaultPda = findPda([
+      Buffer.from("vault"),
+      curvePda.toBuffer(),
+      quoteMint.toBuffer(),
+    ]);
+    curveFeeVaultPda = findPda([
+      Buffer.from("protocol_fee_vault"),
+      curvePda.toBuffer(),
+      quoteMint.toBuffer(),
+    ]);
+  });
+
+  it("Creates a bonding curve", async () => {
+    await program.methods
+      .createBondingCurve(
+        new BN(VIRTUAL_TOKEN_RESERVES),
+        new BN(VIRTUAL_QUOTE_RESERVES),
+        new BN(SALE_ALLOCATION),
+        new BN(GRADUATION_THRESHOLD),
+        CURVE_FEE_RATE,
+        GRADUATION_FEES
+      )
+      .accounts({
+        creator: user.publicKey,
+        protocolConfig: protocolConfigPda,
+        authority: protocolAuthority,
+        tokenMint: launchMint,
+        quoteMint: quoteMint,
+        bondingCurve: curvePda,
+        tokenVault: curveTokenVaultPda,
+        quoteVault: curveQuoteVaultPda,
+        protocolFeeVault: curveFeeVaultPda,
+        creatorTokenAccount: userLaunchAccount,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const curve = await program.account.bondingCurve.fetch(curvePda);
+    expect(curve.realTokenReserves.toNumber()).to.equal(SALE_ALLOCATION);
+    expect(curve.status).to.deep.equal({ trading: {} });
+    expect(curve.graduationFees).to.deep.equal(GRADUATION_FEES);
+  });
+
+  it("Rejects graduation fees that pool creation would reject", async () => {
+    const otherMint = await createMint(connection, user, user.publicKey, null, 6);
+    const otherAccount = await createUserAndAssociatedWallet(
+      otherMint,
+      BigInt(SALE_ALLOCATION)
+    );
+    const otherCurvePda = findPda([
+      Buffer.from("bonding_curve"),
+      otherMint.toBuffer(),
+    ]);
+
+    await expect(
+      program.methods
+        .createBondingCurve(
+          new BN(VIRTUAL_TOKEN_RESERVES),
+          new BN(VIRTUAL_QUOTE_RESERVES),
+          new BN(SALE_ALLOCATION),
+          new BN(GRADUATION_THRESHOLD),
+          CURVE_FEE_RATE,
+          { ...GRADUATION_FEES, protocolFeeShare: 9500 }
+        )
+        .accounts({
+          creator: user.publicKey,
+          protocolConfig: protocolConfigPda,
+          authority: protocolAuthority,
+          tokenMint: otherMint,
+          quoteMint: quoteMint,
+          bondingCurve: otherCurvePda,
+          tokenVault: findPda([
+            Buffer.from("vault"),
+            otherCurvePda.toBuffer(),
+            otherMint.toBuffer(),
+          ]),
+          quoteVault: findPda([
+            Buffer.from("vault"),
+            otherCurvePda.toBuffer(),
+            quoteMint.toBuffer(),
+          ]),
+          protocolFeeVault: findPda([
+            Buffer.from("protocol_fee_vault"),
+            otherCurvePda.toBuffer(),
+            quoteMint.toBuffer(),
+          ]),
+          creatorTokenAccount: otherAccount,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc()
+    ).to.be.rejectedWith(/FeeShareExceedsTotal/);
+  });
+
+  it("Puts up only what arrives after a Token-2022 transfer fee", async () => {
+    const TRANSFER_FEE_BPS = 100; // 1%
+    const feeMintKeypair = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: feeMintKeypair.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMintKeypair.publicKey,
+          user.publicKey,
+          user.publicKey,
+          TRANSFER_FEE_BPS,
+          BigInt(SALE_ALLOCATION),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          feeMintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [user, feeMintKeypair]
+    );
+    const feeMint = feeMintKeypair.publicKey;
+    const feeAccount = getAssociatedTokenAddressSync(
+      feeMint,
+      user.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          user.publicKey,
+          feeAccount,
+          user.publicKey,
+          feeMint,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [user]
+    );
+    await mintTo(
+      connection,
+      user,
+      feeMint,
+      feeAccount,
+      user,
+      BigInt(SALE_ALLOCATION),
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const feeCurvePda = findPda([Buffer.from("bonding_curve"), feeMint.toBuffer()]);
+    const feeTokenVaultPda = findPda([
+      Buffer.from("vault"),
+      feeCurvePda.toBuffer(),
+      feeMint.toBuffer(),
+    ]);
+    await program.methods
+      .createBondingCurve(
+        new BN(VIRTUAL_TOKEN_RESERVES),
+        new BN(VIRTUAL_QUOTE_RESERVES),
+        new BN(SALE_ALLOCATION),
+        new BN(GRADUATION_THRESHOLD),
+        CURVE_FEE_RATE,
+        GRADUATION_FEES
+      )
+      .accounts({
+        creator: user.publicKey,
+        protocolConfig: protocolConfigPda,
+        authority: protocolAuthority,
+        tokenMint: feeMint,
+        quoteMint: quoteMint,
+        bondingCurve: feeCurvePda,
+        tokenVault: feeTokenVaultPda,
+        quoteVault: findPda([
+          Buffer.from("vault"),
+          feeCurvePda.toBuffer(),
+          quoteMint.toBuffer(),
+        ]),
+        protocolFeeVault: findPda([
+          Buffer.from("protocol_fee_vault"),
+          feeCurvePda.toBuffer(),
+          quoteMint.toBuffer(),
+        ]),
+        creatorTokenAccount: feeAccount,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const curve = await program.account.bondingCurve.fetch(feeCurvePda);
+    const vault = await getAccount(
+      connection,
+      feeTokenVaultPda,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const withheld = (SALE_ALLOCATION * TRANSFER_FEE_BPS) / 10_000;
+    expect(curve.realTokenReserves.toNumber()).to.equal(SALE_ALLOCATION - withheld);
+    expect(curve.realTokenReserves.toString()).to.equal(vault.amount.toString());
+  });
+
+  it("Rejects a launch mint with a permanent delegate", async () => {
+    const delegatedMintKeypair = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.PermanentDelegate]);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: delegatedMintKeypair.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializePermanentDelegateInstruction(
+          delegatedMintKeypair.publicKey,
+          user.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          delegatedMintKeypair.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [user, delegatedMintKeypair]
+    );
+    const delegatedMint = delegatedMintKeypair.publicKey;
+    const delegatedAccount = getAssociatedTokenAddressSync(
+      delegatedMint,
+      user.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          user.publicKey,
+          delegatedAccount,
+          user.publicKey,
+          delegatedMint,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [user]
+    );
+
+    // The delegate could drain the curve's vault, so the mint needs allow-listing first.
+    const delegatedCurvePda = findPda([
+      Buffer.from("bonding_curve"),
+      delegatedMint.toBuffer(),
+    ]);
+    await expect(
+      program.methods
+        .createBondingCurve(
+          new BN(VIRTUAL_TOKEN_RESERVES),
+          new BN(VIRTUAL_QUOTE_RESERVES),
+          new BN(SALE_ALLOCATION),
+          new BN(GRADUATION_THRESHOLD),
+          CURVE_FEE_RATE,
+          GRADUATION_FEES
+        )
+        .accounts({
+          creator: user.publicKey,
+          protocolConfig: protocolConfigPda,
+          authority: protocolAuthority,
+          tokenMint: delegatedMint,
+          quoteMint: quoteMint,
+          bondingCurve: delegatedCurvePda,
+          tokenVault: findPda([
+            Buffer.from("vault"),
+            delegatedCurvePda.toBuffer(),
+            delegatedMint.toBuffer(),
+          ]),
+          quoteVault: findPda([
+            Buffer.from("vault"),
+            delegatedCurvePda.toBuffer(),
+            quoteMint.toBuffer(),
+          ]),
+          protocolFeeVault: findPda([
+            Buffer.from("protocol_fee_vault"),
+            delegatedCurvePda.toBuffer(),
+            quoteMint.toBuffer(),
+          ]),
+          creatorTokenAccount: delegatedAccount,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc()
+    ).to.be.rejectedWith(/MintExtensionNotAllowed/);
+  });
+
+  it("Buys and sells along the curve", async () => {
+    const quoteIn = 10 * 10 ** 6;
+    const launchBefore = await getAccount(connection, userLaunchAccount);
+    await program.methods
+      .buyOnBondingCurve(new BN(quoteIn), new BN(1))
+      .accounts(tradeAccounts())
+      .signers([user])
+      .rpc();
+    const launchAfterBuy = await getAccount(connection, userLaunchAccount);
+    const bought = Number(launchAfterBuy.amount - launchBefore.amount);
+
+    // tokens_out = 1000 * 9.9 / (30 + 9.9) ~= 248.1 tokens.
+    expect(bought).to.be.closeTo(248.1 * 10 ** 6, 0.1 * 10 ** 6);
+
+    const quoteBeforeSell = await getAccount(connection, userQuoteAccount);
+    await program.methods
+      .sellOnBondingCurve(new BN(Math.floor(bought / 2)), new BN(1))
+      .accounts(tradeAccounts())
+      .signers([user])
+      .rpc();
+    const quoteAfterSell = await getAccount(connection, userQuoteAccount);
+    const received = Number(quoteAfterSell.amount - quoteBeforeSell.amount);
+    expect(received).to.be.greaterThan(0);
+    expect(received).to.be.lessThan(quoteIn);
+  });
+
+  it("Completes at the graduation threshold and stops trading", async () => {
+    await program.methods
+      .buyOnBondingCurve(new BN(60 * 10 ** 6), new BN(1))
+      .accounts(tradeAccounts())
+      .signers([user])
+      .rpc();
+
+    const curve = await program.account.bondingCurve.fetch(curvePda);
+    expect(curve.status).to.deep.equal({ complete: {} });
+
+    await expect(
+      program.methods
+        .buyOnBondingCurve(new BN(10 ** 6), new BN(1))
+        .accounts(tradeAccounts())
+        .signers([user])
+        .rpc()
+    ).to.be.rejectedWith(/CurveNotTrading/);
+  });
+
+  it("Graduates into an AMM pool with locked LP", async () => {
+    const { mintA, mintB, ammPoolPda, lpMintPda, lpLockVaultPda } =
+      graduatedAmmPdas();
+
+    await program.methods
+      .graduateBondingCurveToAmm()
+      .accounts({
+        protocolConfig: protocolConfigPda,
+        bondingCurve: curvePda,
+        curveTokenVault: curveTokenVaultPda,
+        curveQuoteVault: curveQuoteVaultPda,
+        creatorQuoteAccount: userQuoteAccount,
+        createPool: {
+          payer: user.publicKey,
+          authority: protocolAuthority,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          ammPool: ammPoolPda,
+          lpMint: lpMintPda,
+          lpLockVault: lpLockVaultPda,
+          tokenAVault: findPda([
+            Buffer.from("vault"),
+            ammPoolPda.toBuffer(),
+            mintA.toBuffer(),
+          ]),
+          tokenBVault: findPda([
+            Buffer.from("vault"),
+            ammPoolPda.toBuffer(),
+            mintB.toBuffer(),
+          ]),
+          protocolFeeVaultA: findPda([
+            Buffer.from("protocol_fee_vault"),
+            ammPoolPda.toBuffer(),
+            mintA.toBuffer(),
+          ]),
+          protocolFeeVaultB: findPda([
+            Buffer.from("protocol_fee_vault"),
+            ammPoolPda.toBuffer(),
+            mintB.toBuffer(),
+          ]),
+          systemProgram: SystemProgram.programId,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+      })
+      .signers([user])
+      .rpc();
+
+    const curve = await program.account.bondingCurve.fetch(curvePda);
+    expect(curve.status).to.deep.equal({ graduated: {} });
+    expect(curve.graduatedPool.equals(ammPoolPda)).to.be.true;
+
+    const pool = await program.account.ammPool.fetch(ammPoolPda);
+    expect(pool.feeTier).to.equal(GRADUATION_FEE_TIER);
+    expect(pool.feeRate).to.equal(GRADUATION_FEES.feeRate);
+    expect(pool.protocolFeeShare).to.equal(GRADUATION_FEES.protocolFeeShare);
+    expect(pool.reservesA.toNumber()).to.be.greaterThan(0);
+    expect(pool.reservesB.toNumber()).to.be.greaterThan(0);
+
+    const lockVault = await getAccount(connection, lpLockVaultPda);
+    expect(Number(lockVault.amount)).to.be.greaterThan(0);
+    expect(lockVault.owner.equals(ammPoolPda)).to.be.true;
+  });
+
+  it("Leaves the graduated pool's LP fees in its reserves", async () => {
+    const { mintA, mintB, ammPoolPda, lpMintPda, lpLockVaultPda } =
+      graduatedAmmPdas();
+    const quoteIsA = quoteMint.equals(mintA);
+    const quoteFeeVaultPda = findPda([
+      Buffer.from("protocol_fee_vault"),
+      ammPoolPda.toBuffer(),
+      quoteMint.toBuffer(),
+    ]);
+    const quoteReservesOf = (pool: any) =>
+      (quoteIsA ? pool.reservesA : pool.reservesB) as BN;
+    const quoteFeeGrowthOf = (pool: any) =>
+      (quoteIsA ? pool.feeGrowthPerLpTokenA : pool.feeGrowthPerLpTokenB) as BN;
+
+    const quoteIn = 10 * 10 ** 6;
+    const poolBefore = await program.account.ammPool.fetch(ammPoolPda);
+    const feeVaultBefore = await getAccount(connection, quoteFeeVaultPda);
+    await program.methods
+      .swapOnAmm(new BN(quoteIn), new BN(1))
+      .accounts({
+        trader: user.publicKey,
+        ammPool: ammPoolPda,
+        lpMint: lpMintPda,
+        tokenAMint: mintA,
+        tokenBMint: mintB,
+        userSourceTokenAccount: userQuoteAccount,
+        userDestinationTokenAccount: userLaunchAccount,
+        tokenAVault: poolBefore.tokenAVault,
+        tokenBVault: poolBefore.tokenBVault,
+        protocolFeeVaultA: poolBefore.protocolFeeVaultA,
+        protocolFeeVaultB: poolBefore.protocolFeeVaultB,
+        authority: protocolAuthority,
+        referrerFeeAccount: null,
+        callbackProgram: null,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+        ammOracle: null,
+      })
+      .signers([user])
+      .rpc();
+    const poolAfter = await program.account.ammPool.fetch(ammPoolPda);
+    const feeVaultAfter = await getAccount(connection, quoteFeeVaultPda);
+
+    // The LP fee accrues to the pool's LP tokens...
+    expect(quoteFeeGrowthOf(poolAfter).gt(quoteFeeGrowthOf(poolBefore))).to.be
+      .true;
+    // ...all of which sit in the lock vault, so no position can claim it and
+    // everything but the protocol's share stays in the reserves.
+    const lpMint = await getMint(connection, lpMintPda);
+    const lockVault = await getAccount(connection, lpLockVaultPda);
+    expect(lockVault.amount.toString()).to.equal(lpMint.supply.toString());
+    const protocolFee = Number(feeVaultAfter.amount - feeVaultBefore.amount);
+    expect(
+      quoteReservesOf(poolAfter).sub(quoteReservesOf(poolBefore)).toNumber()
+    ).to.equal(quoteIn - protocolFee);
+  });
+});
@@ -8,6 +8,7 @@ import {
   DlmmSwapResult,
   DlmmPositionBurned,
   DlmmLiquidityModified,
+  DlmmReservesSynced,
 } from "../target/types/dloom";
 import {
   getAssociatedTokenAddressSync,
@@ -16,6 +17,7 @@ import {
   mintTo,
   TOKEN_PROGRAM_ID,
   getAccount,
+  transferChecked,
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import {
@@ -566,6 +568,7 @@ describe("dloom_flow DLMM Tests", () => {
             .accounts({
                 owner: user.publicKey,
                 position: positionOnePda,
+                dlmmPool: dlmmPoolPda,
                 positionMint: positionOneMint.publicKey,
                 userPositionNftAccount: positionNftAccount,
                 tokenProgram: TOKEN_PROGRAM_ID,
@@ -581,4 +584,63 @@ describe("dloom_flow DLMM Tests", () => {
     });
    });
 
+  describe("Reserve Reconciliation", () => {
+    const DONATION = 1 * 10 ** 6;
+
+    it("Skims a donated surplus to the protocol fee vault", async () => {
+      await transferChecked(connection, user, userTokenA, mintA, tokenAVaultPda, user, DONATION, 6);
+      const poolBefore = await program.account.dlmmPool.fetch(dlmmPoolPda);
+      const feeVaultBefore = await getAccount(connection, protocolFeeVaultAPda);
+      const vaultBefore = await getAccount(connection, tokenAVaultPda);
+      const surplus = vaultBefore.amount - BigInt(poolBefore.reservesA.toString());
+
+      await program.methods.dlmmSkimReserves()
+        .accounts({
+          dlmmPool: dlmmPoolPda,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          tokenAVault: tokenAVaultPda,
+          tokenBVault: tokenBVaultPda,
+          protocolFeeVaultA: protocolFeeVaultAPda,
+          protocolFeeVaultB: protocolFeeVaultBPda,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const poolAfter = await program.account.dlmmPool.fetch(dlmmPoolPda);
+      const feeVaultAfter = await getAccount(connection, protocolFeeVaultAPda);
+      expect(surplus >= BigInt(DONATION)).to.be.true;
+      expect(feeVaultAfter.amount - feeVaultBefore.amount).to.equal(surplus);
+      expect(poolAfter.reservesA.eq(poolBefore.reservesA)).to.be.true;
+    });
+
+    it("Syncs tracked reserves to the vault balances", async () => {
+      await transferChecked(connection, user, userTokenB, mintB, tokenBVaultPda, user, DONATION, 6);
+      const poolBefore = await program.account.dlmmPool.fetch(dlmmPoolPda);
+
+      let syncedEvent: DlmmReservesSynced | null = null;
+      const listener = program.addEventListener("dlmmReservesSynced", (event: DlmmReservesSynced) => {
+        syncedEvent = event;
+      });
+
+      await program.methods.dlmmSyncReserves()
+        .accounts({
+          dlmmPool: dlmmPoolPda,
+          tokenAVault: tokenAVaultPda,
+          tokenBVault: tokenBVaultPda,
+        })
+        .rpc();
+
+      const poolAfter = await program.account.dlmmPool.fetch(dlmmPoolPda);
+      const vaultB = await getAccount(connection, tokenBVaultPda);
+      expect(poolAfter.reservesB.toString()).to.equal(vaultB.amount.toString());
+      expect(poolAfter.reservesB.sub(poolBefore.reservesB).gten(DONATION)).to.be.true;
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      expect(syncedEvent).to.not.be.null;
+      expect(syncedEvent.oldReservesB.eq(poolBefore.reservesB)).to.be.true;
+      program.removeEventListener(listener);
+    });
+  });
 });
\ No newline at end of file